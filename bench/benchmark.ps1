@@ -82,6 +82,16 @@ foreach ($Threads in $ThreadCounts) {
     }
 }
 
+# The ntdll deletion path stays opt-in until it beats the Win32 one here
+$Methods += @{
+    Name = "rmbrr --native-delete (16 threads)"
+    Command = {
+        param($Path, $ThreadCount)
+        & $Rmbrr $Path --threads $ThreadCount --native-delete
+    }.GetNewClosure()
+    Threads = 16
+}
+
 # Other tools (don't support thread configuration)
 $Methods += @(
     @{
@@ -225,6 +235,59 @@ foreach ($Line in $Results) {
 
 Write-Host ""
 
+# Junction farm: a pnpm-style tree of links into a store, which rmbrr deletes link by link
+# (one delete-on-close open each) without ever touching the store
+$FarmPackages = if ($env:FARM_PACKAGES) { [int]$env:FARM_PACKAGES } else { 100 }
+$FarmLinks = if ($env:FARM_LINKS) { [int]$env:FARM_LINKS } else { 50 }
+$FarmStore = Join-Path $TestRoot "farm_store"
+$StoreFile = Join-Path $FarmStore "pkg\lib\index.js"
+
+function New-Farm($Path) {
+    foreach ($P in 1..$FarmPackages) {
+        $Modules = Join-Path $Path "p$P\node_modules"
+        New-Item -ItemType Directory -Path $Modules -Force | Out-Null
+        foreach ($L in 1..$FarmLinks) {
+            New-Item -ItemType Junction -Path (Join-Path $Modules "dep$L") -Target (Join-Path $FarmStore "pkg") | Out-Null
+        }
+    }
+}
+
+Write-Host "Junction farm ($FarmPackages packages x $FarmLinks links)..." -ForegroundColor Green
+New-Item -ItemType File -Path $StoreFile -Force | Out-Null
+$Results += ""
+$Results += "Junction farm ($FarmPackages packages x $FarmLinks links):"
+
+# Remove-Item is left out: Windows PowerShell 5.1 follows junctions into their targets
+$FarmMethods = [ordered]@{
+    "rmbrr" = { param($Path) & $Rmbrr $Path }
+    "cmd rmdir" = { param($Path) cmd /c "rmdir /s /q `"$Path`" 2>nul" }
+}
+foreach ($Name in $FarmMethods.Keys) {
+    $TestDir = Join-Path $TestRoot ("farm_" + ($Name -replace '[^a-zA-Z0-9]', '_'))
+    Write-Host "  Building farm for $Name..." -ForegroundColor Gray
+    New-Farm $TestDir
+
+    $Stopwatch = [System.Diagnostics.Stopwatch]::StartNew()
+    & $FarmMethods[$Name] $TestDir
+    $Stopwatch.Stop()
+    $TimeMs = $Stopwatch.ElapsedMilliseconds
+
+    if (Test-Path $TestDir) {
+        Write-Host "  ${Name}: FAILED (directory still exists)" -ForegroundColor Red
+        $Results += "  ${Name}: FAILED (incomplete deletion)"
+        cmd /c "rmdir /s /q `"$TestDir`" 2>nul"
+    } elseif (-not (Test-Path $StoreFile)) {
+        Write-Host "  ${Name}: FAILED (followed links into the store)" -ForegroundColor Red
+        $Results += "  ${Name}: FAILED (followed links into the store)"
+        New-Item -ItemType File -Path $StoreFile -Force | Out-Null
+    } else {
+        Write-Host "  ${Name}: ${TimeMs}ms" -ForegroundColor Green
+        $Results += "  ${Name}: ${TimeMs}ms"
+    }
+}
+
+Write-Host ""
+
 # Save results to file
 $Results | Out-File $ResultsFile -Encoding UTF8
 Write-Host "Results saved to: $ResultsFile" -ForegroundColor Green
@@ -218,6 +218,55 @@ done
 
 echo ""
 
+# Symlink farm: a pnpm-style tree of links into a store, which rmbrr deletes link by link
+# without ever touching the store
+FARM_PACKAGES="${FARM_PACKAGES:-200}"
+FARM_LINKS="${FARM_LINKS:-50}"
+FARM_STORE="${TEST_ROOT}/farm_store"
+FARM_SOURCE="${TEST_ROOT}/farm_source"
+
+echo -e "${GREEN}Symlink farm (${FARM_PACKAGES} packages x ${FARM_LINKS} links)...${NC}"
+mkdir -p "${FARM_STORE}/pkg/lib"
+touch "${FARM_STORE}/pkg/lib/index.js"
+for P in $(seq 1 "$FARM_PACKAGES"); do
+    mkdir -p "${FARM_SOURCE}/p${P}/node_modules"
+    for L in $(seq 1 "$FARM_LINKS"); do
+        ln -s "${FARM_STORE}/pkg" "${FARM_SOURCE}/p${P}/node_modules/dep${L}"
+    done
+done
+
+RESULTS+=("")
+RESULTS+=("Symlink farm (${FARM_PACKAGES} packages x ${FARM_LINKS} links):")
+for METHOD_NAME in "rmbrr" "rm -rf"; do
+    TEST_DIR="${TEST_ROOT}/farm_$(echo "$METHOD_NAME" | tr -cd '[:alnum:]')"
+    cp -a "$FARM_SOURCE" "$TEST_DIR"
+
+    START=$(date +%s%3N)
+    if [ "$METHOD_NAME" = "rmbrr" ]; then
+        "$RMBRR" "$TEST_DIR" 2>/dev/null || true
+    else
+        rm -rf "$TEST_DIR"
+    fi
+    END=$(date +%s%3N)
+
+    TIME_MS=$((END - START))
+    if [ -d "$TEST_DIR" ]; then
+        echo -e "${RED}  ${METHOD_NAME}: FAILED (directory still exists)${NC}"
+        RESULTS+=("  ${METHOD_NAME}: FAILED (incomplete deletion)")
+        rm -rf "$TEST_DIR"
+    elif [ ! -f "${FARM_STORE}/pkg/lib/index.js" ]; then
+        echo -e "${RED}  ${METHOD_NAME}: FAILED (followed links into the store)${NC}"
+        RESULTS+=("  ${METHOD_NAME}: FAILED (followed links into the store)")
+        mkdir -p "${FARM_STORE}/pkg/lib"
+        touch "${FARM_STORE}/pkg/lib/index.js"
+    else
+        echo -e "${GREEN}  ${METHOD_NAME}: ${TIME_MS}ms${NC}"
+        RESULTS+=("  ${METHOD_NAME}: ${TIME_MS}ms")
+    fi
+done
+
+echo ""
+
 # Save results to file
 printf "%s\n" "${RESULTS[@]}" > "$RESULTS_FILE"
 echo -e "${GREEN}Results saved to: ${RESULTS_FILE}${NC}"
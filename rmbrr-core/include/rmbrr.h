@@ -0,0 +1,65 @@
+/*
+ * rmbrr C interface
+ *
+ * Build the shared library with:
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ */
+
+#ifndef RMBRR_H
+#define RMBRR_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Returned when path is NULL or not valid UTF-8 */
+#define RMBRR_INVALID_ARGUMENT (-1)
+
+typedef struct rmbrr_options {
+    uint32_t threads; /* worker threads, 0 = logical CPU count */
+    bool dry_run;     /* scan only, delete nothing */
+    bool force;       /* allow dangerous (but not system) paths */
+} rmbrr_options;
+
+typedef struct rmbrr_progress {
+    uint64_t dirs_completed;
+    uint64_t dirs_total;
+    uint64_t files_deleted;
+    uint64_t dirs_failed; /* completed + failed reaches dirs_total at the end */
+} rmbrr_progress;
+
+typedef struct rmbrr_result {
+    uint64_t dirs_deleted;
+    uint64_t files_deleted;
+    uint64_t failed;
+} rmbrr_result;
+
+/* Invoked periodically on the thread that called rmbrr_delete */
+typedef void (*rmbrr_progress_callback)(const rmbrr_progress *progress, void *user_data);
+
+/*
+ * Delete a directory tree.
+ *
+ * path:      NUL-terminated UTF-8 path
+ * options:   may be NULL for defaults
+ * progress:  may be NULL
+ * user_data: passed through to progress
+ * result:    may be NULL
+ *
+ * Returns 0 on success, 1 for an invalid path or partial failure, 2 for an I/O error,
+ * or RMBRR_INVALID_ARGUMENT.
+ */
+int32_t rmbrr_delete(const char *path,
+                     const rmbrr_options *options,
+                     rmbrr_progress_callback progress,
+                     void *user_data,
+                     rmbrr_result *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RMBRR_H */
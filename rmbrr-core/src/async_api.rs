@@ -0,0 +1,146 @@
+//! Async facade over the deletion pipeline (feature `tokio`)
+//!
+//! The pipeline runs on its own threads, so awaiting a deletion never blocks the runtime.
+//! Dropping the [`Removal`] future (for example when losing a `tokio::select!`) cancels it.
+
+use crate::error::{Result, RmError};
+use crate::pipeline::{self, CancelToken, ProgressUpdate, Report, RmOptions};
+use futures_core::Stream;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+
+/// Progress item yielded by [`ProgressStream`]
+pub type ProgressEvent = ProgressUpdate;
+
+/// Stream of progress snapshots; ends when the deletion finishes
+pub struct ProgressStream {
+    rx: mpsc::UnboundedReceiver<ProgressEvent>,
+}
+
+impl Stream for ProgressStream {
+    type Item = ProgressEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// A running deletion; resolves to the final report
+pub struct Removal {
+    result: oneshot::Receiver<Result<Report>>,
+    progress: Option<ProgressStream>,
+    cancel: CancelToken,
+}
+
+impl Removal {
+    /// Take the progress stream (returns None after the first call)
+    pub fn progress(&mut self) -> Option<ProgressStream> {
+        self.progress.take()
+    }
+
+    /// Request cancellation; the future then resolves to `RmError::Cancelled`
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Future for Removal {
+    type Output = Result<Report>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.result).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            // The pipeline thread panicked
+            Poll::Ready(Err(_)) => Poll::Ready(Err(RmError::Cancelled)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for Removal {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Start deleting a directory tree without blocking the async runtime
+///
+/// Must be awaited (or polled) to observe the result; the deletion starts immediately.
+pub fn remove_dir_all_async(path: impl Into<PathBuf>, options: RmOptions) -> Removal {
+    let path = path.into();
+    let mut options = options;
+    let cancel = options.cancel.get_or_insert_with(CancelToken::new).clone();
+
+    let (result_tx, result_rx) = oneshot::channel();
+    let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+
+    std::thread::Builder::new()
+        .name("rmbrr-pipeline".to_string())
+        .spawn(move || {
+            let result = pipeline::remove_dir_all_with_progress(&path, &options, |update| {
+                progress_tx.send(update).ok();
+            });
+            result_tx.send(result).ok();
+        })
+        .expect("Failed to spawn pipeline thread");
+
+    Removal {
+        result: result_rx,
+        progress: Some(ProgressStream { rx: progress_rx }),
+        cancel,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn next_event(stream: &mut ProgressStream) -> impl Future<Output = Option<ProgressEvent>> + '_ {
+        std::future::poll_fn(move |cx| Pin::new(&mut *stream).poll_next(cx))
+    }
+
+    #[tokio::test]
+    async fn test_remove_dir_all_async() {
+        let temp = std::env::temp_dir().join("win_rmdir_async_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("a/b")).unwrap();
+        fs::File::create(temp.join("a/b/file.txt")).unwrap();
+
+        let mut removal = remove_dir_all_async(&temp, RmOptions::default());
+        let mut progress = removal.progress().unwrap();
+
+        let report = removal.await.unwrap();
+        assert!(!temp.exists());
+        assert_eq!(report.dirs_deleted, 3);
+
+        let mut last = None;
+        while let Some(event) = next_event(&mut progress).await {
+            last = Some(event);
+        }
+        assert_eq!(last.unwrap().dirs_completed, 3);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_before_start() {
+        let temp = std::env::temp_dir().join("win_rmdir_async_cancel_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("a")).unwrap();
+
+        let token = CancelToken::new();
+        token.cancel();
+        let options = RmOptions {
+            cancel: Some(token),
+            ..Default::default()
+        };
+
+        let result = remove_dir_all_async(&temp, options).await;
+        assert!(matches!(result, Err(RmError::Cancelled)));
+        assert!(temp.exists());
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
@@ -0,0 +1,607 @@
+// Work broker: dependency tracking and work dispatch
+//
+// The broker releases each node of a forest once all of its children are done, leaves first.
+// Deletion schedules directories by path; other tree walkers (copying, chmod) can schedule
+// any node id the same way.
+
+use crate::tree::DirectoryTree;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Identifies a node the [`Broker`] schedules; implemented for every hashable, cloneable type
+pub trait NodeId: Clone + Eq + Hash {}
+
+impl<T: Clone + Eq + Hash> NodeId for T {}
+
+/// Why [`Broker::from_children`] refused its input: either would leave nodes waiting forever
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ForestError {
+    #[error("a node is listed as the child of more than one parent")]
+    SharedChild,
+    #[error("the nodes' parent links form a cycle")]
+    Cycle,
+}
+
+/// Leaf-first scheduler: hands out nodes on a channel once all their children are finished
+///
+/// Nodes are directories (`PathBuf`) unless built with [`Broker::from_children`].
+pub struct Broker<T: NodeId = PathBuf> {
+    /// Map: node -> number of children still pending
+    child_counts: Mutex<HashMap<T, usize>>,
+    /// Map: node -> parent node
+    parent_map: Mutex<HashMap<T, T>>,
+    /// Channel sender for pushing work to workers (Option so we can drop it)
+    work_tx: Mutex<Option<Sender<T>>>,
+    /// Total nodes to process; grows as nodes are added to a running broker
+    total_dirs: std::sync::atomic::AtomicUsize,
+    /// Nodes completed, e.g. directories deleted or kept (atomic counter)
+    completed: std::sync::atomic::AtomicUsize,
+    /// Nodes that failed, e.g. directories that could not be removed
+    failed: std::sync::atomic::AtomicUsize,
+    /// Nodes finished in any way; the channel closes when this reaches the total
+    finished: std::sync::atomic::AtomicUsize,
+    /// Nodes left in place, e.g. directories that still hold kept entries
+    kept: std::sync::atomic::AtomicUsize,
+    /// Set once the run is cancelled; workers stop picking up queued nodes
+    cancelled: std::sync::atomic::AtomicBool,
+}
+
+impl Broker {
+    /// Create broker from DirectoryTree, returns (Broker, Sender to drop, Receiver for workers)
+    pub fn new(tree: DirectoryTree) -> (Self, Sender<PathBuf>, Receiver<PathBuf>) {
+        let total_dirs = tree.dirs.len();
+        Self::build(total_dirs, tree.children, tree.leaves)
+    }
+}
+
+impl<T: NodeId> Broker<T> {
+    /// Create a broker for the forest given as nodes with their children
+    ///
+    /// Nodes without children, whether listed with an empty `Vec` or only as someone's child,
+    /// are the leaves, dispatched right away in the order they first appear. A node listed
+    /// more than once counts once. Returns the broker, a sender to drop once workers are
+    /// started, and the workers' receiver.
+    pub fn from_children<C>(children: C) -> Result<(Self, Sender<T>, Receiver<T>), ForestError>
+    where
+        C: IntoIterator<Item = (T, Vec<T>)>,
+    {
+        // Every node, in the order of first appearance
+        let mut nodes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut child_lists: HashMap<T, Vec<T>> = HashMap::new();
+        let mut parents: HashMap<T, T> = HashMap::new();
+        for (parent, children) in children {
+            if seen.insert(parent.clone()) {
+                nodes.push(parent.clone());
+            }
+            for child in children {
+                match parents.get(&child) {
+                    Some(known) if *known == parent => continue,
+                    Some(_) => return Err(ForestError::SharedChild),
+                    None => {}
+                }
+                parents.insert(child.clone(), parent.clone());
+                child_lists
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(child.clone());
+                if seen.insert(child.clone()) {
+                    nodes.push(child);
+                }
+            }
+        }
+        let leaves: Vec<T> = nodes
+            .iter()
+            .filter(|node| !child_lists.contains_key(node))
+            .cloned()
+            .collect();
+
+        // Nodes on a cycle never run out of pending children
+        let mut pending: HashMap<&T, usize> =
+            child_lists.iter().map(|(n, c)| (n, c.len())).collect();
+        let mut ready: Vec<&T> = leaves.iter().collect();
+        let mut reached = 0;
+        while let Some(node) = ready.pop() {
+            reached += 1;
+            if let Some(parent) = parents.get(node) {
+                let count = pending.get_mut(parent).expect("parents have children");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(parent);
+                }
+            }
+        }
+        if reached != nodes.len() {
+            return Err(ForestError::Cycle);
+        }
+
+        Ok(Self::build(nodes.len(), child_lists, leaves))
+    }
+
+    fn build<C>(total_dirs: usize, children: C, leaves: Vec<T>) -> (Self, Sender<T>, Receiver<T>)
+    where
+        C: IntoIterator<Item = (T, Vec<T>)>,
+    {
+        let (tx, rx) = unbounded();
+
+        let mut child_counts = HashMap::new();
+        let mut parent_map = HashMap::new();
+
+        // Build parent map and initialize child counts
+        for (parent, children) in children {
+            child_counts.insert(parent.clone(), children.len());
+            for child in children {
+                parent_map.insert(child, parent.clone());
+            }
+        }
+
+        let broker = Self {
+            child_counts: Mutex::new(child_counts),
+            parent_map: Mutex::new(parent_map),
+            work_tx: Mutex::new(Some(tx.clone())),
+            total_dirs: std::sync::atomic::AtomicUsize::new(total_dirs),
+            completed: std::sync::atomic::AtomicUsize::new(0),
+            failed: std::sync::atomic::AtomicUsize::new(0),
+            finished: std::sync::atomic::AtomicUsize::new(0),
+            kept: std::sync::atomic::AtomicUsize::new(0),
+            cancelled: std::sync::atomic::AtomicBool::new(false),
+        };
+
+        // Push all initial leaves to work queue
+        for leaf in leaves {
+            if let Some(ref tx) = *broker.work_tx.lock().unwrap() {
+                tx.send(leaf).ok();
+            }
+        }
+
+        (broker, tx, rx)
+    }
+
+    /// Mark a node (a directory: deleted) as done, dispatching its parent once that has no
+    /// other children pending
+    pub fn mark_complete(&self, dir: T) {
+        self.completed
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.finish(dir);
+    }
+
+    /// Mark a node as done but left in place (a directory that still holds kept entries)
+    ///
+    /// The parent is still dispatched, so a directory's own selected files get deleted.
+    pub fn mark_kept(&self, dir: T) {
+        self.kept.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.mark_complete(dir);
+    }
+
+    /// Mark a node as failed (a directory that could not be removed)
+    ///
+    /// The parent is still dispatched so the run always terminates; a directory's removal
+    /// will then fail too, but its own files still get deleted.
+    pub fn mark_failed(&self, dir: T) {
+        self.failed
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.finish(dir);
+    }
+
+    /// Add a node to a running broker, dispatched right away
+    ///
+    /// Its parent then waits for it too. The parent must not be finished: it is either still
+    /// waiting for children, open (see [`Broker::add_open_node`]), or being worked on, in
+    /// which case it is dispatched again once the new child is done instead of being marked
+    /// finished now (how a directory that gained entries after the scan gets another pass).
+    /// Returns false, adding nothing, once the broker is cancelled or done.
+    pub fn add_node(&self, node: T, parent: Option<T>) -> bool {
+        if !self.add(node.clone(), parent, 0) {
+            return false;
+        }
+        self.dispatch(node);
+        true
+    }
+
+    /// Add a node that is not dispatched until [`Broker::close_node`], so children can be
+    /// added under it first, e.g. while a scan is still listing it
+    ///
+    /// An open node keeps the broker from finishing. Returns false, adding nothing, once the
+    /// broker is cancelled or done.
+    pub fn add_open_node(&self, node: T, parent: Option<T>) -> bool {
+        self.add(node, parent, 1)
+    }
+
+    /// Stop adding children under an open node; it is dispatched once they are all finished
+    pub fn close_node(&self, node: T) {
+        self.release(node);
+    }
+
+    fn add(&self, node: T, parent: Option<T>, holds: usize) -> bool {
+        // Nothing can finish the run while this lock is held
+        let work_tx = self.work_tx.lock().unwrap();
+        if work_tx.is_none() {
+            return false;
+        }
+        self.total_dirs
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if holds > 0 {
+            // Children may have been registered under it already
+            *self
+                .child_counts
+                .lock()
+                .unwrap()
+                .entry(node.clone())
+                .or_insert(0) += holds;
+        }
+        if let Some(parent) = parent {
+            *self
+                .child_counts
+                .lock()
+                .unwrap()
+                .entry(parent.clone())
+                .or_insert(0) += 1;
+            self.parent_map.lock().unwrap().insert(node, parent);
+        }
+        true
+    }
+
+    fn dispatch(&self, node: T) {
+        if let Some(ref tx) = *self.work_tx.lock().unwrap() {
+            tx.send(node).ok();
+        }
+    }
+
+    fn finish(&self, dir: T) {
+        let parent = self.parent_map.lock().unwrap().get(&dir).cloned();
+        {
+            let mut work_tx = self.work_tx.lock().unwrap();
+            let finished = self
+                .finished
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                + 1;
+            // Check if all work is done - if so, close the channel
+            if finished >= self.total_dirs() {
+                *work_tx = None; // Drop sender to close channel
+                return;
+            }
+        }
+        if let Some(parent) = parent {
+            self.release(parent);
+        }
+    }
+
+    /// Count one child (or the open hold) of `parent` as done, dispatching it at zero
+    fn release(&self, parent: T) {
+        let mut counts = self.child_counts.lock().unwrap();
+
+        if let Some(count) = counts.get_mut(&parent) {
+            *count -= 1;
+
+            // If parent now has no pending children, it becomes a leaf
+            if *count == 0 {
+                counts.remove(&parent);
+                drop(counts); // Release lock before sending
+                self.dispatch(parent);
+            }
+        }
+    }
+
+    /// Stop dispatching work; workers exit after their current node
+    pub fn cancel(&self) {
+        self.cancelled
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        *self.work_tx.lock().unwrap() = None;
+    }
+
+    /// Check whether the run was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Get the number of nodes waiting for children (for monitoring)
+    pub fn pending_count(&self) -> usize {
+        self.child_counts.lock().unwrap().len()
+    }
+
+    /// True once every node has been completed, kept or failed
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(std::sync::atomic::Ordering::SeqCst) >= self.total_dirs()
+    }
+
+    /// Get number of failed nodes (directories that could not be removed)
+    pub fn failed_count(&self) -> usize {
+        self.failed.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Get number of completed nodes
+    pub fn completed_count(&self) -> usize {
+        self.completed.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Get number of nodes kept in place
+    pub fn kept_count(&self) -> usize {
+        self.kept.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Get total nodes
+    pub fn total_dirs(&self) -> usize {
+        self.total_dirs.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::DirectoryTree;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_broker_simple_tree() {
+        // Create tree:
+        //   root
+        //     a (leaf)
+        //     b (leaf)
+
+        let root = PathBuf::from("/root");
+        let a = PathBuf::from("/root/a");
+        let b = PathBuf::from("/root/b");
+
+        let mut tree = DirectoryTree::new();
+        tree.dirs = vec![root.clone(), a.clone(), b.clone()];
+        tree.leaves = vec![a.clone(), b.clone()];
+
+        let mut children = HashMap::new();
+        children.insert(root.clone(), vec![a.clone(), b.clone()]);
+        tree.children = children;
+
+        let (broker, tx, rx) = Broker::new(tree);
+
+        // Should immediately dispatch both leaves
+        assert_eq!(rx.recv().unwrap(), a);
+        assert_eq!(rx.recv().unwrap(), b);
+
+        drop(tx); // Drop sender so we can detect channel closure
+
+        // Mark a complete
+        broker.mark_complete(a);
+
+        // Root still has 1 child pending
+        assert!(rx.try_recv().is_err());
+
+        // Mark b complete
+        broker.mark_complete(b);
+
+        // Now root should be dispatched
+        assert_eq!(rx.recv().unwrap(), root);
+    }
+
+    #[test]
+    fn test_broker_deep_tree() {
+        // Create tree: root -> a -> b -> c
+        let root = PathBuf::from("/root");
+        let a = PathBuf::from("/root/a");
+        let b = PathBuf::from("/root/a/b");
+        let c = PathBuf::from("/root/a/b/c");
+
+        let mut tree = DirectoryTree::new();
+        tree.dirs = vec![root.clone(), a.clone(), b.clone(), c.clone()];
+        tree.leaves = vec![c.clone()];
+
+        let mut children = HashMap::new();
+        children.insert(root.clone(), vec![a.clone()]);
+        children.insert(a.clone(), vec![b.clone()]);
+        children.insert(b.clone(), vec![c.clone()]);
+        tree.children = children;
+
+        let (broker, _tx, rx) = Broker::new(tree);
+
+        // Only leaf c dispatched initially
+        assert_eq!(rx.recv().unwrap(), c);
+
+        // Mark c complete -> b becomes available
+        broker.mark_complete(c.clone());
+        assert_eq!(rx.recv().unwrap(), b);
+
+        // Mark b complete -> a becomes available
+        broker.mark_complete(b.clone());
+        assert_eq!(rx.recv().unwrap(), a);
+
+        // Mark a complete -> root becomes available
+        broker.mark_complete(a.clone());
+        assert_eq!(rx.recv().unwrap(), root);
+    }
+
+    #[test]
+    fn test_broker_pending_count() {
+        let root = PathBuf::from("/root");
+        let a = PathBuf::from("/root/a");
+        let b = PathBuf::from("/root/b");
+
+        let mut tree = DirectoryTree::new();
+        tree.dirs = vec![root.clone(), a.clone(), b.clone()];
+        tree.leaves = vec![a.clone(), b.clone()];
+
+        let mut children = HashMap::new();
+        children.insert(root.clone(), vec![a.clone(), b.clone()]);
+        tree.children = children;
+
+        let (broker, _tx, _rx) = Broker::new(tree);
+
+        // Root has 2 children pending
+        assert_eq!(broker.pending_count(), 1);
+
+        broker.mark_complete(a);
+        assert_eq!(broker.pending_count(), 1);
+
+        broker.mark_complete(b);
+        // Root now has 0 children, should be removed from counts
+        assert_eq!(broker.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_broker_kept_dir_releases_parent() {
+        let root = PathBuf::from("/root");
+        let a = PathBuf::from("/root/a");
+
+        let mut tree = DirectoryTree::new();
+        tree.dirs = vec![root.clone(), a.clone()];
+        tree.leaves = vec![a.clone()];
+        tree.children.insert(root.clone(), vec![a.clone()]);
+
+        let (broker, _tx, rx) = Broker::new(tree);
+        assert_eq!(rx.recv().unwrap(), a);
+
+        // Parent still gets dispatched so its own files can be processed
+        broker.mark_kept(a);
+        assert_eq!(rx.recv().unwrap(), root);
+
+        broker.mark_kept(root);
+        assert_eq!(broker.kept_count(), 2);
+        assert_eq!(broker.completed_count(), 2);
+    }
+
+    #[test]
+    fn test_broker_cancel_stops_dispatch() {
+        let root = PathBuf::from("/root");
+        let a = PathBuf::from("/root/a");
+
+        let mut tree = DirectoryTree::new();
+        tree.dirs = vec![root.clone(), a.clone()];
+        tree.leaves = vec![a.clone()];
+        tree.children.insert(root.clone(), vec![a.clone()]);
+
+        let (broker, tx, rx) = Broker::new(tree);
+        drop(tx);
+        assert_eq!(rx.recv().unwrap(), a);
+
+        broker.cancel();
+        assert!(broker.is_cancelled());
+        broker.mark_complete(a);
+
+        // Parent is never dispatched and the channel is closed
+        assert!(rx.recv().is_err());
+    }
+
+    #[test]
+    fn test_broker_generic_nodes() {
+        // Two independent chains, 1 <- 2 <- 3 and 10 <- 11, as in a parallel copy that
+        // needs children finished before setting a parent's timestamps
+        let (broker, tx, rx) =
+            Broker::from_children([(1u32, vec![2]), (2, vec![3]), (10, vec![11])]).unwrap();
+        drop(tx);
+        assert_eq!(broker.total_dirs(), 5);
+        assert_eq!(rx.recv().unwrap(), 3);
+        assert_eq!(rx.recv().unwrap(), 11);
+
+        broker.mark_complete(11);
+        assert_eq!(rx.recv().unwrap(), 10);
+        broker.mark_complete(3);
+        assert_eq!(rx.recv().unwrap(), 2);
+        broker.mark_complete(2);
+        assert_eq!(rx.recv().unwrap(), 1);
+        broker.mark_complete(10);
+        broker.mark_complete(1);
+
+        assert!(broker.is_finished());
+        assert!(rx.recv().is_err());
+    }
+
+    #[test]
+    fn test_broker_add_nodes_while_running() {
+        // A scan feeding the broker as it goes: the root stays open while it is listed
+        let (broker, tx, rx) = Broker::from_children(Vec::new()).unwrap();
+        drop(tx);
+        assert!(broker.add_open_node("root", None));
+        assert!(broker.add_node("a", Some("root")));
+        assert!(broker.add_open_node("b", Some("root")));
+        broker.close_node("root");
+        assert_eq!(rx.recv().unwrap(), "a");
+        broker.mark_complete("a");
+        // b is still open, so neither b nor root is dispatched
+        assert!(rx.try_recv().is_err());
+
+        assert!(broker.add_node("b/c", Some("b")));
+        broker.close_node("b");
+        assert_eq!(rx.recv().unwrap(), "b/c");
+        broker.mark_complete("b/c");
+        assert_eq!(rx.recv().unwrap(), "b");
+
+        // b turned out to hold a new entry when it was removed: it gets another pass after it
+        assert!(broker.add_node("b/late", Some("b")));
+        assert_eq!(rx.recv().unwrap(), "b/late");
+        broker.mark_complete("b/late");
+        assert_eq!(rx.recv().unwrap(), "b");
+        broker.mark_complete("b");
+        assert_eq!(rx.recv().unwrap(), "root");
+        broker.mark_complete("root");
+
+        assert_eq!(broker.total_dirs(), 5);
+        assert!(broker.is_finished());
+        assert!(rx.recv().is_err());
+        assert!(!broker.add_node("too-late", None));
+    }
+
+    #[test]
+    fn test_broker_derives_leaves() {
+        // 2 has an empty child list, 3 appears only as a child, 4 is listed twice
+        let (broker, tx, rx) =
+            Broker::from_children([(1u32, vec![2, 3, 4]), (2, vec![]), (1, vec![4])]).unwrap();
+        drop(tx);
+        assert_eq!(broker.total_dirs(), 4);
+        let mut leaves = vec![rx.recv().unwrap(), rx.recv().unwrap(), rx.recv().unwrap()];
+        leaves.sort();
+        assert_eq!(leaves, vec![2, 3, 4]);
+        for leaf in leaves {
+            broker.mark_complete(leaf);
+        }
+        assert_eq!(rx.recv().unwrap(), 1);
+        broker.mark_complete(1);
+        assert!(broker.is_finished());
+
+        assert_eq!(
+            Broker::from_children([(1u32, vec![3]), (2, vec![3])]).err(),
+            Some(ForestError::SharedChild)
+        );
+        assert_eq!(
+            Broker::from_children([(1u32, vec![2]), (2, vec![1]), (3, vec![])]).err(),
+            Some(ForestError::Cycle)
+        );
+    }
+
+    #[test]
+    fn test_broker_open_node_after_children() {
+        // A child registered before its parent is opened must still hold the parent back
+        let (broker, tx, rx) = Broker::from_children(Vec::new()).unwrap();
+        drop(tx);
+        assert!(broker.add_node("p/c", Some("p")));
+        assert!(broker.add_open_node("p", None));
+        assert_eq!(rx.recv().unwrap(), "p/c");
+        broker.mark_complete("p/c");
+        assert!(rx.try_recv().is_err());
+        broker.close_node("p");
+        assert_eq!(rx.recv().unwrap(), "p");
+    }
+
+    #[test]
+    fn test_broker_failed_dir_releases_parent() {
+        let root = PathBuf::from("/root");
+        let a = PathBuf::from("/root/a");
+
+        let mut tree = DirectoryTree::new();
+        tree.dirs = vec![root.clone(), a.clone()];
+        tree.leaves = vec![a.clone()];
+        tree.children.insert(root.clone(), vec![a.clone()]);
+
+        let (broker, tx, rx) = Broker::new(tree);
+        drop(tx);
+        assert_eq!(rx.recv().unwrap(), a);
+
+        broker.mark_failed(a);
+        assert_eq!(rx.recv().unwrap(), root);
+        broker.mark_failed(root);
+
+        // Every directory finished, so the channel closes
+        assert!(rx.recv().is_err());
+        assert_eq!(broker.failed_count(), 2);
+        assert_eq!(broker.completed_count(), 0);
+    }
+}
@@ -0,0 +1,363 @@
+//! Error types for rmbrr
+
+use crate::pipeline::Report;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Result type for rmbrr operations
+pub type Result<T> = std::result::Result<T, RmError>;
+
+/// Main error type for rmbrr
+///
+/// Variants that come from the OS keep the original `io::Error` (and its os error code)
+/// as their source.
+#[derive(Debug, Error)]
+pub enum RmError {
+    /// The tree could not be scanned
+    #[error("Failed to scan '{}': {source}", path.display())]
+    ScanError { path: PathBuf, source: io::Error },
+    /// A file could not be deleted
+    #[error("Failed to delete file '{}': {source}", path.display())]
+    DeleteFileError { path: PathBuf, source: io::Error },
+    /// A directory could not be removed
+    #[error("Failed to remove directory '{}': {source}", path.display())]
+    RemoveDirError { path: PathBuf, source: io::Error },
+    /// The target is missing or not a directory
+    #[error("Invalid path '{}': {reason}", path.display())]
+    InvalidPath { path: PathBuf, reason: String },
+    /// The target failed safety checks (system directory, current directory, ...)
+    #[error("Refusing to delete '{}': {reason}", path.display())]
+    SafetyRefused {
+        path: PathBuf,
+        reason: String,
+        /// True if `force` would allow the deletion
+        can_override: bool,
+    },
+    /// Deletion was cancelled before it finished
+    #[error("Operation cancelled")]
+    Cancelled,
+    /// Partial deletion failure - some files/dirs failed; `stats.failures` lists them
+    #[error(
+        "Partial deletion failure: {}/{} items failed",
+        stats.failures.len(),
+        stats.total_items()
+    )]
+    PartialFailure { stats: Report },
+}
+
+/// Represents a single failed file or directory deletion
+#[derive(Debug, Clone)]
+pub struct FailedItem {
+    pub path: PathBuf,
+    pub error: String,
+    pub is_dir: bool,
+    /// Raw OS error code, if the failure came from the OS
+    pub os_error: Option<i32>,
+}
+
+impl FailedItem {
+    /// Record a failed deletion from the I/O error that caused it
+    ///
+    /// Errors raised by EFS, and denials under folders Controlled Folder Access guards, are
+    /// labelled as such, so they group apart from other causes. A denial there may still be
+    /// the ACL's doing, so it is only "possibly" blocked until
+    /// [`confirm_controlled_folder_access`] finds it in Defender's log.
+    pub fn from_io(path: PathBuf, err: &io::Error, is_dir: bool) -> Self {
+        let error = match err.raw_os_error() {
+            Some(code) if is_efs_error(code) => format!("EFS encryption metadata: {}", err),
+            Some(ERROR_ACCESS_DENIED) if crate::winapi::is_controlled_folder(&path) => {
+                format!("{}: {}", POSSIBLY_CONTROLLED_FOLDER_ACCESS, err)
+            }
+            _ => err.to_string(),
+        };
+        Self {
+            path,
+            error,
+            is_dir,
+            os_error: err.raw_os_error(),
+        }
+    }
+
+    /// True if Defender logged Controlled Folder Access blocking it
+    pub fn is_controlled_folder_access(&self) -> bool {
+        self.error.starts_with(CONTROLLED_FOLDER_ACCESS)
+    }
+
+    /// True if it was denied under a guarded folder, but not (yet) found in Defender's log
+    pub fn is_possibly_controlled_folder_access(&self) -> bool {
+        self.error.starts_with(POSSIBLY_CONTROLLED_FOLDER_ACCESS)
+    }
+
+    /// Convert back into a structured error, restoring the OS error where known
+    pub fn to_error(&self) -> RmError {
+        let source = match self.os_error {
+            Some(code) => io::Error::from_raw_os_error(code),
+            None => io::Error::other(self.error.clone()),
+        };
+        let path = self.path.clone();
+        if self.is_dir {
+            RmError::RemoveDirError { path, source }
+        } else {
+            RmError::DeleteFileError { path, source }
+        }
+    }
+}
+
+/// Label of failures Controlled Folder Access is known to have blocked
+pub const CONTROLLED_FOLDER_ACCESS: &str = "Blocked by Controlled Folder Access";
+
+/// Label of access denials under folders guarded by Controlled Folder Access
+pub const POSSIBLY_CONTROLLED_FOLDER_ACCESS: &str = "Possibly blocked by Controlled Folder Access";
+
+/// Relabel the possible Controlled Folder Access denials among `failures` that Defender
+/// logged blocking (event 1123) within the last `window`
+///
+/// Reading Defender's log may need elevation; without it they stay "possibly" blocked.
+pub fn confirm_controlled_folder_access(failures: &mut [FailedItem], window: Duration) {
+    if !failures
+        .iter()
+        .any(FailedItem::is_possibly_controlled_folder_access)
+    {
+        return;
+    }
+    let blocked = crate::winapi::controlled_folder_blocks(window);
+    for failure in failures
+        .iter_mut()
+        .filter(|f| f.is_possibly_controlled_folder_access())
+    {
+        if blocked
+            .iter()
+            .any(|path| crate::winapi::is_within(&failure.path, path))
+        {
+            let rest = &failure.error[POSSIBLY_CONTROLLED_FOLDER_ACCESS.len()..];
+            failure.error = format!("{}{}", CONTROLLED_FOLDER_ACCESS, rest);
+        }
+    }
+}
+
+/// `ERROR_ACCESS_DENIED`, which is all Controlled Folder Access answers
+const ERROR_ACCESS_DENIED: i32 = 5;
+
+/// Windows error codes raised by the Encrypting File System (`ERROR_ENCRYPTION_FAILED`
+/// through `ERROR_WIP_ENCRYPTION_FAILED`)
+pub fn is_efs_error(code: i32) -> bool {
+    (6000..=6023).contains(&code)
+}
+
+/// Error classes [`IgnoredErrors::parse`] accepts by name, with the OS error codes of each
+#[cfg(windows)]
+pub const ERROR_CLASSES: &[(&str, &[i32])] = &[
+    ("ACCESS_DENIED", &[5]),
+    ("NOT_FOUND", &[2, 3]),
+    ("SHARING_VIOLATION", &[32]),
+    ("LOCK_VIOLATION", &[33]),
+    ("DIR_NOT_EMPTY", &[145]),
+    ("PATH_TOO_LONG", &[206]),
+];
+
+/// Error classes [`IgnoredErrors::parse`] accepts by name, with the OS error codes of each
+#[cfg(unix)]
+pub const ERROR_CLASSES: &[(&str, &[i32])] = &[
+    ("ACCESS_DENIED", &[libc::EACCES, libc::EPERM]),
+    ("NOT_FOUND", &[libc::ENOENT]),
+    ("SHARING_VIOLATION", &[libc::EBUSY, libc::ETXTBSY]),
+    ("LOCK_VIOLATION", &[libc::ENOLCK]),
+    ("DIR_NOT_EMPTY", &[libc::ENOTEMPTY]),
+    ("PATH_TOO_LONG", &[libc::ENAMETOOLONG]),
+];
+
+/// Error classes [`IgnoredErrors::parse`] accepts by name, with the OS error codes of each
+#[cfg(not(any(windows, unix)))]
+pub const ERROR_CLASSES: &[(&str, &[i32])] = &[];
+
+/// OS error codes whose failures are counted but never retried, and don't fail the run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoredErrors(Vec<i32>);
+
+impl IgnoredErrors {
+    pub fn new(codes: Vec<i32>) -> Self {
+        Self(codes)
+    }
+
+    /// Parse a comma-separated list of error class names (see [`ERROR_CLASSES`], with or
+    /// without an `ERROR_` prefix) and raw OS error codes
+    pub fn parse(list: &str) -> std::result::Result<Self, String> {
+        let mut codes = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            if let Ok(code) = name.parse::<i32>() {
+                codes.push(code);
+                continue;
+            }
+            let upper = name.to_ascii_uppercase();
+            let class = upper.strip_prefix("ERROR_").unwrap_or(&upper);
+            match ERROR_CLASSES.iter().find(|(n, _)| *n == class) {
+                Some((_, class_codes)) => codes.extend_from_slice(class_codes),
+                None => {
+                    let known: Vec<_> = ERROR_CLASSES.iter().map(|(n, _)| *n).collect();
+                    return Err(format!(
+                        "unknown error class '{}' (expected an OS error code or one of {})",
+                        name,
+                        known.join(", ")
+                    ));
+                }
+            }
+        }
+        if codes.is_empty() {
+            return Err("no error classes given".to_string());
+        }
+        Ok(Self(codes))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether a failure with this OS error code is ignored
+    pub fn contains(&self, os_error: Option<i32>) -> bool {
+        os_error.is_some_and(|code| self.0.contains(&code))
+    }
+}
+
+/// What to do about an entry that could not be deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Leave it and carry on; it is reported with the other failures at the end
+    Skip,
+    /// Try deleting it again (each attempt goes through the retry policy first)
+    Retry,
+    /// Stop the whole deletion
+    Abort,
+}
+
+/// Decides what happens after a failure (see [`ErrorStrategy::Prompt`])
+pub type ErrorHandler = Arc<dyn Fn(&FailedItem) -> Decision + Send + Sync>;
+
+/// What to do when a file or directory cannot be deleted
+#[derive(Clone, Default)]
+pub enum ErrorStrategy {
+    /// Stop the whole deletion at the first failure
+    FailFast,
+    /// Record the failure and keep going; failures are reported at the end
+    #[default]
+    Continue,
+    /// Ask the handler after each failure whether to skip the entry, retry it or stop.
+    /// Called from worker threads, possibly concurrently
+    Prompt(ErrorHandler),
+}
+
+impl ErrorStrategy {
+    /// What to do now that `item` failed
+    pub fn decide(&self, item: &FailedItem) -> Decision {
+        match self {
+            ErrorStrategy::FailFast => Decision::Abort,
+            ErrorStrategy::Continue => Decision::Skip,
+            ErrorStrategy::Prompt(handler) => handler(item),
+        }
+    }
+}
+
+impl fmt::Debug for ErrorStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorStrategy::FailFast => f.write_str("FailFast"),
+            ErrorStrategy::Continue => f.write_str("Continue"),
+            ErrorStrategy::Prompt(_) => f.write_str("Prompt(..)"),
+        }
+    }
+}
+
+impl RmError {
+    /// Get the exit code for this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RmError::ScanError { .. }
+            | RmError::DeleteFileError { .. }
+            | RmError::RemoveDirError { .. } => 2,
+            RmError::InvalidPath { .. } => 1,
+            RmError::SafetyRefused { .. } => 1,
+            RmError::PartialFailure { .. } => 1,
+            RmError::Cancelled => 130,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_item_round_trip() {
+        let err = io::Error::from(io::ErrorKind::PermissionDenied);
+        let item = FailedItem::from_io(PathBuf::from("/x/file"), &err, false);
+        assert_eq!(item.os_error, None);
+        assert!(matches!(item.to_error(), RmError::DeleteFileError { .. }));
+
+        let os_err = io::Error::from_raw_os_error(2);
+        let item = FailedItem::from_io(PathBuf::from("/x"), &os_err, true);
+        assert_eq!(item.os_error, Some(2));
+        let RmError::RemoveDirError { source, .. } = item.to_error() else {
+            panic!("expected RemoveDirError");
+        };
+        assert_eq!(source.raw_os_error(), Some(2));
+        assert_eq!(item.to_error().exit_code(), 2);
+    }
+
+    #[test]
+    fn test_controlled_folder_access_unconfirmed() {
+        let mut failures = vec![FailedItem {
+            path: PathBuf::from("/home/me/Documents/a.txt"),
+            error: format!("{}: Access is denied.", POSSIBLY_CONTROLLED_FOLDER_ACCESS),
+            is_dir: false,
+            os_error: Some(ERROR_ACCESS_DENIED),
+        }];
+        assert!(failures[0].is_possibly_controlled_folder_access());
+        assert!(!failures[0].is_controlled_folder_access());
+        // Nothing in Defender's log (none at all off Windows) leaves it unconfirmed
+        confirm_controlled_folder_access(&mut failures, Duration::from_secs(60));
+        assert!(failures[0].is_possibly_controlled_folder_access());
+    }
+
+    #[test]
+    fn test_efs_errors_labelled() {
+        // ERROR_NO_USER_KEYS
+        let err = io::Error::from_raw_os_error(6006);
+        let item = FailedItem::from_io(PathBuf::from("/x/secret"), &err, false);
+        assert!(item.error.starts_with("EFS encryption metadata: "));
+        assert_eq!(item.os_error, Some(6006));
+        assert!(!is_efs_error(5));
+    }
+
+    #[test]
+    fn test_error_strategy() {
+        let item = FailedItem::from_io(PathBuf::from("/x"), &io::Error::other("busy"), false);
+        assert_eq!(ErrorStrategy::FailFast.decide(&item), Decision::Abort);
+        assert_eq!(ErrorStrategy::default().decide(&item), Decision::Skip);
+
+        let prompt = ErrorStrategy::Prompt(Arc::new(|item| {
+            if item.is_dir {
+                Decision::Skip
+            } else {
+                Decision::Retry
+            }
+        }));
+        assert_eq!(prompt.decide(&item), Decision::Retry);
+        assert_eq!(format!("{:?}", prompt), "Prompt(..)");
+    }
+
+    #[test]
+    fn test_ignored_errors() {
+        let ignored = IgnoredErrors::parse("access_denied, ERROR_SHARING_VIOLATION,1234").unwrap();
+        let denied = ERROR_CLASSES[0].1[0];
+        assert!(ignored.contains(Some(denied)));
+        assert!(ignored.contains(Some(1234)));
+        assert!(!ignored.contains(Some(ERROR_CLASSES[1].1[0])));
+        assert!(!ignored.contains(None));
+        assert!(IgnoredErrors::parse("DISK_ON_FIRE").is_err());
+        assert!(IgnoredErrors::parse(" , ").is_err());
+        assert!(IgnoredErrors::default().is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+//! Events emitted while a deletion runs, for progress displays and machine-readable output
+
+use crate::error::FailedItem;
+use crossbeam_channel::{Receiver, Sender};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A single step of a deletion, sent by the pipeline and its workers as it happens
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The scan finished and deletion is starting
+    Started {
+        root: PathBuf,
+        dirs_total: usize,
+        files_total: usize,
+    },
+    /// A worker picked up a directory (its files are deleted next, then the directory)
+    DirStarted { worker: usize, path: PathBuf },
+    /// A directory was handled; `kept` is set when a filter left it in place
+    DirDone { path: PathBuf, kept: bool },
+    /// A file was deleted
+    FileDone { path: PathBuf },
+    /// A file or directory could not be deleted
+    Error(FailedItem),
+    /// All workers have stopped
+    Finished {
+        root: PathBuf,
+        dirs_deleted: usize,
+        files_deleted: usize,
+        failed: usize,
+        elapsed: Duration,
+        cancelled: bool,
+    },
+}
+
+pub type EventSender = Sender<Event>;
+pub type EventReceiver = Receiver<Event>;
+
+/// Create an unbounded event channel
+pub fn channel() -> (EventSender, EventReceiver) {
+    crossbeam_channel::unbounded()
+}
+
+/// Send an event if anyone is listening; a dropped receiver is not an error
+pub fn emit(events: &Option<EventSender>, event: Event) {
+    if let Some(tx) = events {
+        tx.send(event).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_without_receiver() {
+        let (tx, rx) = channel();
+        let events = Some(tx);
+        emit(&events, Event::FileDone { path: "a".into() });
+        assert!(matches!(rx.try_recv(), Ok(Event::FileDone { .. })));
+
+        drop(rx);
+        emit(&events, Event::FileDone { path: "b".into() });
+        emit(&None, Event::FileDone { path: "c".into() });
+    }
+}
@@ -0,0 +1,170 @@
+//! C-compatible interface to the deletion engine (feature `ffi`)
+//!
+//! Build a shared library with:
+//! `cargo rustc --release -p rmbrr-core --lib --features ffi --crate-type cdylib`
+//! and include `include/rmbrr.h`.
+
+use crate::error::RmError;
+use crate::pipeline::{self, RmOptions};
+use std::ffi::{c_char, c_void, CStr};
+use std::path::Path;
+
+/// Invalid arguments (null or non-UTF-8 path)
+pub const RMBRR_INVALID_ARGUMENT: i32 = -1;
+
+/// Deletion options; a null pointer means defaults
+#[repr(C)]
+pub struct RmbrrOptions {
+    /// Worker threads (0 = logical CPU count)
+    pub threads: u32,
+    /// Scan only, delete nothing
+    pub dry_run: bool,
+    /// Allow dangerous (but not system) paths
+    pub force: bool,
+}
+
+/// Progress snapshot passed to the callback
+#[repr(C)]
+pub struct RmbrrProgress {
+    pub dirs_completed: u64,
+    pub dirs_total: u64,
+    pub files_deleted: u64,
+    pub dirs_failed: u64,
+}
+
+/// Final counts written to the caller's result struct
+#[repr(C)]
+pub struct RmbrrResult {
+    pub dirs_deleted: u64,
+    pub files_deleted: u64,
+    pub failed: u64,
+}
+
+/// Progress callback; invoked on the calling thread
+pub type RmbrrProgressCallback = Option<extern "C" fn(*const RmbrrProgress, *mut c_void)>;
+
+/// Delete a directory tree
+///
+/// Returns 0 on success, the CLI exit code on failure (1 = invalid path or partial failure,
+/// 2 = I/O error) or `RMBRR_INVALID_ARGUMENT`.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated UTF-8 string. `options` and `result` must be null
+/// or valid pointers. `user_data` is passed through to `progress` untouched.
+#[no_mangle]
+pub unsafe extern "C" fn rmbrr_delete(
+    path: *const c_char,
+    options: *const RmbrrOptions,
+    progress: RmbrrProgressCallback,
+    user_data: *mut c_void,
+    result: *mut RmbrrResult,
+) -> i32 {
+    if path.is_null() {
+        return RMBRR_INVALID_ARGUMENT;
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return RMBRR_INVALID_ARGUMENT;
+    };
+
+    let options = match options.as_ref() {
+        Some(o) => RmOptions {
+            threads: (o.threads > 0).then_some(o.threads as usize),
+            dry_run: o.dry_run,
+            force: o.force,
+            ..Default::default()
+        },
+        None => RmOptions::default(),
+    };
+
+    let outcome = pipeline::remove_dir_all_with_progress(Path::new(path), &options, |update| {
+        if let Some(callback) = progress {
+            let snapshot = RmbrrProgress {
+                dirs_completed: update.dirs_completed as u64,
+                dirs_total: update.dirs_total as u64,
+                files_deleted: update.files_deleted as u64,
+                dirs_failed: update.dirs_failed as u64,
+            };
+            callback(&snapshot, user_data);
+        }
+    });
+
+    let (report, code) = match outcome {
+        Ok(report) => (report, 0),
+        Err(e) => {
+            let code = e.exit_code();
+            match e {
+                // Counts are still meaningful when only some entries failed
+                RmError::PartialFailure { stats } => (stats, code),
+                _ => (Default::default(), code),
+            }
+        }
+    };
+    let counts = RmbrrResult {
+        dirs_deleted: report.dirs_deleted as u64,
+        files_deleted: report.files_deleted as u64,
+        failed: report.failures.len() as u64,
+    };
+
+    if let Some(result) = result.as_mut() {
+        *result = counts;
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::fs;
+
+    extern "C" fn count_calls(_progress: *const RmbrrProgress, user_data: *mut c_void) {
+        unsafe { *(user_data as *mut u32) += 1 };
+    }
+
+    #[test]
+    fn test_rmbrr_delete() {
+        let temp = std::env::temp_dir().join("win_rmdir_ffi_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("a/b")).unwrap();
+        fs::File::create(temp.join("a/b/file.txt")).unwrap();
+
+        let path = CString::new(temp.to_str().unwrap()).unwrap();
+        let mut calls = 0u32;
+        let mut result = RmbrrResult {
+            dirs_deleted: 0,
+            files_deleted: 0,
+            failed: 0,
+        };
+
+        let code = unsafe {
+            rmbrr_delete(
+                path.as_ptr(),
+                std::ptr::null(),
+                Some(count_calls),
+                &mut calls as *mut u32 as *mut c_void,
+                &mut result,
+            )
+        };
+
+        assert_eq!(code, 0);
+        assert!(!temp.exists());
+        assert_eq!(result.dirs_deleted, 3);
+        assert_eq!(result.files_deleted, 1);
+        assert!(calls >= 1);
+    }
+
+    #[test]
+    fn test_rmbrr_delete_null_path() {
+        let code = unsafe {
+            rmbrr_delete(
+                std::ptr::null(),
+                std::ptr::null(),
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(code, RMBRR_INVALID_ARGUMENT);
+    }
+}
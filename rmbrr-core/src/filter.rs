@@ -0,0 +1,392 @@
+// File selection for partial deletions (--only, --except, --keep, --older-than,
+// --larger-than)
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// A glob pattern matched against file names or root-relative paths
+///
+/// `*` matches within one path component, `**` matches across components and `?`
+/// matches a single character. Patterns without a `/` match the file name only.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    glob: Vec<char>,
+    match_path: bool,
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Self {
+        let normalized = pattern.trim().replace('\\', "/");
+        let normalized = normalized.trim_start_matches("./");
+        Self {
+            match_path: normalized.contains('/'),
+            glob: normalized.chars().collect(),
+        }
+    }
+
+    /// Check a root-relative path against the pattern
+    pub fn matches(&self, relative: &Path) -> bool {
+        let text: Vec<char> = if self.match_path {
+            relative
+                .to_string_lossy()
+                .replace('\\', "/")
+                .chars()
+                .collect()
+        } else {
+            match relative.file_name() {
+                Some(name) => name.to_string_lossy().chars().collect(),
+                None => return false,
+            }
+        };
+        glob_match(&self.glob, &text)
+    }
+}
+
+// Case-sensitive directories (WSL) can hold both `Foo` and `foo`; matching ignores case on
+// Windows regardless, so such pairs are kept or selected together, never one by mistake
+fn chars_eq(a: char, b: char) -> bool {
+    if cfg!(windows) {
+        a.eq_ignore_ascii_case(&b)
+    } else {
+        a == b
+    }
+}
+
+fn components_eq(a: &std::path::Component, b: &std::path::Component) -> bool {
+    if cfg!(windows) {
+        a.as_os_str().to_string_lossy().to_lowercase()
+            == b.as_os_str().to_string_lossy().to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+/// Check whether `path` is `prefix` or lies beneath it
+fn path_starts_with(path: &Path, prefix: &Path) -> bool {
+    let mut path = path.components();
+    prefix
+        .components()
+        .all(|p| path.next().is_some_and(|c| components_eq(&c, &p)))
+}
+
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // `**/` may also match zero directories
+            if rest.first() == Some(&'/') && glob_match(&rest[1..], text) {
+                return true;
+            }
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => match text.first() {
+            Some(&c) if c != '/' => glob_match(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some(&p) => match text.first() {
+            Some(&c) if chars_eq(p, c) => glob_match(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+    }
+}
+
+/// Decides which files under a root are deleted
+///
+/// When a filter is selective, directories are only removed if they end up empty.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    root: PathBuf,
+    /// Only files matching one of these patterns are deleted (empty = all files)
+    pub only: Vec<Pattern>,
+    /// Files and directories matching one of these patterns are kept, directories with
+    /// everything under them
+    pub except: Vec<Pattern>,
+    /// Root-relative files and subtrees that are never deleted
+    pub keep: Vec<PathBuf>,
+    /// Files modified more recently than this are kept
+    pub min_age: Option<Duration>,
+    /// Files of this many bytes or fewer are kept
+    pub min_size: Option<u64>,
+    /// Empty the root but never remove the root directory itself
+    pub keep_root: bool,
+}
+
+impl Filter {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            only: Vec::new(),
+            except: Vec::new(),
+            keep: Vec::new(),
+            min_age: None,
+            min_size: None,
+            keep_root: false,
+        }
+    }
+
+    /// Check whether a directory is the filter's root
+    pub fn is_root(&self, dir: &Path) -> bool {
+        dir == self.root
+    }
+
+    /// Add a path to keep, given relative to the root (absolute paths under the root also work)
+    pub fn add_keep(&mut self, path: &Path) {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let normalized: PathBuf = relative
+            .components()
+            .filter(|c| !matches!(c, std::path::Component::CurDir))
+            .collect();
+        if !normalized.as_os_str().is_empty() {
+            self.keep.push(normalized);
+        }
+    }
+
+    /// Check whether a path is kept, either directly or inside a kept subtree
+    pub fn is_kept(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        self.keep.iter().any(|k| path_starts_with(relative, k))
+            || self.except.iter().any(|p| p.matches(relative))
+    }
+
+    /// True if some entries may be left behind (directories must then be emptied first)
+    pub fn is_selective(&self) -> bool {
+        !self.only.is_empty()
+            || !self.except.is_empty()
+            || !self.keep.is_empty()
+            || self.min_age.is_some()
+            || self.min_size.is_some()
+            || self.keep_root
+    }
+
+    /// Decide whether a file should be deleted
+    pub fn should_delete_file(&self, path: &Path) -> bool {
+        if self.is_kept(path) {
+            return false;
+        }
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        if !self.only.is_empty() && !self.only.iter().any(|p| p.matches(relative)) {
+            return false;
+        }
+        if self
+            .min_size
+            .is_some_and(|min_size| !is_larger_than(path, min_size))
+        {
+            return false;
+        }
+        match self.min_age {
+            Some(min_age) => is_older_than(path, min_age),
+            None => true,
+        }
+    }
+}
+
+/// Check a file's size; files that can't be inspected count as small
+fn is_larger_than(path: &Path, min_size: u64) -> bool {
+    path.symlink_metadata().is_ok_and(|m| m.len() > min_size)
+}
+
+/// Check a file's modification time; files that can't be inspected count as recent
+fn is_older_than(path: &Path, min_age: Duration) -> bool {
+    path.symlink_metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= min_age)
+}
+
+/// Parse an age such as `30m`, `12h`, `7d` or `2w` (a bare number means days)
+pub fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid age '{}' (expected e.g. 12h, 7d)", value))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown age unit '{}' (use s, m, h, d or w)", unit)),
+    };
+    // Wrapping around would turn a huge age into a small one, selecting recent files
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("age '{}' is too large", value))
+}
+
+/// Parse a byte size like 512K, 50M or 2G (binary multiples); a bare number is bytes
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}' (expected e.g. 512K, 50M)", value))?;
+    let shift = match unit
+        .to_ascii_uppercase()
+        .trim_end_matches("IB")
+        .trim_end_matches('B')
+    {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        _ => return Err(format!("unknown size unit '{}' (use K, M, G or T)", unit)),
+    };
+    number
+        .checked_mul(1 << shift)
+        .ok_or_else(|| format!("size '{}' is too large", value))
+}
+
+/// Parse a comma-separated pattern list, ignoring empty entries
+pub fn parse_patterns<S: AsRef<str>>(values: &[S]) -> Vec<Pattern> {
+    values
+        .iter()
+        .flat_map(|v| v.as_ref().split(','))
+        .filter(|p| !p.trim().is_empty())
+        .map(Pattern::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_patterns() {
+        let tmp = Pattern::new("*.tmp");
+        assert!(tmp.matches(Path::new("a.tmp")));
+        assert!(tmp.matches(Path::new("deep/dir/b.tmp")));
+        assert!(!tmp.matches(Path::new("a.tmp.bak")));
+        assert!(Pattern::new("log?.txt").matches(Path::new("x/log1.txt")));
+    }
+
+    #[test]
+    fn test_path_patterns() {
+        let pattern = Pattern::new("build/*.o");
+        assert!(pattern.matches(Path::new("build/a.o")));
+        assert!(!pattern.matches(Path::new("build/sub/a.o")));
+
+        let pattern = Pattern::new("build/**/*.o");
+        assert!(pattern.matches(Path::new("build/sub/deeper/a.o")));
+        assert!(pattern.matches(Path::new("build/a.o")));
+    }
+
+    #[test]
+    fn test_filter_only() {
+        let mut filter = Filter::new(Path::new("/root"));
+        assert!(filter.should_delete_file(Path::new("/root/anything")));
+
+        filter.only = parse_patterns(&["*.tmp, *.log", ""]);
+        assert!(filter.is_selective());
+        assert!(filter.should_delete_file(Path::new("/root/a/x.log")));
+        assert!(!filter.should_delete_file(Path::new("/root/a/x.txt")));
+    }
+
+    #[test]
+    fn test_filter_except() {
+        let mut filter = Filter::new(Path::new("/root"));
+        filter.except = parse_patterns(&["*.dat,important/**", "artifacts"]);
+        assert!(filter.is_selective());
+        assert!(!filter.should_delete_file(Path::new("/root/build/out.dat")));
+        assert!(!filter.should_delete_file(Path::new("/root/important/a/b.txt")));
+        assert!(filter.should_delete_file(Path::new("/root/build/out.o")));
+        // A matching directory is kept whole
+        assert!(filter.is_kept(Path::new("/root/build/artifacts")));
+    }
+
+    #[test]
+    fn test_filter_keep() {
+        let mut filter = Filter::new(Path::new("/root"));
+        filter.add_keep(Path::new("config/"));
+        filter.add_keep(Path::new("./cache/manifest.json"));
+        filter.add_keep(Path::new("/root/abs"));
+
+        assert!(filter.is_selective());
+        assert!(filter.is_kept(Path::new("/root/config")));
+        assert!(filter.is_kept(Path::new("/root/config/app/settings.toml")));
+        assert!(filter.is_kept(Path::new("/root/abs/x")));
+        assert!(!filter.is_kept(Path::new("/root/configuration")));
+        assert!(!filter.should_delete_file(Path::new("/root/cache/manifest.json")));
+        assert!(filter.should_delete_file(Path::new("/root/cache/other.bin")));
+    }
+
+    #[test]
+    fn test_min_age() {
+        let temp = std::env::temp_dir().join("win_rmdir_min_age_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        std::fs::create_dir_all(&temp).unwrap();
+        let fresh = temp.join("fresh.tmp");
+        std::fs::File::create(&fresh).unwrap();
+
+        let mut filter = Filter::new(&temp);
+        filter.min_age = Some(Duration::from_secs(3600));
+        assert!(filter.is_selective());
+        assert!(!filter.should_delete_file(&fresh));
+
+        filter.min_age = Some(Duration::ZERO);
+        assert!(filter.should_delete_file(&fresh));
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_min_size() {
+        let temp = std::env::temp_dir().join("win_rmdir_min_size_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        std::fs::create_dir_all(&temp).unwrap();
+        std::fs::write(temp.join("big.log"), [0u8; 2048]).unwrap();
+        std::fs::write(temp.join("app.conf"), "x=1").unwrap();
+
+        let mut filter = Filter::new(&temp);
+        filter.min_size = Some(1024);
+        assert!(filter.is_selective());
+        assert!(filter.should_delete_file(&temp.join("big.log")));
+        assert!(!filter.should_delete_file(&temp.join("app.conf")));
+        assert!(!filter.should_delete_file(&temp.join("missing")));
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_age("7"), Ok(Duration::from_secs(7 * 86400)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * 86400)));
+        assert!(parse_age("7y").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("99999999999999999w").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("100"), Ok(100));
+        assert_eq!(parse_size("512K"), Ok(512 * 1024));
+        assert_eq!(parse_size("50MB"), Ok(50 << 20));
+        assert_eq!(parse_size("2GiB"), Ok(2 << 30));
+        assert!(parse_size("5X").is_err());
+        assert!(parse_size("M").is_err());
+    }
+}
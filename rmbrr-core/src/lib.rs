@@ -0,0 +1,46 @@
+//! Parallel directory deletion engine used by the `rmbrr` CLI
+//!
+//! [`remove_dir_all`] scans a tree, then deletes it leaf-first on a pool of worker threads.
+//! On Windows, entries are removed with POSIX delete semantics so they leave the namespace
+//! immediately. The lower-level pieces ([`tree`], [`broker`], [`worker`]) can be combined
+//! directly for custom pipelines.
+//!
+//! ```no_run
+//! let report = rmbrr_core::remove_dir_all("target".as_ref(), &rmbrr_core::RmOptions::default())?;
+//! println!("{} files deleted", report.files_deleted);
+//! # Ok::<(), rmbrr_core::error::RmError>(())
+//! ```
+
+#[cfg(feature = "tokio")]
+pub mod async_api;
+pub mod broker;
+pub mod error;
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod manifest;
+pub mod output;
+pub mod pipeline;
+pub mod progress;
+#[cfg(feature = "python")]
+mod python;
+pub mod retry;
+pub mod safety;
+pub mod targets;
+pub mod trace;
+pub mod tree;
+pub mod tuner;
+pub mod winapi;
+pub mod worker;
+
+pub use error::{Decision, ErrorStrategy, FailedItem, RmError};
+pub use events::Event;
+pub use pipeline::{
+    remove_dir_all, remove_dir_all_with_progress, run_scoped, CancelToken, ProgressUpdate, Report,
+    RmOptions, ScopedHooks,
+};
+pub use retry::RetryPolicy;
+
+#[cfg(feature = "tokio")]
+pub use async_api::{remove_dir_all_async, ProgressEvent, ProgressStream, Removal};
@@ -0,0 +1,231 @@
+//! Compliance manifest: one JSON line per file, written just before the file is deleted
+//!
+//! Each line holds the path, size, timestamps and, if asked for, a content hash, giving an
+//! auditable record of what a run removed. Hashing runs on the worker threads that delete
+//! the files, so its cost is tracked separately ([`Manifest::hash_stats`]).
+
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Content hash recorded for each file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Name used in the manifest's `hash_algorithm` field
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+}
+
+/// Writes manifest lines from any thread
+pub struct Manifest {
+    writer: Mutex<Box<dyn Write + Send>>,
+    hash: Option<HashAlgorithm>,
+    hashed_bytes: AtomicU64,
+    /// Time spent hashing, summed over all threads
+    hash_nanos: AtomicU64,
+}
+
+impl Manifest {
+    /// Create (or truncate) the manifest file at `path`
+    ///
+    /// Lines go to the file unbuffered, each in one write, so a record is in the file before
+    /// its file is deleted.
+    pub fn create(path: &Path, hash: Option<HashAlgorithm>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::to_writer(Box::new(file), hash))
+    }
+
+    pub fn to_writer(writer: Box<dyn Write + Send>, hash: Option<HashAlgorithm>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            hash,
+            hashed_bytes: AtomicU64::new(0),
+            hash_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Record `path` as it is now; an error means the file must not be deleted unrecorded
+    ///
+    /// The line is flushed to the writer's destination before this returns Ok (handed to the
+    /// OS, not synced to disk), so a crash right after the deletion cannot lose it.
+    pub fn record(&self, path: &Path) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(path)?;
+        let mut line = String::from("{\"path\":");
+        // Absolute, so the record stands on its own whatever directory the run started in
+        let absolute = std::path::absolute(path)?;
+        push_json_string(&mut line, &absolute.to_string_lossy());
+        write!(line, ",\"size\":{}", metadata.len()).unwrap();
+        for (name, time) in [
+            ("modified", metadata.modified()),
+            ("accessed", metadata.accessed()),
+            ("created", metadata.created()),
+        ] {
+            match time.ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()) {
+                Some(since) => write!(line, ",\"{}\":{}", name, since.as_millis()).unwrap(),
+                None => write!(line, ",\"{}\":null", name).unwrap(),
+            }
+        }
+        // Links are recorded, not followed
+        if let Some(algorithm) = self.hash.filter(|_| metadata.is_file()) {
+            let hash = self.hash_file(path, algorithm)?;
+            write!(
+                line,
+                ",\"hash_algorithm\":\"{}\",\"hash\":\"{}\"",
+                algorithm.name(),
+                hash
+            )
+            .unwrap();
+        }
+        let recorded = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        writeln!(line, ",\"recorded\":{}}}", recorded.as_millis()).unwrap();
+
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(line.as_bytes())?;
+        writer.flush()
+    }
+
+    /// Flush buffered lines to the file
+    pub fn flush(&self) -> io::Result<()> {
+        self.writer.lock().unwrap().flush()
+    }
+
+    /// Bytes hashed so far and the thread time spent hashing them
+    pub fn hash_stats(&self) -> (u64, Duration) {
+        (
+            self.hashed_bytes.load(Ordering::Relaxed),
+            Duration::from_nanos(self.hash_nanos.load(Ordering::Relaxed)),
+        )
+    }
+
+    fn hash_file(&self, path: &Path, algorithm: HashAlgorithm) -> io::Result<String> {
+        let started = Instant::now();
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut total = 0u64;
+        let digest = match algorithm {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                loop {
+                    let read = file.read(&mut buffer)?;
+                    if read == 0 {
+                        break;
+                    }
+                    hasher.update(&buffer[..read]);
+                    total += read as u64;
+                }
+                hasher.finalize().to_vec()
+            }
+        };
+        self.hashed_bytes.fetch_add(total, Ordering::Relaxed);
+        self.hash_nanos
+            .fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+
+        let mut hex = String::with_capacity(digest.len() * 2);
+        for byte in digest {
+            write!(hex, "{:02x}", byte).unwrap();
+        }
+        Ok(hex)
+    }
+}
+
+/// Append `value` as a JSON string literal
+fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Collects written lines for inspection
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record_with_hash() {
+        let temp = std::env::temp_dir().join("win_rmdir_manifest_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+        let file = temp.join("report.txt");
+        fs::write(&file, "abc").unwrap();
+
+        let out = Shared::default();
+        let manifest = Manifest::to_writer(Box::new(out.clone()), Some(HashAlgorithm::Sha256));
+        manifest.record(&file).unwrap();
+        assert!(manifest.record(&temp.join("missing")).is_err());
+
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.contains("report.txt\","));
+        assert!(text.contains("\"size\":3,"));
+        assert!(text.contains(
+            "\"hash\":\"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\""
+        ));
+        assert_eq!(manifest.hash_stats().0, 3);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_record_flushes() {
+        let temp = std::env::temp_dir().join("win_rmdir_manifest_flush_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+        let file = temp.join("data.bin");
+        fs::write(&file, "x").unwrap();
+
+        // Nothing reaches `out` until the writer is flushed
+        let out = Shared::default();
+        let manifest = Manifest::to_writer(Box::new(io::BufWriter::new(out.clone())), None);
+        manifest.record(&file).unwrap();
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert!(text.contains("data.bin\","));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_json_string_escaping() {
+        let mut out = String::new();
+        push_json_string(&mut out, "C:\\a \"b\"\n\u{1}");
+        assert_eq!(out, r#""C:\\a \"b\"\n\u0001""#);
+    }
+}
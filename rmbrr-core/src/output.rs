@@ -0,0 +1,387 @@
+//! Terminal output helpers: ANSI colors and progress-line coordination
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Diagnostics of one kind written per `LOG_WINDOW`; the rest are only counted
+const LOG_BURST: usize = 20;
+const LOG_WINDOW: Duration = Duration::from_secs(1);
+/// Warnings held while a progress line is on screen; any more are only counted
+const PENDING_LIMIT: usize = 1000;
+
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(false);
+/// Whether a progress line is on screen, holding warnings back in `PENDING_WARNINGS`
+static PROGRESS_ACTIVE: AtomicBool = AtomicBool::new(false);
+static PENDING_WARNINGS: OnceLock<(Sender<String>, Receiver<String>)> = OnceLock::new();
+/// Warnings that found the queue full
+static DROPPED_WARNINGS: AtomicUsize = AtomicUsize::new(0);
+static WARNINGS_FILE: OnceLock<Mutex<BufWriter<File>>> = OnceLock::new();
+static DIVERTED_WARNINGS: AtomicUsize = AtomicUsize::new(0);
+static WARNING_LABEL: OnceLock<&'static str> = OnceLock::new();
+/// Channel to the logger thread; `None` if it could not be started
+static LOGGER: OnceLock<Option<Sender<LogMessage>>> = OnceLock::new();
+
+/// Colors used for user-facing output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Cyan,
+    Bold,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Cyan => "36",
+            Color::Bold => "1",
+        }
+    }
+}
+
+/// Replace the "Warning:" prefix of [`warn`], e.g. with a translation; only the first call
+/// takes effect
+pub fn set_warning_label(label: &'static str) {
+    WARNING_LABEL.set(label).ok();
+}
+
+/// Enable or disable colored output globally
+pub fn set_color_enabled(enabled: bool) {
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn color_enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Decide whether `auto` color mode should produce colors for stdout
+///
+/// Honors the NO_COLOR convention (https://no-color.org) and `TERM=dumb`.
+pub fn auto_color() -> bool {
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if std::env::var("TERM").is_ok_and(|t| t == "dumb") {
+        return false;
+    }
+    io::stdout().is_terminal() && crate::winapi::enable_virtual_terminal()
+}
+
+/// Whether the terminal shows OSC 9;4 progress sequences on its tab: Windows Terminal, ConEmu
+/// and the terminals that adopted them
+pub fn terminal_progress_supported() -> bool {
+    std::env::var_os("WT_SESSION").is_some_and(|v| !v.is_empty())
+        || std::env::var("ConEmuANSI").is_ok_and(|v| v == "ON")
+        || std::env::var("TERM_PROGRAM").is_ok_and(|p| matches!(p.as_str(), "WezTerm" | "ghostty"))
+}
+
+/// What stdout is connected to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Console,
+    /// Pipe or socket, typically a CI log collector or another program
+    Pipe,
+    /// Redirected to a regular file (or a device such as /dev/null)
+    File,
+}
+
+pub fn stdout_kind() -> StreamKind {
+    if io::stdout().is_terminal() {
+        StreamKind::Console
+    } else if crate::winapi::stdout_is_pipe() {
+        StreamKind::Pipe
+    } else {
+        StreamKind::File
+    }
+}
+
+/// Wrap text in the given color if colors are enabled
+pub fn paint(text: impl Display, color: Color) -> String {
+    if color_enabled() {
+        format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Mark whether a `\r`-rewritten progress line is currently on screen
+///
+/// While it is, warnings are queued for [`print_pending_warnings`]; ending it prints them.
+pub fn set_progress_active(active: bool) {
+    PROGRESS_ACTIVE.store(active, Ordering::SeqCst);
+    print_pending_warnings();
+}
+
+/// Print the warnings queued since the last call on their own lines, clearing the progress
+/// line first; its owner redraws it afterwards
+pub fn print_pending_warnings() {
+    let mut lines: Vec<String> = pending_warnings().1.try_iter().collect();
+    let dropped = DROPPED_WARNINGS.swap(0, Ordering::Relaxed);
+    if dropped > 0 {
+        let label = WARNING_LABEL.get().copied().unwrap_or("Warning:");
+        let line = format!("{} more warnings dropped", dropped);
+        lines.push(format!("{} {}", paint(label, Color::Yellow), line));
+    }
+    write_warnings(lines);
+}
+
+fn pending_warnings() -> &'static (Sender<String>, Receiver<String>) {
+    PENDING_WARNINGS.get_or_init(|| crossbeam_channel::bounded(PENDING_LIMIT))
+}
+
+fn write_warnings(lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    let mut stderr = io::stderr().lock();
+    if stderr.is_terminal() {
+        write!(stderr, "\r\x1b[2K").ok();
+    }
+    for line in lines {
+        writeln!(stderr, "{}", line).ok();
+    }
+}
+
+/// Write warnings to `file` instead of stderr from now on; only the first call takes effect
+pub fn divert_warnings(file: File) {
+    WARNINGS_FILE.set(Mutex::new(BufWriter::new(file))).ok();
+}
+
+/// Flush the file given to [`divert_warnings`], returning how many warnings went to it
+pub fn flush_warnings() -> io::Result<usize> {
+    if let Some(file) = WARNINGS_FILE.get() {
+        file.lock().unwrap().flush()?;
+    }
+    Ok(DIVERTED_WARNINGS.load(Ordering::Relaxed))
+}
+
+/// Print a warning to stderr (or the file given to [`divert_warnings`]) without leaving it
+/// glued to a progress line
+///
+/// While a progress line is on screen the warning waits in a queue, so it cannot land in the
+/// middle of a redraw; the progress reporter prints it above the next one.
+pub fn warn(msg: impl Display) {
+    let label = WARNING_LABEL.get().copied().unwrap_or("Warning:");
+    if let Some(file) = WARNINGS_FILE.get() {
+        writeln!(file.lock().unwrap(), "{} {}", label, msg).ok();
+        DIVERTED_WARNINGS.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    let line = format!("{} {}", paint(label, Color::Yellow), msg);
+    if !PROGRESS_ACTIVE.load(Ordering::SeqCst) {
+        writeln!(io::stderr().lock(), "{}", line).ok();
+        return;
+    }
+    if let Err(TrySendError::Full(_)) = pending_warnings().0.try_send(line) {
+        DROPPED_WARNINGS.fetch_add(1, Ordering::Relaxed);
+    }
+    // The progress line may have ended since; nobody else would print this one then
+    if !PROGRESS_ACTIVE.load(Ordering::SeqCst) {
+        print_pending_warnings();
+    }
+}
+
+enum LogMessage {
+    Warning {
+        kind: &'static str,
+        text: String,
+    },
+    /// Answered once everything sent before it is written
+    Flush(Sender<()>),
+}
+
+/// Send a worker diagnostic to the logger thread, which writes it like [`warn`]
+///
+/// Workers never wait on the console this way, and on the console a flood of one `kind`
+/// (thousands of "Failed to delete" lines from a locked-down tree) is cut to `LOG_BURST`
+/// lines a second plus a count of the rest; a [`divert_warnings`] file gets them all.
+pub fn log(kind: &'static str, msg: impl Display) {
+    match LOGGER.get_or_init(spawn_logger) {
+        Some(logger) => {
+            let text = msg.to_string();
+            logger.send(LogMessage::Warning { kind, text }).ok();
+        }
+        None => warn(msg),
+    }
+}
+
+/// Wait until every diagnostic sent to [`log`] so far is written, along with the counts of
+/// those held back
+pub fn flush_log() {
+    let Some(Some(logger)) = LOGGER.get() else {
+        return;
+    };
+    let (done, written) = crossbeam_channel::bounded(1);
+    if logger.send(LogMessage::Flush(done)).is_ok() {
+        written.recv().ok();
+    }
+}
+
+fn spawn_logger() -> Option<Sender<LogMessage>> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let spawned = std::thread::Builder::new()
+        .name("rmbrr-log".to_string())
+        .spawn(move || run_logger(rx, || WARNINGS_FILE.get().is_some(), warn));
+    spawned.ok().map(|_| tx)
+}
+
+/// The logger thread's loop: hands each admitted message to `write`, or every message while
+/// `unlimited` (a warnings file) says so
+fn run_logger(
+    rx: Receiver<LogMessage>,
+    unlimited: impl Fn() -> bool,
+    mut write: impl FnMut(String),
+) {
+    let mut limiter = RateLimiter::default();
+    for message in rx {
+        match message {
+            LogMessage::Warning { text, .. } if unlimited() => write(text),
+            LogMessage::Warning { kind, text } => {
+                if let Some(suppressed) = limiter.admit(kind, Instant::now()) {
+                    if suppressed > 0 {
+                        write(suppressed_line(kind, suppressed));
+                    }
+                    write(text);
+                }
+            }
+            LogMessage::Flush(done) => {
+                for (kind, suppressed) in limiter.take_suppressed() {
+                    write(suppressed_line(kind, suppressed));
+                }
+                done.send(()).ok();
+            }
+        }
+    }
+}
+
+fn suppressed_line(kind: &str, count: usize) -> String {
+    format!("{} more \"{}\" messages suppressed", count, kind)
+}
+
+/// Per-kind budget of `LOG_BURST` messages every `LOG_WINDOW`
+#[derive(Default)]
+struct RateLimiter {
+    /// Kind -> (window start, messages written in it, messages suppressed since the last one)
+    kinds: HashMap<&'static str, (Instant, usize, usize)>,
+}
+
+impl RateLimiter {
+    /// `Some(n)` if a message of `kind` may be written now, `n` being how many of its kind
+    /// were suppressed before it
+    fn admit(&mut self, kind: &'static str, now: Instant) -> Option<usize> {
+        let (start, written, suppressed) = self.kinds.entry(kind).or_insert((now, 0, 0));
+        if now.duration_since(*start) >= LOG_WINDOW {
+            *start = now;
+            *written = 0;
+        }
+        if *written < LOG_BURST {
+            *written += 1;
+            Some(std::mem::take(suppressed))
+        } else {
+            *suppressed += 1;
+            None
+        }
+    }
+
+    /// Kinds with suppressed messages not yet reported, and how many
+    fn take_suppressed(&mut self) -> Vec<(&'static str, usize)> {
+        let mut taken: Vec<_> = self
+            .kinds
+            .iter_mut()
+            .filter(|(_, (_, _, suppressed))| *suppressed > 0)
+            .map(|(kind, (_, _, suppressed))| (*kind, std::mem::take(suppressed)))
+            .collect();
+        taken.sort();
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        set_color_enabled(true);
+        assert_eq!(paint("x", Color::Red), "\x1b[31mx\x1b[0m");
+        set_color_enabled(false);
+        assert_eq!(paint("x", Color::Red), "x");
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::default();
+        for _ in 0..LOG_BURST {
+            assert_eq!(limiter.admit("Failed to delete", start), Some(0));
+        }
+        assert_eq!(limiter.admit("Failed to delete", start), None);
+        assert_eq!(limiter.admit("Failed to delete", start), None);
+        // Other kinds have their own budget
+        assert_eq!(limiter.admit("Cannot read", start), Some(0));
+        assert_eq!(limiter.take_suppressed(), vec![("Failed to delete", 2)]);
+        assert!(limiter.take_suppressed().is_empty());
+
+        assert_eq!(limiter.admit("Failed to delete", start), None);
+        let later = start + LOG_WINDOW;
+        assert_eq!(limiter.admit("Failed to delete", later), Some(1));
+    }
+
+    #[test]
+    fn test_flush_log() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let written = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let lines = written.clone();
+        let logger = std::thread::spawn(move || {
+            run_logger(rx, || false, |line| lines.lock().unwrap().push(line))
+        });
+        for i in 0..LOG_BURST + 3 {
+            let text = format!("failed {}", i);
+            tx.send(LogMessage::Warning {
+                kind: "Failed",
+                text,
+            })
+            .unwrap();
+        }
+        let text = "other".to_string();
+        tx.send(LogMessage::Warning {
+            kind: "Other",
+            text,
+        })
+        .unwrap();
+        let (done, flushed) = crossbeam_channel::bounded(1);
+        tx.send(LogMessage::Flush(done)).unwrap();
+        flushed.recv().unwrap();
+
+        // Everything sent before the flush is written by the time it returns, in order, with
+        // the held-back count last
+        let mut expected: Vec<_> = (0..LOG_BURST).map(|i| format!("failed {}", i)).collect();
+        expected.push("other".to_string());
+        expected.push(suppressed_line("Failed", 3));
+        assert_eq!(*written.lock().unwrap(), expected);
+
+        drop(tx);
+        logger.join().unwrap();
+    }
+
+    #[test]
+    fn test_warnings_wait_for_progress_line() {
+        set_progress_active(true);
+        warn("queued");
+        let queued: Vec<_> = pending_warnings().1.try_iter().collect();
+        assert!(queued.iter().any(|line| line.ends_with(" queued")));
+        warn("printed");
+        set_progress_active(false);
+        assert!(pending_warnings().1.is_empty());
+    }
+}
@@ -0,0 +1,472 @@
+//! High-level deletion pipeline (scan, schedule, delete) for library users
+
+use crate::broker::Broker;
+use crate::error::{Decision, ErrorStrategy, FailedItem, IgnoredErrors, Result, RmError};
+use crate::events::{self, Event, EventSender};
+use crate::output;
+use crate::retry::RetryPolicy;
+use crate::safety::{self, SafetyCheck};
+use crate::targets;
+use crate::tree;
+use crate::worker::{self, ErrorTracker, Spawner, WorkerConfig};
+use crossbeam_channel::{select, Receiver, Sender};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Options for [`remove_dir_all`]
+#[derive(Debug, Clone, Default)]
+pub struct RmOptions {
+    /// Number of worker threads (None = logical CPU count)
+    pub threads: Option<usize>,
+    /// Give directory removal its own pool of this many threads; `threads` then only delete files
+    pub dir_threads: Option<usize>,
+    /// Scan and count only, delete nothing
+    pub dry_run: bool,
+    /// Start on the leaf directories in random order (see [`tree::DirectoryTree::shuffle_leaves`])
+    pub shuffle: bool,
+    /// Allow dangerous (but not system) paths, like the CLI's --force
+    pub force: bool,
+    /// If the target is a symlink or junction, delete the tree it points to (after the same
+    /// safety checks on the real path) and then the link; by default only the link is removed
+    pub dereference_root: bool,
+    /// Stop the deletion early when this token is cancelled
+    pub cancel: Option<CancelToken>,
+    /// Receives every [`Event`] of the deletion, from `Started` to `Finished`
+    pub events: Option<EventSender>,
+    /// Whether to stop, continue or ask when an entry cannot be deleted
+    pub errors: ErrorStrategy,
+    /// Retries for deletions that fail transiently (none by default)
+    pub retry: RetryPolicy,
+    /// Failures with these error codes are counted in [`Report::ignored`] instead of failing
+    /// the deletion, and never retried
+    pub ignore_errors: IgnoredErrors,
+    /// Run the workers on the caller's threads instead of new `std::thread`s
+    pub spawner: Option<Spawner>,
+}
+
+/// Shared flag used to cancel a running deletion from another thread
+///
+/// Cancellation takes effect after each worker finishes its current directory.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Progress snapshot passed to progress callbacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressUpdate {
+    pub dirs_completed: usize,
+    /// Directories that could not be removed; with `dirs_completed` they add up to
+    /// `dirs_total` once the run ends
+    pub dirs_failed: usize,
+    pub dirs_total: usize,
+    pub files_deleted: usize,
+}
+
+/// Callbacks for [`run_scoped`]; unlike those in [`RmOptions`], they may borrow from the caller
+/// and need not be `Send`: all of them run on the calling thread
+#[derive(Default)]
+pub struct ScopedHooks<'a> {
+    /// Called periodically, like the callback of [`remove_dir_all_with_progress`]
+    pub on_progress: Option<&'a mut dyn FnMut(ProgressUpdate)>,
+    /// Called with every [`Event`] of the deletion, from `Started` to `Finished`
+    pub on_event: Option<&'a mut dyn FnMut(&Event)>,
+    /// Asked after each failure whether to go on, in place of `RmOptions::errors`; the
+    /// worker that failed waits for the answer
+    pub on_error: Option<&'a mut dyn FnMut(&FailedItem) -> bool>,
+}
+
+/// A failure a worker asks the calling thread about, with where to send the answer
+type ErrorQuestion = (FailedItem, Sender<bool>);
+
+/// Summary of a finished deletion
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub dirs_deleted: usize,
+    pub files_deleted: usize,
+    pub scan_time: Duration,
+    pub delete_time: Duration,
+    pub failures: Vec<FailedItem>,
+    /// Entries another process deleted first; they count as deleted, not as failures
+    pub already_gone: usize,
+    /// Failures of a class in `RmOptions::ignore_errors`; their entries were left in place
+    pub ignored: usize,
+    /// Links the scan met, for the link audit of the summary
+    pub links: Vec<tree::FoundLink>,
+}
+
+impl Report {
+    /// Items deleted plus items that failed
+    pub fn total_items(&self) -> usize {
+        self.dirs_deleted + self.files_deleted + self.failures.len()
+    }
+}
+
+/// Delete a directory tree in parallel
+///
+/// Returns `RmError::PartialFailure` if some entries could not be deleted, or the first
+/// failure's own error if `options.errors` stopped the deletion.
+pub fn remove_dir_all(path: &Path, options: &RmOptions) -> Result<Report> {
+    remove_dir_all_with_progress(path, options, |_| {})
+}
+
+/// Delete a directory tree, calling `on_progress` periodically from the calling thread
+///
+/// Progress is driven by worker events, so the calling thread sleeps until something happens.
+pub fn remove_dir_all_with_progress<F>(
+    path: &Path,
+    options: &RmOptions,
+    mut on_progress: F,
+) -> Result<Report>
+where
+    F: FnMut(ProgressUpdate),
+{
+    let hooks = ScopedHooks {
+        on_progress: Some(&mut on_progress),
+        ..Default::default()
+    };
+    run(path, options, hooks, false)
+}
+
+/// Delete a directory tree with workers on `std::thread::scope` threads, so `hooks` can borrow
+/// from the caller's stack instead of being shared through `Arc`s
+///
+/// Every worker has exited when this returns, even if a hook panics; the deletion is then
+/// cancelled first. `options.spawner` is not used.
+pub fn run_scoped(path: &Path, options: &RmOptions, hooks: ScopedHooks<'_>) -> Result<Report> {
+    run(path, options, hooks, true)
+}
+
+fn run(
+    path: &Path,
+    options: &RmOptions,
+    mut hooks: ScopedHooks<'_>,
+    scoped: bool,
+) -> Result<Report> {
+    if targets::is_link(path) {
+        if options.dereference_root {
+            let real = path.canonicalize().map_err(|e| RmError::InvalidPath {
+                path: path.to_path_buf(),
+                reason: format!("cannot resolve link: {}", e),
+            })?;
+            let report = run(&real, options, hooks, scoped)?;
+            if !options.dry_run {
+                remove_link(path)?;
+            }
+            return Ok(report);
+        }
+        if !options.dry_run {
+            remove_link(path)?;
+        }
+        return Ok(Report {
+            dirs_deleted: 1,
+            ..Default::default()
+        });
+    }
+
+    validate_target(path, options.force)?;
+
+    let start = Instant::now();
+    let mut tree = tree::discover_tree(path).map_err(|source| RmError::ScanError {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let scan_time = start.elapsed();
+    output::flush_log();
+
+    let dirs_total = tree.dirs.len();
+    let file_count = tree.file_count;
+    let cancelled = || options.cancel.as_ref().is_some_and(|c| c.is_cancelled());
+
+    if cancelled() {
+        return Err(RmError::Cancelled);
+    }
+
+    if options.dry_run {
+        return Ok(Report {
+            dirs_deleted: dirs_total,
+            files_deleted: file_count,
+            scan_time,
+            ..Default::default()
+        });
+    }
+
+    let worker_count = options.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+    });
+
+    let delete_start = Instant::now();
+    if options.shuffle {
+        tree.shuffle_leaves();
+    }
+    let links = std::mem::take(&mut tree.links);
+    let (broker, tx, rx) = Broker::new(tree);
+    let broker = Arc::new(broker);
+    let error_tracker = Arc::new(ErrorTracker::new());
+    let (event_tx, event_rx) = events::channel();
+    let (question_tx, question_rx) = crossbeam_channel::unbounded::<ErrorQuestion>();
+    let errors = match hooks.on_error {
+        // Workers can't call a borrowed hook, so they ask the calling thread
+        Some(_) => {
+            // Kept here too, so the questions channel never disconnects while it is polled
+            let question_tx = question_tx.clone();
+            ErrorStrategy::Prompt(Arc::new(move |item: &FailedItem| {
+                let (answer_tx, answer_rx) = crossbeam_channel::bounded(1);
+                if question_tx.send((item.clone(), answer_tx)).is_ok()
+                    && answer_rx.recv() == Ok(true)
+                {
+                    Decision::Skip
+                } else {
+                    Decision::Abort
+                }
+            }))
+        }
+        None => options.errors.clone(),
+    };
+    let config = WorkerConfig {
+        events: Some(event_tx),
+        errors,
+        retry: options.retry,
+        ignore_errors: options.ignore_errors.clone(),
+        spawner: options.spawner.clone(),
+        ..Default::default()
+    };
+
+    emit(
+        options,
+        &mut hooks,
+        Event::Started {
+            root: path.to_path_buf(),
+            dirs_total,
+            files_total: file_count,
+        },
+    );
+
+    let mut update = ProgressUpdate {
+        dirs_completed: 0,
+        dirs_failed: 0,
+        dirs_total,
+        files_deleted: 0,
+    };
+    let mut drive = |hooks: &mut ScopedHooks<'_>| {
+        drive(
+            options,
+            hooks,
+            &broker,
+            &event_rx,
+            &question_rx,
+            &mut update,
+        )
+    };
+    if scoped {
+        std::thread::scope(|scope| {
+            let handles = worker::spawn_scoped_workers(
+                scope,
+                worker_count,
+                options.dir_threads,
+                rx,
+                &broker,
+                config,
+                &error_tracker,
+            );
+            drop(tx);
+            // Without this, a panicking hook would wait on the scope for the whole deletion
+            let cancel_on_unwind = CancelOnUnwind(&broker);
+            drive(&mut hooks);
+            drop(cancel_on_unwind);
+            for handle in handles {
+                handle.join().ok();
+            }
+        });
+    } else {
+        let handles = match options.dir_threads {
+            Some(dir_threads) => worker::spawn_split_workers(
+                worker_count,
+                dir_threads,
+                rx,
+                broker.clone(),
+                config,
+                error_tracker.clone(),
+            ),
+            None => worker::spawn_workers(
+                worker_count,
+                rx,
+                broker.clone(),
+                config,
+                error_tracker.clone(),
+            ),
+        };
+        drop(tx);
+        drive(&mut hooks);
+        for handle in handles {
+            handle.join().ok();
+        }
+    }
+    output::flush_log();
+    if let Some(ref mut on_progress) = hooks.on_progress {
+        on_progress(update);
+    }
+
+    let report = Report {
+        dirs_deleted: broker.completed_count() - broker.kept_count(),
+        files_deleted: update.files_deleted,
+        scan_time,
+        delete_time: delete_start.elapsed(),
+        failures: error_tracker.get_failures(),
+        already_gone: error_tracker.gone_count(),
+        ignored: error_tracker.ignored_count(),
+        links,
+    };
+    emit(
+        options,
+        &mut hooks,
+        Event::Finished {
+            root: path.to_path_buf(),
+            dirs_deleted: report.dirs_deleted,
+            files_deleted: report.files_deleted,
+            failed: report.failures.len(),
+            elapsed: report.delete_time,
+            cancelled: broker.is_cancelled(),
+        },
+    );
+
+    // Stopped by the error strategy rather than the cancel token
+    if let Some(item) = error_tracker.aborted_by() {
+        return Err(item.to_error());
+    }
+    if broker.is_cancelled() {
+        return Err(RmError::Cancelled);
+    }
+
+    if !report.failures.is_empty() {
+        return Err(RmError::PartialFailure { stats: report });
+    }
+    Ok(report)
+}
+
+/// Relay worker events and answer their error questions until every worker has exited,
+/// calling the progress hook every `PROGRESS_INTERVAL`
+fn drive(
+    options: &RmOptions,
+    hooks: &mut ScopedHooks<'_>,
+    broker: &Broker,
+    event_rx: &Receiver<Event>,
+    question_rx: &Receiver<ErrorQuestion>,
+    update: &mut ProgressUpdate,
+) {
+    let cancelled = || options.cancel.as_ref().is_some_and(|c| c.is_cancelled());
+    let mut last_progress = Instant::now();
+    if let Some(ref mut on_progress) = hooks.on_progress {
+        on_progress(*update);
+    }
+
+    // The event channel disconnects once every worker has exited and dropped its sender
+    loop {
+        select! {
+            recv(event_rx) -> event => {
+                let Ok(event) = event else {
+                    break;
+                };
+                match event {
+                    Event::DirDone { .. } => update.dirs_completed += 1,
+                    Event::Error(ref item) if item.is_dir => update.dirs_failed += 1,
+                    Event::FileDone { .. } => update.files_deleted += 1,
+                    _ => {}
+                }
+                emit(options, hooks, event);
+            }
+            recv(question_rx) -> question => {
+                if let (Ok((item, answer)), Some(on_error)) = (question, hooks.on_error.as_mut()) {
+                    answer.send(on_error(&item)).ok();
+                }
+            }
+            default(PROGRESS_INTERVAL) => {}
+        }
+        if cancelled() && !broker.is_cancelled() {
+            broker.cancel();
+        }
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            if let Some(ref mut on_progress) = hooks.on_progress {
+                on_progress(*update);
+            }
+            last_progress = Instant::now();
+        }
+    }
+}
+
+fn emit(options: &RmOptions, hooks: &mut ScopedHooks<'_>, event: Event) {
+    if let Some(ref mut on_event) = hooks.on_event {
+        on_event(&event);
+    }
+    events::emit(&options.events, event);
+}
+
+/// Cancels the deletion if dropped while the calling thread unwinds
+struct CancelOnUnwind<'a>(&'a Broker);
+
+impl Drop for CancelOnUnwind<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.cancel();
+        }
+    }
+}
+
+/// Check the target exists, is a directory, and passes safety checks
+fn remove_link(path: &Path) -> Result<()> {
+    targets::remove_link(path).map_err(|source| RmError::RemoveDirError {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn validate_target(path: &Path, force: bool) -> Result<()> {
+    if !path.is_dir() {
+        let reason = if path.exists() {
+            "not a directory"
+        } else {
+            "path does not exist"
+        };
+        return Err(RmError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        });
+    }
+
+    match safety::check_path_safety(path) {
+        SafetyCheck::Safe => Ok(()),
+        SafetyCheck::Dangerous { can_override, .. } if can_override && force => Ok(()),
+        SafetyCheck::Dangerous {
+            reason,
+            can_override,
+        } => Err(RmError::SafetyRefused {
+            path: path.to_path_buf(),
+            reason,
+            can_override,
+        }),
+        // Wiping a drive needs confirmations only the CLI can ask for
+        SafetyCheck::DriveRoot { reason } => Err(RmError::SafetyRefused {
+            path: path.to_path_buf(),
+            reason,
+            can_override: false,
+        }),
+    }
+}
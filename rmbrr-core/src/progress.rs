@@ -0,0 +1,80 @@
+// Shared live progress state, updated by workers and read by displays
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Live counters and per-worker activity for a running deletion
+pub struct Progress {
+    /// Files deleted so far
+    files_deleted: AtomicUsize,
+    /// Directory each worker is currently processing (indexed by worker id)
+    active: Mutex<Vec<Option<PathBuf>>>,
+}
+
+impl Progress {
+    pub fn new(worker_count: usize) -> Self {
+        Self {
+            files_deleted: AtomicUsize::new(0),
+            active: Mutex::new(vec![None; worker_count]),
+        }
+    }
+
+    /// Record that a worker started processing a directory
+    pub fn set_active(&self, worker: usize, dir: &std::path::Path) {
+        let mut active = self.active.lock().unwrap();
+        if worker >= active.len() {
+            active.resize(worker + 1, None);
+        }
+        active[worker] = Some(dir.to_path_buf());
+    }
+
+    /// Record that a worker is idle
+    pub fn set_idle(&self, worker: usize) {
+        if let Some(slot) = self.active.lock().unwrap().get_mut(worker) {
+            *slot = None;
+        }
+    }
+
+    pub fn add_file_deleted(&self) {
+        self.files_deleted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get number of files deleted so far
+    pub fn files_deleted(&self) -> usize {
+        self.files_deleted.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot of what each worker is doing
+    pub fn active(&self) -> Vec<Option<PathBuf>> {
+        self.active.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_worker_activity() {
+        let progress = Progress::new(2);
+        progress.set_active(1, Path::new("/root/a"));
+
+        let active = progress.active();
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0], None);
+        assert_eq!(active[1], Some(PathBuf::from("/root/a")));
+
+        progress.set_idle(1);
+        assert_eq!(progress.active()[1], None);
+    }
+
+    #[test]
+    fn test_files_deleted_counter() {
+        let progress = Progress::new(1);
+        progress.add_file_deleted();
+        progress.add_file_deleted();
+        assert_eq!(progress.files_deleted(), 2);
+    }
+}
@@ -0,0 +1,89 @@
+//! Python bindings (feature `python`)
+//!
+//! Build and install the extension module with `maturin develop --release`, then:
+//! `import rmbrr; rmbrr.remove("build", threads=8)`.
+
+use crate::error::RmError;
+use crate::pipeline::{self, RmOptions};
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+fn to_py_err(err: RmError) -> PyErr {
+    match err {
+        RmError::InvalidPath { .. } | RmError::SafetyRefused { .. } => {
+            PyValueError::new_err(err.to_string())
+        }
+        _ => PyOSError::new_err(err.to_string()),
+    }
+}
+
+/// Delete a directory tree in parallel
+///
+/// `on_progress`, if given, is called with a dict of `dirs_completed`, `dirs_failed`,
+/// `dirs_total` and `files_deleted`. Returns a dict of final counts and timings (in seconds).
+#[pyfunction]
+#[pyo3(signature = (path, threads=None, dry_run=false, on_progress=None))]
+fn remove(
+    py: Python<'_>,
+    path: PathBuf,
+    threads: Option<usize>,
+    dry_run: bool,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    if threads == Some(0) {
+        return Err(PyValueError::new_err("threads must be at least 1"));
+    }
+    let options = RmOptions {
+        threads,
+        dry_run,
+        ..Default::default()
+    };
+
+    // The GIL is released while deleting and re-acquired only for progress callbacks;
+    // the first exception raised by the callback stops further calls and is re-raised.
+    let mut callback_error: Option<PyErr> = None;
+    let outcome = py.allow_threads(|| {
+        pipeline::remove_dir_all_with_progress(&path, &options, |update| {
+            let Some(callback) = &on_progress else {
+                return;
+            };
+            if callback_error.is_some() {
+                return;
+            }
+            Python::with_gil(|py| {
+                let result = (|| {
+                    let dict = PyDict::new(py);
+                    dict.set_item("dirs_completed", update.dirs_completed)?;
+                    dict.set_item("dirs_failed", update.dirs_failed)?;
+                    dict.set_item("dirs_total", update.dirs_total)?;
+                    dict.set_item("files_deleted", update.files_deleted)?;
+                    callback.call1(py, (dict,))
+                })();
+                if let Err(e) = result {
+                    callback_error = Some(e);
+                }
+            });
+        })
+    });
+
+    let report = outcome.map_err(to_py_err)?;
+    if let Some(e) = callback_error {
+        return Err(e);
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("dirs_deleted", report.dirs_deleted)?;
+    dict.set_item("files_deleted", report.files_deleted)?;
+    dict.set_item("already_gone", report.already_gone)?;
+    dict.set_item("scan_time", report.scan_time.as_secs_f64())?;
+    dict.set_item("delete_time", report.delete_time.as_secs_f64())?;
+    Ok(dict.into_any().unbind())
+}
+
+#[pymodule]
+fn rmbrr(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(remove, m)?)?;
+    Ok(())
+}
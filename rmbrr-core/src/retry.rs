@@ -0,0 +1,134 @@
+//! Retrying deletions that fail for transient reasons (sharing violations, antivirus or
+//! indexer handles, busy resources)
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::time::Duration;
+
+/// Longest wait between two attempts, however many retries are configured
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// How often and how patiently to retry a failed deletion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Extra attempts after the first failure (0 = never retry)
+    pub retries: u32,
+    /// Wait before the first retry; doubles after each attempt
+    pub delay: Duration,
+    /// Randomize each wait by ±50% so workers don't retry in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            delay: Duration::from_millis(100),
+            jitter: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `attempt` (0-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self
+            .delay
+            .saturating_mul(1 << attempt.min(16))
+            .min(MAX_DELAY);
+        if self.jitter {
+            base.mul_f64(0.5 + random_fraction())
+        } else {
+            base
+        }
+    }
+}
+
+/// Whether an error is likely to go away if the deletion is tried again shortly
+pub fn is_transient(err: &io::Error) -> bool {
+    // ERROR_ACCESS_DENIED (also returned for delete-pending files), ERROR_SHARING_VIOLATION,
+    // ERROR_LOCK_VIOLATION
+    #[cfg(windows)]
+    if matches!(err.raw_os_error(), Some(5 | 32 | 33)) {
+        return true;
+    }
+    matches!(
+        err.kind(),
+        io::ErrorKind::ResourceBusy
+            | io::ErrorKind::DirectoryNotEmpty
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+    )
+}
+
+/// Parse a delay like `200ms`, `2s` or `1m` (a bare number means milliseconds)
+pub fn parse_delay(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid delay '{}' (expected e.g. 200ms, 2s)", value))?;
+    match unit {
+        "" | "ms" => Ok(Duration::from_millis(number)),
+        "s" => Ok(Duration::from_secs(number)),
+        "m" => number
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("delay '{}' is too large", value)),
+        _ => Err(format!("unknown delay unit '{}' (use ms, s or m)", unit)),
+    }
+}
+
+/// A number in [0, 1) from the standard library's per-process random hash keys
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            retries: 5,
+            delay: Duration::from_millis(200),
+            jitter: false,
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(200));
+        assert_eq!(policy.delay(2), Duration::from_millis(800));
+        assert_eq!(policy.delay(30), MAX_DELAY);
+
+        let jittered = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for _ in 0..20 {
+            let d = jittered.delay(0);
+            assert!(d >= Duration::from_millis(100) && d < Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&io::ErrorKind::ResourceBusy.into()));
+        assert!(!is_transient(&io::ErrorKind::NotFound.into()));
+    }
+
+    #[test]
+    fn test_parse_delay() {
+        assert_eq!(parse_delay("200ms"), Ok(Duration::from_millis(200)));
+        assert_eq!(parse_delay("250"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_delay("2s"), Ok(Duration::from_secs(2)));
+        assert!(parse_delay("2h").is_err());
+        assert!(parse_delay("fast").is_err());
+        assert!(parse_delay("999999999999999999m").is_err());
+    }
+}
@@ -0,0 +1,506 @@
+//! Safety checks and validation for rmbrr
+
+use std::env;
+use std::path::{Component, Path, PathBuf, Prefix};
+use std::process::Command;
+
+/// Check if a path is a protected system directory
+pub fn is_system_directory(path: &Path) -> bool {
+    let canonical = path.canonicalize().ok();
+    let path_str = path.to_string_lossy();
+    let canonical_str = canonical.as_ref().map(|p| p.to_string_lossy());
+
+    // Check common system directories
+    #[cfg(windows)]
+    {
+        let protected_windows = [
+            "C:\\Windows",
+            "C:\\Windows\\System32",
+            "C:\\Program Files",
+            "C:\\Program Files (x86)",
+            "C:\\ProgramData",
+            "C:\\",
+            "C:\\Users",
+        ];
+
+        for protected in &protected_windows {
+            if path_str.eq_ignore_ascii_case(protected) {
+                return true;
+            }
+            // Canonical paths are verbatim (`\\?\C:\Windows`), also when the system volume
+            // was named by its GUID path
+            if let Some(ref canonical) = canonical_str {
+                let canonical = canonical.strip_prefix(r"\\?\").unwrap_or(canonical);
+                if canonical.eq_ignore_ascii_case(protected) {
+                    return true;
+                }
+            }
+        }
+
+        // The system drive's root; other drive roots are checked by `check_path_safety`
+        if is_drive_root(path) && is_system_drive(path) {
+            return true;
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        let protected_unix = [
+            "/", "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/root", "/sbin",
+            "/sys", "/usr", "/var",
+        ];
+
+        for protected in &protected_unix {
+            if path_str == *protected {
+                return true;
+            }
+            if let Some(ref canonical) = canonical_str {
+                if canonical.as_ref() == *protected {
+                    return true;
+                }
+            }
+        }
+    }
+
+    // Check if it's the user's home directory
+    if let Ok(home) = env::var("HOME") {
+        let home_path = PathBuf::from(home);
+        if let (Ok(p1), Ok(p2)) = (path.canonicalize(), home_path.canonicalize()) {
+            if p1 == p2 {
+                return true;
+            }
+        }
+    }
+
+    // Windows USERPROFILE
+    #[cfg(windows)]
+    {
+        if let Ok(userprofile) = env::var("USERPROFILE") {
+            let user_path = PathBuf::from(userprofile);
+            if let (Ok(p1), Ok(p2)) = (path.canonicalize(), user_path.canonicalize()) {
+                if p1 == p2 {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Rewrite a device path (`\\.\D:\data`) as the verbatim path it names (`\\?\D:\data`),
+/// which is the form the rest of rmbrr understands; other paths are returned unchanged
+///
+/// Device paths are still normalized by Windows, so separators are unified and `.` and `..`
+/// resolved here before the prefix is switched.
+pub fn device_path_to_verbatim(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    let Some(rest) = text
+        .strip_prefix(r"\\.\")
+        .or_else(|| text.strip_prefix("//./"))
+    else {
+        return path.to_path_buf();
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split(['\\', '/']) {
+        match part {
+            "" | "." => {}
+            // Never climb above the device name itself
+            ".." if parts.len() > 1 => {
+                parts.pop();
+            }
+            ".." => {}
+            _ => parts.push(part),
+        }
+    }
+    let mut verbatim = format!(r"\\?\{}", parts.join("\\"));
+    if parts.len() == 1 && rest.len() > parts[0].len() {
+        // `\\.\D:\` is the drive's root directory, `\\.\D:` the volume itself
+        verbatim.push('\\');
+    }
+    PathBuf::from(verbatim)
+}
+
+/// Drive letter of a Windows path (`D` for `D:\data`), if it has one
+pub fn drive_letter(path: &Path) -> Option<char> {
+    match path.components().next()? {
+        Component::Prefix(prefix) => match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                Some((letter as char).to_ascii_uppercase())
+            }
+            Prefix::DeviceNS(name) => match name.as_encoded_bytes() {
+                [letter, b':'] if letter.is_ascii_alphabetic() => {
+                    Some((*letter as char).to_ascii_uppercase())
+                }
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Name of the volume a `\\?\Volume{GUID}\...` (or `\\.\Volume{GUID}\...`) path addresses
+/// (`Volume{GUID}`), for volumes mounted without a drive letter
+pub fn volume_guid(path: &Path) -> Option<String> {
+    match path.components().next()? {
+        Component::Prefix(prefix) => match prefix.kind() {
+            Prefix::Verbatim(name) | Prefix::DeviceNS(name) => {
+                let name = name.to_str()?;
+                let is_volume = name.len() > 8
+                    && name
+                        .get(..7)
+                        .is_some_and(|start| start.eq_ignore_ascii_case("Volume{"))
+                    && name.ends_with('}');
+                is_volume.then(|| name.to_string())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Check if a path is the root of a drive (`D:\`, or `\\?\Volume{GUID}\` for a volume
+/// without a letter) rather than a directory on it
+pub fn is_drive_root(path: &Path) -> bool {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut components = path.components();
+    (drive_letter(&path).is_some() || volume_guid(&path).is_some())
+        && components.next().is_some()
+        && components.next() == Some(Component::RootDir)
+        && components.next().is_none()
+}
+
+/// Check if a path is on the drive Windows runs from (`%SystemDrive%`, normally C:)
+///
+/// Volume GUID paths are compared by volume; one that cannot be resolved counts as the
+/// system drive.
+pub fn is_system_drive(path: &Path) -> bool {
+    let system = env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+    if volume_guid(path).is_some() {
+        let system_root = PathBuf::from(format!("{}\\", system));
+        return match (
+            crate::winapi::volume_id(path),
+            crate::winapi::volume_id(&system_root),
+        ) {
+            (Ok(volume), Ok(system)) => volume.eq_ignore_ascii_case(&system),
+            _ => true,
+        };
+    }
+    drive_letter(path).is_some_and(|letter| {
+        system
+            .chars()
+            .next()
+            .is_some_and(|s| s.eq_ignore_ascii_case(&letter))
+    })
+}
+
+/// Check if path is within current working directory
+pub fn is_in_current_directory(path: &Path) -> bool {
+    if let Ok(cwd) = env::current_dir() {
+        if let (Ok(p1), Ok(p2)) = (path.canonicalize(), cwd.canonicalize()) {
+            // Check if path is the CWD itself or a parent of CWD
+            return p1 == p2 || cwd.starts_with(&p1);
+        }
+    }
+    false
+}
+
+/// The running rmbrr binary, or the binary of the process that started it, if it lies under
+/// `path` (e.g. a build script deleting its own output directory)
+pub fn running_executable_in(path: &Path) -> Option<PathBuf> {
+    let target = path.canonicalize().ok()?;
+    [env::current_exe().ok(), crate::winapi::parent_process_exe()]
+        .into_iter()
+        .flatten()
+        .find(|exe| exe.canonicalize().is_ok_and(|exe| exe.starts_with(&target)))
+}
+
+/// Get a human-readable description of why a path might be dangerous
+pub fn get_danger_reason(path: &Path) -> Option<String> {
+    if is_system_directory(path) {
+        return Some(format!(
+            "'{}' is a system directory - deleting it could break your system",
+            path.display()
+        ));
+    }
+
+    if is_in_current_directory(path) {
+        return Some(format!(
+            "'{}' contains or is your current working directory",
+            path.display()
+        ));
+    }
+
+    if let Some(exe) = running_executable_in(path) {
+        return Some(format!(
+            "'{}' contains the running program {}",
+            path.display(),
+            exe.display()
+        ));
+    }
+
+    None
+}
+
+/// Safety check result
+#[derive(Debug)]
+pub enum SafetyCheck {
+    Safe,
+    Dangerous {
+        reason: String,
+        can_override: bool,
+    },
+    /// The root of a data drive: only its contents may go, and only past the CLI's explicit
+    /// confirmations (`--keep-root --i-know-what-im-doing`, a preview and a typed answer)
+    DriveRoot {
+        reason: String,
+    },
+}
+
+/// Perform comprehensive safety checks on a path
+pub fn check_path_safety(path: &Path) -> SafetyCheck {
+    if is_drive_root(path) && !is_system_drive(path) {
+        return SafetyCheck::DriveRoot {
+            reason: format!(
+                "'{}' is the root of a drive - this would erase everything on it",
+                path.display()
+            ),
+        };
+    }
+    if let Some(reason) = get_danger_reason(path) {
+        SafetyCheck::Dangerous {
+            reason,
+            can_override: !is_system_directory(path), // System dirs cannot be overridden
+        }
+    } else {
+        SafetyCheck::Safe
+    }
+}
+
+/// A git working copy holding work that would be lost by deleting it
+#[derive(Debug, Clone)]
+pub struct GitRisk {
+    /// Working copy root (the directory containing `.git`)
+    pub repo: PathBuf,
+    /// Number of modified, staged, or untracked entries
+    pub uncommitted: usize,
+    /// Number of local commits not present on any remote
+    pub unpushed: usize,
+}
+
+/// Run git in a repository and return its stdout, or None if git is unavailable or fails
+fn git_output(repo: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Check a single working copy for uncommitted changes or unpushed commits
+pub fn git_risk(repo: &Path) -> Option<GitRisk> {
+    let status = git_output(repo, &["status", "--porcelain"])?;
+    let uncommitted = status.lines().filter(|l| !l.trim().is_empty()).count();
+    let unpushed = git_output(
+        repo,
+        &["log", "--branches", "--not", "--remotes", "--oneline"],
+    )
+    .map(|log| log.lines().count())
+    .unwrap_or(0);
+
+    (uncommitted > 0 || unpushed > 0).then(|| GitRisk {
+        repo: repo.to_path_buf(),
+        uncommitted,
+        unpushed,
+    })
+}
+
+/// Find git working copies among scanned directories that hold unsaved work
+///
+/// A working copy has a `.git` directory, or, for worktrees and submodules, a `.git` file
+/// whose `gitdir:` line points to the repository.
+pub fn find_git_risks(dirs: &[PathBuf]) -> Vec<GitRisk> {
+    dirs.iter()
+        .filter_map(|dir| {
+            if dir.file_name().is_some_and(|n| n == ".git") {
+                git_risk(dir.parent()?)
+            } else if has_gitdir_file(dir) {
+                git_risk(dir)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// True if `dir` holds a `.git` file pointing to its repository
+fn has_gitdir_file(dir: &Path) -> bool {
+    let file = dir.join(".git");
+    file.symlink_metadata().is_ok_and(|m| m.is_file())
+        && std::fs::read_to_string(&file).is_ok_and(|text| text.starts_with("gitdir:"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_directory_detection() {
+        #[cfg(unix)]
+        {
+            assert!(is_system_directory(Path::new("/")));
+            assert!(is_system_directory(Path::new("/bin")));
+            assert!(is_system_directory(Path::new("/usr")));
+            assert!(!is_system_directory(Path::new("/tmp/test")));
+        }
+
+        #[cfg(windows)]
+        {
+            assert!(is_system_directory(Path::new("C:\\")));
+            assert!(is_system_directory(Path::new("C:\\Windows")));
+            assert!(!is_system_directory(Path::new("C:\\temp\\test")));
+        }
+    }
+
+    #[test]
+    fn test_temp_directory_safe() {
+        let temp = std::env::temp_dir();
+        assert!(!is_system_directory(&temp));
+    }
+
+    #[test]
+    fn test_git_risk_detects_uncommitted_changes() {
+        let temp = std::env::temp_dir().join("win_rmdir_git_risk_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        std::fs::create_dir_all(&temp).unwrap();
+
+        let initialized = Command::new("git")
+            .arg("init")
+            .arg("-q")
+            .arg(&temp)
+            .status()
+            .is_ok_and(|s| s.success());
+        if !initialized {
+            // git not installed; nothing to check
+            std::fs::remove_dir_all(&temp).ok();
+            return;
+        }
+
+        assert!(git_risk(&temp).is_none());
+
+        std::fs::write(temp.join("work.txt"), "unsaved").unwrap();
+        let risks = find_git_risks(&[temp.join(".git")]);
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].uncommitted, 1);
+
+        // A worktree or submodule only has a .git file naming the repository
+        let linked = temp.join("linked");
+        std::fs::create_dir_all(&linked).unwrap();
+        let gitdir = format!("gitdir: {}\n", temp.join(".git").display());
+        std::fs::write(linked.join(".git"), gitdir).unwrap();
+        std::fs::write(linked.join("draft.txt"), "unsaved").unwrap();
+        let risks = find_git_risks(std::slice::from_ref(&linked));
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].repo, linked);
+        assert!(risks[0].uncommitted > 0);
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_safety_check() {
+        let temp = std::env::temp_dir().join("test_safety");
+
+        match check_path_safety(&temp) {
+            SafetyCheck::Safe => {
+                // Expected - temp dir is safe
+            }
+            SafetyCheck::Dangerous { .. } | SafetyCheck::DriveRoot { .. } => {
+                panic!("Temp directory should be safe");
+            }
+        }
+    }
+
+    #[test]
+    fn test_running_executable_detected() {
+        let exe = env::current_exe().unwrap();
+        let dir = exe.parent().unwrap();
+        assert!(running_executable_in(dir).is_some());
+        assert!(matches!(
+            check_path_safety(dir),
+            SafetyCheck::Dangerous {
+                can_override: true,
+                ..
+            }
+        ));
+
+        let temp = std::env::temp_dir().join("win_rmdir_running_exe_test");
+        std::fs::create_dir_all(&temp).unwrap();
+        assert!(running_executable_in(&temp).is_none());
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_device_path_to_verbatim() {
+        let convert = |path: &str| device_path_to_verbatim(Path::new(path));
+        assert_eq!(convert(r"\\.\D:\data"), PathBuf::from(r"\\?\D:\data"));
+        assert_eq!(
+            convert(r"\\.\D:\data/./old\..\logs\\"),
+            PathBuf::from(r"\\?\D:\data\logs")
+        );
+        assert_eq!(convert("//./D:/data"), PathBuf::from(r"\\?\D:\data"));
+        assert_eq!(convert(r"\\.\D:\"), PathBuf::from(r"\\?\D:\"));
+        assert_eq!(convert(r"\\.\D:\..\.."), PathBuf::from(r"\\?\D:\"));
+        assert_eq!(convert(r"\\.\D:"), PathBuf::from(r"\\?\D:"));
+        assert_eq!(
+            convert(r"\\.\UNC\server\share\dir"),
+            PathBuf::from(r"\\?\UNC\server\share\dir")
+        );
+        assert_eq!(convert(r"\\?\D:\data"), PathBuf::from(r"\\?\D:\data"));
+        assert_eq!(convert("relative/dir"), PathBuf::from("relative/dir"));
+    }
+
+    #[test]
+    fn test_drive_roots() {
+        assert_eq!(drive_letter(Path::new("/tmp")), None);
+        assert!(!is_drive_root(Path::new("/")));
+
+        #[cfg(windows)]
+        {
+            assert_eq!(drive_letter(Path::new("d:\\data")), Some('D'));
+            assert!(is_drive_root(Path::new("D:\\")));
+            assert!(!is_drive_root(Path::new("D:\\data")));
+            assert!(is_system_drive(Path::new("C:\\")));
+            assert!(!is_system_drive(Path::new("Q:\\")));
+
+            let volume = r"\\?\Volume{3f2504e0-4f89-11d3-9a0c-0305e82c3301}";
+            assert_eq!(
+                volume_guid(Path::new(&format!(r"{}\data", volume))).as_deref(),
+                Some(&volume[4..])
+            );
+            assert_eq!(volume_guid(Path::new(r"\\?\C:\data")), None);
+            assert_eq!(volume_guid(Path::new(r"\\?\GLOBALROOT\Device")), None);
+            assert!(is_drive_root(Path::new(&format!(r"{}\", volume))));
+            assert!(!is_drive_root(Path::new(&format!(r"{}\data", volume))));
+
+            assert_eq!(drive_letter(Path::new(r"\\.\d:\data")), Some('D'));
+            assert_eq!(drive_letter(Path::new(r"\\.\pipe\name")), None);
+            assert_eq!(
+                volume_guid(Path::new(&format!(r"\\.\{}\data", &volume[4..]))).as_deref(),
+                Some(&volume[4..])
+            );
+            assert!(is_drive_root(Path::new(r"\\.\D:\")));
+
+            // The GUID path of the system volume is the system drive
+            let system = crate::winapi::volume_id(Path::new("C:\\")).unwrap();
+            assert!(is_system_drive(Path::new(&system)));
+            assert!(is_system_directory(Path::new(&system)));
+        }
+    }
+}
@@ -0,0 +1,525 @@
+//! Target list handling: normalization, overlap detection between multiple deletion targets,
+//! and targets that are themselves links
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Component, Path, PathBuf, Prefix, PrefixComponent};
+
+use crate::error::RmError;
+use crate::safety;
+
+/// Bring a target into the one form that safety checks, overlap detection and the broker's
+/// path keys all work on
+///
+/// Device paths become verbatim paths, `.` and `..` are collapsed as the OS would resolve
+/// them (on Unix a `..` after a symlink leaves its target, not the link), separators are
+/// unified and trailing ones dropped, 8.3 short names are expanded and drive letters
+/// lowercased, so `dir/` and `dir` (as `rm -rf dir/` users expect) or `C:\PROGRA~1\App` and
+/// `c:\Program Files\App` name the same target. Roots such as `/` and `C:\` stay roots, and a
+/// bare verbatim volume (`\\?\D:`, `\\?\Volume{GUID}`, which open the volume itself) gets
+/// its root directory back.
+pub fn normalize(path: &Path) -> PathBuf {
+    let path = safety::device_path_to_verbatim(path);
+    let mut normal = PathBuf::new();
+    // Names a `..` may still remove
+    let mut depth = 0;
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => normal.push(lowercase_drive(prefix)),
+            Component::RootDir => normal.push(component),
+            Component::CurDir => {}
+            // Windows resolves `..` lexically itself; Unix goes up from where a link leads
+            Component::ParentDir if depth > 0 && cfg!(unix) && is_symlink(&normal) => {
+                match normal.canonicalize() {
+                    Ok(target) => {
+                        normal = target;
+                        normal.pop();
+                        depth = normal
+                            .components()
+                            .filter(|c| matches!(c, Component::Normal(_)))
+                            .count();
+                    }
+                    // A broken link: left for the OS to refuse
+                    Err(_) => {
+                        normal.push("..");
+                        depth = 0;
+                    }
+                }
+            }
+            Component::ParentDir if depth > 0 => {
+                normal.pop();
+                depth -= 1;
+            }
+            // `..` of a root is the root itself
+            Component::ParentDir if normal.has_root() => {}
+            Component::ParentDir => normal.push(".."),
+            Component::Normal(name) => {
+                normal.push(name);
+                depth += 1;
+            }
+        }
+    }
+
+    let bare_volume = path.to_string_lossy().starts_with(r"\\?\")
+        && (safety::drive_letter(&normal).is_some() || safety::volume_guid(&normal).is_some())
+        && normal.components().nth(1).is_none();
+    if bare_volume {
+        normal.push(std::path::MAIN_SEPARATOR_STR);
+    }
+    if normal.as_os_str().is_empty() {
+        normal.push(".");
+    }
+    // Only short names carry a `~`; expanding needs the path to exist
+    if normal.to_string_lossy().contains('~') {
+        if let Some(long) = crate::winapi::long_path(&normal) {
+            return long;
+        }
+    }
+    normal
+}
+
+fn is_symlink(path: &Path) -> bool {
+    path.symlink_metadata()
+        .is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+/// Expand `%NAME%`, `$NAME` and `${NAME}` environment variables in `text`, as scheduled tasks
+/// and service configs often pass such strings unexpanded
+///
+/// A variable that is not set is an error naming it, not an empty string that could turn
+/// `$BUILD/out` into `/out`. `%%` and `$$` stand for a literal `%` and `$`.
+pub fn expand_env_vars(text: &str) -> Result<String, String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find(['%', '$']) {
+        expanded.push_str(&rest[..at]);
+        let sigil = if rest[at..].starts_with('%') {
+            '%'
+        } else {
+            '$'
+        };
+        let after = &rest[at + 1..];
+        if after.starts_with(sigil) {
+            expanded.push(sigil);
+            rest = &after[1..];
+            continue;
+        }
+        let (name, len) = match sigil {
+            // Windows names may hold parentheses: %ProgramFiles(x86)%
+            '%' => match after.find('%') {
+                Some(end)
+                    if after[..end]
+                        .chars()
+                        .all(|c| is_name_char(c) || "()".contains(c)) =>
+                {
+                    (&after[..end], end + 1)
+                }
+                _ => ("", 0),
+            },
+            _ => match after.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) if braced[..end].chars().all(is_name_char) => {
+                        (&braced[..end], end + 2)
+                    }
+                    _ => ("", 0),
+                },
+                None => {
+                    let end = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+                    (&after[..end], end)
+                }
+            },
+        };
+        if name.is_empty() {
+            // Not a variable: `100%`, a lone `$`
+            expanded.push(sigil);
+            rest = after;
+            continue;
+        }
+        expanded.push_str(&std::env::var(name).map_err(|_| name.to_string())?);
+        rest = &after[len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Expand the environment variables in a target (see [`expand_env_vars`])
+pub fn expand_env(path: &Path) -> Result<PathBuf, RmError> {
+    // Variable references are ASCII; a path that is not Unicode is taken as it is
+    let Some(text) = path.to_str() else {
+        return Ok(path.to_path_buf());
+    };
+    expand_env_vars(text)
+        .map(PathBuf::from)
+        .map_err(|name| RmError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: format!("environment variable {} is not set", name),
+        })
+}
+
+/// The targets of a run, normalized: those typed on the command line, then those listed in
+/// a file (`--from-file`)
+///
+/// Typed targets are taken as they are unless `expand_typed`: the shell has expanded them
+/// already, and names such as `$Recycle.Bin` or a folder literally called `$HOME` must
+/// stay what they say. Listed targets never went through a shell, so theirs are expanded.
+pub fn collect_targets(
+    typed: &[PathBuf],
+    listed: &[PathBuf],
+    expand_typed: bool,
+) -> Result<Vec<PathBuf>, RmError> {
+    let typed = typed.iter().map(|path| {
+        if expand_typed {
+            expand_env(path)
+        } else {
+            Ok(path.clone())
+        }
+    });
+    typed
+        .chain(listed.iter().map(|path| expand_env(path)))
+        .map(|path| path.map(|path| normalize(&path)))
+        .collect()
+}
+
+/// A path prefix with its drive letter, if any, in lowercase
+fn lowercase_drive(prefix: PrefixComponent) -> OsString {
+    match prefix.kind() {
+        Prefix::Disk(letter) => format!("{}:", letter.to_ascii_lowercase() as char).into(),
+        Prefix::VerbatimDisk(letter) => {
+            format!(r"\\?\{}:", letter.to_ascii_lowercase() as char).into()
+        }
+        _ => prefix.as_os_str().to_os_string(),
+    }
+}
+
+/// Targets listed in a file, one per line
+///
+/// Blank lines and lines starting with `#` are skipped, surrounding whitespace and a pair of
+/// double quotes (as pasted from a command line) are stripped, and so is a UTF-8 byte order
+/// mark from editors such as Notepad.
+pub fn parse_target_list(text: &str) -> Vec<PathBuf> {
+    text.trim_start_matches('\u{feff}')
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let unquoted = line
+                .strip_prefix('"')
+                .and_then(|line| line.strip_suffix('"'));
+            PathBuf::from(unquoted.unwrap_or(line))
+        })
+        .collect()
+}
+
+/// A target dropped because another target already covers it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlap {
+    /// The target that will not be processed on its own
+    pub dropped: PathBuf,
+    /// The target that contains (or is the same directory as) the dropped one
+    pub covered_by: PathBuf,
+}
+
+/// Resolve a target to the form used for overlap comparison
+///
+/// Canonicalization resolves junctions, symlinks and (on Windows) casing differences.
+fn resolve(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Remove targets that are nested inside, or identical to, another target
+///
+/// Returns the remaining targets in their original order along with the dropped overlaps,
+/// so the same subtree is never scheduled by two brokers at once.
+pub fn dedupe_targets(paths: &[PathBuf]) -> (Vec<PathBuf>, Vec<Overlap>) {
+    let resolved: Vec<PathBuf> = paths.iter().map(|p| resolve(p)).collect();
+    let mut keep = vec![true; paths.len()];
+    let mut overlaps = Vec::new();
+
+    for i in 0..paths.len() {
+        for j in 0..paths.len() {
+            if i == j || !keep[j] {
+                continue;
+            }
+            let same = resolved[i] == resolved[j];
+            // For identical targets keep the first occurrence
+            if (same && j < i) || (!same && resolved[i].starts_with(&resolved[j])) {
+                keep[i] = false;
+                overlaps.push(Overlap {
+                    dropped: paths[i].clone(),
+                    covered_by: paths[j].clone(),
+                });
+                break;
+            }
+        }
+    }
+
+    let remaining = paths
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(p, _)| p.clone())
+        .collect();
+
+    (remaining, overlaps)
+}
+
+/// How many suggestions [`similar_dirs`] returns at most
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Directories beside a missing target whose names are close to its name, closest first
+///
+/// Names are compared without regard to case, within one edit (a swapped pair of letters
+/// counts as one) for short names and one per four characters for longer ones, so `app-v1` finds `app-v2` but not `app-v2-backup`.
+pub fn similar_dirs(path: &Path) -> Vec<PathBuf> {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_lowercase()) else {
+        return Vec::new();
+    };
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let max_distance = (name.chars().count() / 4).max(1);
+    let mut close: Vec<(usize, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let other = entry.file_name().to_string_lossy().to_lowercase();
+            let distance = edit_distance(&name, &other);
+            (distance <= max_distance && entry.path().is_dir())
+                .then(|| (distance, path.with_file_name(entry.file_name())))
+        })
+        .collect();
+    close.sort();
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbours) between two
+/// strings, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// True if the target itself is a symlink or junction (without following it)
+pub fn is_link(path: &Path) -> bool {
+    path.symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink())
+}
+
+/// Remove a link target, leaving whatever it points to alone
+pub fn remove_link(path: &Path) -> io::Result<()> {
+    // Directory links (and junctions) are directory entries on Windows, files elsewhere
+    if cfg!(windows) {
+        crate::winapi::remove_dir(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_normalize() {
+        let normal = |path: &str| normalize(Path::new(path));
+        assert_eq!(normal("build/"), PathBuf::from("build"));
+        assert_eq!(normal("a/b//"), PathBuf::from("a/b"));
+        assert_eq!(normal("./a/./b/../c"), PathBuf::from("a/c"));
+        assert_eq!(normal("a/.."), PathBuf::from("."));
+        assert_eq!(normal("../../a"), PathBuf::from("../../a"));
+        assert_eq!(normal("/"), PathBuf::from("/"));
+        assert_eq!(normal("/../tmp/"), PathBuf::from("/tmp"));
+
+        #[cfg(windows)]
+        {
+            assert_eq!(normal(r"D:/data\logs\"), PathBuf::from(r"d:\data\logs"));
+            assert_eq!(normal(r"D:\"), PathBuf::from(r"d:\"));
+            assert_eq!(normal(r"\\.\D:\data"), PathBuf::from(r"\\?\d:\data"));
+            assert_eq!(normal(r"\\?\D:"), PathBuf::from(r"\\?\d:\"));
+            let volume = r"\\?\Volume{3f2504e0-4f89-11d3-9a0c-0305e82c3301}";
+            let root = PathBuf::from(format!(r"{}\", volume));
+            assert_eq!(normal(volume), root);
+            assert_eq!(normal(&format!(r"{}\data\", volume)), root.join("data"));
+
+            // 8.3 names are expanded where the volume keeps them, and left alone otherwise
+            if Path::new(r"C:\PROGRA~1").exists() {
+                assert_eq!(normal(r"C:\PROGRA~1"), PathBuf::from(r"c:\Program Files"));
+            }
+            assert_eq!(normal(r"C:\NOSUCH~1\x"), PathBuf::from(r"c:\NOSUCH~1\x"));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_normalize_parent_of_symlink() {
+        let temp = std::env::temp_dir().join("win_rmdir_normalize_link_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        std::fs::create_dir_all(temp.join("real/inner")).unwrap();
+        std::fs::create_dir_all(temp.join("a")).unwrap();
+        std::os::unix::fs::symlink(temp.join("real/inner"), temp.join("a/link")).unwrap();
+
+        // The OS takes `a/link/..` to `real`, not `a`
+        let real = temp.join("real").canonicalize().unwrap();
+        assert_eq!(normalize(&temp.join("a/link/../x")), real.join("x"));
+        assert_eq!(normalize(&temp.join("a/plain/../x")), temp.join("a/x"));
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        std::env::set_var("RMBRR_EXPAND_TEST", "/data/ci");
+        let expand = |text: &str| expand_env_vars(text);
+        assert_eq!(expand("%RMBRR_EXPAND_TEST%/out").unwrap(), "/data/ci/out");
+        assert_eq!(expand("$RMBRR_EXPAND_TEST/out").unwrap(), "/data/ci/out");
+        assert_eq!(expand("${RMBRR_EXPAND_TEST}out").unwrap(), "/data/ciout");
+        assert_eq!(expand("plain/path").unwrap(), "plain/path");
+        assert_eq!(expand("100% done, 5%").unwrap(), "100% done, 5%");
+        assert_eq!(expand("cost$/$$HOME/%%x%%").unwrap(), "cost$/$HOME/%x%");
+        assert_eq!(
+            expand("$RMBRR_UNSET_TEST/out").unwrap_err(),
+            "RMBRR_UNSET_TEST"
+        );
+        assert!(matches!(
+            expand_env(Path::new("%RMBRR_UNSET_TEST%")),
+            Err(RmError::InvalidPath { .. })
+        ));
+    }
+
+    #[test]
+    fn test_collect_targets() {
+        std::env::set_var("RMBRR_COLLECT_TEST", "/data/ci");
+        let typed = [
+            PathBuf::from("$RMBRR_COLLECT_TEST"),
+            PathBuf::from("$Recycle.Bin/"),
+        ];
+        let listed = [PathBuf::from("$RMBRR_COLLECT_TEST/out")];
+        // A literal `$NAME` on the command line is left as typed
+        assert_eq!(
+            collect_targets(&typed, &listed, false).unwrap(),
+            vec![
+                PathBuf::from("$RMBRR_COLLECT_TEST"),
+                PathBuf::from("$Recycle.Bin"),
+                PathBuf::from("/data/ci/out"),
+            ]
+        );
+        assert_eq!(
+            collect_targets(&typed[..1], &[], true).unwrap(),
+            vec![PathBuf::from("/data/ci")]
+        );
+        assert!(collect_targets(&typed[1..], &[], true).is_err());
+    }
+
+    #[test]
+    fn test_parse_target_list() {
+        let text = "\u{feff}# nightly cleanup\r\nD:\\ci\\a\r\n\r\n  \"D:\\ci\\with space\"  \n#D:\\skipped\nbuild/out";
+        assert_eq!(
+            parse_target_list(text),
+            vec![
+                PathBuf::from("D:\\ci\\a"),
+                PathBuf::from("D:\\ci\\with space"),
+                PathBuf::from("build/out"),
+            ]
+        );
+        assert!(parse_target_list("\n# nothing\n").is_empty());
+    }
+
+    #[test]
+    fn test_nested_and_duplicate_targets() {
+        let temp = std::env::temp_dir().join("win_rmdir_overlap_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("a/nested")).unwrap();
+        fs::create_dir_all(temp.join("b")).unwrap();
+
+        let paths = vec![
+            temp.join("a/nested"),
+            temp.join("b"),
+            temp.join("a"),
+            temp.join("b/../b"),
+        ];
+        let (remaining, overlaps) = dedupe_targets(&paths);
+
+        assert_eq!(remaining, vec![temp.join("b"), temp.join("a")]);
+        assert_eq!(overlaps.len(), 2);
+        assert_eq!(overlaps[0].dropped, temp.join("a/nested"));
+        assert_eq!(overlaps[0].covered_by, temp.join("a"));
+        assert_eq!(overlaps[1].covered_by, temp.join("b"));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_disjoint_targets_untouched() {
+        let paths = vec![
+            PathBuf::from("/nonexistent/one"),
+            PathBuf::from("/nonexistent/two"),
+        ];
+        let (remaining, overlaps) = dedupe_targets(&paths);
+        assert_eq!(remaining, paths);
+        assert!(overlaps.is_empty());
+    }
+
+    #[test]
+    fn test_similar_dirs() {
+        let temp = std::env::temp_dir().join("win_rmdir_similar_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("app-v2")).unwrap();
+        fs::create_dir_all(temp.join("App-v1-backup")).unwrap();
+        fs::create_dir_all(temp.join("build")).unwrap();
+        fs::write(temp.join("app-v3"), b"a file, not a target").unwrap();
+
+        assert_eq!(
+            similar_dirs(&temp.join("app-v1")),
+            vec![temp.join("app-v2")]
+        );
+        assert_eq!(similar_dirs(&temp.join("BUILD")), vec![temp.join("build")]);
+        assert_eq!(similar_dirs(&temp.join("bulid")), vec![temp.join("build")]);
+        assert!(similar_dirs(&temp.join("dist")).is_empty());
+        assert!(similar_dirs(&temp.join("missing/app-v1")).is_empty());
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_link_keeps_target() {
+        let temp = std::env::temp_dir().join("win_rmdir_link_root_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("real/sub")).unwrap();
+        std::os::unix::fs::symlink(temp.join("real"), temp.join("link")).unwrap();
+
+        assert!(is_link(&temp.join("link")));
+        assert!(!is_link(&temp.join("real")));
+        remove_link(&temp.join("link")).unwrap();
+        assert!(temp.join("real/sub").exists());
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
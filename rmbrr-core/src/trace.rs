@@ -0,0 +1,86 @@
+//! Timing spans for performance analysis (the CLI exports them as a Chrome trace)
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Thread id used for spans recorded outside the worker pool (scan, setup)
+pub const MAIN_THREAD: usize = 0;
+
+/// Thread id for spans recorded by worker `id`
+pub fn worker_thread_id(id: usize) -> usize {
+    id + 1
+}
+
+/// One timed operation
+#[derive(Debug, Clone)]
+pub struct Span {
+    /// What was timed, e.g. the directory path
+    pub name: String,
+    /// Kind of span: "scan", "delete", "wait" or "retry"
+    pub category: &'static str,
+    pub thread: usize,
+    /// Offset from the tracer's creation
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// Collects spans from any thread
+pub struct Tracer {
+    origin: Instant,
+    spans: Mutex<Vec<Span>>,
+}
+
+impl Tracer {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            spans: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record a span that started at `started` and ends now
+    pub fn record(
+        &self,
+        category: &'static str,
+        name: impl Into<String>,
+        thread: usize,
+        started: Instant,
+    ) {
+        let span = Span {
+            name: name.into(),
+            category,
+            thread,
+            start: started.saturating_duration_since(self.origin),
+            duration: started.elapsed(),
+        };
+        self.spans.lock().unwrap().push(span);
+    }
+
+    pub fn spans(&self) -> Vec<Span> {
+        self.spans.lock().unwrap().clone()
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_span() {
+        let tracer = Tracer::new();
+        let started = Instant::now();
+        tracer.record("delete", "/x", worker_thread_id(2), started);
+
+        let spans = tracer.spans();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].thread, 3);
+        assert_eq!(spans[0].category, "delete");
+        assert!(spans[0].duration <= started.elapsed());
+    }
+}
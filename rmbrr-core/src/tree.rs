@@ -0,0 +1,794 @@
+// Directory tree discovery and dependency graph construction
+
+use crate::filter::Filter;
+use crate::winapi::LinkKind;
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Files a sized scan keeps in `DirectoryTree::largest`
+pub const LARGEST_FILES: usize = 100;
+
+/// Longest path Windows accepts, in UTF-16 units, even with the `\\?\` prefix
+pub const MAX_PATH_CHARS: usize = 32_767;
+
+/// Nesting below which a tree looks hand-made; deeper ones usually come from runaway
+/// recursion (a build copying into itself, a junction loop unrolled by another tool)
+pub const SUSPICIOUS_DEPTH: usize = 256;
+
+#[derive(Debug)]
+pub struct DirectoryTree {
+    /// All directories in the tree
+    pub dirs: Vec<PathBuf>,
+    /// All files in the tree (for two-phase deletion); empty after `discover_tree_dirs_only`
+    pub files: Vec<PathBuf>,
+    /// Map of directory -> list of child directories
+    pub children: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Directories with no subdirectories (initial leaves)
+    pub leaves: Vec<PathBuf>,
+    /// Total number of files in the tree
+    pub file_count: usize,
+    /// Directories that will survive because a filter keeps some of their contents
+    pub kept_dirs: HashSet<PathBuf>,
+    /// Total size of the files to delete, counting hardlinked files once (0 unless scanned
+    /// with `discover_tree_sized`)
+    pub bytes: u64,
+    /// Files to delete whose data is EFS-encrypted
+    pub encrypted_files: usize,
+    /// Disk space the deletion should give back: the allocated size of the files, which
+    /// compression and sparse ranges make smaller than `bytes`, leaving out files hardlinked
+    /// from outside the tree (0 unless sized)
+    pub allocated_bytes: u64,
+    /// Part of `bytes` that stays allocated because the files have hardlinks outside the tree
+    pub shared_bytes: u64,
+    /// Symlinks, junctions and other links found; they are deleted as entries, never followed
+    pub links: Vec<FoundLink>,
+    /// The biggest files to delete with their sizes, largest first; hardlinked files are
+    /// listed once (empty unless sized, at most `LARGEST_FILES`)
+    pub largest: Vec<(PathBuf, u64)>,
+    /// File index of each directory on the root's volume (empty unless recorded with
+    /// `record_dir_ids`)
+    pub dir_ids: HashMap<PathBuf, u64>,
+    /// The entry with the longest absolute path and that path's length in characters
+    /// (UTF-16 units on Windows)
+    pub longest_path: Option<(PathBuf, usize)>,
+    /// The most deeply nested directory and its depth (the root's subdirectories are 1)
+    pub deepest_dir: Option<(PathBuf, usize)>,
+    /// Entries whose absolute path is longer than `MAX_PATH_CHARS`
+    pub too_long_paths: usize,
+}
+
+/// A link met during the scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundLink {
+    pub path: PathBuf,
+    pub kind: LinkKind,
+    /// False if a filter keeps it
+    pub selected: bool,
+}
+
+impl DirectoryTree {
+    pub fn new() -> Self {
+        Self {
+            dirs: Vec::new(),
+            files: Vec::new(),
+            children: HashMap::new(),
+            leaves: Vec::new(),
+            file_count: 0,
+            kept_dirs: HashSet::new(),
+            bytes: 0,
+            shared_bytes: 0,
+            allocated_bytes: 0,
+            encrypted_files: 0,
+            links: Vec::new(),
+            largest: Vec::new(),
+            dir_ids: HashMap::new(),
+            longest_path: None,
+            deepest_dir: None,
+            too_long_paths: 0,
+        }
+    }
+
+    /// Record the file ID of every directory on the same volume as the first (the root),
+    /// for deletion by ID; directories whose ID cannot be read are left out
+    ///
+    /// This opens each directory; a scan with `discover_tree_by_id` reads the IDs from its
+    /// listings instead, so this is for trees that were not scanned, such as a loaded plan.
+    pub fn record_dir_ids(&mut self) {
+        let mut volume = None;
+        for dir in &self.dirs {
+            let Ok((dir_volume, index)) = crate::winapi::file_id(dir) else {
+                continue;
+            };
+            if *volume.get_or_insert(dir_volume) == dir_volume {
+                self.dir_ids.insert(dir.clone(), index);
+            }
+        }
+    }
+
+    /// Put the initial leaves in random order, so workers start out spread across the
+    /// volume instead of all in sibling directories
+    pub fn shuffle_leaves(&mut self) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(self.leaves.len());
+        shuffle(&mut self.leaves, hasher.finish());
+    }
+
+    /// Put the initial leaves in path order, so a single worker starts on them the same way
+    /// every run
+    pub fn sort_leaves(&mut self) {
+        self.leaves.sort();
+    }
+
+    /// Add the tree of another root, so one broker and worker pool delete both as a forest
+    ///
+    /// The roots must not overlap. `dirs` keeps this tree's root first. Files hardlinked
+    /// between the two trees are counted in both.
+    pub fn merge(&mut self, other: DirectoryTree) {
+        self.dirs.extend(other.dirs);
+        self.files.extend(other.files);
+        self.children.extend(other.children);
+        self.leaves.extend(other.leaves);
+        self.file_count += other.file_count;
+        self.kept_dirs.extend(other.kept_dirs);
+        self.bytes += other.bytes;
+        self.encrypted_files += other.encrypted_files;
+        self.allocated_bytes += other.allocated_bytes;
+        self.shared_bytes += other.shared_bytes;
+        self.links.extend(other.links);
+        self.largest.extend(other.largest);
+        self.largest.sort_by_key(|&(_, size)| Reverse(size));
+        self.largest.truncate(LARGEST_FILES);
+        self.dir_ids.extend(other.dir_ids);
+        if other.longest_path.as_ref().map(|(_, len)| *len)
+            > self.longest_path.as_ref().map(|(_, len)| *len)
+        {
+            self.longest_path = other.longest_path;
+        }
+        if other.deepest_dir.as_ref().map(|(_, depth)| *depth)
+            > self.deepest_dir.as_ref().map(|(_, depth)| *depth)
+        {
+            self.deepest_dir = other.deepest_dir;
+        }
+        self.too_long_paths += other.too_long_paths;
+    }
+}
+
+/// Fisher-Yates shuffle driven by splitmix64
+fn shuffle<T>(items: &mut [T], mut seed: u64) {
+    for i in (1..items.len()).rev() {
+        seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        items.swap(i, (z % (i as u64 + 1)) as usize);
+    }
+}
+
+impl Default for DirectoryTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn discover_tree(root: &Path) -> io::Result<DirectoryTree> {
+    discover_tree_filtered(root, None)
+}
+
+/// Discover the tree, recording only files the filter selects for deletion
+///
+/// Directories that will still contain entries after deletion are recorded in `kept_dirs`.
+pub fn discover_tree_filtered(root: &Path, filter: Option<&Filter>) -> io::Result<DirectoryTree> {
+    scan_tree(root, filter, false, true, false)
+}
+
+/// Like `discover_tree_filtered`, also totalling file sizes into `bytes`
+///
+/// On Unix this costs a stat per file; on Windows sizes come with the listing. Non-empty
+/// files are also checked for hardlinks, to count each file once and find those whose space
+/// is not freed (`shared_bytes`), and for their allocated size (`allocated_bytes`).
+pub fn discover_tree_sized(root: &Path, filter: Option<&Filter>) -> io::Result<DirectoryTree> {
+    scan_tree(root, filter, true, true, false)
+}
+
+/// Discover only the directories and their parent-child links, counting files without
+/// recording their paths
+///
+/// Workers list each directory's files themselves anyway, so this is all a deletion needs;
+/// it saves the memory of one path per file. `sized` totals sizes as `discover_tree_sized`
+/// does.
+pub fn discover_tree_dirs_only(
+    root: &Path,
+    filter: Option<&Filter>,
+    sized: bool,
+) -> io::Result<DirectoryTree> {
+    scan_tree(root, filter, sized, false, false)
+}
+
+/// Discover the tree as `discover_tree_filtered` does (or `discover_tree_dirs_only` unless
+/// `record_files`), also recording the file ID of each directory on the root's volume into
+/// `dir_ids` for deletion by ID
+///
+/// The IDs come with the directory listings on Windows; other platforms list none.
+pub fn discover_tree_by_id(
+    root: &Path,
+    filter: Option<&Filter>,
+    sized: bool,
+    record_files: bool,
+) -> io::Result<DirectoryTree> {
+    scan_tree(root, filter, sized, record_files, true)
+}
+
+fn scan_tree(
+    root: &Path,
+    filter: Option<&Filter>,
+    measure: bool,
+    record_files: bool,
+    record_ids: bool,
+) -> io::Result<DirectoryTree> {
+    let mut tree = DirectoryTree::new();
+    // The root's own ID is not in any listing this scan makes
+    let root_id = if record_ids {
+        crate::winapi::file_id(root).ok()
+    } else {
+        None
+    };
+    if let Some((_, index)) = root_id {
+        tree.dir_ids.insert(root.to_path_buf(), index);
+    }
+    let mut scanner = Scanner {
+        filter,
+        measure,
+        record_files,
+        root_volume: root_id.map(|(volume, _)| volume),
+        tree,
+        all_dirs: HashSet::new(),
+        has_children: HashSet::new(),
+        hardlinked: HashMap::new(),
+        largest: BinaryHeap::new(),
+        // Relative targets are measured as the absolute paths Windows sees
+        prefix_chars: std::path::absolute(root)
+            .map_or(0, |abs| path_chars(&abs).saturating_sub(path_chars(root))),
+    };
+
+    scanner.scan(root, 0)?;
+
+    let Scanner {
+        mut tree,
+        all_dirs,
+        has_children,
+        hardlinked,
+        largest,
+        ..
+    } = scanner;
+    tree.largest = largest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((size, path))| (path, size))
+        .collect();
+
+    for file in hardlinked.values() {
+        tree.bytes += file.size;
+        if file.found < file.links {
+            tree.shared_bytes += file.size;
+        } else {
+            tree.allocated_bytes += file.allocated;
+        }
+    }
+
+    tree.dirs = all_dirs.into_iter().collect();
+    tree.dirs.sort();
+
+    for dir in &tree.dirs {
+        if !has_children.contains(dir) {
+            tree.leaves.push(dir.clone());
+        }
+    }
+
+    Ok(tree)
+}
+
+struct Scanner<'a> {
+    filter: Option<&'a Filter>,
+    measure: bool,
+    /// False to only count files
+    record_files: bool,
+    /// Volume of the root when directory IDs are recorded
+    root_volume: Option<u64>,
+    tree: DirectoryTree,
+    all_dirs: HashSet<PathBuf>,
+    has_children: HashSet<PathBuf>,
+    /// Files with several hardlinks, by identity; sized once they are all scanned
+    hardlinked: HashMap<(u64, u64), HardlinkedFile>,
+    /// Min-heap of the biggest files so far
+    largest: BinaryHeap<Reverse<(u64, PathBuf)>>,
+    /// Characters the absolute form of a path has beyond its scanned form
+    prefix_chars: usize,
+}
+
+/// Length of a path in characters: UTF-16 units on Windows, as its limits count them
+fn path_chars(path: &Path) -> usize {
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        path.as_os_str().encode_wide().count()
+    }
+    #[cfg(not(windows))]
+    {
+        path.to_string_lossy().chars().count()
+    }
+}
+
+/// Note `path` in the longest-path statistics
+fn measure_path(
+    longest_path: &mut Option<(PathBuf, usize)>,
+    too_long_paths: &mut usize,
+    prefix_chars: usize,
+    path: &Path,
+) {
+    let longest = longest_path.as_ref().map_or(0, |(_, len)| *len);
+    // Never fewer bytes than characters, so most paths are ruled out without counting
+    if path.as_os_str().len() + prefix_chars <= longest.min(MAX_PATH_CHARS) {
+        return;
+    }
+    let len = path_chars(path) + prefix_chars;
+    if len > MAX_PATH_CHARS {
+        *too_long_paths += 1;
+    }
+    if len > longest {
+        *longest_path = Some((path.to_path_buf(), len));
+    }
+}
+
+fn offer_largest(largest: &mut BinaryHeap<Reverse<(u64, PathBuf)>>, path: &Path, size: u64) {
+    if largest.len() < LARGEST_FILES {
+        largest.push(Reverse((size, path.to_path_buf())));
+    } else if largest.peek().is_some_and(|Reverse((min, _))| size > *min) {
+        largest.pop();
+        largest.push(Reverse((size, path.to_path_buf())));
+    }
+}
+
+struct HardlinkedFile {
+    size: u64,
+    allocated: u64,
+    links: u32,
+    /// Links to it selected for deletion
+    found: u32,
+}
+
+impl Scanner<'_> {
+    /// Scan a directory recursively, returning true if it will end up empty
+    fn scan(&mut self, dir: &Path, depth: usize) -> io::Result<bool> {
+        self.all_dirs.insert(dir.to_path_buf());
+        if self
+            .tree
+            .deepest_dir
+            .as_ref()
+            .is_none_or(|(_, d)| depth > *d)
+        {
+            self.tree.deepest_dir = Some((dir.to_path_buf(), depth));
+        }
+        let prefix_chars = self.prefix_chars;
+        let longest_path = &mut self.tree.longest_path;
+        let too_long_paths = &mut self.tree.too_long_paths;
+        measure_path(longest_path, too_long_paths, prefix_chars, dir);
+
+        let mut child_dirs = Vec::new();
+        let mut keeps_files = false; // or kept subdirectories
+        let filter = self.filter;
+        let files = &mut self.tree.files;
+        let file_count = &mut self.tree.file_count;
+        let record_files = self.record_files;
+        let bytes = &mut self.tree.bytes;
+        let allocated_bytes = &mut self.tree.allocated_bytes;
+        let links = &mut self.tree.links;
+        let hardlinked = &mut self.hardlinked;
+        let largest = &mut self.largest;
+        let encrypted_files = &mut self.tree.encrypted_files;
+        let dir_ids = &mut self.tree.dir_ids;
+        let root_volume = self.root_volume;
+        let measure = self.measure;
+
+        if let Err(e) =
+            crate::winapi::enumerate_entries(dir, measure, |path, is_dir, size, link, meta| {
+                let selected = !is_dir && filter.is_none_or(|f| f.should_delete_file(path));
+                // Directories are measured when scanned
+                if !is_dir {
+                    measure_path(longest_path, too_long_paths, prefix_chars, path);
+                }
+                if let Some(kind) = link {
+                    links.push(FoundLink {
+                        path: path.to_path_buf(),
+                        kind,
+                        selected,
+                    });
+                }
+                if is_dir && filter.is_some_and(|f| f.is_kept(path)) {
+                    // Kept subtrees are never scanned or scheduled
+                    keeps_files = true;
+                } else if is_dir {
+                    if let Some((volume, index)) = meta.id {
+                        if root_volume == Some(volume) {
+                            dir_ids.insert(path.to_path_buf(), index);
+                        }
+                    }
+                    child_dirs.push(path.to_path_buf());
+                } else if selected {
+                    *file_count += 1;
+                    if record_files {
+                        files.push(path.to_path_buf());
+                    }
+                    if meta.attributes & crate::winapi::ATTRIBUTE_ENCRYPTED != 0 {
+                        *encrypted_files += 1;
+                    }
+                    if measure && size > 0 {
+                        let allocated = meta.allocated;
+                        match (meta.id, meta.links) {
+                            (Some(id), Some(links)) if links > 1 => {
+                                let file = hardlinked.entry(id).or_insert(HardlinkedFile {
+                                    size,
+                                    allocated,
+                                    links,
+                                    found: 0,
+                                });
+                                file.found += 1;
+                                if file.found == 1 {
+                                    offer_largest(largest, path, size);
+                                }
+                            }
+                            _ => {
+                                *bytes += size;
+                                *allocated_bytes += allocated;
+                                offer_largest(largest, path, size);
+                            }
+                        }
+                    }
+                } else {
+                    keeps_files = true;
+                }
+                Ok(())
+            })
+        {
+            crate::output::log(
+                "Cannot read",
+                format!("Cannot read {}: {}", dir.display(), e),
+            );
+            return Ok(true);
+        }
+
+        let mut emptied = !keeps_files;
+
+        if !child_dirs.is_empty() {
+            self.has_children.insert(dir.to_path_buf());
+
+            for child in &child_dirs {
+                emptied &= self.scan(child, depth + 1)?;
+            }
+
+            self.tree.children.insert(dir.to_path_buf(), child_dirs);
+        }
+
+        if filter.is_some_and(|f| f.keep_root && f.is_root(dir)) {
+            emptied = false;
+        }
+
+        if !emptied {
+            self.tree.kept_dirs.insert(dir.to_path_buf());
+        }
+
+        Ok(emptied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn create_test_tree(base: &Path) -> io::Result<()> {
+        // Structure:
+        // base/
+        //   a/
+        //     a1/
+        //     a2/
+        //   b/
+        //   c/
+        //     c1/
+
+        fs::create_dir_all(base.join("a/a1"))?;
+        fs::create_dir_all(base.join("a/a2"))?;
+        fs::create_dir(base.join("b"))?;
+        fs::create_dir_all(base.join("c/c1"))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_tree() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_test");
+        let _ = fs::remove_dir_all(&temp);
+
+        create_test_tree(&temp).unwrap();
+
+        let tree = discover_tree(&temp).unwrap();
+
+        // Should find 7 directories: base, a, a1, a2, b, c, c1
+        assert_eq!(tree.dirs.len(), 7);
+
+        // Leaves should be: a1, a2, b, c1 (4 total)
+        assert_eq!(tree.leaves.len(), 4);
+
+        // Verify leaves don't have children
+        for leaf in &tree.leaves {
+            assert!(!tree.children.contains_key(leaf));
+        }
+
+        // Cleanup
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_discover_tree_sized() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_sized_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("sub")).unwrap();
+        fs::write(temp.join("a.bin"), [0u8; 100]).unwrap();
+        fs::write(temp.join("sub/b.bin"), [0u8; 23]).unwrap();
+
+        let tree = discover_tree_sized(&temp, None).unwrap();
+        assert_eq!(tree.bytes, 123);
+        assert_eq!(
+            tree.largest,
+            vec![(temp.join("a.bin"), 100), (temp.join("sub/b.bin"), 23)]
+        );
+        let unsized_tree = discover_tree(&temp).unwrap();
+        assert_eq!(unsized_tree.bytes, 0);
+        assert!(unsized_tree.largest.is_empty());
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_longest_path_and_depth() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_depth_test");
+        let _ = fs::remove_dir_all(&temp);
+        create_test_tree(&temp).unwrap();
+        let long_name = "n".repeat(100);
+        fs::write(temp.join("b").join(&long_name), b"").unwrap();
+
+        let tree = discover_tree(&temp).unwrap();
+        let (deepest, depth) = tree.deepest_dir.unwrap();
+        assert_eq!(depth, 2);
+        assert!(deepest.ends_with("a1") || deepest.ends_with("a2") || deepest.ends_with("c1"));
+        let (path, len) = tree.longest_path.unwrap();
+        assert_eq!(path, temp.join("b").join(&long_name));
+        assert_eq!(len, path_chars(&path));
+        assert_eq!(tree.too_long_paths, 0);
+
+        let (mut longest, mut too_long) = (None, 0);
+        let huge = PathBuf::from("d/".repeat(MAX_PATH_CHARS / 2 + 1));
+        measure_path(&mut longest, &mut too_long, 0, &huge);
+        measure_path(&mut longest, &mut too_long, 0, Path::new("d"));
+        assert_eq!(too_long, 1);
+        assert_eq!(longest.unwrap().1, path_chars(&huge));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_record_dir_ids() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_ids_test");
+        let _ = fs::remove_dir_all(&temp);
+        create_test_tree(&temp).unwrap();
+
+        let mut tree = discover_tree(&temp).unwrap();
+        tree.record_dir_ids();
+        assert_eq!(tree.dir_ids.len(), 7);
+        let (_, index) = crate::winapi::file_id(&temp.join("a/a1")).unwrap();
+        assert_eq!(tree.dir_ids[&temp.join("a/a1")], index);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_discover_tree_by_id() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_scan_ids_test");
+        let _ = fs::remove_dir_all(&temp);
+        create_test_tree(&temp).unwrap();
+
+        let tree = discover_tree_by_id(&temp, None, false, true).unwrap();
+        assert_eq!(tree.dir_ids.len(), 7);
+        for dir in [temp.clone(), temp.join("a/a1"), temp.join("c/c1")] {
+            let (_, index) = crate::winapi::file_id(&dir).unwrap();
+            assert_eq!(tree.dir_ids[&dir], index);
+        }
+        assert!(discover_tree(&temp).unwrap().dir_ids.is_empty());
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_discover_tree_dirs_only() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_dirs_only_test");
+        let _ = fs::remove_dir_all(&temp);
+        create_test_tree(&temp).unwrap();
+        fs::write(temp.join("a/a1/file"), b"x").unwrap();
+
+        let full = discover_tree(&temp).unwrap();
+        let tree = discover_tree_dirs_only(&temp, None, false).unwrap();
+        assert!(tree.files.is_empty());
+        assert_eq!(tree.file_count, full.file_count);
+        assert_eq!(tree.dirs, full.dirs);
+        assert_eq!(tree.children, full.children);
+        assert_eq!(tree.leaves, full.leaves);
+        assert_eq!(discover_tree_dirs_only(&temp, None, true).unwrap().bytes, 1);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sparse_file_allocation() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_sparse_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+        // Extending without writing leaves a hole on filesystems with sparse support
+        fs::File::create(temp.join("sparse.img"))
+            .unwrap()
+            .set_len(64 * 1024 * 1024)
+            .unwrap();
+
+        let tree = discover_tree_sized(&temp, None).unwrap();
+        assert_eq!(tree.bytes, 64 * 1024 * 1024);
+        assert!(tree.allocated_bytes < tree.bytes);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_hardlinks_counted_once() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_hardlink_test");
+        let outside = std::env::temp_dir().join("win_rmdir_tree_hardlink_outside");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("sub")).unwrap();
+        fs::write(temp.join("inner.bin"), [0u8; 100]).unwrap();
+        fs::hard_link(temp.join("inner.bin"), temp.join("sub/inner2.bin")).unwrap();
+        fs::write(temp.join("shared.bin"), [0u8; 40]).unwrap();
+        fs::hard_link(temp.join("shared.bin"), &outside).unwrap();
+
+        let tree = discover_tree_sized(&temp, None).unwrap();
+        assert_eq!(tree.bytes, 140);
+        assert_eq!(tree.shared_bytes, 40);
+
+        fs::remove_dir_all(&temp).ok();
+        fs::remove_file(&outside).ok();
+    }
+
+    #[test]
+    fn test_single_dir() {
+        let temp = std::env::temp_dir().join("win_rmdir_single_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir(&temp).unwrap();
+
+        let tree = discover_tree(&temp).unwrap();
+
+        // Just the root directory
+        assert_eq!(tree.dirs.len(), 1);
+        assert_eq!(tree.leaves.len(), 1);
+
+        fs::remove_dir(&temp).ok();
+    }
+
+    #[test]
+    fn test_filtered_tree_keeps_dirs() {
+        let temp = std::env::temp_dir().join("win_rmdir_filtered_tree_test");
+        let _ = fs::remove_dir_all(&temp);
+
+        fs::create_dir_all(temp.join("logs")).unwrap();
+        fs::create_dir_all(temp.join("src")).unwrap();
+        fs::File::create(temp.join("logs/a.log")).unwrap();
+        fs::File::create(temp.join("src/main.rs")).unwrap();
+        fs::File::create(temp.join("src/b.log")).unwrap();
+
+        let mut filter = Filter::new(&temp);
+        filter.only = crate::filter::parse_patterns(&["*.log"]);
+        let tree = discover_tree_filtered(&temp, Some(&filter)).unwrap();
+
+        assert_eq!(tree.file_count, 2);
+        assert!(tree.kept_dirs.contains(&temp.join("src")));
+        assert!(tree.kept_dirs.contains(&temp));
+        assert!(!tree.kept_dirs.contains(&temp.join("logs")));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let temp = std::env::temp_dir().join("win_rmdir_deep_test");
+        let _ = fs::remove_dir_all(&temp);
+
+        // Create 10 levels deep
+        let mut path = temp.clone();
+        for i in 0..10 {
+            path = path.join(format!("level{}", i));
+        }
+        fs::create_dir_all(&path).unwrap();
+
+        let tree = discover_tree(&temp).unwrap();
+
+        // Should have 11 directories (root + 10 levels)
+        assert_eq!(tree.dirs.len(), 11);
+
+        // Only the deepest is a leaf
+        assert_eq!(tree.leaves.len(), 1);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_merge() {
+        let temp = std::env::temp_dir().join("win_rmdir_merge_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("one/a")).unwrap();
+        fs::create_dir_all(temp.join("two/b/c")).unwrap();
+        fs::write(temp.join("one/a/x"), [0u8; 10]).unwrap();
+        fs::write(temp.join("two/b/c/y"), [0u8; 30]).unwrap();
+
+        let mut tree = discover_tree_sized(&temp.join("one"), None).unwrap();
+        tree.merge(discover_tree_sized(&temp.join("two"), None).unwrap());
+
+        assert_eq!(tree.dirs.len(), 5);
+        assert_eq!(tree.dirs[0], temp.join("one"));
+        assert_eq!(tree.leaves.len(), 2);
+        assert_eq!(tree.file_count, 2);
+        assert_eq!(tree.bytes, 40);
+        assert_eq!(tree.largest[0], (temp.join("two/b/c/y"), 30));
+        assert_eq!(tree.deepest_dir, Some((temp.join("two/b/c"), 2)));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut items: Vec<u32> = (0..100).collect();
+        shuffle(&mut items, 42);
+        assert_ne!(items, (0..100).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+
+        // Same seed, same order
+        let (mut a, mut b) = ((0..10).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+        shuffle(&mut a, 7);
+        shuffle(&mut b, 7);
+        assert_eq!(a, b);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_links_recorded_not_followed() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_links_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("root")).unwrap();
+        fs::create_dir_all(temp.join("outside/inner")).unwrap();
+        std::os::unix::fs::symlink(temp.join("outside"), temp.join("root/link")).unwrap();
+
+        let tree = discover_tree(&temp.join("root")).unwrap();
+        assert_eq!(tree.dirs, vec![temp.join("root")]);
+        assert_eq!(
+            tree.links,
+            vec![FoundLink {
+                path: temp.join("root/link"),
+                kind: LinkKind::Symlink,
+                selected: true,
+            }]
+        );
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
@@ -0,0 +1,155 @@
+//! Adaptive worker count (the CLI's `--threads auto`)
+//!
+//! Every worker is spawned up front, but only the first `limit` take work; the rest park.
+//! A sampling thread raises the limit while each step still buys throughput, then settles
+//! on the last count that did.
+
+use crate::broker::Broker;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long each worker count is measured before deciding the next
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+/// How often parked workers and the sampler check for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// A step must raise throughput by this fraction to count as a gain
+const MIN_GAIN: f64 = 0.05;
+/// Most workers `auto` will ever run
+const MAX_AUTO_WORKERS: usize = 64;
+
+/// Shared between workers, which count finished items and wait for their turn, and the sampler
+pub struct ThreadTuner {
+    limit: AtomicUsize,
+    max: usize,
+    /// Files and directories finished so far
+    items: AtomicUsize,
+    settled: AtomicBool,
+}
+
+impl ThreadTuner {
+    /// Start with `start` active workers and never go beyond `max`
+    pub fn new(start: usize, max: usize) -> Self {
+        let max = max.max(1);
+        Self {
+            limit: AtomicUsize::new(start.clamp(1, max)),
+            max,
+            items: AtomicUsize::new(0),
+            settled: AtomicBool::new(false),
+        }
+    }
+
+    /// Defaults for a machine with `cpus` logical CPUs: start at 2, allow up to twice the CPUs
+    pub fn auto(cpus: usize) -> Self {
+        Self::new(2, (cpus * 2).clamp(2, MAX_AUTO_WORKERS))
+    }
+
+    /// Workers currently allowed to take work
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Workers to spawn
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// True once the count stopped changing
+    pub fn is_settled(&self) -> bool {
+        self.settled.load(Ordering::Relaxed)
+    }
+
+    /// Count one deleted file or directory
+    pub fn record(&self) {
+        self.items.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Park `worker` until the limit includes it; false if the run ended first
+    pub fn wait_turn(&self, worker: usize, broker: &Broker) -> bool {
+        while worker >= self.limit() {
+            if broker.is_finished() || broker.is_cancelled() {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        true
+    }
+
+    /// Sample throughput and adjust the limit until the broker finishes
+    pub fn spawn(self: Arc<Self>, broker: Arc<Broker>) -> JoinHandle<()> {
+        thread::Builder::new()
+            .name("thread-tuner".to_string())
+            .spawn(move || {
+                let mut climb = Climb::default();
+                let mut sampled = (Instant::now(), 0);
+                while !self.is_settled() && !broker.is_finished() && !broker.is_cancelled() {
+                    thread::sleep(POLL_INTERVAL);
+                    let elapsed = sampled.0.elapsed();
+                    if elapsed < SAMPLE_INTERVAL {
+                        continue;
+                    }
+                    let items = self.items.load(Ordering::Relaxed);
+                    let rate = (items - sampled.1) as f64 / elapsed.as_secs_f64();
+                    sampled = (Instant::now(), items);
+
+                    let next = climb.step(self.limit(), self.max, rate);
+                    self.limit.store(next, Ordering::Relaxed);
+                    self.settled.store(climb.settled, Ordering::Relaxed);
+                }
+            })
+            .expect("Failed to spawn tuner thread")
+    }
+}
+
+/// Hill climb over worker counts
+#[derive(Debug, Default)]
+struct Climb {
+    best_rate: f64,
+    best_limit: usize,
+    settled: bool,
+}
+
+impl Climb {
+    /// Next limit after measuring `rate` items/s with `limit` workers
+    fn step(&mut self, limit: usize, max: usize, rate: f64) -> usize {
+        if self.settled {
+            return limit;
+        }
+        if self.best_limit == 0 || rate > self.best_rate * (1.0 + MIN_GAIN) {
+            self.best_rate = rate;
+            self.best_limit = limit;
+            if limit < max {
+                return (limit + (limit / 2).max(1)).min(max);
+            }
+        }
+        // No gain from the last step: go back to the count before it
+        self.settled = true;
+        self.best_limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_climb_settles_on_best_count() {
+        let mut climb = Climb::default();
+        assert_eq!(climb.step(2, 16, 100.0), 3);
+        assert_eq!(climb.step(3, 16, 150.0), 4);
+        assert_eq!(climb.step(4, 16, 200.0), 6);
+        // Two more workers bought nothing
+        assert_eq!(climb.step(6, 16, 202.0), 4);
+        assert!(climb.settled);
+        assert_eq!(climb.step(4, 16, 500.0), 4);
+    }
+
+    #[test]
+    fn test_climb_stops_at_max() {
+        let mut climb = Climb::default();
+        assert_eq!(climb.step(2, 3, 10.0), 3);
+        assert_eq!(climb.step(3, 3, 20.0), 3);
+        assert!(climb.settled);
+    }
+}
@@ -0,0 +1,3310 @@
+// Platform-specific file/directory deletion implementations
+
+use std::io;
+use std::path::Path;
+#[cfg(windows)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(windows)]
+use windows::core::PCWSTR;
+#[cfg(windows)]
+use windows::Wdk::Storage::FileSystem::{
+    FILE_DISPOSITION_DELETE, FILE_DISPOSITION_IGNORE_READONLY_ATTRIBUTE,
+    FILE_DISPOSITION_INFORMATION_EX, FILE_DISPOSITION_INFORMATION_EX_FLAGS,
+    FILE_DISPOSITION_POSIX_SEMANTICS,
+};
+#[cfg(windows)]
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+#[cfg(windows)]
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, FileDispositionInfoEx, FindClose, FindFirstFileExW, FindNextFileW,
+    SetFileInformationByHandle, DELETE, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_REPARSE_POINT,
+    FILE_FLAGS_AND_ATTRIBUTES, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT,
+    FILE_SHARE_DELETE, FILE_SHARE_READ, FILE_SHARE_WRITE, FINDEX_INFO_LEVELS, FINDEX_SEARCH_OPS,
+    FIND_FIRST_EX_FLAGS, OPEN_EXISTING, WIN32_FIND_DATAW,
+};
+
+/// Targets reach here already normalized ([`crate::targets::normalize`]); a device path
+/// (`\\.\`) that did not is one Windows opens as it is
+#[cfg(windows)]
+fn path_to_wide(path: &Path) -> Vec<u16> {
+    let path_str = path.to_string_lossy();
+    let prefixed =
+        if path.is_absolute() && !path_str.starts_with(r"\\?\") && !path_str.starts_with(r"\\.\") {
+            format!(r"\\?\{}", path.display())
+        } else {
+            path_str.to_string()
+        };
+
+    prefixed.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Delete file using POSIX semantics (immediate namespace removal)
+/// Requires Windows 10 1607+ with NTFS
+///
+/// Only DELETE access is requested and the data is never opened, so EFS-encrypted files
+/// go even when the current user lacks the key.
+#[cfg(windows)]
+pub fn delete_file(path: &Path) -> io::Result<()> {
+    let wide_path = path_to_wide(path);
+    unsafe { posix_delete_file(&wide_path) }
+}
+
+/// Delete directory using POSIX semantics (immediate namespace removal)
+/// Requires Windows 10 1607+ with NTFS
+#[cfg(windows)]
+pub fn remove_dir(path: &Path) -> io::Result<()> {
+    let wide_path = path_to_wide(path);
+    unsafe { posix_delete_dir(&wide_path) }
+}
+
+/// Owned file handle, closed when dropped
+#[cfg(windows)]
+pub struct FileHandle(HANDLE);
+
+#[cfg(windows)]
+impl FileHandle {
+    /// Open an existing file or directory with the given access rights and flags
+    ///
+    /// # Safety
+    ///
+    /// `wide_path` must be NUL-terminated.
+    pub unsafe fn open(
+        wide_path: &[u16],
+        access: u32,
+        flags: FILE_FLAGS_AND_ATTRIBUTES,
+    ) -> io::Result<Self> {
+        CreateFileW(
+            PCWSTR(wide_path.as_ptr()),
+            access,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            OPEN_EXISTING,
+            flags,
+            HANDLE::default(),
+        )
+        .map(Self)
+        .map_err(win_error)
+    }
+
+    pub fn as_raw(&self) -> HANDLE {
+        self.0
+    }
+}
+
+#[cfg(windows)]
+impl Drop for FileHandle {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.0).ok();
+        }
+    }
+}
+
+/// Owned directory search handle from FindFirstFileExW, closed when dropped
+#[cfg(windows)]
+pub struct FindHandle(HANDLE);
+
+#[cfg(windows)]
+impl Drop for FindHandle {
+    fn drop(&mut self) {
+        unsafe {
+            FindClose(self.0).ok();
+        }
+    }
+}
+
+/// Convert a Windows API error (an HRESULT wrapping a Win32 code) into an io::Error
+#[cfg(windows)]
+fn win_error(e: windows::core::Error) -> io::Error {
+    io::Error::from_raw_os_error(e.code().0 & 0xFFFF)
+}
+
+/// Set by [`enter_background_mode`]: deletion handles ask the storage stack for low priority
+#[cfg(windows)]
+static LOW_IO_PRIORITY: AtomicBool = AtomicBool::new(false);
+
+/// Hint the storage stack to service this handle's I/O at low priority
+#[cfg(windows)]
+unsafe fn lower_io_priority(handle: &FileHandle) {
+    use windows::Win32::Storage::FileSystem::{
+        FileIoPriorityHintInfo, IoPriorityHintLow, FILE_IO_PRIORITY_HINT_INFO,
+    };
+
+    let mut info = FILE_IO_PRIORITY_HINT_INFO {
+        PriorityHint: IoPriorityHintLow,
+    };
+    // Only a hint: filesystems that don't support it delete at normal priority
+    let _ = SetFileInformationByHandle(
+        handle.as_raw(),
+        FileIoPriorityHintInfo,
+        &mut info as *mut _ as *mut _,
+        std::mem::size_of::<FILE_IO_PRIORITY_HINT_INFO>() as u32,
+    );
+}
+
+/// Set by [`disable_posix_delete`]: entries are deleted with the classic disposition
+#[cfg(windows)]
+static CLASSIC_DELETE: AtomicBool = AtomicBool::new(false);
+
+/// Delete with the classic disposition (`FileDispositionInfo`, as DeleteFileW does) instead
+/// of POSIX semantics, for backup, sync or security filters that mishandle the extended one
+///
+/// An entry another process still has open then stays in its directory until closed, so
+/// its parent can't be removed meanwhile. Read-only entries have their attribute cleared
+/// first, as the classic disposition can't ignore it.
+#[cfg(windows)]
+pub fn disable_posix_delete() {
+    CLASSIC_DELETE.store(true, Ordering::Relaxed);
+}
+
+/// Unix has only the one way of deleting
+#[cfg(not(windows))]
+pub fn disable_posix_delete() {}
+
+/// Set by [`enable_native_delete`]: entries are opened and deleted through ntdll directly
+#[cfg(windows)]
+static NATIVE_DELETE: AtomicBool = AtomicBool::new(false);
+
+#[cfg(windows)]
+type NtOpenFileFn = unsafe extern "system" fn(
+    *mut HANDLE,
+    u32,
+    *const ObjectAttributes,
+    *mut IoStatusBlock,
+    u32,
+    u32,
+) -> i32;
+#[cfg(windows)]
+type NtSetInformationFileFn = unsafe extern "system" fn(
+    HANDLE,
+    *mut IoStatusBlock,
+    *const core::ffi::c_void,
+    u32,
+    i32,
+) -> i32;
+#[cfg(windows)]
+type RtlNtStatusToDosErrorFn = unsafe extern "system" fn(i32) -> u32;
+
+/// The ntdll entry points of the native deletion path, looked up at run time so a system
+/// without them falls back to the Win32 calls
+#[cfg(windows)]
+struct NtApi {
+    open_file: NtOpenFileFn,
+    set_information_file: NtSetInformationFileFn,
+    status_to_dos_error: RtlNtStatusToDosErrorFn,
+}
+
+#[cfg(windows)]
+static NT_API: std::sync::OnceLock<Option<NtApi>> = std::sync::OnceLock::new();
+
+#[cfg(windows)]
+fn nt_api() -> Option<&'static NtApi> {
+    use windows::core::{s, w};
+    use windows::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress};
+    type FarProc = unsafe extern "system" fn() -> isize;
+
+    NT_API
+        .get_or_init(|| unsafe {
+            let ntdll = GetModuleHandleW(w!("ntdll.dll")).ok()?;
+            let open_file = GetProcAddress(ntdll, s!("NtOpenFile"))?;
+            let set_information_file = GetProcAddress(ntdll, s!("NtSetInformationFile"))?;
+            let status_to_dos_error = GetProcAddress(ntdll, s!("RtlNtStatusToDosError"))?;
+            Some(NtApi {
+                open_file: std::mem::transmute::<FarProc, NtOpenFileFn>(open_file),
+                set_information_file: std::mem::transmute::<FarProc, NtSetInformationFileFn>(
+                    set_information_file,
+                ),
+                status_to_dos_error: std::mem::transmute::<FarProc, RtlNtStatusToDosErrorFn>(
+                    status_to_dos_error,
+                ),
+            })
+        })
+        .as_ref()
+}
+
+#[cfg(windows)]
+#[repr(C)]
+struct UnicodeString {
+    /// In bytes, without a terminating NUL
+    length: u16,
+    maximum_length: u16,
+    buffer: *const u16,
+}
+
+#[cfg(windows)]
+#[repr(C)]
+struct ObjectAttributes {
+    length: u32,
+    root_directory: HANDLE,
+    object_name: *const UnicodeString,
+    attributes: u32,
+    security_descriptor: *const core::ffi::c_void,
+    security_quality_of_service: *const core::ffi::c_void,
+}
+
+#[cfg(windows)]
+#[repr(C)]
+struct IoStatusBlock {
+    status: usize,
+    information: usize,
+}
+
+/// Delete files and directories with NtOpenFile and NtSetInformationFile instead of
+/// CreateFileW and SetFileInformationByHandle, skipping the Win32 path translation and
+/// parameter checks per entry; false (and no change) if ntdll lacks the entry points
+///
+/// Relative paths keep using the Win32 calls.
+#[cfg(windows)]
+pub fn enable_native_delete() -> bool {
+    let available = nt_api().is_some();
+    NATIVE_DELETE.store(available, Ordering::Relaxed);
+    available
+}
+
+/// The native deletion path exists only on Windows
+#[cfg(not(windows))]
+pub fn enable_native_delete() -> bool {
+    false
+}
+
+/// Open `wide_path` for deletion and set `flags` on it through ntdll; None if the path has
+/// no NT equivalent here (it is not `\\?\`-prefixed), for the caller to use Win32 instead
+#[cfg(windows)]
+unsafe fn native_delete(api: &NtApi, wide_path: &[u16], flags: u32) -> Option<io::Result<()>> {
+    const DOS_PREFIX: [u16; 4] = [b'\\' as u16, b'\\' as u16, b'?' as u16, b'\\' as u16];
+    const NT_PREFIX: [u16; 4] = [b'\\' as u16, b'?' as u16, b'?' as u16, b'\\' as u16];
+    const OBJ_CASE_INSENSITIVE: u32 = 0x40;
+    const FILE_OPEN_FOR_BACKUP_INTENT: u32 = 0x4000;
+    const FILE_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+    const FILE_DISPOSITION_INFORMATION_EX_CLASS: i32 = 64;
+
+    let rest = wide_path.strip_prefix(&DOS_PREFIX[..])?;
+    let rest = rest.strip_suffix(&[0]).unwrap_or(rest);
+    let nt_path: Vec<u16> = NT_PREFIX.iter().chain(rest).copied().collect();
+    let byte_len = u16::try_from(nt_path.len() * 2).ok()?;
+    let name = UnicodeString {
+        length: byte_len,
+        maximum_length: byte_len,
+        buffer: nt_path.as_ptr(),
+    };
+    let attributes = ObjectAttributes {
+        length: std::mem::size_of::<ObjectAttributes>() as u32,
+        root_directory: HANDLE::default(),
+        object_name: &name,
+        attributes: OBJ_CASE_INSENSITIVE,
+        security_descriptor: std::ptr::null(),
+        security_quality_of_service: std::ptr::null(),
+    };
+    let status_error =
+        |status: i32| io::Error::from_raw_os_error((api.status_to_dos_error)(status) as i32);
+
+    let mut raw = HANDLE::default();
+    let mut io_status = IoStatusBlock {
+        status: 0,
+        information: 0,
+    };
+    // The same access, sharing and reparse handling as the Win32 path
+    let status = (api.open_file)(
+        &mut raw,
+        DELETE.0,
+        &attributes,
+        &mut io_status,
+        (FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE).0,
+        FILE_OPEN_FOR_BACKUP_INTENT | FILE_OPEN_REPARSE_POINT,
+    );
+    if status < 0 {
+        return Some(Err(status_error(status)));
+    }
+    let handle = FileHandle(raw);
+    if LOW_IO_PRIORITY.load(Ordering::Relaxed) {
+        lower_io_priority(&handle);
+    }
+    let info = FILE_DISPOSITION_INFORMATION_EX {
+        Flags: FILE_DISPOSITION_INFORMATION_EX_FLAGS(flags),
+    };
+    let status = (api.set_information_file)(
+        handle.as_raw(),
+        &mut io_status,
+        &info as *const _ as *const _,
+        std::mem::size_of::<FILE_DISPOSITION_INFORMATION_EX>() as u32,
+        FILE_DISPOSITION_INFORMATION_EX_CLASS,
+    );
+    Some(if status < 0 {
+        Err(status_error(status))
+    } else {
+        Ok(())
+    })
+}
+
+/// Delete through ntdll if [`enable_native_delete`] turned that on and it applies to the path
+#[cfg(windows)]
+unsafe fn try_native_delete(wide_path: &[u16], flags: u32) -> Option<io::Result<()>> {
+    // The native path sets the extended disposition, which classic deletion avoids
+    if !NATIVE_DELETE.load(Ordering::Relaxed) || CLASSIC_DELETE.load(Ordering::Relaxed) {
+        return None;
+    }
+    native_delete(nt_api()?, wide_path, flags)
+}
+
+#[cfg(windows)]
+unsafe fn posix_delete_file(wide_path: &[u16]) -> io::Result<()> {
+    posix_delete(wide_path)
+}
+
+/// Read-only directories (extracted packages, `.git/objects`) go like read-only files
+#[cfg(windows)]
+unsafe fn posix_delete_dir(wide_path: &[u16]) -> io::Result<()> {
+    posix_delete(wide_path)
+}
+
+#[cfg(windows)]
+unsafe fn posix_delete(wide_path: &[u16]) -> io::Result<()> {
+    let flags = FILE_DISPOSITION_DELETE.0 | FILE_DISPOSITION_POSIX_SEMANTICS.0;
+    match delete_with_flags(
+        wide_path,
+        flags | FILE_DISPOSITION_IGNORE_READONLY_ATTRIBUTE.0,
+    ) {
+        Err(e) if refused_for_readonly(&e) => {
+            if clear_readonly(wide_path) {
+                delete_with_flags(wide_path, flags)
+            } else {
+                Err(e)
+            }
+        }
+        result => result,
+    }
+}
+
+/// True for the errors a read-only entry can cause despite
+/// `FILE_DISPOSITION_IGNORE_READONLY_ATTRIBUTE`: builds before Windows 10 1809 reject the
+/// flag, and some filesystems ignore it; clearing the attribute does the same
+#[cfg(windows)]
+fn refused_for_readonly(e: &io::Error) -> bool {
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER};
+
+    e.raw_os_error() == Some(ERROR_INVALID_PARAMETER.0 as i32)
+        || e.raw_os_error() == Some(ERROR_ACCESS_DENIED.0 as i32)
+}
+
+/// Drop the read-only attribute of `wide_path`; false if it was not set or stays set
+#[cfg(windows)]
+unsafe fn clear_readonly(wide_path: &[u16]) -> bool {
+    use windows::Win32::Storage::FileSystem::{
+        GetFileAttributesW, SetFileAttributesW, FILE_ATTRIBUTE_READONLY, INVALID_FILE_ATTRIBUTES,
+    };
+
+    let path = PCWSTR(wide_path.as_ptr());
+    let attributes = GetFileAttributesW(path);
+    if attributes == INVALID_FILE_ATTRIBUTES || attributes & FILE_ATTRIBUTE_READONLY.0 == 0 {
+        return false;
+    }
+    let cleared = FILE_FLAGS_AND_ATTRIBUTES(attributes & !FILE_ATTRIBUTE_READONLY.0);
+    SetFileAttributesW(path, cleared).is_ok()
+}
+
+#[cfg(windows)]
+unsafe fn delete_with_flags(wide_path: &[u16], flags: u32) -> io::Result<()> {
+    if let Some(result) = try_native_delete(wide_path, flags) {
+        return result;
+    }
+    // Backup semantics lets this also open directory links, which the scan treats as files
+    let handle = FileHandle::open(
+        wide_path,
+        DELETE.0,
+        FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT,
+    )?;
+    if LOW_IO_PRIORITY.load(Ordering::Relaxed) {
+        lower_io_priority(&handle);
+    }
+    set_delete_disposition(&handle, flags)
+}
+
+/// Mark an open handle for deletion; the entry disappears when the handle is closed
+///
+/// After [`disable_posix_delete`], `flags` other than the delete itself are left out.
+#[cfg(windows)]
+unsafe fn set_delete_disposition(handle: &FileHandle, flags: u32) -> io::Result<()> {
+    use windows::Win32::Foundation::BOOLEAN;
+    use windows::Win32::Storage::FileSystem::{FileDispositionInfo, FILE_DISPOSITION_INFO};
+
+    if CLASSIC_DELETE.load(Ordering::Relaxed) {
+        let mut info = FILE_DISPOSITION_INFO {
+            DeleteFile: BOOLEAN(1),
+        };
+        return SetFileInformationByHandle(
+            handle.as_raw(),
+            FileDispositionInfo,
+            &mut info as *mut _ as *mut _,
+            std::mem::size_of::<FILE_DISPOSITION_INFO>() as u32,
+        )
+        .map_err(win_error);
+    }
+    let mut info = FILE_DISPOSITION_INFORMATION_EX {
+        Flags: FILE_DISPOSITION_INFORMATION_EX_FLAGS(flags),
+    };
+
+    SetFileInformationByHandle(
+        handle.as_raw(),
+        FileDispositionInfoEx,
+        &mut info as *mut _ as *mut _,
+        std::mem::size_of::<FILE_DISPOSITION_INFORMATION_EX>() as u32,
+    )
+    .map_err(win_error)
+}
+
+// Unix implementations - just use standard library
+#[cfg(not(windows))]
+pub fn delete_file(path: &Path) -> io::Result<()> {
+    std::fs::remove_file(path)
+}
+
+#[cfg(not(windows))]
+pub fn remove_dir(path: &Path) -> io::Result<()> {
+    std::fs::remove_dir(path)
+}
+
+/// Delete a symlink, junction or other link itself by the cheapest call available; its
+/// target is never opened
+///
+/// The link is opened as a reparse point with delete-on-close, so closing that one handle
+/// removes it: two calls instead of the open, disposition and close of [`delete_file`].
+/// Links are rarely held open by anyone else, which is all POSIX semantics would add.
+/// Read-only links and other refusals are retried through [`delete_file`].
+#[cfg(windows)]
+pub fn delete_link(path: &Path) -> io::Result<()> {
+    use windows::Win32::Storage::FileSystem::FILE_FLAG_DELETE_ON_CLOSE;
+
+    let wide_path = path_to_wide(path);
+    let flags =
+        FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_DELETE_ON_CLOSE;
+    match unsafe { FileHandle::open(&wide_path, DELETE.0, flags) } {
+        Ok(_handle) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(e),
+        Err(_) => unsafe { posix_delete_file(&wide_path) },
+    }
+}
+
+/// Unlinking a symlink never follows it, so links need nothing beyond [`delete_file`]
+#[cfg(not(windows))]
+pub use self::delete_file as delete_link;
+
+/// Enable ANSI escape processing on the console's stdout and stderr
+///
+/// Returns false if stdout is not a console that understands escape sequences.
+#[cfg(windows)]
+pub fn enable_virtual_terminal() -> bool {
+    use windows::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, CONSOLE_MODE,
+        ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE,
+    };
+
+    let mut stdout_ok = false;
+    for (std_handle, is_stdout) in [(STD_OUTPUT_HANDLE, true), (STD_ERROR_HANDLE, false)] {
+        unsafe {
+            let Ok(handle) = GetStdHandle(std_handle) else {
+                continue;
+            };
+            let mut mode = CONSOLE_MODE::default();
+            if GetConsoleMode(handle, &mut mode).is_err() {
+                continue;
+            }
+            let enabled = SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING).is_ok();
+            if is_stdout {
+                stdout_ok = enabled;
+            }
+        }
+    }
+    stdout_ok
+}
+
+/// Terminals on Unix interpret ANSI escapes natively
+#[cfg(not(windows))]
+pub fn enable_virtual_terminal() -> bool {
+    true
+}
+
+/// Check whether stdout is a pipe (as in CI runners) rather than a file or console
+#[cfg(windows)]
+pub fn stdout_is_pipe() -> bool {
+    use windows::Win32::Storage::FileSystem::{GetFileType, FILE_TYPE_PIPE};
+    use windows::Win32::System::Console::{GetStdHandle, STD_OUTPUT_HANDLE};
+
+    unsafe {
+        GetStdHandle(STD_OUTPUT_HANDLE).is_ok_and(|handle| GetFileType(handle) == FILE_TYPE_PIPE)
+    }
+}
+
+/// Check whether stdout is a pipe or socket (as in CI runners) rather than a file or terminal
+#[cfg(not(windows))]
+pub fn stdout_is_pipe() -> bool {
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::FileTypeExt;
+
+    io::stdout()
+        .as_fd()
+        .try_clone_to_owned()
+        .and_then(|fd| std::fs::File::from(fd).metadata())
+        .is_ok_and(|m| m.file_type().is_fifo() || m.file_type().is_socket())
+}
+
+#[cfg(windows)]
+static CONSOLE_CTRL_HANDLER: std::sync::OnceLock<fn()> = std::sync::OnceLock::new();
+
+#[cfg(windows)]
+unsafe extern "system" fn console_ctrl_routine(ctrl_type: u32) -> windows::Win32::Foundation::BOOL {
+    use windows::Win32::System::Console::{
+        CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT,
+    };
+
+    let handled = matches!(
+        ctrl_type,
+        CTRL_BREAK_EVENT | CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT | CTRL_SHUTDOWN_EVENT
+    );
+    match CONSOLE_CTRL_HANDLER.get() {
+        Some(handler) if handled => {
+            handler();
+            true.into()
+        }
+        _ => false.into(),
+    }
+}
+
+/// Call `handler` on Ctrl+Break, console close, logoff and shutdown (Ctrl+C is left alone)
+///
+/// The handler runs on a system thread; for close, logoff and shutdown the process is
+/// terminated as soon as it returns, so it should block until cleanup is done.
+#[cfg(windows)]
+pub fn install_console_ctrl_handler(handler: fn()) -> bool {
+    use windows::Win32::System::Console::SetConsoleCtrlHandler;
+
+    CONSOLE_CTRL_HANDLER.set(handler).is_ok()
+        && unsafe { SetConsoleCtrlHandler(Some(console_ctrl_routine), true).is_ok() }
+}
+
+/// Call `handler` on SIGTERM and SIGHUP, which service managers, `kill` and closed
+/// terminals send
+///
+/// Must run before other threads are spawned: they inherit a mask blocking both signals,
+/// leaving them to a waiter thread, so the handler runs as ordinary code rather than in a
+/// signal context.
+#[cfg(unix)]
+pub fn install_console_ctrl_handler(handler: fn()) -> bool {
+    // SAFETY: sigset_t is plain data initialized by sigemptyset before use
+    let set = unsafe {
+        let mut set: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGTERM);
+        libc::sigaddset(&mut set, libc::SIGHUP);
+        if libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut()) != 0 {
+            return false;
+        }
+        set
+    };
+    std::thread::Builder::new()
+        .name("signal-waiter".to_string())
+        .spawn(move || {
+            let mut signal = 0;
+            if unsafe { libc::sigwait(&set, &mut signal) } == 0 {
+                handler();
+            }
+        })
+        .is_ok()
+}
+
+/// No shutdown notifications elsewhere
+#[cfg(not(any(windows, unix)))]
+pub fn install_console_ctrl_handler(_handler: fn()) -> bool {
+    false
+}
+
+/// Kind of link found while enumerating; links are never followed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LinkKind {
+    Symlink,
+    /// Directory junction (Windows)
+    Junction,
+    /// Folder a volume is mounted on (Windows)
+    MountPoint,
+    /// App execution alias, as found under WindowsApps
+    AppExecLink,
+}
+
+impl LinkKind {
+    /// Plural name for summaries
+    pub fn plural(self) -> &'static str {
+        match self {
+            LinkKind::Symlink => "symlinks",
+            LinkKind::Junction => "junctions",
+            LinkKind::MountPoint => "mount points",
+            LinkKind::AppExecLink => "app execution aliases",
+        }
+    }
+}
+
+/// `FILE_ATTRIBUTE_ENCRYPTED`: the file's data is encrypted with EFS
+pub const ATTRIBUTE_ENCRYPTED: u32 = 0x4000;
+
+/// What [`enumerate_entries`] learns about an entry beyond its kind and size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryMeta {
+    /// `FILE_ATTRIBUTE_*` bits (0 on other platforms)
+    pub attributes: u32,
+    /// Space the file takes on disk, which compression and sparse ranges make smaller than
+    /// its size (0 unless sizes were asked for)
+    pub allocated: u64,
+    /// Volume and file index, or device and inode, where the listing tells
+    pub id: Option<(u64, u64)>,
+    /// Hardlink count of a file (None unless sizes were asked for)
+    pub links: Option<u32>,
+}
+
+/// Enumerate files in a directory; the callback gets each entry's path and whether it is a directory
+pub fn enumerate_files<F>(dir: &Path, mut callback: F) -> io::Result<()>
+where
+    F: FnMut(&Path, bool) -> io::Result<()>,
+{
+    enumerate_entries(dir, false, |path, is_dir, _, _, _| callback(path, is_dir))
+}
+
+/// What kind of link `path` itself is, if it is one
+#[cfg(windows)]
+pub fn link_kind(path: &Path) -> Option<LinkKind> {
+    use windows::Win32::Storage::FileSystem::{
+        FileAttributeTagInfo, GetFileInformationByHandleEx, FILE_ATTRIBUTE_TAG_INFO,
+        FILE_READ_ATTRIBUTES,
+    };
+
+    let wide_path = path_to_wide(path);
+    let flags = FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT;
+    unsafe {
+        let handle = FileHandle::open(&wide_path, FILE_READ_ATTRIBUTES.0, flags).ok()?;
+        let mut info = FILE_ATTRIBUTE_TAG_INFO::default();
+        GetFileInformationByHandleEx(
+            handle.as_raw(),
+            FileAttributeTagInfo,
+            &mut info as *mut _ as *mut _,
+            std::mem::size_of::<FILE_ATTRIBUTE_TAG_INFO>() as u32,
+        )
+        .ok()?;
+        if info.FileAttributes & FILE_ATTRIBUTE_REPARSE_POINT.0 == 0 {
+            return None;
+        }
+        reparse_link_kind(path, info.ReparseTag)
+    }
+}
+
+/// What kind of link `path` itself is, if it is one
+#[cfg(not(windows))]
+pub fn link_kind(path: &Path) -> Option<LinkKind> {
+    path.symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink())
+        .then_some(LinkKind::Symlink)
+}
+
+/// Classify a reparse point from its tag; None for reparse points that aren't links
+/// (cloud placeholders, dedup, ...), which are deleted like ordinary entries
+#[cfg(windows)]
+fn reparse_link_kind(path: &Path, tag: u32) -> Option<LinkKind> {
+    const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+    const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+    const IO_REPARSE_TAG_APPEXECLINK: u32 = 0x8000_001B;
+
+    match tag {
+        IO_REPARSE_TAG_SYMLINK => Some(LinkKind::Symlink),
+        // Junctions and volume mount points share a tag; only the target tells them apart
+        IO_REPARSE_TAG_MOUNT_POINT => {
+            let target = std::fs::read_link(path).unwrap_or_default();
+            let target = target.to_string_lossy();
+            if target.starts_with(r"\\?\Volume{") || target.starts_with(r"\??\Volume{") {
+                Some(LinkKind::MountPoint)
+            } else {
+                Some(LinkKind::Junction)
+            }
+        }
+        IO_REPARSE_TAG_APPEXECLINK => Some(LinkKind::AppExecLink),
+        _ => None,
+    }
+}
+
+/// Enumerate files in a directory using direct Windows API, with file sizes and attributes
+///
+/// Sizes and file IDs come with the directory listing; `with_size` adds the hardlink count
+/// (asked by name relative to the listed directory, without opening the file) and the
+/// allocation of NTFS-compressed and sparse files, whose other files are taken at their
+/// logical size (cluster rounding aside). Links are reported with their kind and never as
+/// directories, so callers don't descend into them.
+#[cfg(windows)]
+pub fn enumerate_entries<F>(dir: &Path, with_size: bool, mut callback: F) -> io::Result<()>
+where
+    F: FnMut(&Path, bool, u64, Option<LinkKind>, &EntryMeta) -> io::Result<()>,
+{
+    match unsafe { list_with_ids(dir, with_size, &mut callback) } {
+        // FAT and some network filesystems list no file IDs
+        None => unsafe { find_entries(dir, with_size, &mut callback) },
+        Some(result) => result,
+    }
+}
+
+/// List `dir` through a handle on it, with each entry's file ID; None, before any entry is
+/// reported, if the filesystem cannot list IDs
+#[cfg(windows)]
+unsafe fn list_with_ids<F>(dir: &Path, with_size: bool, callback: &mut F) -> Option<io::Result<()>>
+where
+    F: FnMut(&Path, bool, u64, Option<LinkKind>, &EntryMeta) -> io::Result<()>,
+{
+    use std::os::windows::ffi::OsStringExt;
+    use windows::Win32::Foundation::{
+        ERROR_INVALID_LEVEL, ERROR_INVALID_PARAMETER, ERROR_NOT_SUPPORTED, ERROR_NO_MORE_FILES,
+    };
+    use windows::Win32::Storage::FileSystem::{
+        FileIdExtdDirectoryInfo, FileIdExtdDirectoryRestartInfo, GetFileInformationByHandle,
+        GetFileInformationByHandleEx, BY_HANDLE_FILE_INFORMATION, FILE_ID_EXTD_DIR_INFO,
+        FILE_LIST_DIRECTORY,
+    };
+
+    let wide_dir = path_to_wide(dir);
+    let handle =
+        match FileHandle::open(&wide_dir, FILE_LIST_DIRECTORY.0, FILE_FLAG_BACKUP_SEMANTICS) {
+            Ok(handle) => handle,
+            Err(e) => return Some(Err(e)),
+        };
+    let mut dir_info = BY_HANDLE_FILE_INFORMATION::default();
+    if let Err(e) = GetFileInformationByHandle(handle.as_raw(), &mut dir_info) {
+        return Some(Err(win_error(e)));
+    }
+    let volume = dir_info.dwVolumeSerialNumber as u64;
+
+    // 64 KiB of records per call, aligned for them
+    let mut buf = vec![0u64; 8 * 1024];
+    let mut class = FileIdExtdDirectoryRestartInfo;
+    loop {
+        let listed = GetFileInformationByHandleEx(
+            handle.as_raw(),
+            class,
+            buf.as_mut_ptr().cast(),
+            (buf.len() * 8) as u32,
+        );
+        match listed {
+            Ok(()) => {}
+            Err(e) if e.code() == ERROR_NO_MORE_FILES.to_hresult() => return Some(Ok(())),
+            Err(e)
+                if class == FileIdExtdDirectoryRestartInfo
+                    && [
+                        ERROR_INVALID_PARAMETER,
+                        ERROR_INVALID_LEVEL,
+                        ERROR_NOT_SUPPORTED,
+                    ]
+                    .iter()
+                    .any(|code| e.code() == code.to_hresult()) =>
+            {
+                return None;
+            }
+            Err(e) => return Some(Err(win_error(e))),
+        }
+        class = FileIdExtdDirectoryInfo;
+
+        let mut offset = 0;
+        loop {
+            let info = &*(buf.as_ptr().cast::<u8>().add(offset) as *const FILE_ID_EXTD_DIR_INFO);
+            let name = std::slice::from_raw_parts(
+                info.FileName.as_ptr(),
+                info.FileNameLength as usize / 2,
+            );
+            if name != [b'.' as u16] && name != [b'.' as u16, b'.' as u16] {
+                let full_path = dir.join(std::ffi::OsString::from_wide(name));
+                let attributes = info.FileAttributes;
+                let link = if attributes & FILE_ATTRIBUTE_REPARSE_POINT.0 != 0 {
+                    reparse_link_kind(&full_path, info.ReparsePointTag)
+                } else {
+                    None
+                };
+                let is_dir = link.is_none() && attributes & FILE_ATTRIBUTE_DIRECTORY.0 != 0;
+                let size = info.EndOfFile as u64;
+                // NTFS file IDs fit in the low 64 bits, the index OpenFileById takes
+                let mut index = [0u8; 8];
+                index.copy_from_slice(&info.FileId.Identifier[..8]);
+                let mut meta = EntryMeta {
+                    attributes,
+                    allocated: packed_allocation(&full_path, size, attributes, with_size),
+                    id: Some((volume, u64::from_le_bytes(index))),
+                    links: None,
+                };
+                if with_size && !is_dir {
+                    meta.links = link_count(&handle, name)
+                        .or_else(|| file_links(&full_path).map(|(_, links)| links));
+                }
+                if let Err(e) = callback(&full_path, is_dir, size, link, &meta) {
+                    return Some(Err(e));
+                }
+            }
+            if info.NextEntryOffset == 0 {
+                break;
+            }
+            offset += info.NextEntryOffset as usize;
+        }
+    }
+}
+
+/// List `dir` with FindFirstFileExW, for filesystems without file IDs in their listing
+#[cfg(windows)]
+unsafe fn find_entries<F>(dir: &Path, with_size: bool, callback: &mut F) -> io::Result<()>
+where
+    F: FnMut(&Path, bool, u64, Option<LinkKind>, &EntryMeta) -> io::Result<()>,
+{
+    let search_path = dir.join("*");
+    let wide_path = path_to_wide(&search_path);
+
+    let mut find_data: WIN32_FIND_DATAW = std::mem::zeroed();
+    let handle = FindFirstFileExW(
+        PCWSTR(wide_path.as_ptr()),
+        FINDEX_INFO_LEVELS(1),
+        &mut find_data as *mut _ as *mut _,
+        FINDEX_SEARCH_OPS(0),
+        None,
+        FIND_FIRST_EX_FLAGS(0),
+    )
+    .map(FindHandle)
+    .map_err(win_error)?;
+
+    loop {
+        let name_len = find_data
+            .cFileName
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(find_data.cFileName.len());
+        let filename = String::from_utf16_lossy(&find_data.cFileName[..name_len]);
+
+        if filename != "." && filename != ".." {
+            let full_path = dir.join(&filename);
+            let link = if find_data.dwFileAttributes & FILE_ATTRIBUTE_REPARSE_POINT.0 != 0 {
+                // For reparse points dwReserved0 holds the reparse tag
+                reparse_link_kind(&full_path, find_data.dwReserved0)
+            } else {
+                None
+            };
+            let is_dir =
+                link.is_none() && (find_data.dwFileAttributes & FILE_ATTRIBUTE_DIRECTORY.0) != 0;
+            let size = ((find_data.nFileSizeHigh as u64) << 32) | find_data.nFileSizeLow as u64;
+            let attributes = find_data.dwFileAttributes;
+            let mut meta = EntryMeta {
+                attributes,
+                allocated: packed_allocation(&full_path, size, attributes, with_size),
+                ..Default::default()
+            };
+            // No IDs or link counts in this listing; the file itself has them
+            if with_size && !is_dir {
+                if let Some((id, links)) = file_links(&full_path) {
+                    meta.id = Some(id);
+                    meta.links = Some(links);
+                }
+            }
+            callback(&full_path, is_dir, size, link, &meta)?;
+        }
+
+        if FindNextFileW(handle.0, &mut find_data).is_err() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The allocation [`EntryMeta`] reports for a listed file: asked of compressed and sparse
+/// files, the logical size of others, 0 unless `with_size`
+#[cfg(windows)]
+fn packed_allocation(path: &Path, size: u64, attributes: u32, with_size: bool) -> u64 {
+    use windows::Win32::Storage::FileSystem::{
+        FILE_ATTRIBUTE_COMPRESSED, FILE_ATTRIBUTE_SPARSE_FILE,
+    };
+
+    if !with_size {
+        0
+    } else if attributes & (FILE_ATTRIBUTE_COMPRESSED.0 | FILE_ATTRIBUTE_SPARSE_FILE.0) != 0 {
+        compressed_size(path).unwrap_or(size)
+    } else {
+        size
+    }
+}
+
+#[cfg(windows)]
+type NtQueryInformationByNameFn = unsafe extern "system" fn(
+    *const ObjectAttributes,
+    *mut IoStatusBlock,
+    *mut core::ffi::c_void,
+    u32,
+    i32,
+) -> i32;
+
+/// NtQueryInformationByName, which Windows 10 1709 added; looked up at run time so older
+/// systems fall back to opening each file
+#[cfg(windows)]
+fn query_by_name() -> Option<NtQueryInformationByNameFn> {
+    use windows::core::{s, w};
+    use windows::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress};
+    type FarProc = unsafe extern "system" fn() -> isize;
+    static QUERY: std::sync::OnceLock<Option<NtQueryInformationByNameFn>> =
+        std::sync::OnceLock::new();
+
+    *QUERY.get_or_init(|| unsafe {
+        let ntdll = GetModuleHandleW(w!("ntdll.dll")).ok()?;
+        let query = GetProcAddress(ntdll, s!("NtQueryInformationByName"))?;
+        Some(std::mem::transmute::<FarProc, NtQueryInformationByNameFn>(
+            query,
+        ))
+    })
+}
+
+/// Hardlink count of the entry `name` in the directory open as `dir`, asked by name so the
+/// file is not opened; None where the system or filesystem cannot say
+#[cfg(windows)]
+unsafe fn link_count(dir: &FileHandle, name: &[u16]) -> Option<u32> {
+    use windows::Wdk::Storage::FileSystem::FILE_STAT_INFORMATION;
+    const FILE_STAT_INFORMATION_CLASS: i32 = 68;
+
+    let query = query_by_name()?;
+    let byte_len = u16::try_from(name.len() * 2).ok()?;
+    let name = UnicodeString {
+        length: byte_len,
+        maximum_length: byte_len,
+        buffer: name.as_ptr(),
+    };
+    let attributes = ObjectAttributes {
+        length: std::mem::size_of::<ObjectAttributes>() as u32,
+        root_directory: dir.as_raw(),
+        object_name: &name,
+        attributes: 0,
+        security_descriptor: std::ptr::null(),
+        security_quality_of_service: std::ptr::null(),
+    };
+    let mut io_status = IoStatusBlock {
+        status: 0,
+        information: 0,
+    };
+    let mut info = FILE_STAT_INFORMATION::default();
+    let status = query(
+        &attributes,
+        &mut io_status,
+        &mut info as *mut _ as *mut _,
+        std::mem::size_of::<FILE_STAT_INFORMATION>() as u32,
+        FILE_STAT_INFORMATION_CLASS,
+    );
+    (status >= 0).then_some(info.NumberOfLinks)
+}
+
+/// Enumerate files in a directory using standard library (Unix)
+///
+/// File sizes need a stat per file, so they are only looked up (otherwise 0) when `with_size` is set;
+/// the same stat gives the block count, so sparse files and filesystem compression show in
+/// `allocated`. Symlinks are reported as such and never as directories. Entries deleted
+/// while the directory is listed are skipped.
+#[cfg(not(windows))]
+pub fn enumerate_entries<F>(dir: &Path, with_size: bool, mut callback: F) -> io::Result<()>
+where
+    F: FnMut(&Path, bool, u64, Option<LinkKind>, &EntryMeta) -> io::Result<()>,
+{
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            file_type => file_type?,
+        };
+        let is_dir = file_type.is_dir();
+        let mut meta = EntryMeta::default();
+        let size = if with_size && !is_dir {
+            // Not followed: a link is sized as itself
+            match entry.metadata() {
+                Ok(metadata) => {
+                    stat_meta(&mut meta, &metadata);
+                    metadata.len()
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            }
+        } else {
+            0
+        };
+        let link = file_type.is_symlink().then_some(LinkKind::Symlink);
+        callback(&path, is_dir, size, link, &meta)?;
+    }
+    Ok(())
+}
+
+/// Space an NTFS-compressed or sparse file takes on disk
+#[cfg(windows)]
+fn compressed_size(path: &Path) -> Option<u64> {
+    use windows::Win32::Storage::FileSystem::GetCompressedFileSizeW;
+
+    let wide_path = path_to_wide(path);
+    let mut high = 0u32;
+    let low = unsafe { GetCompressedFileSizeW(PCWSTR(wide_path.as_ptr()), Some(&mut high)) };
+    // INVALID_FILE_SIZE is also a valid low word; only the last error tells them apart
+    if low == u32::MAX && io::Error::last_os_error().raw_os_error() != Some(0) {
+        return None;
+    }
+    Some(((high as u64) << 32) | low as u64)
+}
+
+/// Fill in what a file's stat tells beyond its size: the space its blocks take, its
+/// identity and its hardlink count
+#[cfg(unix)]
+fn stat_meta(meta: &mut EntryMeta, metadata: &std::fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+
+    meta.allocated = metadata.blocks() * 512;
+    meta.id = Some((metadata.dev(), metadata.ino()));
+    meta.links = Some(metadata.nlink() as u32);
+}
+
+#[cfg(not(any(windows, unix)))]
+fn stat_meta(meta: &mut EntryMeta, metadata: &std::fs::Metadata) {
+    meta.allocated = metadata.len();
+}
+
+/// Identity of a file (volume and file index) and its hardlink count, from the file itself
+#[cfg(windows)]
+fn file_links(path: &Path) -> Option<((u64, u64), u32)> {
+    use windows::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_READ_ATTRIBUTES,
+    };
+
+    let wide_path = path_to_wide(path);
+    unsafe {
+        let handle = FileHandle::open(
+            &wide_path,
+            FILE_READ_ATTRIBUTES.0,
+            FILE_FLAG_OPEN_REPARSE_POINT,
+        )
+        .ok()?;
+        let mut info = BY_HANDLE_FILE_INFORMATION::default();
+        GetFileInformationByHandle(handle.as_raw(), &mut info).ok()?;
+        let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+        Some((
+            (info.dwVolumeSerialNumber as u64, index),
+            info.nNumberOfLinks,
+        ))
+    }
+}
+
+/// Identity of a file or directory (volume and file index, or device and inode), without
+/// following a link at `path`
+#[cfg(windows)]
+pub fn file_id(path: &Path) -> io::Result<(u64, u64)> {
+    use windows::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_READ_ATTRIBUTES,
+    };
+
+    let wide_path = path_to_wide(path);
+    unsafe {
+        let handle = FileHandle::open(
+            &wide_path,
+            FILE_READ_ATTRIBUTES.0,
+            FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT,
+        )?;
+        let mut info = BY_HANDLE_FILE_INFORMATION::default();
+        GetFileInformationByHandle(handle.as_raw(), &mut info).map_err(win_error)?;
+        let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+        Ok((info.dwVolumeSerialNumber as u64, index))
+    }
+}
+
+/// Identity of a file or directory (volume and file index, or device and inode), without
+/// following a link at `path`
+#[cfg(unix)]
+pub fn file_id(path: &Path) -> io::Result<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = path.symlink_metadata()?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn file_id(_path: &Path) -> io::Result<(u64, u64)> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Directories to remove by file ID rather than by path: each is opened with `OpenFileById`
+/// against a handle on the volume, so one renamed since the scan is still found, and deep
+/// paths are not parsed again
+///
+/// A directory moved out from under the root is treated as gone, never followed.
+#[cfg(windows)]
+pub struct DirIds {
+    volume: FileHandle,
+    /// Where the root is, as the handles of directories under it spell their paths
+    root: std::path::PathBuf,
+    ids: std::collections::HashMap<std::path::PathBuf, u64>,
+}
+
+// The handle is only passed to OpenFileById as a volume hint, which any thread may do
+#[cfg(windows)]
+unsafe impl Send for DirIds {}
+#[cfg(windows)]
+unsafe impl Sync for DirIds {}
+
+#[cfg(windows)]
+impl DirIds {
+    /// Open the volume of `root` for the directories of `ids` (file indexes on that volume)
+    pub fn open(
+        root: &Path,
+        ids: std::collections::HashMap<std::path::PathBuf, u64>,
+    ) -> io::Result<Self> {
+        use windows::Win32::Storage::FileSystem::FILE_READ_ATTRIBUTES;
+
+        let wide_path = path_to_wide(root);
+        let volume = unsafe {
+            FileHandle::open(
+                &wide_path,
+                FILE_READ_ATTRIBUTES.0,
+                FILE_FLAG_BACKUP_SEMANTICS,
+            )?
+        };
+        let root = unsafe { final_path(&volume) }?;
+        Ok(Self { volume, root, ids })
+    }
+
+    /// Open the directory recorded for `dir`; None if it has no ID
+    ///
+    /// A directory deleted since the scan, or moved out of the root, is `NotFound`.
+    unsafe fn open_dir(&self, dir: &Path, access: u32) -> Option<io::Result<FileHandle>> {
+        use windows::Win32::Foundation::ERROR_INVALID_PARAMETER;
+        use windows::Win32::Storage::FileSystem::{
+            FileIdType, OpenFileById, FILE_ID_DESCRIPTOR, FILE_ID_DESCRIPTOR_0,
+        };
+
+        let id = *self.ids.get(dir)?;
+        let descriptor = FILE_ID_DESCRIPTOR {
+            dwSize: std::mem::size_of::<FILE_ID_DESCRIPTOR>() as u32,
+            Type: FileIdType,
+            Anonymous: FILE_ID_DESCRIPTOR_0 { FileId: id as i64 },
+        };
+        let handle = OpenFileById(
+            self.volume.as_raw(),
+            &descriptor,
+            access,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT,
+        );
+        Some(match handle {
+            Ok(handle) => {
+                let handle = FileHandle(handle);
+                match final_path(&handle) {
+                    Ok(path) if path.starts_with(&self.root) => Ok(handle),
+                    Ok(_) => Err(io::ErrorKind::NotFound.into()),
+                    Err(e) => Err(e),
+                }
+            }
+            // What OpenFileById answers for an ID no file has any more
+            Err(e) if e.code() == ERROR_INVALID_PARAMETER.to_hresult() => {
+                Err(io::ErrorKind::NotFound.into())
+            }
+            Err(e) => Err(win_error(e)),
+        })
+    }
+
+    /// Where the directory recorded for `dir` is now, if it has an ID and is still under
+    /// the root
+    pub fn current_path(&self, dir: &Path) -> Option<std::path::PathBuf> {
+        use windows::Win32::Storage::FileSystem::FILE_READ_ATTRIBUTES;
+
+        unsafe {
+            let handle = self.open_dir(dir, FILE_READ_ATTRIBUTES.0)?.ok()?;
+            final_path(&handle).ok()
+        }
+    }
+
+    /// Remove the directory recorded for `dir` with POSIX semantics (unless
+    /// [`disable_posix_delete`] was called); None if it has no ID
+    ///
+    /// A read-only directory has its attribute cleared where the disposition can't ignore it,
+    /// as deletion by path does.
+    pub fn remove(&self, dir: &Path) -> Option<io::Result<()>> {
+        use windows::Win32::Storage::FileSystem::{FILE_READ_ATTRIBUTES, FILE_WRITE_ATTRIBUTES};
+
+        let flags = FILE_DISPOSITION_DELETE.0 | FILE_DISPOSITION_POSIX_SEMANTICS.0;
+        let access = DELETE.0 | FILE_READ_ATTRIBUTES.0;
+        unsafe {
+            let handle = match self.open_dir(dir, access)? {
+                Ok(handle) => handle,
+                Err(e) => return Some(Err(e)),
+            };
+            Some(
+                match set_delete_disposition(
+                    &handle,
+                    flags | FILE_DISPOSITION_IGNORE_READONLY_ATTRIBUTE.0,
+                ) {
+                    Err(e) if refused_for_readonly(&e) => {
+                        let cleared = self
+                            .open_dir(dir, FILE_READ_ATTRIBUTES.0 | FILE_WRITE_ATTRIBUTES.0)
+                            .and_then(Result::ok)
+                            .is_some_and(|attrs| clear_readonly_handle(&attrs));
+                        if cleared {
+                            set_delete_disposition(&handle, flags)
+                        } else {
+                            Err(e)
+                        }
+                    }
+                    result => result,
+                },
+            )
+        }
+    }
+}
+
+/// The full path of an open file or directory, `\\?\`-prefixed
+#[cfg(windows)]
+unsafe fn final_path(handle: &FileHandle) -> io::Result<std::path::PathBuf> {
+    use std::os::windows::ffi::OsStringExt;
+    use windows::Win32::Storage::FileSystem::{GetFinalPathNameByHandleW, FILE_NAME_NORMALIZED};
+
+    let mut buf = vec![0u16; 512];
+    loop {
+        let len =
+            GetFinalPathNameByHandleW(handle.as_raw(), &mut buf, FILE_NAME_NORMALIZED) as usize;
+        if len == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if len < buf.len() {
+            buf.truncate(len);
+            return Ok(std::ffi::OsString::from_wide(&buf).into());
+        }
+        buf.resize(len, 0);
+    }
+}
+
+/// Drop the read-only attribute of an open file or directory (opened with
+/// `FILE_WRITE_ATTRIBUTES`); false if it was not set or stays set
+#[cfg(windows)]
+unsafe fn clear_readonly_handle(handle: &FileHandle) -> bool {
+    use windows::Win32::Storage::FileSystem::{
+        FileBasicInfo, GetFileInformationByHandleEx, FILE_ATTRIBUTE_NORMAL,
+        FILE_ATTRIBUTE_READONLY, FILE_BASIC_INFO,
+    };
+
+    let mut info = FILE_BASIC_INFO::default();
+    let size = std::mem::size_of::<FILE_BASIC_INFO>() as u32;
+    let read = GetFileInformationByHandleEx(
+        handle.as_raw(),
+        FileBasicInfo,
+        &mut info as *mut _ as *mut _,
+        size,
+    );
+    if read.is_err() || info.FileAttributes & FILE_ATTRIBUTE_READONLY.0 == 0 {
+        return false;
+    }
+    info.FileAttributes &= !FILE_ATTRIBUTE_READONLY.0;
+    if info.FileAttributes == 0 {
+        // 0 would leave the attributes as they are
+        info.FileAttributes = FILE_ATTRIBUTE_NORMAL.0;
+    }
+    // Zero times are left as they are
+    info.CreationTime = 0;
+    info.LastAccessTime = 0;
+    info.LastWriteTime = 0;
+    info.ChangeTime = 0;
+    SetFileInformationByHandle(
+        handle.as_raw(),
+        FileBasicInfo,
+        &mut info as *mut _ as *mut _,
+        size,
+    )
+    .is_ok()
+}
+
+/// The target root, held open from the safety checks to its removal so that the root itself
+/// cannot be renamed, or swapped for a link, while the run goes on
+///
+/// On Windows the handle denies other processes delete access, which a rename needs too, and
+/// the root is finally removed through it, so the root directory removed is the one checked.
+/// Unix cannot hold a name in place; there the root's identity is checked again just before
+/// it is removed. Only the root is pinned: everything below it is still listed and deleted
+/// by path.
+pub struct RootPin {
+    path: std::path::PathBuf,
+    id: (u64, u64),
+    #[cfg(windows)]
+    handle: FileHandle,
+}
+
+// The handle is only used to mark the directory for deletion, which any thread may do
+#[cfg(windows)]
+unsafe impl Send for RootPin {}
+#[cfg(windows)]
+unsafe impl Sync for RootPin {}
+
+impl RootPin {
+    /// Pin the directory `root` names now; a link is refused
+    #[cfg(windows)]
+    pub fn open(root: &Path) -> io::Result<Self> {
+        use windows::Win32::Storage::FileSystem::{
+            GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_READ_ATTRIBUTES,
+        };
+
+        let wide_path = path_to_wide(root);
+        unsafe {
+            let handle = CreateFileW(
+                PCWSTR(wide_path.as_ptr()),
+                DELETE.0 | FILE_READ_ATTRIBUTES.0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT,
+                HANDLE::default(),
+            )
+            .map(FileHandle)
+            .map_err(win_error)?;
+            let mut info = BY_HANDLE_FILE_INFORMATION::default();
+            GetFileInformationByHandle(handle.as_raw(), &mut info).map_err(win_error)?;
+            if info.dwFileAttributes & FILE_ATTRIBUTE_REPARSE_POINT.0 != 0
+                || info.dwFileAttributes & FILE_ATTRIBUTE_DIRECTORY.0 == 0
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is no longer a directory", root.display()),
+                ));
+            }
+            let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+            Ok(Self {
+                path: root.to_path_buf(),
+                id: (info.dwVolumeSerialNumber as u64, index),
+                handle,
+            })
+        }
+    }
+
+    /// Pin the directory `root` names now; a link is refused
+    #[cfg(not(windows))]
+    pub fn open(root: &Path) -> io::Result<Self> {
+        if !root.symlink_metadata()?.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is no longer a directory", root.display()),
+            ));
+        }
+        Ok(Self {
+            path: root.to_path_buf(),
+            id: file_id(root)?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The same pin under another spelling of its path, such as the one a saved plan uses
+    pub fn with_path(mut self, path: &Path) -> io::Result<Self> {
+        self.path = path.to_path_buf();
+        self.verify()?;
+        Ok(self)
+    }
+
+    /// Check that the path still names the pinned directory
+    pub fn verify(&self) -> io::Result<()> {
+        if file_id(&self.path)? == self.id {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} was replaced since it was checked", self.path.display()),
+            ))
+        }
+    }
+
+    /// Remove the pinned directory, which must be empty
+    ///
+    /// A read-only root has its attribute cleared where the disposition can't ignore it, as
+    /// deletion by path does; the attribute is written through a second handle that must
+    /// open the pinned directory.
+    #[cfg(windows)]
+    pub fn remove(&self) -> io::Result<()> {
+        use windows::Win32::Storage::FileSystem::{
+            GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_READ_ATTRIBUTES,
+            FILE_WRITE_ATTRIBUTES,
+        };
+
+        let flags = FILE_DISPOSITION_DELETE.0 | FILE_DISPOSITION_POSIX_SEMANTICS.0;
+        unsafe {
+            match set_delete_disposition(
+                &self.handle,
+                flags | FILE_DISPOSITION_IGNORE_READONLY_ATTRIBUTE.0,
+            ) {
+                Err(e) if refused_for_readonly(&e) => {
+                    let cleared = FileHandle::open(
+                        &path_to_wide(&self.path),
+                        FILE_READ_ATTRIBUTES.0 | FILE_WRITE_ATTRIBUTES.0,
+                        FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT,
+                    )
+                    .ok()
+                    .filter(|handle| {
+                        let mut info = BY_HANDLE_FILE_INFORMATION::default();
+                        if GetFileInformationByHandle(handle.as_raw(), &mut info).is_err() {
+                            return false;
+                        }
+                        let index =
+                            ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+                        (info.dwVolumeSerialNumber as u64, index) == self.id
+                    })
+                    .is_some_and(|handle| clear_readonly_handle(&handle));
+                    if cleared {
+                        set_delete_disposition(&self.handle, flags)
+                    } else {
+                        Err(e)
+                    }
+                }
+                result => result,
+            }
+        }
+    }
+
+    /// Remove the pinned directory, which must be empty
+    #[cfg(not(windows))]
+    pub fn remove(&self) -> io::Result<()> {
+        self.verify()?;
+        remove_dir(&self.path)
+    }
+}
+
+/// Opening by file ID needs `OpenFileById`; Unix has no unprivileged equivalent
+#[cfg(not(windows))]
+pub struct DirIds;
+
+#[cfg(not(windows))]
+impl DirIds {
+    pub fn open(
+        _root: &Path,
+        _ids: std::collections::HashMap<std::path::PathBuf, u64>,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "deleting by file ID is only supported on Windows",
+        ))
+    }
+
+    pub fn current_path(&self, _dir: &Path) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    pub fn remove(&self, _dir: &Path) -> Option<io::Result<()>> {
+        None
+    }
+}
+
+/// True if a directory has the per-directory case-sensitivity flag (set by WSL or
+/// `fsutil file setCaseSensitiveInfo`), so `Foo` and `foo` can coexist in it
+#[cfg(windows)]
+pub fn is_case_sensitive_dir(dir: &Path) -> bool {
+    use windows::Win32::Storage::FileSystem::{
+        FileCaseSensitiveInfo, GetFileInformationByHandleEx, FILE_READ_ATTRIBUTES,
+    };
+    // FILE_CASE_SENSITIVE_INFO.Flags
+    const FILE_CS_FLAG_CASE_SENSITIVE_DIR: u32 = 1;
+
+    let wide_path = path_to_wide(dir);
+    unsafe {
+        let Ok(handle) = FileHandle::open(
+            &wide_path,
+            FILE_READ_ATTRIBUTES.0,
+            FILE_FLAG_BACKUP_SEMANTICS,
+        ) else {
+            return false;
+        };
+        let mut flags = 0u32;
+        GetFileInformationByHandleEx(
+            handle.as_raw(),
+            FileCaseSensitiveInfo,
+            &mut flags as *mut u32 as *mut _,
+            std::mem::size_of::<u32>() as u32,
+        )
+        .is_ok()
+            && flags & FILE_CS_FLAG_CASE_SENSITIVE_DIR != 0
+    }
+}
+
+/// Unix directories are always case-sensitive; only the Windows per-directory flag is reported
+#[cfg(not(windows))]
+pub fn is_case_sensitive_dir(_dir: &Path) -> bool {
+    false
+}
+
+/// Free space available to the caller on the volume holding `path`
+#[cfg(windows)]
+pub fn free_space(path: &Path) -> io::Result<u64> {
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    // A directory name needs its trailing separator for UNC roots
+    let dir = format!("{}\\", path.display().to_string().trim_end_matches('\\'));
+    let wide: Vec<u16> = dir.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    unsafe {
+        GetDiskFreeSpaceExW(PCWSTR(wide.as_ptr()), Some(&mut available), None, None)
+            .map_err(win_error)?;
+    }
+    Ok(available)
+}
+
+/// Free space available to the caller on the filesystem holding `path`
+#[cfg(not(windows))]
+pub fn free_space(path: &Path) -> io::Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// A stable name for the volume holding `path`: its `\\?\Volume{GUID}\` name, or the
+/// volume root where it has none (network shares)
+#[cfg(windows)]
+pub fn volume_id(path: &Path) -> io::Result<String> {
+    use windows::Win32::Storage::FileSystem::{
+        GetVolumeNameForVolumeMountPointW, GetVolumePathNameW,
+    };
+
+    let wide = path_to_wide(path);
+    let mut root = [0u16; 1024];
+    let mut name = [0u16; 64];
+    unsafe {
+        GetVolumePathNameW(PCWSTR(wide.as_ptr()), &mut root).map_err(win_error)?;
+        let root_len = root.iter().position(|&c| c == 0).unwrap_or(root.len());
+        if GetVolumeNameForVolumeMountPointW(PCWSTR(root.as_ptr()), &mut name).is_ok() {
+            let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+            return Ok(String::from_utf16_lossy(&name[..len]));
+        }
+        Ok(String::from_utf16_lossy(&root[..root_len]))
+    }
+}
+
+/// The long form of a path holding 8.3 short names (`C:\PROGRA~1` for `C:\Program Files`),
+/// or None if it does not exist
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> Option<std::path::PathBuf> {
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use windows::Win32::Storage::FileSystem::GetLongPathNameW;
+
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut buffer = vec![0u16; 1024];
+    loop {
+        let len = unsafe { GetLongPathNameW(PCWSTR(wide.as_ptr()), Some(&mut buffer)) } as usize;
+        match len {
+            0 => return None,
+            // Too small: `len` is the size needed, terminator included
+            _ if len > buffer.len() => buffer.resize(len, 0),
+            _ => return Some(std::ffi::OsString::from_wide(&buffer[..len]).into()),
+        }
+    }
+}
+
+/// Paths have no short names outside Windows
+#[cfg(not(windows))]
+pub fn long_path(_path: &Path) -> Option<std::path::PathBuf> {
+    None
+}
+
+/// A stable name for the filesystem holding `path`: its mount point
+#[cfg(not(windows))]
+pub fn volume_id(path: &Path) -> io::Result<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = path.canonicalize()?;
+    let device = path.metadata()?.dev();
+    let mut mount = path.as_path();
+    while let Some(parent) = mount.parent() {
+        if parent.metadata()?.dev() != device {
+            break;
+        }
+        mount = parent;
+    }
+    Ok(mount.display().to_string())
+}
+
+/// Run the rest of the process at background priority: low CPU and I/O priority for the
+/// process, plus a low I/O priority hint on every deletion handle
+#[cfg(windows)]
+pub fn enter_background_mode() -> io::Result<()> {
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, SetPriorityClass, PROCESS_MODE_BACKGROUND_BEGIN,
+    };
+
+    LOW_IO_PRIORITY.store(true, Ordering::Relaxed);
+    unsafe { SetPriorityClass(GetCurrentProcess(), PROCESS_MODE_BACKGROUND_BEGIN) }
+        .map_err(win_error)
+}
+
+/// Run the rest of the process at background priority (nice 10)
+#[cfg(not(windows))]
+pub fn enter_background_mode() -> io::Result<()> {
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, 10) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Executable of the process that started this one (a build script's shell, say)
+#[cfg(windows)]
+pub fn parent_process_exe() -> Option<std::path::PathBuf> {
+    use windows::core::PWSTR;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::{
+        GetCurrentProcessId, OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let snapshot = FileHandle(CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0).ok()?);
+        let pid = GetCurrentProcessId();
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        Process32FirstW(snapshot.as_raw(), &mut entry).ok()?;
+        while entry.th32ProcessID != pid {
+            Process32NextW(snapshot.as_raw(), &mut entry).ok()?;
+        }
+
+        let parent = FileHandle(
+            OpenProcess(
+                PROCESS_QUERY_LIMITED_INFORMATION,
+                false,
+                entry.th32ParentProcessID,
+            )
+            .ok()?,
+        );
+        let mut name = vec![0u16; 32768];
+        let mut len = name.len() as u32;
+        QueryFullProcessImageNameW(
+            parent.as_raw(),
+            PROCESS_NAME_WIN32,
+            PWSTR(name.as_mut_ptr()),
+            &mut len,
+        )
+        .ok()?;
+        Some(String::from_utf16_lossy(&name[..len as usize]).into())
+    }
+}
+
+/// Executable of the process that started this one
+#[cfg(target_os = "linux")]
+pub fn parent_process_exe() -> Option<std::path::PathBuf> {
+    let ppid = unsafe { libc::getppid() };
+    std::fs::read_link(format!("/proc/{}/exe", ppid)).ok()
+}
+
+/// Not available on this platform
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn parent_process_exe() -> Option<std::path::PathBuf> {
+    None
+}
+
+/// How a running process uses a path inside a tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessUse {
+    /// Its current working directory
+    WorkingDir(std::path::PathBuf),
+    /// A loaded DLL, shared library or mapped file
+    Module(std::path::PathBuf),
+}
+
+/// A running process whose working directory or loaded modules are inside a tree
+#[derive(Debug, Clone)]
+pub struct ProcessInTree {
+    pub pid: u32,
+    pub name: String,
+    pub uses: Vec<ProcessUse>,
+}
+
+/// Find other processes using paths under `root` (canonical), skipping those that cannot be
+/// inspected (other users' processes without administrator rights, exiting processes)
+#[cfg(windows)]
+pub fn processes_using(root: &Path) -> Vec<ProcessInTree> {
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::GetCurrentProcessId;
+
+    let mut found = Vec::new();
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) else {
+            return found;
+        };
+        let snapshot = FileHandle(snapshot);
+        let own_pid = GetCurrentProcessId();
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut more = Process32FirstW(snapshot.as_raw(), &mut entry).is_ok();
+        while more {
+            let pid = entry.th32ProcessID;
+            if pid != own_pid && pid != 0 {
+                let mut uses = Vec::new();
+                if let Some(cwd) = process_cwd(pid).filter(|cwd| under(cwd, root)) {
+                    uses.push(ProcessUse::WorkingDir(cwd));
+                }
+                uses.extend(
+                    process_modules(pid)
+                        .into_iter()
+                        .filter(|module| under(module, root))
+                        .map(ProcessUse::Module),
+                );
+                if !uses.is_empty() {
+                    let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(0);
+                    found.push(ProcessInTree {
+                        pid,
+                        name: String::from_utf16_lossy(&entry.szExeFile[..len]),
+                        uses,
+                    });
+                }
+            }
+            more = Process32NextW(snapshot.as_raw(), &mut entry).is_ok();
+        }
+    }
+    found
+}
+
+/// Case-insensitive `starts_with`, as Windows paths compare
+#[cfg(windows)]
+fn under(path: &Path, root: &Path) -> bool {
+    let lower = |p: &Path| p.to_string_lossy().to_lowercase();
+    let root = lower(root);
+    let root = root.strip_prefix(r"\\?\").unwrap_or(&root).to_string();
+    Path::new(&lower(path)).starts_with(root)
+}
+
+/// Paths of the modules loaded by a process
+#[cfg(windows)]
+fn process_modules(pid: u32) -> Vec<std::path::PathBuf> {
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Module32FirstW, Module32NextW, MODULEENTRY32W, TH32CS_SNAPMODULE,
+        TH32CS_SNAPMODULE32,
+    };
+
+    let mut modules = Vec::new();
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE | TH32CS_SNAPMODULE32, pid)
+        else {
+            return modules;
+        };
+        let snapshot = FileHandle(snapshot);
+        let mut entry = MODULEENTRY32W {
+            dwSize: std::mem::size_of::<MODULEENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut more = Module32FirstW(snapshot.as_raw(), &mut entry).is_ok();
+        while more {
+            let len = entry.szExePath.iter().position(|&c| c == 0).unwrap_or(0);
+            modules.push(String::from_utf16_lossy(&entry.szExePath[..len]).into());
+            more = Module32NextW(snapshot.as_raw(), &mut entry).is_ok();
+        }
+    }
+    modules
+}
+
+/// A process's current directory, read from its process parameters block
+#[cfg(all(windows, target_pointer_width = "64"))]
+fn process_cwd(pid: u32) -> Option<std::path::PathBuf> {
+    use std::ffi::c_void;
+    use windows::Wdk::System::Threading::{NtQueryInformationProcess, ProcessBasicInformation};
+    use windows::Win32::System::Diagnostics::Debug::ReadProcessMemory;
+    use windows::Win32::System::Threading::{
+        OpenProcess, PROCESS_BASIC_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ,
+    };
+
+    // x64 layouts: PEB.ProcessParameters, then
+    // RTL_USER_PROCESS_PARAMETERS.CurrentDirectory.DosPath (a UNICODE_STRING)
+    const PROCESS_PARAMETERS_OFFSET: usize = 0x20;
+    const CURRENT_DIRECTORY_OFFSET: usize = 0x38;
+
+    unsafe {
+        let process = FileHandle(
+            OpenProcess(
+                PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_VM_READ,
+                false,
+                pid,
+            )
+            .ok()?,
+        );
+        let read = |address: usize, buffer: *mut c_void, size: usize| {
+            ReadProcessMemory(
+                process.as_raw(),
+                address as *const c_void,
+                buffer,
+                size,
+                None,
+            )
+            .ok()
+        };
+
+        let mut info = PROCESS_BASIC_INFORMATION::default();
+        NtQueryInformationProcess(
+            process.as_raw(),
+            ProcessBasicInformation,
+            &mut info as *mut _ as *mut c_void,
+            std::mem::size_of::<PROCESS_BASIC_INFORMATION>() as u32,
+            std::ptr::null_mut(),
+        )
+        .ok()
+        .ok()?;
+
+        let mut parameters = 0usize;
+        read(
+            info.PebBaseAddress as usize + PROCESS_PARAMETERS_OFFSET,
+            &mut parameters as *mut _ as *mut c_void,
+            std::mem::size_of::<usize>(),
+        )?;
+        // UNICODE_STRING: byte length, capacity, padding, buffer pointer
+        let mut dos_path = [0usize; 2];
+        read(
+            parameters + CURRENT_DIRECTORY_OFFSET,
+            dos_path.as_mut_ptr() as *mut c_void,
+            std::mem::size_of_val(&dos_path),
+        )?;
+        let byte_len = dos_path[0] & 0xFFFF;
+        let mut path = vec![0u16; byte_len / 2];
+        read(dos_path[1], path.as_mut_ptr() as *mut c_void, byte_len)?;
+        Some(String::from_utf16_lossy(&path).into())
+    }
+}
+
+#[cfg(all(windows, not(target_pointer_width = "64")))]
+fn process_cwd(_pid: u32) -> Option<std::path::PathBuf> {
+    None
+}
+
+/// Find other processes using paths under `root` (canonical), skipping those that cannot be
+/// inspected (other users' processes without root, exiting processes)
+#[cfg(target_os = "linux")]
+pub fn processes_using(root: &Path) -> Vec<ProcessInTree> {
+    let own_pid = std::process::id();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut found = Vec::new();
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid == own_pid {
+            continue;
+        }
+        let proc_dir = entry.path();
+        let mut uses = Vec::new();
+        if let Ok(cwd) = std::fs::read_link(proc_dir.join("cwd")) {
+            if cwd.starts_with(root) {
+                uses.push(ProcessUse::WorkingDir(cwd));
+            }
+        }
+        if let Ok(maps) = std::fs::read_to_string(proc_dir.join("maps")) {
+            let mut modules: Vec<std::path::PathBuf> = maps
+                .lines()
+                // address perms offset dev inode path
+                .filter_map(|line| line.splitn(6, char::is_whitespace).nth(5))
+                .map(|path| std::path::PathBuf::from(path.trim_start()))
+                .filter(|path| path.starts_with(root))
+                .collect();
+            modules.dedup();
+            uses.extend(modules.into_iter().map(ProcessUse::Module));
+        }
+        if !uses.is_empty() {
+            let name = std::fs::read_to_string(proc_dir.join("comm")).unwrap_or_default();
+            found.push(ProcessInTree {
+                pid,
+                name: name.trim_end().to_string(),
+                uses,
+            });
+        }
+    }
+    found
+}
+
+/// Process inspection is not implemented on this platform
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn processes_using(_root: &Path) -> Vec<ProcessInTree> {
+    Vec::new()
+}
+
+/// The user's default locale name, such as `de-DE`
+#[cfg(windows)]
+pub fn user_locale() -> Option<String> {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut name = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut name) };
+    // The length includes the terminating null
+    (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]))
+}
+
+/// Unix locales come from the environment (`LANG` and friends)
+#[cfg(not(windows))]
+pub fn user_locale() -> Option<String> {
+    None
+}
+
+/// True if `path` is on a network share (UNC path or mapped network drive)
+#[cfg(windows)]
+pub fn is_network_path(path: &Path) -> bool {
+    use std::path::{Component, Prefix};
+    use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+    const DRIVE_REMOTE: u32 = 4;
+
+    let Ok(path) = std::path::absolute(crate::safety::device_path_to_verbatim(path)) else {
+        return false;
+    };
+    let root = match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::UNC(..) | Prefix::VerbatimUNC(..) => return true,
+            Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => format!("{}:\\", drive as char),
+            Prefix::Verbatim(_) if crate::safety::volume_guid(&path).is_some() => {
+                format!("{}\\", prefix.as_os_str().to_string_lossy())
+            }
+            _ => return false,
+        },
+        _ => return false,
+    };
+    let wide: Vec<u16> = root.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe { GetDriveTypeW(PCWSTR(wide.as_ptr())) == DRIVE_REMOTE }
+}
+
+/// True if `path` is on an NFS or SMB/CIFS mount
+#[cfg(target_os = "linux")]
+pub fn is_network_path(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    const NFS_SUPER_MAGIC: u32 = 0x6969;
+    const SMB_SUPER_MAGIC: u32 = 0x517B;
+    const CIFS_MAGIC_NUMBER: u32 = 0xFF53_4D42;
+    const SMB2_MAGIC_NUMBER: u32 = 0xFE53_4D42;
+
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    matches!(
+        stat.f_type as u32,
+        NFS_SUPER_MAGIC | SMB_SUPER_MAGIC | CIFS_MAGIC_NUMBER | SMB2_MAGIC_NUMBER
+    )
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn is_network_path(_path: &Path) -> bool {
+    false
+}
+
+/// True if `path` is `folder` or inside it, ignoring case and the `\\?\` prefix as
+/// Windows does
+pub(crate) fn is_within(path: &Path, folder: &Path) -> bool {
+    let plain = |p: &Path| {
+        let text = p.to_string_lossy().replace('/', "\\").to_lowercase();
+        let text = text
+            .strip_prefix(r"\\?\")
+            .map(str::to_string)
+            .unwrap_or(text);
+        text.trim_end_matches('\\').to_string()
+    };
+    let (path, folder) = (plain(path), plain(folder));
+    path.strip_prefix(&folder)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+}
+
+/// Folders that Windows Security's Controlled Folder Access guards while it is on (and
+/// blocking, not just auditing): the known user and public folders, plus those added in
+/// its settings
+///
+/// Read once. Settings that cannot be read count as off.
+#[cfg(windows)]
+pub fn controlled_folders() -> &'static [std::path::PathBuf] {
+    static FOLDERS: std::sync::OnceLock<Vec<std::path::PathBuf>> = std::sync::OnceLock::new();
+    FOLDERS.get_or_init(|| unsafe { read_controlled_folders() }.unwrap_or_default())
+}
+
+#[cfg(windows)]
+unsafe fn read_controlled_folders() -> Option<Vec<std::path::PathBuf>> {
+    use std::os::windows::ffi::OsStringExt;
+    use windows::core::{w, PWSTR};
+    use windows::Win32::System::Com::CoTaskMemFree;
+    use windows::Win32::System::Registry::{
+        RegCloseKey, RegEnumValueW, RegGetValueW, RegOpenKeyExW, HKEY, HKEY_LOCAL_MACHINE,
+        KEY_READ, KEY_WOW64_64KEY, RRF_RT_REG_DWORD, RRF_SUBKEY_WOW6464KEY,
+    };
+    use windows::Win32::UI::Shell::{
+        FOLDERID_Desktop, FOLDERID_Documents, FOLDERID_Favorites, FOLDERID_Music,
+        FOLDERID_Pictures, FOLDERID_PublicDesktop, FOLDERID_PublicDocuments, FOLDERID_PublicMusic,
+        FOLDERID_PublicPictures, FOLDERID_PublicVideos, FOLDERID_Videos, SHGetKnownFolderPath,
+        KF_FLAG_DEFAULT,
+    };
+
+    // Group policy, then the local setting
+    const KEYS: [PCWSTR; 2] = [
+        w!(
+            r"SOFTWARE\Policies\Microsoft\Windows Defender\Windows Defender Exploit Guard\Controlled Folder Access"
+        ),
+        w!(
+            r"SOFTWARE\Microsoft\Windows Defender\Windows Defender Exploit Guard\Controlled Folder Access"
+        ),
+    ];
+    const FOLDER_KEYS: [PCWSTR; 2] = [
+        w!(
+            r"SOFTWARE\Policies\Microsoft\Windows Defender\Windows Defender Exploit Guard\Controlled Folder Access\ProtectedFolders"
+        ),
+        w!(
+            r"SOFTWARE\Microsoft\Windows Defender\Windows Defender Exploit Guard\Controlled Folder Access\ProtectedFolders"
+        ),
+    ];
+
+    let mode = KEYS.iter().find_map(|key| {
+        let mut value = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            *key,
+            w!("EnableControlledFolderAccess"),
+            RRF_RT_REG_DWORD | RRF_SUBKEY_WOW6464KEY,
+            None,
+            Some(&mut value as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+        .is_ok()
+        .then_some(value)
+    })?;
+    // 1 blocks; 2 and up only audit or cover disk sectors
+    if mode != 1 {
+        return None;
+    }
+
+    let mut folders = Vec::new();
+    for id in [
+        FOLDERID_Desktop,
+        FOLDERID_Documents,
+        FOLDERID_Favorites,
+        FOLDERID_Music,
+        FOLDERID_Pictures,
+        FOLDERID_Videos,
+        FOLDERID_PublicDesktop,
+        FOLDERID_PublicDocuments,
+        FOLDERID_PublicMusic,
+        FOLDERID_PublicPictures,
+        FOLDERID_PublicVideos,
+    ] {
+        if let Ok(path) = SHGetKnownFolderPath(&id, KF_FLAG_DEFAULT, HANDLE::default()) {
+            folders.push(std::ffi::OsString::from_wide(path.as_wide()).into());
+            CoTaskMemFree(Some(path.0 as *const _));
+        }
+    }
+    // Added folders are the value names of the key
+    for key in FOLDER_KEYS {
+        let mut hkey = HKEY::default();
+        if RegOpenKeyExW(
+            HKEY_LOCAL_MACHINE,
+            key,
+            0,
+            KEY_READ | KEY_WOW64_64KEY,
+            &mut hkey,
+        )
+        .is_err()
+        {
+            continue;
+        }
+        let mut name = vec![0u16; 32_768];
+        for index in 0.. {
+            let mut len = name.len() as u32;
+            let status = RegEnumValueW(
+                hkey,
+                index,
+                PWSTR(name.as_mut_ptr()),
+                &mut len,
+                None,
+                None,
+                None,
+                None,
+            );
+            if status.is_err() {
+                break;
+            }
+            folders.push(std::ffi::OsString::from_wide(&name[..len as usize]).into());
+        }
+        let _ = RegCloseKey(hkey);
+    }
+    Some(folders)
+}
+
+#[cfg(not(windows))]
+pub fn controlled_folders() -> &'static [std::path::PathBuf] {
+    &[]
+}
+
+/// Paths Defender logged Controlled Folder Access blocking (event 1123) within the last
+/// `window`
+///
+/// Empty if its log cannot be read, which takes elevation on some systems.
+#[cfg(windows)]
+pub fn controlled_folder_blocks(window: std::time::Duration) -> Vec<std::path::PathBuf> {
+    unsafe { read_controlled_folder_blocks(window) }.unwrap_or_default()
+}
+
+#[cfg(windows)]
+unsafe fn read_controlled_folder_blocks(
+    window: std::time::Duration,
+) -> Option<Vec<std::path::PathBuf>> {
+    use windows::core::{w, HSTRING};
+    use windows::Win32::System::EventLog::{
+        EvtClose, EvtNext, EvtQuery, EvtQueryChannelPath, EvtRender, EvtRenderEventXml, EVT_HANDLE,
+    };
+
+    let query = HSTRING::from(format!(
+        "*[System[EventID=1123 and TimeCreated[timediff(@SystemTime) <= {}]]]",
+        window.as_millis()
+    ));
+    let results = EvtQuery(
+        EVT_HANDLE::default(),
+        w!("Microsoft-Windows-Windows Defender/Operational"),
+        &query,
+        EvtQueryChannelPath.0,
+    )
+    .ok()?;
+    let mut paths = Vec::new();
+    let mut events = [0isize; 16];
+    let mut buffer = vec![0u16; 4096];
+    loop {
+        let mut returned = 0;
+        if EvtNext(results, &mut events, u32::MAX, 0, &mut returned).is_err() {
+            break;
+        }
+        for &event in &events[..returned as usize] {
+            let event = EVT_HANDLE(event);
+            let mut used = 0;
+            for _ in 0..2 {
+                let mut count = 0;
+                let rendered = EvtRender(
+                    EVT_HANDLE::default(),
+                    event,
+                    EvtRenderEventXml.0,
+                    (buffer.len() * 2) as u32,
+                    Some(buffer.as_mut_ptr().cast()),
+                    &mut used,
+                    &mut count,
+                );
+                if rendered.is_ok() {
+                    let xml = String::from_utf16_lossy(&buffer[..used as usize / 2]);
+                    paths.extend(event_data(&xml, "Path").map(std::path::PathBuf::from));
+                    break;
+                }
+                // Too small: grow to the size it asked for and render again
+                if used as usize <= buffer.len() * 2 {
+                    break;
+                }
+                buffer.resize(used as usize / 2 + 1, 0);
+            }
+            let _ = EvtClose(event);
+        }
+    }
+    let _ = EvtClose(results);
+    Some(paths)
+}
+
+#[cfg(not(windows))]
+pub fn controlled_folder_blocks(_window: std::time::Duration) -> Vec<std::path::PathBuf> {
+    Vec::new()
+}
+
+/// The `<Data Name='name'>` value of a rendered event, with XML escapes undone
+#[cfg_attr(not(windows), allow(dead_code))]
+fn event_data(xml: &str, name: &str) -> Option<String> {
+    let open = format!("<Data Name='{}'>", name);
+    let start = xml.find(&open)? + open.len();
+    let len = xml[start..].find("</Data>")?;
+    Some(
+        xml[start..start + len]
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// True if Controlled Folder Access guards `path`, so writes and deletes by apps it does not
+/// trust are blocked as access denied
+pub fn is_controlled_folder(path: &Path) -> bool {
+    let folders = controlled_folders();
+    if folders.is_empty() {
+        return false;
+    }
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    folders.iter().any(|folder| is_within(&path, folder))
+}
+
+/// Restrict the calling thread to the processors of NUMA node `node`
+#[cfg(windows)]
+pub fn pin_to_numa_node(node: u16) -> io::Result<()> {
+    use windows::Win32::System::SystemInformation::GROUP_AFFINITY;
+    use windows::Win32::System::Threading::{
+        GetCurrentThread, GetNumaNodeProcessorMaskEx, SetThreadGroupAffinity,
+    };
+
+    let mut affinity = GROUP_AFFINITY::default();
+    unsafe {
+        if !GetNumaNodeProcessorMaskEx(node, &mut affinity).as_bool() || affinity.Mask == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no NUMA node {}", node),
+            ));
+        }
+        if !SetThreadGroupAffinity(GetCurrentThread(), &affinity, None).as_bool() {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Restrict the calling thread to the processors of NUMA node `node`
+#[cfg(target_os = "linux")]
+pub fn pin_to_numa_node(node: u16) -> io::Result<()> {
+    let list = std::fs::read_to_string(format!("/sys/devices/system/node/node{}/cpulist", node))
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, format!("no NUMA node {}", node)))?;
+    let cpus = parse_cpu_list(&list)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unreadable NUMA cpu list"))?;
+    if cpus.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("NUMA node {} has no processors", node),
+        ));
+    }
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn pin_to_numa_node(_node: u16) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "NUMA placement is only supported on Windows and Linux",
+    ))
+}
+
+/// Parse a Linux cpu list like "0-3,8-11"
+#[cfg(target_os = "linux")]
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|r| !r.is_empty()) {
+        let (first, last): (usize, usize) = match range.split_once('-') {
+            Some((first, last)) => (first.parse().ok()?, last.parse().ok()?),
+            None => {
+                let cpu = range.parse().ok()?;
+                (cpu, cpu)
+            }
+        };
+        cpus.extend(first..=last);
+    }
+    Some(cpus)
+}
+
+/// Enable the privileges needed to take ownership of other users' files; fails unless the
+/// process runs as administrator
+#[cfg(windows)]
+pub fn enable_take_ownership() -> io::Result<()> {
+    use windows::Win32::Foundation::{GetLastError, ERROR_NOT_ALL_ASSIGNED, LUID};
+    use windows::Win32::Security::{
+        AdjustTokenPrivileges, LookupPrivilegeValueW, LUID_AND_ATTRIBUTES, SE_BACKUP_NAME,
+        SE_PRIVILEGE_ENABLED, SE_RESTORE_NAME, SE_TAKE_OWNERSHIP_NAME, TOKEN_ADJUST_PRIVILEGES,
+        TOKEN_PRIVILEGES, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY,
+            &mut token,
+        )
+        .map_err(win_error)?;
+        let token = FileHandle(token);
+
+        for name in [SE_TAKE_OWNERSHIP_NAME, SE_RESTORE_NAME, SE_BACKUP_NAME] {
+            let mut luid = LUID::default();
+            LookupPrivilegeValueW(PCWSTR::null(), name, &mut luid).map_err(win_error)?;
+            let privileges = TOKEN_PRIVILEGES {
+                PrivilegeCount: 1,
+                Privileges: [LUID_AND_ATTRIBUTES {
+                    Luid: luid,
+                    Attributes: SE_PRIVILEGE_ENABLED,
+                }],
+            };
+            AdjustTokenPrivileges(token.as_raw(), false, Some(&privileges), 0, None, None)
+                .map_err(win_error)?;
+            // Succeeds without enabling anything when the account lacks the privilege
+            if GetLastError() == ERROR_NOT_ALL_ASSIGNED {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "taking ownership requires running as administrator",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Make the Administrators group the owner of `path` and replace its ACL with full control
+/// for Administrators (needs [`enable_take_ownership`] first)
+#[cfg(windows)]
+pub fn take_ownership(path: &Path) -> io::Result<()> {
+    use windows::Win32::Security::Authorization::{SetNamedSecurityInfoW, SE_FILE_OBJECT};
+    use windows::Win32::Security::{
+        AddAccessAllowedAceEx, InitializeAcl, ACL, ACL_REVISION, CONTAINER_INHERIT_ACE,
+        DACL_SECURITY_INFORMATION, OBJECT_INHERIT_ACE, OWNER_SECURITY_INFORMATION,
+        PROTECTED_DACL_SECURITY_INFORMATION, PSID, SECURITY_MAX_SID_SIZE,
+    };
+    use windows::Win32::Storage::FileSystem::FILE_ALL_ACCESS;
+
+    let wide_path = path_to_wide(path);
+    unsafe {
+        let mut sid_buf = [0u8; SECURITY_MAX_SID_SIZE as usize];
+        let sid = administrators_sid(&mut sid_buf)?;
+
+        // Room for the ACL header and one ACE holding the SID
+        let mut acl_buf = [0u64; 32];
+        let acl = acl_buf.as_mut_ptr() as *mut ACL;
+        InitializeAcl(acl, std::mem::size_of_val(&acl_buf) as u32, ACL_REVISION)
+            .map_err(win_error)?;
+        AddAccessAllowedAceEx(
+            acl,
+            ACL_REVISION,
+            OBJECT_INHERIT_ACE | CONTAINER_INHERIT_ACE,
+            FILE_ALL_ACCESS.0,
+            sid,
+        )
+        .map_err(win_error)?;
+
+        // Inherited deny entries must not survive, so the new ACL is protected
+        let status = SetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION
+                | DACL_SECURITY_INFORMATION
+                | PROTECTED_DACL_SECURITY_INFORMATION,
+            sid,
+            PSID::default(),
+            Some(acl),
+            None,
+        );
+        if status.0 != 0 {
+            return Err(io::Error::from_raw_os_error(status.0 as i32));
+        }
+    }
+    Ok(())
+}
+
+/// Make the Administrators group the owner of `path`, leaving its ACL alone (needs
+/// [`enable_take_ownership`] first)
+#[cfg(windows)]
+pub fn set_owner(path: &Path) -> io::Result<()> {
+    use windows::Win32::Security::Authorization::{SetNamedSecurityInfoW, SE_FILE_OBJECT};
+    use windows::Win32::Security::{OWNER_SECURITY_INFORMATION, PSID, SECURITY_MAX_SID_SIZE};
+
+    let wide_path = path_to_wide(path);
+    unsafe {
+        let mut sid_buf = [0u8; SECURITY_MAX_SID_SIZE as usize];
+        let sid = administrators_sid(&mut sid_buf)?;
+        let status = SetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            sid,
+            PSID::default(),
+            None,
+            None,
+        );
+        if status.0 != 0 {
+            return Err(io::Error::from_raw_os_error(status.0 as i32));
+        }
+    }
+    Ok(())
+}
+
+/// The Administrators group's SID, written into `buf`
+#[cfg(windows)]
+unsafe fn administrators_sid(
+    buf: &mut [u8; windows::Win32::Security::SECURITY_MAX_SID_SIZE as usize],
+) -> io::Result<windows::Win32::Security::PSID> {
+    use windows::Win32::Security::{CreateWellKnownSid, WinBuiltinAdministratorsSid, PSID};
+
+    let mut sid_len = buf.len() as u32;
+    let sid = PSID(buf.as_mut_ptr() as *mut _);
+    CreateWellKnownSid(
+        WinBuiltinAdministratorsSid,
+        PSID::default(),
+        sid,
+        &mut sid_len,
+    )
+    .map_err(win_error)?;
+    Ok(sid)
+}
+
+/// Taking ownership is a Windows ACL operation
+#[cfg(not(windows))]
+pub fn enable_take_ownership() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "taking ownership is only supported on Windows",
+    ))
+}
+
+#[cfg(not(windows))]
+pub fn take_ownership(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "taking ownership is only supported on Windows",
+    ))
+}
+
+#[cfg(not(windows))]
+pub fn set_owner(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "taking ownership is only supported on Windows",
+    ))
+}
+
+/// Take ownership of `root` and everything below it, directories before their contents so
+/// they can be listed; links are not followed. Returns (entries changed, entries that failed);
+/// a directory that cannot be listed counts as failed
+///
+/// Only the root's ACL is replaced; Windows carries its inheritable full control down to
+/// every entry that inherits, so below the root only the owner is set.
+pub fn take_ownership_recursive(root: &Path) -> (usize, usize) {
+    let (mut taken, mut failed) = match take_ownership(root) {
+        Ok(()) => (1, 0),
+        Err(_) => (0, 1),
+    };
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let listed = enumerate_entries(&dir, false, |path, is_dir, _, _, _| {
+            if set_owner(path).is_ok() {
+                taken += 1;
+            } else {
+                failed += 1;
+            }
+            if is_dir {
+                pending.push(path.to_path_buf());
+            }
+            Ok(())
+        });
+        if listed.is_err() {
+            failed += 1;
+        }
+    }
+    (taken, failed)
+}
+
+/// Account owning `path`, as `DOMAIN\name` (or the SID string when it has no account)
+#[cfg(windows)]
+pub fn owner(path: &Path) -> io::Result<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{LocalFree, HLOCAL};
+    use windows::Win32::Security::Authorization::{
+        ConvertSidToStringSidW, GetNamedSecurityInfoW, SE_FILE_OBJECT,
+    };
+    use windows::Win32::Security::{
+        LookupAccountSidW, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID, SID_NAME_USE,
+    };
+
+    let wide_path = path_to_wide(path);
+    unsafe {
+        let mut sid = PSID::default();
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        let status = GetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            Some(&mut sid),
+            None,
+            None,
+            None,
+            &mut descriptor,
+        );
+        if status.0 != 0 {
+            return Err(io::Error::from_raw_os_error(status.0 as i32));
+        }
+
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let (mut name_len, mut domain_len) = (name.len() as u32, domain.len() as u32);
+        let mut sid_use = SID_NAME_USE::default();
+        let account = LookupAccountSidW(
+            PCWSTR::null(),
+            sid,
+            PWSTR(name.as_mut_ptr()),
+            &mut name_len,
+            PWSTR(domain.as_mut_ptr()),
+            &mut domain_len,
+            &mut sid_use,
+        );
+        let owner = match account {
+            Ok(()) => {
+                let name = String::from_utf16_lossy(&name[..name_len as usize]);
+                let domain = String::from_utf16_lossy(&domain[..domain_len as usize]);
+                Ok(if domain.is_empty() {
+                    name
+                } else {
+                    format!("{}\\{}", domain, name)
+                })
+            }
+            // Deleted accounts and foreign domains leave only the SID
+            Err(_) => {
+                let mut text = PWSTR::null();
+                ConvertSidToStringSidW(sid, &mut text)
+                    .map_err(win_error)
+                    .map(|()| {
+                        let owner = text.to_string().unwrap_or_default();
+                        LocalFree(HLOCAL(text.0 as _));
+                        owner
+                    })
+            }
+        };
+        LocalFree(HLOCAL(descriptor.0));
+        owner
+    }
+}
+
+/// Account owning `path`, or its uid when that has no passwd entry
+#[cfg(unix)]
+pub fn owner(path: &Path) -> io::Result<String> {
+    use std::ffi::CStr;
+    use std::os::unix::fs::MetadataExt;
+
+    let uid = std::fs::symlink_metadata(path)?.uid();
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut found = std::ptr::null_mut();
+    let status = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut found,
+        )
+    };
+    if status != 0 || found.is_null() {
+        return Ok(uid.to_string());
+    }
+    Ok(unsafe { CStr::from_ptr(passwd.pw_name) }
+        .to_string_lossy()
+        .into_owned())
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn owner(_path: &Path) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "file owners are not available on this platform",
+    ))
+}
+
+/// Keeps new entries out of a tree while it is deleted; dropping it restores the root's
+/// previous permissions if the root is still there (see [`hold_root`])
+///
+/// The deny entry outlives the process unless it is removed, so exits that skip destructors
+/// must call [`release_root_holds`] first.
+#[cfg(windows)]
+pub struct RootHold {
+    id: u64,
+}
+
+/// A root whose permissions [`hold_root`] changed, with what to restore
+#[cfg(windows)]
+struct HeldRoot {
+    id: u64,
+    wide_path: Vec<u16>,
+    path: std::path::PathBuf,
+    /// The root's original security descriptor, holding the DACL to restore
+    descriptor: windows::Win32::Security::PSECURITY_DESCRIPTOR,
+    dacl: *mut windows::Win32::Security::ACL,
+}
+
+// The descriptor is a LocalAlloc block owned by this value alone
+#[cfg(windows)]
+unsafe impl Send for HeldRoot {}
+
+/// Roots held right now, for [`release_root_holds`]
+#[cfg(windows)]
+static HELD_ROOTS: std::sync::Mutex<Vec<HeldRoot>> = std::sync::Mutex::new(Vec::new());
+
+#[cfg(windows)]
+static NEXT_HOLD: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+#[cfg(windows)]
+fn held_roots() -> std::sync::MutexGuard<'static, Vec<HeldRoot>> {
+    HELD_ROOTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Deny everyone the right to create files and subdirectories in `root` and, through
+/// inheritance, every directory below it, so writers cannot refill the tree mid-run
+///
+/// Deleting needs neither right, so the run itself is unaffected. Windows applies the
+/// inherited entry to every existing subdirectory, which costs a pass over the tree.
+#[cfg(windows)]
+pub fn hold_root(root: &Path) -> io::Result<RootHold> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{LocalFree, HLOCAL};
+    use windows::Win32::Security::Authorization::{
+        GetNamedSecurityInfoW, SetEntriesInAclW, SetNamedSecurityInfoW, DENY_ACCESS,
+        EXPLICIT_ACCESS_W, NO_MULTIPLE_TRUSTEE, SE_FILE_OBJECT, TRUSTEE_IS_SID,
+        TRUSTEE_IS_WELL_KNOWN_GROUP, TRUSTEE_W,
+    };
+    use windows::Win32::Security::{
+        CreateWellKnownSid, WinWorldSid, ACL, DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR,
+        PSID, SECURITY_MAX_SID_SIZE, SUB_CONTAINERS_ONLY_INHERIT,
+    };
+    use windows::Win32::Storage::FileSystem::{FILE_ADD_FILE, FILE_ADD_SUBDIRECTORY};
+
+    let wide_path = path_to_wide(root);
+    unsafe {
+        let mut dacl: *mut ACL = std::ptr::null_mut();
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        let status = GetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            None,
+            None,
+            Some(&mut dacl),
+            None,
+            &mut descriptor,
+        );
+        if status.0 != 0 {
+            return Err(io::Error::from_raw_os_error(status.0 as i32));
+        }
+        let held = HeldRoot {
+            id: NEXT_HOLD.fetch_add(1, Ordering::Relaxed),
+            wide_path,
+            path: root.to_path_buf(),
+            descriptor,
+            dacl,
+        };
+
+        let mut sid_buf = [0u8; SECURITY_MAX_SID_SIZE as usize];
+        let mut sid_len = SECURITY_MAX_SID_SIZE;
+        let everyone = PSID(sid_buf.as_mut_ptr() as *mut _);
+        CreateWellKnownSid(WinWorldSid, PSID::default(), everyone, &mut sid_len)
+            .map_err(win_error)?;
+        let deny = EXPLICIT_ACCESS_W {
+            grfAccessPermissions: FILE_ADD_FILE.0 | FILE_ADD_SUBDIRECTORY.0,
+            grfAccessMode: DENY_ACCESS,
+            grfInheritance: SUB_CONTAINERS_ONLY_INHERIT,
+            Trustee: TRUSTEE_W {
+                pMultipleTrustee: std::ptr::null_mut(),
+                MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+                TrusteeForm: TRUSTEE_IS_SID,
+                TrusteeType: TRUSTEE_IS_WELL_KNOWN_GROUP,
+                ptstrName: PWSTR(everyone.0 as *mut u16),
+            },
+        };
+        let mut denied: *mut ACL = std::ptr::null_mut();
+        let status = SetEntriesInAclW(Some(&[deny]), Some(held.dacl), &mut denied);
+        if status.0 != 0 {
+            return Err(io::Error::from_raw_os_error(status.0 as i32));
+        }
+        // Registered before it takes effect, so an exit in between still restores it
+        let id = held.id;
+        let wide_path = held.wide_path.clone();
+        held_roots().push(held);
+        let hold = RootHold { id };
+        let status = SetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            PSID::default(),
+            PSID::default(),
+            Some(denied),
+            None,
+        );
+        LocalFree(HLOCAL(denied as _));
+        if status.0 != 0 {
+            return Err(io::Error::from_raw_os_error(status.0 as i32));
+        }
+        Ok(hold)
+    }
+}
+
+#[cfg(windows)]
+impl Drop for RootHold {
+    fn drop(&mut self) {
+        let mut held = held_roots();
+        if let Some(index) = held.iter().position(|root| root.id == self.id) {
+            let root = held.swap_remove(index);
+            drop(held);
+            root.restore();
+        }
+    }
+}
+
+/// Give every root still held its permissions back, for exits that skip destructors
+/// (`process::exit`); the holds are released as if dropped
+#[cfg(windows)]
+pub fn release_root_holds() {
+    let held = std::mem::take(&mut *held_roots());
+    for root in held {
+        root.restore();
+    }
+}
+
+/// Nothing is held on other platforms
+#[cfg(not(windows))]
+pub fn release_root_holds() {}
+
+#[cfg(windows)]
+impl HeldRoot {
+    fn restore(&self) {
+        use windows::Win32::Security::Authorization::{SetNamedSecurityInfoW, SE_FILE_OBJECT};
+        use windows::Win32::Security::{DACL_SECURITY_INFORMATION, PSID};
+
+        unsafe {
+            // Whatever survived the run (kept entries, failures) gets its permissions back
+            if self.path.exists() {
+                let status = SetNamedSecurityInfoW(
+                    PCWSTR(self.wide_path.as_ptr()),
+                    SE_FILE_OBJECT,
+                    DACL_SECURITY_INFORMATION,
+                    PSID::default(),
+                    PSID::default(),
+                    Some(self.dacl),
+                    None,
+                );
+                if status.0 != 0 {
+                    crate::output::warn(format!(
+                        "Could not restore the permissions of {}: {}",
+                        self.path.display(),
+                        io::Error::from_raw_os_error(status.0 as i32)
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for HeldRoot {
+    fn drop(&mut self) {
+        use windows::Win32::Foundation::{LocalFree, HLOCAL};
+
+        unsafe {
+            LocalFree(HLOCAL(self.descriptor.0));
+        }
+    }
+}
+
+/// Blocking new entries needs Windows ACLs; on Unix, revoking write permission would also
+/// stop the deletion itself
+#[cfg(not(windows))]
+pub struct RootHold;
+
+#[cfg(not(windows))]
+pub fn hold_root(_root: &Path) -> io::Result<RootHold> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "holding the root is only supported on Windows",
+    ))
+}
+
+/// Put this process in a Job Object that caps its CPU share (percent of all cores) and
+/// disk bandwidth (bytes/sec across all volumes)
+#[cfg(windows)]
+pub fn limit_job(cpu_percent: Option<u32>, io_bytes_per_sec: Option<u64>) -> io::Result<()> {
+    use windows::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
+        SetInformationJobObject, SetIoRateControlInformationJobObject,
+        JOBOBJECT_CPU_RATE_CONTROL_INFORMATION, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0,
+        JOBOBJECT_IO_RATE_CONTROL_INFORMATION, JOB_OBJECT_CPU_RATE_CONTROL_ENABLE,
+        JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP, JOB_OBJECT_IO_RATE_CONTROL_ENABLE,
+    };
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    unsafe {
+        let job = CreateJobObjectW(None, PCWSTR::null()).map_err(win_error)?;
+        let result = (|| {
+            if let Some(percent) = cpu_percent {
+                let info = JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+                    ControlFlags: JOB_OBJECT_CPU_RATE_CONTROL_ENABLE
+                        | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+                    // In hundredths of a percent
+                    Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 {
+                        CpuRate: percent.clamp(1, 100) * 100,
+                    },
+                };
+                SetInformationJobObject(
+                    job,
+                    JobObjectCpuRateControlInformation,
+                    &info as *const _ as *const _,
+                    std::mem::size_of_val(&info) as u32,
+                )
+                .map_err(win_error)?;
+            }
+            if let Some(bytes) = io_bytes_per_sec {
+                // No volume name: the cap applies to every volume
+                let info = JOBOBJECT_IO_RATE_CONTROL_INFORMATION {
+                    MaxBandwidth: bytes.min(i64::MAX as u64) as i64,
+                    ControlFlags: JOB_OBJECT_IO_RATE_CONTROL_ENABLE.0 as u32,
+                    ..Default::default()
+                };
+                let code = SetIoRateControlInformationJobObject(job, &info);
+                if code != 0 {
+                    return Err(io::Error::from_raw_os_error(code as i32));
+                }
+            }
+            AssignProcessToJobObject(job, GetCurrentProcess()).map_err(win_error)
+        })();
+        // The job lives on as long as this process is in it
+        let _ = CloseHandle(job);
+        result
+    }
+}
+
+/// Job Objects are Windows-only
+#[cfg(not(windows))]
+pub fn limit_job(_cpu_percent: Option<u32>, _io_bytes_per_sec: Option<u64>) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "resource limits need Windows Job Objects",
+    ))
+}
+
+/// Progress shown on the console window's taskbar button (`ITaskbarList3`), as Explorer's
+/// copy dialogs show theirs
+///
+/// COM objects stay on the thread that opened them, so this is neither `Send` nor `Sync`.
+#[cfg(windows)]
+pub struct TaskbarProgress {
+    list: windows::Win32::UI::Shell::ITaskbarList3,
+    window: windows::Win32::Foundation::HWND,
+}
+
+#[cfg(windows)]
+impl TaskbarProgress {
+    /// Connect to the taskbar button of this process's console window; None without one (a
+    /// service, output redirected into another program) or without a taskbar
+    pub fn open() -> Option<Self> {
+        use windows::Win32::System::Com::{
+            CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+        };
+        use windows::Win32::System::Console::GetConsoleWindow;
+        use windows::Win32::UI::Shell::{ITaskbarList3, TaskbarList};
+
+        unsafe {
+            let window = GetConsoleWindow();
+            if window.is_invalid() {
+                return None;
+            }
+            // COM stays initialized for the rest of the calling thread
+            CoInitializeEx(None, COINIT_APARTMENTTHREADED).ok().ok()?;
+            let list: ITaskbarList3 =
+                CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER).ok()?;
+            list.HrInit().ok()?;
+            Some(Self { list, window })
+        }
+    }
+
+    /// Green bar at `done` out of `total`
+    pub fn set(&self, done: u64, total: u64) {
+        use windows::Win32::UI::Shell::TBPF_NORMAL;
+        unsafe {
+            let _ = self.list.SetProgressState(self.window, TBPF_NORMAL);
+            let _ = self.list.SetProgressValue(self.window, done, total.max(1));
+        }
+    }
+
+    /// Full red bar: the run ended with failures
+    pub fn set_error(&self) {
+        use windows::Win32::UI::Shell::TBPF_ERROR;
+        unsafe {
+            let _ = self.list.SetProgressValue(self.window, 1, 1);
+            let _ = self.list.SetProgressState(self.window, TBPF_ERROR);
+        }
+    }
+
+    /// Back to a plain button
+    pub fn clear(&self) {
+        use windows::Win32::UI::Shell::TBPF_NOPROGRESS;
+        unsafe {
+            let _ = self.list.SetProgressState(self.window, TBPF_NOPROGRESS);
+        }
+    }
+}
+
+/// Taskbar buttons are a Windows feature
+#[cfg(not(windows))]
+pub struct TaskbarProgress;
+
+#[cfg(not(windows))]
+impl TaskbarProgress {
+    pub fn open() -> Option<Self> {
+        None
+    }
+
+    pub fn set(&self, _done: u64, _total: u64) {}
+
+    pub fn set_error(&self) {}
+
+    pub fn clear(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+
+    #[test]
+    fn test_delete_file() {
+        let temp_dir = std::env::temp_dir();
+        let test_file = temp_dir.join("win_rmdir_test_file.txt");
+
+        // Create test file
+        let mut file = File::create(&test_file).unwrap();
+        file.write_all(b"test").unwrap();
+        drop(file);
+
+        assert!(test_file.exists());
+
+        // Delete it
+        delete_file(&test_file).unwrap();
+
+        assert!(!test_file.exists());
+    }
+
+    #[test]
+    fn test_delete_nonexistent_file() {
+        let temp_dir = std::env::temp_dir();
+        let test_file = temp_dir.join("nonexistent_file_12345.txt");
+
+        let result = delete_file(&test_file);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_remove_dir() {
+        let temp_dir = std::env::temp_dir();
+        let test_dir = temp_dir.join("win_rmdir_test_dir");
+
+        // Create test directory
+        std::fs::create_dir(&test_dir).unwrap();
+        assert!(test_dir.exists());
+
+        // Delete it
+        remove_dir(&test_dir).unwrap();
+
+        assert!(!test_dir.exists());
+    }
+
+    #[test]
+    fn test_remove_nonexistent_dir() {
+        let temp_dir = std::env::temp_dir();
+        let test_dir = temp_dir.join("nonexistent_dir_12345");
+
+        let result = remove_dir(&test_dir);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_enumerate_skips_vanished_entries() {
+        let dir = std::env::temp_dir().join("win_rmdir_vanished_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("a"), b"a").unwrap();
+        std::fs::write(dir.join("b"), b"b").unwrap();
+
+        // The first entry listed deletes the other before it is looked at
+        let mut seen = Vec::new();
+        enumerate_entries(&dir, true, |path, _, _, _, _| {
+            for name in ["a", "b"] {
+                let _ = std::fs::remove_file(dir.join(name));
+            }
+            seen.push(path.to_path_buf());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(seen.len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_free_space() {
+        assert!(free_space(&std::env::temp_dir()).unwrap() > 0);
+    }
+
+    #[test]
+    fn test_local_path_is_not_network() {
+        assert!(!is_network_path(&std::env::temp_dir()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_processes_using_finds_working_dir() {
+        let temp = std::env::temp_dir().join("win_rmdir_processes_test");
+        std::fs::create_dir_all(&temp).unwrap();
+        let root = temp.canonicalize().unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("10")
+            .current_dir(&root)
+            .spawn()
+            .unwrap();
+
+        let found = processes_using(&root);
+        child.kill().ok();
+        child.wait().ok();
+        let process = found.iter().find(|p| p.pid == child.id()).unwrap();
+        assert_eq!(process.uses, vec![ProcessUse::WorkingDir(root.clone())]);
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0-3,8-9\n"), Some(vec![0, 1, 2, 3, 8, 9]));
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("a-b"), None);
+    }
+
+    #[test]
+    fn test_volume_id() {
+        let temp = std::env::temp_dir();
+        let id = volume_id(&temp).unwrap();
+        assert!(!id.is_empty());
+        assert_eq!(volume_id(&temp.join(".")).unwrap(), id);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_hold_root_blocks_new_entries() {
+        let temp = std::env::temp_dir().join("win_rmdir_hold_root_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        std::fs::create_dir_all(temp.join("sub")).unwrap();
+
+        let hold = hold_root(&temp).unwrap();
+        assert!(std::fs::write(temp.join("new"), b"x").is_err());
+        assert!(std::fs::write(temp.join("sub").join("new"), b"x").is_err());
+        drop(hold);
+        std::fs::write(temp.join("new"), b"x").unwrap();
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_is_within() {
+        let docs = Path::new(r"C:\Users\me\Documents");
+        assert!(is_within(Path::new(r"c:\users\ME\documents\a\b"), docs));
+        assert!(is_within(Path::new(r"\\?\C:\Users\me\Documents"), docs));
+        assert!(is_within(Path::new("C:/Users/me/Documents/x"), docs));
+        assert!(!is_within(Path::new(r"C:\Users\me\Documents2"), docs));
+        assert!(!is_within(Path::new(r"C:\Users\me"), docs));
+        assert!(!is_controlled_folder(&std::env::temp_dir()));
+    }
+
+    #[test]
+    fn test_event_data() {
+        let xml = "<Event><EventData><Data Name='Process Name'>C:\\rmbrr.exe</Data>\
+                   <Data Name='Path'>C:\\Users\\me\\Documents\\R&amp;D\\a.txt</Data>\
+                   </EventData></Event>";
+        assert_eq!(
+            event_data(xml, "Path").as_deref(),
+            Some(r"C:\Users\me\Documents\R&D\a.txt")
+        );
+        assert_eq!(event_data(xml, "Target"), None);
+    }
+
+    #[test]
+    fn test_root_pin() {
+        let temp = std::env::temp_dir().join("win_rmdir_root_pin_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        let root = temp.join("root");
+        std::fs::create_dir_all(&root).unwrap();
+
+        let pin = RootPin::open(&root).unwrap();
+        pin.verify().unwrap();
+        // Windows keeps the pinned root from being renamed; Unix notices the swap
+        if std::fs::rename(&root, temp.join("moved")).is_ok() {
+            std::fs::create_dir(&root).unwrap();
+            assert_eq!(pin.verify().unwrap_err().kind(), io::ErrorKind::InvalidData);
+            assert!(pin.remove().is_err());
+            assert!(root.exists());
+        } else {
+            pin.remove().unwrap();
+            drop(pin);
+            assert!(!root.exists());
+        }
+        assert!(RootPin::open(&temp.join("missing")).is_err());
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_hold_root_unsupported() {
+        let err = hold_root(&std::env::temp_dir()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_readonly_dir_and_file() {
+        let temp = std::env::temp_dir().join("win_rmdir_readonly_dir_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        let dir = temp.join("objects");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("pack");
+        std::fs::write(&file, b"x").unwrap();
+        for path in [&file, &dir] {
+            let mut permissions = std::fs::metadata(path).unwrap().permissions();
+            permissions.set_readonly(true);
+            std::fs::set_permissions(path, permissions).unwrap();
+        }
+
+        delete_file(&file).unwrap();
+        remove_dir(&dir).unwrap();
+        assert!(!dir.exists());
+
+        // The fallback for builds that reject FILE_DISPOSITION_IGNORE_READONLY_ATTRIBUTE
+        let other = temp.join("other");
+        std::fs::write(&other, b"x").unwrap();
+        let mut permissions = std::fs::metadata(&other).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&other, permissions).unwrap();
+        let wide = path_to_wide(&other);
+        assert!(unsafe { clear_readonly(&wide) });
+        assert!(!unsafe { clear_readonly(&wide) });
+        assert!(!std::fs::metadata(&other).unwrap().permissions().readonly());
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_native_delete() {
+        let temp = std::env::temp_dir().join("win_rmdir_native_delete_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        std::fs::create_dir_all(&temp).unwrap();
+        let file = temp.join("readonly");
+        std::fs::write(&file, b"x").unwrap();
+        let mut permissions = std::fs::metadata(&file).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&file, permissions).unwrap();
+
+        let api = nt_api().expect("ntdll exports the native entry points");
+        let flags = FILE_DISPOSITION_DELETE.0
+            | FILE_DISPOSITION_POSIX_SEMANTICS.0
+            | FILE_DISPOSITION_IGNORE_READONLY_ATTRIBUTE.0;
+        let result = unsafe { native_delete(api, &path_to_wide(&file), flags) };
+        result
+            .expect("absolute paths take the native path")
+            .unwrap();
+        assert!(!file.exists());
+        let missing = unsafe { native_delete(api, &path_to_wide(&file), flags) };
+        assert_eq!(
+            missing.unwrap().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        // Relative paths are left to the Win32 calls
+        let relative: Vec<u16> = "relative\0".encode_utf16().collect();
+        assert!(unsafe { native_delete(api, &relative, flags) }.is_none());
+
+        let dir = path_to_wide(&temp);
+        let flags = FILE_DISPOSITION_DELETE.0 | FILE_DISPOSITION_POSIX_SEMANTICS.0;
+        unsafe { native_delete(api, &dir, flags) }.unwrap().unwrap();
+        assert!(!temp.exists());
+    }
+
+    /// Before/after timing of the link path: `cargo test -p rmbrr-core --release
+    /// delete_link_timing -- --ignored --nocapture`
+    #[cfg(windows)]
+    #[test]
+    #[ignore = "timing comparison; run by hand"]
+    fn test_delete_link_timing() {
+        const LINKS: usize = 2000;
+        let temp = std::env::temp_dir().join("win_rmdir_link_timing_test");
+        let _ = std::fs::remove_dir_all(&temp);
+        let store = temp.join("store");
+        std::fs::create_dir_all(store.join("pkg")).unwrap();
+        std::fs::write(store.join("pkg").join("index.js"), b"x").unwrap();
+
+        let farm = |name: &str| {
+            let dir = temp.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            let links: Vec<_> = (0..LINKS).map(|i| dir.join(format!("dep{}", i))).collect();
+            for link in &links {
+                let status = std::process::Command::new("cmd")
+                    .arg("/c")
+                    .arg("mklink")
+                    .arg("/J")
+                    .arg(link)
+                    .arg(store.join("pkg"))
+                    .stdout(std::process::Stdio::null())
+                    .status()
+                    .unwrap();
+                assert!(status.success());
+            }
+            links
+        };
+        let time = |links: Vec<std::path::PathBuf>, delete: fn(&Path) -> io::Result<()>| {
+            let start = std::time::Instant::now();
+            for link in &links {
+                delete(link).unwrap();
+            }
+            start.elapsed()
+        };
+
+        let before = time(farm("before"), delete_file);
+        let after = time(farm("after"), delete_link);
+        println!(
+            "{} junctions: delete_file {:?}, delete_link {:?} ({:.2}x)",
+            LINKS,
+            before,
+            after,
+            before.as_secs_f64() / after.as_secs_f64()
+        );
+        assert!(store.join("pkg").join("index.js").exists());
+
+        std::fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_native_delete_unavailable() {
+        assert!(!enable_native_delete());
+    }
+}
@@ -0,0 +1,1538 @@
+// Worker thread deletion logic
+
+use crate::broker::Broker;
+use crate::error::{Decision, ErrorStrategy, FailedItem, IgnoredErrors};
+use crate::events::{self, Event, EventSender};
+use crate::filter::Filter;
+use crate::manifest::Manifest;
+use crate::output;
+use crate::progress::Progress;
+use crate::retry::{self, RetryPolicy};
+use crate::trace::{self, Tracer};
+use crate::tuner::ThreadTuner;
+use crate::winapi::{
+    self, delete_file, delete_link, enumerate_entries, enumerate_files, remove_dir,
+};
+use crossbeam_channel::{Receiver, Sender};
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle, Scope, ScopedJoinHandle};
+use std::time::Instant;
+
+/// Files per batch when deleting the contents of one directory
+const CHUNK_SIZE: usize = 4096;
+/// Helper threads a directory gets once it holds more than one batch of files
+const CHUNK_HELPERS: usize = 3;
+/// Times a directory that gained entries after the scan is cleared again by default
+pub const DEFAULT_RESCANS: u32 = 3;
+
+/// Configuration for worker error handling
+#[derive(Clone)]
+pub struct WorkerConfig {
+    /// If true, print verbose error messages
+    pub verbose: bool,
+    /// Whether to keep going after a failed deletion
+    pub errors: ErrorStrategy,
+    /// Live progress state to update, if anything is displaying it
+    pub progress: Option<Arc<Progress>>,
+    /// Restricts which files are deleted; directories are then kept unless emptied
+    pub filter: Option<Arc<Filter>>,
+    /// Per-entry events (deleted files and dirs, failures), if anything is listening
+    pub events: Option<EventSender>,
+    /// Records per-directory, queue-wait and retry spans when set
+    pub trace: Option<Arc<Tracer>>,
+    /// Retries for deletions that fail transiently
+    pub retry: RetryPolicy,
+    /// Parks workers beyond an adaptively tuned count (`--threads auto`)
+    pub tuner: Option<Arc<ThreadTuner>>,
+    /// Keep workers on the processors of this NUMA node, near the storage they delete from
+    pub numa_node: Option<u16>,
+    /// On access denied, take ownership of the entry and try once more
+    /// (needs [`winapi::enable_take_ownership`])
+    pub take_ownership: bool,
+    /// Each file is recorded here before it is deleted; files that cannot be recorded stay
+    pub manifest: Option<Arc<Manifest>>,
+    /// When a directory turns out not to be empty (entries were created after the scan),
+    /// delete the newcomers and try again up to this many times before it counts as failed
+    pub rescans: u32,
+    /// Starts the worker threads instead of `std::thread`
+    pub spawner: Option<Spawner>,
+    /// Delete each directory's files in path order on the worker itself, without helper
+    /// threads, so runs are reproducible
+    pub serial: bool,
+    /// Remove the directories recorded here by file ID, and find those renamed since the
+    /// scan by it
+    pub dir_ids: Option<Arc<winapi::DirIds>>,
+    /// The roots held since the safety checks; each is removed through its pin
+    pub root_pins: Vec<Arc<winapi::RootPin>>,
+    /// Failures with these error codes are never retried; they are counted apart and leave
+    /// their entries (and the directories above them) in place without failing the run
+    pub ignore_errors: IgnoredErrors,
+    /// The directories come from a saved plan, not this run's scan: each is checked to still
+    /// be a directory (not a link put in its place) before its files are listed
+    pub planned: bool,
+}
+
+/// Body of one worker, handed to a [`Spawner`]
+pub type WorkerBody = Box<dyn FnOnce() + Send>;
+
+/// Runs workers on threads of the embedder's choosing (a pool, or threads with its own naming,
+/// priorities and panic handling) instead of new `std::thread`s
+///
+/// It is called once per worker with a suggested thread name and the worker's body, which
+/// must run to completion on its own thread: workers block on a channel between directories.
+/// A panic in the body propagates to whatever runs it after the worker is marked finished.
+/// The short-lived helpers that split up very large directories stay scoped `std::thread`s.
+#[derive(Clone)]
+pub struct Spawner(Arc<dyn Fn(String, WorkerBody) -> io::Result<()> + Send + Sync>);
+
+impl Spawner {
+    pub fn new<F>(spawn: F) -> Self
+    where
+        F: Fn(String, WorkerBody) -> io::Result<()> + Send + Sync + 'static,
+    {
+        Self(Arc::new(spawn))
+    }
+}
+
+impl fmt::Debug for Spawner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Spawner")
+    }
+}
+
+/// A running worker, joined the same way whether it got a `std::thread` or a [`Spawner`]
+pub struct WorkerHandle(HandleKind);
+
+enum HandleKind {
+    Thread(JoinHandle<()>),
+    /// Receives whether the body panicked once it has finished
+    Spawned(Receiver<bool>),
+}
+
+/// Reports the end of a spawned body, by return or by panic
+struct Finished(Sender<bool>);
+
+impl Drop for Finished {
+    fn drop(&mut self) {
+        self.0.send(thread::panicking()).ok();
+    }
+}
+
+impl WorkerHandle {
+    /// Wait for the worker to finish; `Err` if it panicked
+    pub fn join(self) -> thread::Result<()> {
+        match self.0 {
+            HandleKind::Thread(handle) => handle.join(),
+            HandleKind::Spawned(finished) => match finished.recv() {
+                Ok(true) => Err(Box::new("worker panicked")),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        match &self.0 {
+            HandleKind::Thread(handle) => handle.is_finished(),
+            HandleKind::Spawned(finished) => !finished.is_empty(),
+        }
+    }
+}
+
+/// Start a worker on `spawner`, or on a new thread named `name` without one
+fn start_worker<F>(name: String, spawner: Option<&Spawner>, body: F) -> WorkerHandle
+where
+    F: FnOnce() + Send + 'static,
+{
+    let Some(spawner) = spawner else {
+        let handle = thread::Builder::new()
+            .name(name)
+            .spawn(body)
+            .expect("Failed to spawn worker thread");
+        return WorkerHandle(HandleKind::Thread(handle));
+    };
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    let finished = Finished(tx);
+    (spawner.0)(
+        name,
+        Box::new(move || {
+            let _finished = finished;
+            body()
+        }),
+    )
+    .expect("Failed to spawn worker thread");
+    WorkerHandle(HandleKind::Spawned(rx))
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            verbose: false,
+            errors: ErrorStrategy::Continue,
+            progress: None,
+            filter: None,
+            events: None,
+            trace: None,
+            retry: RetryPolicy::default(),
+            tuner: None,
+            numa_node: None,
+            take_ownership: false,
+            manifest: None,
+            rescans: DEFAULT_RESCANS,
+            spawner: None,
+            serial: false,
+            dir_ids: None,
+            root_pins: Vec::new(),
+            ignore_errors: IgnoredErrors::default(),
+            planned: false,
+        }
+    }
+}
+
+/// Shared error tracking state
+pub struct ErrorTracker {
+    failures: Mutex<Vec<FailedItem>>,
+    /// The failure that stopped the deletion under `ErrorStrategy::FailFast`/`Prompt`
+    aborted_by: Mutex<Option<FailedItem>>,
+    /// Entries another process deleted before we got to them
+    gone: AtomicUsize,
+    /// Failures of a class the run ignores
+    ignored: Mutex<Vec<FailedItem>>,
+}
+
+impl ErrorTracker {
+    pub fn new() -> Self {
+        Self {
+            failures: Mutex::new(Vec::new()),
+            aborted_by: Mutex::new(None),
+            gone: AtomicUsize::new(0),
+            ignored: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record an entry that was already gone; that counts as deleted, not as a failure
+    pub fn record_gone(&self) {
+        self.gone.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of entries that were already gone when we tried to delete them
+    pub fn gone_count(&self) -> usize {
+        self.gone.load(Ordering::Relaxed)
+    }
+
+    /// Record that `item` stopped the deletion; only the first such failure is kept
+    pub fn abort(&self, item: FailedItem) {
+        self.aborted_by.lock().unwrap().get_or_insert(item);
+    }
+
+    /// The failure that stopped the deletion, if the error strategy stopped it
+    pub fn aborted_by(&self) -> Option<FailedItem> {
+        self.aborted_by.lock().unwrap().clone()
+    }
+
+    pub fn record_failure(&self, item: FailedItem) {
+        self.failures.lock().unwrap().push(item);
+    }
+
+    pub fn get_failures(&self) -> Vec<FailedItem> {
+        self.failures.lock().unwrap().clone()
+    }
+
+    pub fn failure_count(&self) -> usize {
+        self.failures.lock().unwrap().len()
+    }
+
+    /// Record a failure of an ignored class; it is counted, but not as a failure
+    pub fn record_ignored(&self, item: FailedItem) {
+        self.ignored.lock().unwrap().push(item);
+    }
+
+    /// Failures of an ignored class
+    pub fn get_ignored(&self) -> Vec<FailedItem> {
+        self.ignored.lock().unwrap().clone()
+    }
+
+    pub fn ignored_count(&self) -> usize {
+        self.ignored.lock().unwrap().len()
+    }
+
+    /// Files left in place by failures, ignored ones included; the deleted files are the
+    /// scanned ones less these
+    pub fn files_not_deleted(&self) -> usize {
+        let files = |items: &Mutex<Vec<FailedItem>>| {
+            items.lock().unwrap().iter().filter(|f| !f.is_dir).count()
+        };
+        files(&self.failures) + files(&self.ignored)
+    }
+
+    /// Whether an entry directly in `dir` failed, ignored failures included
+    pub fn has_failure_in(&self, dir: &Path) -> bool {
+        let in_dir = |f: &FailedItem| f.path.parent() == Some(dir);
+        self.failures.lock().unwrap().iter().any(in_dir)
+            || self.ignored.lock().unwrap().iter().any(in_dir)
+    }
+
+    /// Whether entries directly in `dir` failed, all of them with ignored errors
+    fn only_ignored_in(&self, dir: &Path) -> bool {
+        let in_dir = |f: &FailedItem| f.path.parent() == Some(dir);
+        self.ignored.lock().unwrap().iter().any(in_dir)
+            && !self.failures.lock().unwrap().iter().any(in_dir)
+    }
+
+    /// Get the most recent `n` failures, oldest first
+    pub fn recent(&self, n: usize) -> Vec<FailedItem> {
+        let failures = self.failures.lock().unwrap();
+        let start = failures.len().saturating_sub(n);
+        failures[start..].to_vec()
+    }
+}
+
+impl Default for ErrorTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Spawn a pool of worker threads to process deletion work
+///
+/// Returns a vector of join handles that can be used to wait for all workers to complete.
+/// Workers will exit when the channel is closed (no more work available).
+pub fn spawn_workers(
+    count: usize,
+    rx: Receiver<PathBuf>,
+    broker: Arc<Broker>,
+    config: WorkerConfig,
+    error_tracker: Arc<ErrorTracker>,
+) -> Vec<WorkerHandle> {
+    start_planned(plan_workers(count, None, rx), broker, config, error_tracker)
+}
+
+/// Spawn separate pools for the two halves of each directory's deletion
+///
+/// `file_workers` threads delete the files of directories the broker releases and hand each
+/// directory on; `dir_workers` threads remove the emptied directories, which releases their
+/// parents. A flood of file deletions then never delays unlocking parent directories.
+/// Dir workers are numbered after file workers in progress and trace output.
+pub fn spawn_split_workers(
+    file_workers: usize,
+    dir_workers: usize,
+    rx: Receiver<PathBuf>,
+    broker: Arc<Broker>,
+    config: WorkerConfig,
+    error_tracker: Arc<ErrorTracker>,
+) -> Vec<WorkerHandle> {
+    let plan = plan_workers(file_workers, Some(dir_workers), rx);
+    start_planned(plan, broker, config, error_tracker)
+}
+
+/// Like [`spawn_workers`], or [`spawn_split_workers`] with `dir_workers`, but on threads of
+/// `scope` that borrow the broker and error tracker instead of sharing them through `Arc`s
+///
+/// `config.spawner` is not used: its workers could outlive the borrows.
+pub fn spawn_scoped_workers<'scope, 'env>(
+    scope: &'scope Scope<'scope, 'env>,
+    count: usize,
+    dir_workers: Option<usize>,
+    rx: Receiver<PathBuf>,
+    broker: &'env Broker,
+    config: WorkerConfig,
+    error_tracker: &'env ErrorTracker,
+) -> Vec<ScopedJoinHandle<'scope, ()>> {
+    plan_workers(count, dir_workers, rx)
+        .into_iter()
+        .map(|planned| {
+            let config = config.clone();
+            thread::Builder::new()
+                .name(planned.name())
+                .spawn_scoped(scope, move || {
+                    planned.run(broker, config, error_tracker);
+                })
+                .expect("Failed to spawn worker thread")
+        })
+        .collect()
+}
+
+/// One worker to start: what it does and where it gets and hands on directories
+struct PlannedWorker {
+    id: usize,
+    stage: Stage,
+    rx: Receiver<PathBuf>,
+    next: Option<Sender<PathBuf>>,
+}
+
+impl PlannedWorker {
+    fn name(&self) -> String {
+        match self.stage {
+            Stage::Remove => format!("rmdir-worker-{}", self.id),
+            _ => format!("worker-{}", self.id),
+        }
+    }
+
+    fn run(self, broker: &Broker, config: WorkerConfig, error_tracker: &ErrorTracker) {
+        let Self {
+            id,
+            stage,
+            rx,
+            next,
+        } = self;
+        run_worker(id, stage, rx, next, broker, config, error_tracker)
+    }
+}
+
+/// `count` workers doing whole directories, or with `dir_workers` that many file workers
+/// handing their directories to a pool of that many directory removers
+fn plan_workers(
+    count: usize,
+    dir_workers: Option<usize>,
+    rx: Receiver<PathBuf>,
+) -> Vec<PlannedWorker> {
+    let Some(dir_workers) = dir_workers else {
+        return (0..count)
+            .map(|id| PlannedWorker {
+                id,
+                stage: Stage::All,
+                rx: rx.clone(),
+                next: None,
+            })
+            .collect();
+    };
+    // Dir workers exit once every file worker has exited and dropped its sender
+    let (emptied_tx, emptied_rx) = crossbeam_channel::unbounded();
+    let file_workers = count.max(1);
+    let mut plan: Vec<_> = (0..file_workers)
+        .map(|id| PlannedWorker {
+            id,
+            stage: Stage::Files,
+            rx: rx.clone(),
+            next: Some(emptied_tx.clone()),
+        })
+        .collect();
+    plan.extend((0..dir_workers.max(1)).map(|i| PlannedWorker {
+        id: file_workers + i,
+        stage: Stage::Remove,
+        rx: emptied_rx.clone(),
+        next: None,
+    }));
+    plan
+}
+
+fn start_planned(
+    plan: Vec<PlannedWorker>,
+    broker: Arc<Broker>,
+    config: WorkerConfig,
+    error_tracker: Arc<ErrorTracker>,
+) -> Vec<WorkerHandle> {
+    plan.into_iter()
+        .map(|planned| {
+            let broker = broker.clone();
+            let config = config.clone();
+            let error_tracker = error_tracker.clone();
+            let spawner = config.spawner.clone();
+            start_worker(planned.name(), spawner.as_ref(), move || {
+                planned.run(&broker, config, &error_tracker)
+            })
+        })
+        .collect()
+}
+
+/// Which part of a directory's deletion a worker performs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// Files, then the directory itself
+    All,
+    /// Only the files; the directory goes on to a `Remove` worker
+    Files,
+    /// Only the (emptied) directory
+    Remove,
+}
+
+pub fn worker_thread(
+    id: usize,
+    rx: Receiver<PathBuf>,
+    broker: Arc<Broker>,
+    config: WorkerConfig,
+    error_tracker: Arc<ErrorTracker>,
+) {
+    run_worker(id, Stage::All, rx, None, &broker, config, &error_tracker)
+}
+
+fn run_worker(
+    id: usize,
+    stage: Stage,
+    rx: Receiver<PathBuf>,
+    next: Option<Sender<PathBuf>>,
+    broker: &Broker,
+    config: WorkerConfig,
+    error_tracker: &ErrorTracker,
+) {
+    pin_to_numa_node(&config);
+    let thread = trace::worker_thread_id(id);
+    loop {
+        let waiting = Instant::now();
+        // Only deletion workers are tuned; directory removal is cheap and unblocks parents
+        if let Some(tuner) = config.tuner.as_ref().filter(|_| stage != Stage::Remove) {
+            if !tuner.wait_turn(id, broker) {
+                break;
+            }
+        }
+        let Ok(dir) = rx.recv() else {
+            break;
+        };
+        if let Some(ref tracer) = config.trace {
+            tracer.record("wait", "wait", thread, waiting);
+        }
+        // Directories already queued are left alone after cancellation
+        if broker.is_cancelled() {
+            break;
+        }
+        let started = Instant::now();
+
+        if let Some(ref progress) = config.progress {
+            progress.set_active(id, &dir);
+        }
+        if config.events.is_some() && stage != Stage::Remove {
+            let path = dir.clone();
+            events::emit(&config.events, Event::DirStarted { worker: id, path });
+        }
+
+        // A panic on one directory must not take down the run
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| match stage {
+            Stage::All => process_dir(dir.clone(), id, broker, &config, error_tracker),
+            Stage::Files => {
+                if delete_dir_files(&dir, id, broker, &config, error_tracker) {
+                    if let Some(ref next) = next {
+                        next.send(dir.clone()).ok();
+                    }
+                }
+            }
+            Stage::Remove => remove_emptied_dir(dir.clone(), id, broker, &config, error_tracker),
+        }));
+        if let Err(payload) = outcome {
+            let msg = panic_message(payload.as_ref());
+            output::warn((panic_texts().panicked)(id, &dir, &msg));
+            let item = FailedItem {
+                path: dir.clone(),
+                error: format!("{}: {}", PANICKED, msg),
+                is_dir: true,
+                os_error: None,
+            };
+            if record_failure(item, &config, error_tracker) == Decision::Abort {
+                broker.cancel();
+            }
+            broker.mark_failed(dir.clone());
+        }
+
+        if let Some(ref tracer) = config.trace {
+            let category = if stage == Stage::Remove {
+                "rmdir"
+            } else {
+                "delete"
+            };
+            tracer.record(category, dir.display().to_string(), thread, started);
+        }
+        if let Some(ref progress) = config.progress {
+            progress.set_idle(id);
+        }
+    }
+}
+
+/// Delete a directory's files, then the directory itself, and report the result to the broker
+fn process_dir(
+    dir: PathBuf,
+    worker: usize,
+    broker: &Broker,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) {
+    if delete_dir_files(&dir, worker, broker, config, error_tracker) {
+        remove_emptied_dir(dir, worker, broker, config, error_tracker);
+    }
+}
+
+/// Delete the files directly in `dir`; false if the error strategy stopped the run
+fn delete_dir_files(
+    dir: &Path,
+    worker: usize,
+    broker: &Broker,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) -> bool {
+    // Listing a link would delete the files of whatever it points to
+    let mut listed = match config.planned.then(|| dir.symlink_metadata()) {
+        Some(Ok(metadata)) if !metadata.is_dir() => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "no longer a directory since the plan was saved",
+        )),
+        Some(Err(e)) => Err(e),
+        _ => delete_files_in_dir(dir, worker, config, error_tracker),
+    };
+    // Renamed since the scan: its ID tells where it went
+    if listed
+        .as_ref()
+        .is_err_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+    {
+        if let Some(moved) = config
+            .dir_ids
+            .as_ref()
+            .and_then(|ids| ids.current_path(dir))
+        {
+            listed = delete_files_in_dir(&moved, worker, config, error_tracker);
+        }
+    }
+    if let Err(e) = listed {
+        // The error strategy stopped the run on one of this directory's files
+        if error_tracker.aborted_by().is_some() {
+            broker.cancel();
+            return false;
+        }
+        // Another process deleted the directory; removing it will find it gone too
+        if e.kind() == std::io::ErrorKind::NotFound {
+            return true;
+        }
+        let msg = format!("{}", e);
+        if config.verbose {
+            output::log(
+                "Failed to delete files in",
+                format!("Failed to delete files in {}: {}", dir.display(), msg),
+            );
+        }
+    }
+    true
+}
+
+/// Remove a directory whose files are gone (or keep it) and report the result to the broker
+fn remove_emptied_dir(
+    dir: PathBuf,
+    worker: usize,
+    broker: &Broker,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) {
+    let selective = config.filter.as_ref().is_some_and(|f| f.is_selective());
+    let keep_root = config
+        .filter
+        .as_ref()
+        .is_some_and(|f| f.keep_root && f.is_root(&dir));
+    // The root of a contents-only deletion stays, even once emptied
+    if keep_root {
+        dir_done(dir, true, broker, config);
+    } else {
+        // A filter's kept entries leave directories non-empty for good
+        let retryable = |e: &std::io::Error| {
+            retry::is_transient(e)
+                && !(selective && e.kind() == std::io::ErrorKind::DirectoryNotEmpty)
+        };
+        let remove = |dir: &Path| remove_recorded_dir(dir, config);
+        let mut result = with_retries(&dir, worker, config, retryable, remove);
+        let not_empty = |result: &std::io::Result<()>| {
+            result
+                .as_ref()
+                .is_err_and(|e| e.kind() == std::io::ErrorKind::DirectoryNotEmpty)
+        };
+        // Entries that already failed to delete are not newcomers; going over them again
+        // would only repeat their failures
+        let mut rescans = 0;
+        while !selective
+            && rescans < config.rescans
+            && not_empty(&result)
+            && !error_tracker.has_failure_in(&dir)
+        {
+            rescans += 1;
+            if config.verbose {
+                output::log(
+                    "gained entries after the scan",
+                    format!(
+                        "{} gained entries after the scan; deleting them",
+                        dir.display()
+                    ),
+                );
+            }
+            delete_newcomers(&dir, worker, config, error_tracker);
+            result = with_retries(&dir, worker, config, retryable, remove);
+        }
+        loop {
+            match result {
+                Ok(()) => dir_done(dir, false, broker, config),
+                // Another process (or a second rmbrr) deleted it first
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    error_tracker.record_gone();
+                    dir_done(dir, false, broker, config);
+                }
+                // Still holds entries the filter keeps
+                Err(e) if selective && e.kind() == std::io::ErrorKind::DirectoryNotEmpty => {
+                    dir_done(dir, true, broker, config)
+                }
+                Err(e) => {
+                    let msg = format!("{}", e);
+                    let item = FailedItem::from_io(dir.clone(), &e, true);
+                    let ignored = is_ignored(&item, config, error_tracker);
+                    match record_failure(item, config, error_tracker) {
+                        Decision::Retry => {
+                            result = with_retries(&dir, worker, config, retryable, remove);
+                            continue;
+                        }
+                        Decision::Abort => broker.cancel(),
+                        Decision::Skip => {}
+                    }
+
+                    if config.verbose && ignored {
+                        output::log(
+                            "Ignored failure to remove",
+                            format!("Ignored failure to remove {}: {}", dir.display(), msg),
+                        );
+                    } else if config.verbose {
+                        output::log(
+                            "Failed to remove",
+                            format!("Failed to remove {}: {}", dir.display(), msg),
+                        );
+                    }
+                    broker.mark_failed(dir);
+                }
+            }
+            break;
+        }
+    }
+}
+
+/// Remove `dir` through the root pin or by its recorded file ID if it has one, else by path
+fn remove_recorded_dir(dir: &Path, config: &WorkerConfig) -> io::Result<()> {
+    if let Some(pin) = config.root_pins.iter().find(|pin| pin.path() == dir) {
+        return pin.remove();
+    }
+    match config.dir_ids.as_ref().and_then(|ids| ids.remove(dir)) {
+        Some(result) => result,
+        None => remove_dir(dir),
+    }
+}
+
+/// Apply the configured NUMA placement to the calling thread
+fn pin_to_numa_node(config: &WorkerConfig) {
+    if let Some(node) = config.numa_node {
+        // Callers check the node exists up front; placement is only an optimization
+        winapi::pin_to_numa_node(node).ok();
+    }
+}
+
+/// Delete what appeared in `dir` after the scan: its files, then new subdirectories with
+/// their contents. Failures are recorded as usual; the caller's retry of `dir` tells whether
+/// it worked.
+fn delete_newcomers(
+    dir: &Path,
+    worker: usize,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) {
+    let mut subdirs = Vec::new();
+    // Links are never reported as directories, so this stays inside the tree
+    let listed = enumerate_files(dir, |path, is_dir| {
+        if is_dir {
+            subdirs.push(path.to_path_buf());
+        }
+        Ok(())
+    });
+    if listed.is_err() {
+        return;
+    }
+    delete_files_in_dir(dir, worker, config, error_tracker).ok();
+    for subdir in subdirs {
+        delete_newcomers(&subdir, worker, config, error_tracker);
+        while let Err(e) = with_retries(&subdir, worker, config, retry::is_transient, remove_dir) {
+            let item = FailedItem::from_io(subdir.clone(), &e, true);
+            if record_failure(item, config, error_tracker) != Decision::Retry {
+                break;
+            }
+        }
+    }
+}
+
+/// Run a deletion, retrying failures `retryable` accepts per the configured retry policy,
+/// then falling back to taking ownership if access is still denied
+fn with_retries(
+    path: &Path,
+    worker: usize,
+    config: &WorkerConfig,
+    retryable: impl Fn(&std::io::Error) -> bool,
+    op: impl Fn(&Path) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    match retry_transient(path, worker, config, retryable, &op) {
+        Err(e)
+            if config.take_ownership
+                && e.kind() == std::io::ErrorKind::PermissionDenied
+                && !config.ignore_errors.contains(e.raw_os_error()) =>
+        {
+            // Report the original denial if ownership cannot be taken either
+            winapi::take_ownership(path).map_err(|_| e)?;
+            op(path)
+        }
+        result => result,
+    }
+}
+
+fn retry_transient(
+    path: &Path,
+    worker: usize,
+    config: &WorkerConfig,
+    retryable: impl Fn(&std::io::Error) -> bool,
+    op: impl Fn(&Path) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let mut attempt = 0;
+    loop {
+        match op(path) {
+            Err(e)
+                if attempt < config.retry.retries
+                    && retryable(&e)
+                    && !config.ignore_errors.contains(e.raw_os_error()) =>
+            {
+                let waiting = Instant::now();
+                thread::sleep(config.retry.delay(attempt));
+                attempt += 1;
+                if let Some(ref tracer) = config.trace {
+                    let name = format!("retry {} ({})", path.display(), e);
+                    tracer.record("retry", name, trace::worker_thread_id(worker), waiting);
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Report a handled directory to the broker and any event listener
+fn dir_done(dir: PathBuf, kept: bool, broker: &Broker, config: &WorkerConfig) {
+    if let Some(ref tuner) = config.tuner {
+        tuner.record();
+    }
+    if config.events.is_some() {
+        let path = dir.clone();
+        events::emit(&config.events, Event::DirDone { path, kept });
+    }
+    if kept {
+        broker.mark_kept(dir);
+    } else {
+        broker.mark_complete(dir);
+    }
+}
+
+/// Record a failure unless the error strategy asks to retry it; returns the strategy's
+/// decision
+/// Whether `item` failed with an error of a class the run ignores
+fn is_ignored(item: &FailedItem, config: &WorkerConfig, error_tracker: &ErrorTracker) -> bool {
+    // A directory kept only by ignored residue is residue too
+    config.ignore_errors.contains(item.os_error)
+        || (item.is_dir
+            && !config.ignore_errors.is_empty()
+            && error_tracker.only_ignored_in(&item.path))
+}
+
+fn record_failure(
+    item: FailedItem,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) -> Decision {
+    if is_ignored(&item, config, error_tracker) {
+        error_tracker.record_ignored(item);
+        return Decision::Skip;
+    }
+    // A directory kept by failures already decided on follows from them; no need to ask
+    let decision = if item.is_dir && error_tracker.has_failure_in(&item.path) {
+        Decision::Skip
+    } else {
+        config.errors.decide(&item)
+    };
+    // Running a directory that panicked again would only panic again
+    let decision = if decision == Decision::Retry && item.error.starts_with(PANICKED) {
+        output::warn((panic_texts().not_retrying)(&item.path));
+        Decision::Skip
+    } else {
+        decision
+    };
+    match decision {
+        Decision::Retry => return decision,
+        Decision::Abort => error_tracker.abort(item.clone()),
+        Decision::Skip => {}
+    }
+    if config.events.is_some() {
+        events::emit(&config.events, Event::Error(item.clone()));
+    }
+    error_tracker.record_failure(item);
+    decision
+}
+
+/// Start of the error recorded for a directory whose worker panicked
+const PANICKED: &str = "worker panicked";
+
+/// Text of the warnings about a panicked worker, e.g. translated (see [`set_panic_texts`])
+#[derive(Clone, Copy)]
+pub struct PanicTexts {
+    /// From the worker number, its directory and the panic message
+    pub panicked: fn(usize, &Path, &str) -> String,
+    /// From the directory that is skipped instead of retried
+    pub not_retrying: fn(&Path) -> String,
+}
+
+static PANIC_TEXTS: OnceLock<PanicTexts> = OnceLock::new();
+
+/// Replace the English warnings about panicked workers; only the first call takes effect
+pub fn set_panic_texts(texts: PanicTexts) {
+    PANIC_TEXTS.set(texts).ok();
+}
+
+fn panic_texts() -> PanicTexts {
+    PANIC_TEXTS.get().copied().unwrap_or(PanicTexts {
+        panicked: |id, dir, msg| format!("Worker {} panicked on {}: {}", id, dir.display(), msg),
+        not_retrying: |dir| {
+            format!(
+                "Not retrying {}: its worker panicked, so it is skipped",
+                dir.display()
+            )
+        },
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+fn delete_files_in_dir(
+    dir: &Path,
+    worker: usize,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) -> std::io::Result<()> {
+    if config.serial {
+        return delete_files_sorted(dir, worker, config, error_tracker);
+    }
+    delete_files_chunked(dir, worker, config, error_tracker, CHUNK_SIZE)
+}
+
+/// Delete the files in `dir` one by one in path order
+fn delete_files_sorted(
+    dir: &Path,
+    worker: usize,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) -> std::io::Result<()> {
+    let mut files = Vec::new();
+    enumerate_entries(dir, false, |path, is_dir, _, link, _| {
+        let selected = config
+            .filter
+            .as_ref()
+            .is_none_or(|f| f.should_delete_file(path));
+        if !is_dir && selected {
+            files.push((path.to_path_buf(), link.is_some()));
+        }
+        Ok(())
+    })?;
+    files.sort();
+    let aborted = AtomicBool::new(false);
+    delete_chunk(&files, worker, config, error_tracker, &aborted);
+    if aborted.load(Ordering::Relaxed) {
+        return Err(std::io::ErrorKind::Interrupted.into());
+    }
+    Ok(())
+}
+
+/// Delete the files in `dir` in batches of `chunk_size`
+///
+/// Once a directory proves bigger than one batch, full batches go to a few helper threads
+/// while enumeration carries on, so one huge directory doesn't serialize the run.
+fn delete_files_chunked(
+    dir: &Path,
+    worker: usize,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+    chunk_size: usize,
+) -> std::io::Result<()> {
+    let aborted = AtomicBool::new(false);
+    let result = thread::scope(|scope| {
+        let aborted = &aborted;
+        let mut chunk = Vec::new();
+        let mut helpers: Option<Sender<Vec<(PathBuf, bool)>>> = None;
+        let result = enumerate_entries(dir, false, |path, is_dir, _, link, _| {
+            if aborted.load(Ordering::Relaxed) {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            let selected = config
+                .filter
+                .as_ref()
+                .is_none_or(|f| f.should_delete_file(path));
+            if is_dir || !selected {
+                return Ok(());
+            }
+            chunk.push((path.to_path_buf(), link.is_some()));
+            if chunk.len() >= chunk_size {
+                let tx = helpers.get_or_insert_with(|| {
+                    let (tx, rx) = crossbeam_channel::bounded::<Vec<_>>(CHUNK_HELPERS);
+                    for _ in 0..CHUNK_HELPERS {
+                        let rx = rx.clone();
+                        scope.spawn(move || {
+                            pin_to_numa_node(config);
+                            for chunk in rx {
+                                delete_chunk(&chunk, worker, config, error_tracker, aborted);
+                            }
+                        });
+                    }
+                    tx
+                });
+                tx.send(std::mem::take(&mut chunk)).ok();
+            }
+            Ok(())
+        });
+        drop(helpers);
+        // The last partial batch, which for most directories is all of them
+        if result.is_ok() {
+            delete_chunk(&chunk, worker, config, error_tracker, aborted);
+        }
+        result
+    });
+    if aborted.load(Ordering::Relaxed) {
+        return Err(std::io::ErrorKind::Interrupted.into());
+    }
+    result
+}
+
+/// Delete files picked outside any scanned tree (such as `--until-free` candidates) the way
+/// workers delete theirs: recorded in the manifest, retried, and failures ignored or decided
+/// on by the error strategy; false if it stopped the run
+pub fn delete_files(
+    files: &[PathBuf],
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+) -> bool {
+    let files: Vec<_> = files
+        .iter()
+        .map(|path| {
+            let link = path
+                .symlink_metadata()
+                .is_ok_and(|m| m.file_type().is_symlink());
+            (path.clone(), link)
+        })
+        .collect();
+    let aborted = AtomicBool::new(false);
+    delete_chunk(&files, 0, config, error_tracker, &aborted);
+    !aborted.load(Ordering::Relaxed)
+}
+
+/// Delete a batch of files, each with whether it is a link; sets `aborted` if the error
+/// strategy stops the run
+///
+/// Links take [`winapi::delete_link`], which is cheaper and never touches their targets, so
+/// trees made mostly of links (pnpm stores, Nix-style profiles) go quickly.
+fn delete_chunk(
+    files: &[(PathBuf, bool)],
+    worker: usize,
+    config: &WorkerConfig,
+    error_tracker: &ErrorTracker,
+    aborted: &AtomicBool,
+) {
+    for (path, is_link) in files {
+        if aborted.load(Ordering::Relaxed) {
+            return;
+        }
+        let recorded = match config.manifest {
+            Some(ref manifest) => manifest.record(path),
+            None => Ok(()),
+        };
+        let delete = if *is_link { delete_link } else { delete_file };
+        let mut deleted =
+            recorded.and_then(|()| with_retries(path, worker, config, retry::is_transient, delete));
+        // Goes round again only when the error strategy asks for a retry
+        loop {
+            if deleted
+                .as_ref()
+                .is_err_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+            {
+                // Another process deleted it between the listing and now
+                error_tracker.record_gone();
+            } else if let Err(e) = deleted {
+                let msg = format!("{}", e);
+                let item = FailedItem::from_io(path.clone(), &e, false);
+                let ignored = is_ignored(&item, config, error_tracker);
+                match record_failure(item, config, error_tracker) {
+                    Decision::Retry => {
+                        deleted = with_retries(path, worker, config, retry::is_transient, delete);
+                        continue;
+                    }
+                    Decision::Abort => {
+                        aborted.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                    Decision::Skip => {}
+                }
+
+                if config.verbose && ignored {
+                    output::log(
+                        "Ignored failure to delete",
+                        format!("Ignored failure to delete {}: {}", path.display(), msg),
+                    );
+                } else if config.verbose {
+                    output::log(
+                        "Failed to delete",
+                        format!("Failed to delete {}: {}", path.display(), msg),
+                    );
+                }
+            } else {
+                if let Some(ref progress) = config.progress {
+                    progress.add_file_deleted();
+                }
+                if let Some(ref tuner) = config.tuner {
+                    tuner.record();
+                }
+                if config.events.is_some() {
+                    let path = path.clone();
+                    events::emit(&config.events, Event::FileDone { path });
+                }
+            }
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broker::Broker;
+    use crate::tree;
+    use std::fs::{self, File};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_delete_files_in_dir() {
+        let temp = std::env::temp_dir().join("win_rmdir_delete_files_test");
+        let _ = fs::remove_dir_all(&temp);
+
+        fs::create_dir(&temp).unwrap();
+        File::create(temp.join("a.txt")).unwrap();
+        File::create(temp.join("b.txt")).unwrap();
+        File::create(temp.join("c.txt")).unwrap();
+
+        assert_eq!(fs::read_dir(&temp).unwrap().count(), 3);
+
+        let config = WorkerConfig::default();
+        let error_tracker = Arc::new(ErrorTracker::new());
+        delete_files_in_dir(&temp, 0, &config, &error_tracker).unwrap();
+
+        // Files should be deleted, dir still exists
+        assert_eq!(fs::read_dir(&temp).unwrap().count(), 0);
+        assert!(temp.exists());
+
+        fs::remove_dir(&temp).ok();
+    }
+
+    #[test]
+    fn test_serial_deletes_files_in_order() {
+        let temp = std::env::temp_dir().join("win_rmdir_serial_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir(&temp).unwrap();
+        for name in ["m", "c", "x", "a", "k"] {
+            File::create(temp.join(name)).unwrap();
+        }
+
+        let (event_tx, event_rx) = events::channel();
+        let config = WorkerConfig {
+            events: Some(event_tx),
+            serial: true,
+            ..Default::default()
+        };
+        let error_tracker = ErrorTracker::new();
+        delete_files_in_dir(&temp, 0, &config, &error_tracker).unwrap();
+
+        let deleted: Vec<PathBuf> = event_rx
+            .try_iter()
+            .filter_map(|event| match event {
+                Event::FileDone { path } => Some(path),
+                _ => None,
+            })
+            .collect();
+        let names = ["a", "c", "k", "m", "x"];
+        assert_eq!(deleted, names.map(|name| temp.join(name)));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_already_gone_is_not_a_failure() {
+        let temp = std::env::temp_dir().join("win_rmdir_already_gone_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir(&temp).unwrap();
+        let (broker, _tx, _rx) = Broker::new(tree::discover_tree(&temp).unwrap());
+        // Another process gets there first
+        fs::remove_dir(&temp).unwrap();
+
+        let config = WorkerConfig::default();
+        let error_tracker = Arc::new(ErrorTracker::new());
+        let aborted = AtomicBool::new(false);
+        delete_chunk(
+            &[(temp.join("a.txt"), false)],
+            0,
+            &config,
+            &error_tracker,
+            &aborted,
+        );
+        assert!(delete_dir_files(&temp, 0, &broker, &config, &error_tracker));
+        remove_emptied_dir(temp.clone(), 0, &broker, &config, &error_tracker);
+
+        assert_eq!(error_tracker.failure_count(), 0);
+        assert_eq!(error_tracker.gone_count(), 2);
+        assert!(broker.is_finished());
+        assert_eq!(broker.completed_count(), 1);
+    }
+
+    #[test]
+    fn test_rescan_deletes_newcomers() {
+        let temp = std::env::temp_dir().join("win_rmdir_rescan_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir(&temp).unwrap();
+        let error_tracker = Arc::new(ErrorTracker::new());
+        let add_newcomers = || {
+            fs::create_dir_all(temp.join("new/inner")).unwrap();
+            File::create(temp.join("new/inner/late.log")).unwrap();
+            File::create(temp.join("late.txt")).unwrap();
+        };
+
+        // Without rescans the directory fails
+        let (broker, _tx, _rx) = Broker::new(tree::discover_tree(&temp).unwrap());
+        add_newcomers();
+        let config = WorkerConfig {
+            rescans: 0,
+            ..Default::default()
+        };
+        remove_emptied_dir(temp.clone(), 0, &broker, &config, &error_tracker);
+        assert_eq!(broker.failed_count(), 1);
+        assert!(temp.exists());
+
+        fs::remove_dir_all(&temp).unwrap();
+        fs::create_dir(&temp).unwrap();
+        let (broker, _tx, _rx) = Broker::new(tree::discover_tree(&temp).unwrap());
+        add_newcomers();
+        let config = WorkerConfig::default();
+        remove_emptied_dir(temp.clone(), 0, &broker, &config, &error_tracker);
+        assert_eq!(broker.completed_count(), 1);
+        assert!(!temp.exists());
+
+        // A file that already failed is left alone
+        fs::create_dir(&temp).unwrap();
+        let (broker, _tx, _rx) = Broker::new(tree::discover_tree(&temp).unwrap());
+        File::create(temp.join("stuck.txt")).unwrap();
+        let error_tracker = Arc::new(ErrorTracker::new());
+        error_tracker.record_failure(FailedItem {
+            path: temp.join("stuck.txt"),
+            error: "Access is denied.".to_string(),
+            is_dir: false,
+            os_error: None,
+        });
+        remove_emptied_dir(temp.clone(), 0, &broker, &config, &error_tracker);
+        assert_eq!(broker.failed_count(), 1);
+        assert!(temp.join("stuck.txt").exists());
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_ignored_residue() {
+        let temp = std::env::temp_dir().join("win_rmdir_ignored_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("sub")).unwrap();
+        File::create(temp.join("sub/locked.dll")).unwrap();
+        let (broker, _tx, _rx) = Broker::new(tree::discover_tree(&temp).unwrap());
+        let config = WorkerConfig {
+            errors: ErrorStrategy::FailFast,
+            ignore_errors: IgnoredErrors::new(vec![32]),
+            ..Default::default()
+        };
+        let error_tracker = ErrorTracker::new();
+        let locked = FailedItem {
+            path: temp.join("sub/locked.dll"),
+            error: "The file is in use.".to_string(),
+            is_dir: false,
+            os_error: Some(32),
+        };
+        assert_eq!(
+            record_failure(locked, &config, &error_tracker),
+            Decision::Skip
+        );
+
+        // Directories kept only by the residue are not failures either, nor do they stop
+        // the deletion
+        remove_emptied_dir(temp.join("sub"), 0, &broker, &config, &error_tracker);
+        remove_emptied_dir(temp.clone(), 0, &broker, &config, &error_tracker);
+        assert_eq!(error_tracker.failure_count(), 0);
+        assert_eq!(error_tracker.ignored_count(), 3);
+        assert!(error_tracker.aborted_by().is_none());
+        assert!(!broker.is_cancelled());
+        assert!(temp.join("sub/locked.dll").exists());
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_ignored_file_not_deleted() {
+        let temp = std::env::temp_dir().join("win_rmdir_ignored_file_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir(&temp).unwrap();
+        File::create(temp.join("a.txt")).unwrap();
+        File::create(temp.join("denied.txt")).unwrap();
+        let config = WorkerConfig {
+            ignore_errors: IgnoredErrors::parse("ACCESS_DENIED").unwrap(),
+            ..Default::default()
+        };
+        let error_tracker = ErrorTracker::new();
+
+        assert!(delete_files(&[temp.join("a.txt")], &config, &error_tracker));
+        let (_, codes) = crate::error::ERROR_CLASSES
+            .iter()
+            .find(|(name, _)| *name == "ACCESS_DENIED")
+            .unwrap();
+        let denied = io::Error::from_raw_os_error(codes[0]);
+        let item = FailedItem::from_io(temp.join("denied.txt"), &denied, false);
+        assert_eq!(
+            record_failure(item, &config, &error_tracker),
+            Decision::Skip
+        );
+
+        // Ignored, so not a failure, but not deleted either
+        assert_eq!(error_tracker.failure_count(), 0);
+        assert_eq!(error_tracker.ignored_count(), 1);
+        let files_deleted = 2 - error_tracker.files_not_deleted();
+        assert_eq!(files_deleted, 1);
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_prompt_retry() {
+        let temp = std::env::temp_dir().join("win_rmdir_prompt_retry_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("sub")).unwrap();
+        let (broker, _tx, _rx) = Broker::new(tree::discover_tree(&temp).unwrap());
+        // Arrived after the scan, and cleared away before the answer
+        let late = temp.join("sub/late.txt");
+        File::create(&late).unwrap();
+        let asked = Arc::new(AtomicUsize::new(0));
+        let counter = asked.clone();
+        let config = WorkerConfig {
+            errors: ErrorStrategy::Prompt(Arc::new(move |_: &FailedItem| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                fs::remove_file(&late).ok();
+                Decision::Retry
+            })),
+            rescans: 0,
+            ..Default::default()
+        };
+        let error_tracker = ErrorTracker::new();
+
+        remove_emptied_dir(temp.join("sub"), 0, &broker, &config, &error_tracker);
+        assert_eq!(asked.load(Ordering::SeqCst), 1);
+        assert_eq!(error_tracker.failure_count(), 0);
+        assert!(!temp.join("sub").exists());
+
+        // A panicked directory is never retried, whatever the answer
+        let panicked = FailedItem {
+            path: temp.join("sub"),
+            error: format!("{}: boom", PANICKED),
+            is_dir: true,
+            os_error: None,
+        };
+        assert_eq!(
+            record_failure(panicked, &config, &error_tracker),
+            Decision::Skip
+        );
+        assert_eq!(error_tracker.failure_count(), 1);
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_delete_files_records_manifest() {
+        let temp = std::env::temp_dir().join("win_rmdir_delete_files_manifest_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+        let file = temp.join("old.iso");
+        File::create(&file).unwrap();
+        let list = temp.join("manifest.jsonl");
+        let config = WorkerConfig {
+            manifest: Some(Arc::new(Manifest::create(&list, None).unwrap())),
+            ..Default::default()
+        };
+        let error_tracker = ErrorTracker::new();
+
+        assert!(delete_files(
+            std::slice::from_ref(&file),
+            &config,
+            &error_tracker
+        ));
+        assert!(!file.exists());
+        assert!(fs::read_to_string(&list).unwrap().contains("old.iso"));
+        assert_eq!(error_tracker.failure_count(), 0);
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_custom_spawner() {
+        let temp = std::env::temp_dir().join("win_rmdir_spawner_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("a/b")).unwrap();
+        fs::create_dir_all(temp.join("c")).unwrap();
+        File::create(temp.join("a/b/file.txt")).unwrap();
+
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let seen = names.clone();
+        let config = WorkerConfig {
+            spawner: Some(Spawner::new(move |name, body| {
+                seen.lock().unwrap().push(name.clone());
+                thread::Builder::new()
+                    .name(format!("app-{}", name))
+                    .spawn(body)
+                    .map(|_| ())
+            })),
+            ..Default::default()
+        };
+        let (broker, tx, rx) = Broker::new(tree::discover_tree(&temp).unwrap());
+        let broker = Arc::new(broker);
+        let handles = spawn_workers(2, rx, broker.clone(), config.clone(), Default::default());
+        drop(tx);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(!temp.exists());
+        assert_eq!(*names.lock().unwrap(), ["worker-0", "worker-1"]);
+
+        // Panics reach the embedder's thread, and join reports them
+        let handle = start_worker("boom".into(), config.spawner.as_ref(), || panic!("boom"));
+        assert!(handle.join().is_err());
+    }
+
+    #[test]
+    fn test_spawn_workers_concurrent_consumption() {
+        // Create a simple tree with multiple leaves to test parallel consumption
+        let temp_root = std::env::temp_dir().join("win_rmdir_spawn_test");
+        let _ = fs::remove_dir_all(&temp_root);
+
+        // Create structure: root with 3 leaf dirs
+        fs::create_dir(&temp_root).unwrap();
+        let leaf1 = temp_root.join("leaf1");
+        let leaf2 = temp_root.join("leaf2");
+        let leaf3 = temp_root.join("leaf3");
+        fs::create_dir(&leaf1).unwrap();
+        fs::create_dir(&leaf2).unwrap();
+        fs::create_dir(&leaf3).unwrap();
+
+        // Add a file to each leaf so they have content to delete
+        File::create(leaf1.join("file.txt")).unwrap();
+        File::create(leaf2.join("file.txt")).unwrap();
+        File::create(leaf3.join("file.txt")).unwrap();
+
+        // Discover the tree and create broker
+        let tree = tree::discover_tree(&temp_root).unwrap();
+        let (broker, tx, rx) = Broker::new(tree);
+        let broker = Arc::new(broker);
+
+        // Drop the external sender - broker will close channel when done
+        drop(tx);
+
+        // Track how many workers actually process work
+        let work_count = Arc::new(AtomicUsize::new(0));
+        let work_count_clone = work_count.clone();
+
+        // Spawn 3 workers
+        let worker_count = 3;
+        let handles: Vec<_> = (0..worker_count)
+            .map(|i| {
+                let rx = rx.clone();
+                let broker = broker.clone();
+                let work_count = work_count_clone.clone();
+                thread::Builder::new()
+                    .name(format!("test-worker-{}", i))
+                    .spawn(move || {
+                        let config = WorkerConfig::default();
+                        let error_tracker = Arc::new(ErrorTracker::new());
+                        while let Ok(dir) = rx.recv_timeout(Duration::from_millis(100)) {
+                            work_count.fetch_add(1, Ordering::SeqCst);
+                            let _ = delete_files_in_dir(&dir, i, &config, &error_tracker);
+                            let _ = remove_dir(&dir);
+                            broker.mark_complete(dir);
+                        }
+                    })
+                    .expect("Failed to spawn test worker")
+            })
+            .collect();
+
+        // Drop sender to close channel eventually
+        drop(rx);
+
+        // Wait for all workers
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Verify work was distributed (at least 3 leaf dirs were processed)
+        let total_work = work_count.load(Ordering::SeqCst);
+        assert!(
+            total_work >= 3,
+            "Expected at least 3 work items processed, got {}",
+            total_work
+        );
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_root);
+    }
+
+    #[test]
+    fn test_spawn_split_workers() {
+        let temp = std::env::temp_dir().join("win_rmdir_split_pools_test");
+        let _ = fs::remove_dir_all(&temp);
+        for leaf in ["a/b/c", "a/d", "e"] {
+            fs::create_dir_all(temp.join(leaf)).unwrap();
+            File::create(temp.join(leaf).join("file.txt")).unwrap();
+        }
+
+        let tree = tree::discover_tree(&temp).unwrap();
+        let (broker, tx, rx) = Broker::new(tree);
+        let broker = Arc::new(broker);
+        let error_tracker = Arc::new(ErrorTracker::new());
+        let handles = spawn_split_workers(
+            3,
+            1,
+            rx,
+            broker.clone(),
+            WorkerConfig::default(),
+            error_tracker.clone(),
+        );
+        drop(tx);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(error_tracker.failure_count(), 0);
+        assert!(!temp.exists());
+    }
+
+    #[test]
+    fn test_delete_files_chunked() {
+        let temp = std::env::temp_dir().join("win_rmdir_chunked_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("sub")).unwrap();
+        for i in 0..25 {
+            File::create(temp.join(format!("{}.txt", i))).unwrap();
+        }
+
+        let config = WorkerConfig {
+            progress: Some(Arc::new(Progress::new(1))),
+            ..Default::default()
+        };
+        let error_tracker = Arc::new(ErrorTracker::new());
+        delete_files_chunked(&temp, 0, &config, &error_tracker, 4).unwrap();
+
+        assert_eq!(config.progress.unwrap().files_deleted(), 25);
+        assert_eq!(fs::read_dir(&temp).unwrap().count(), 1);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
@@ -1,6 +1,6 @@
 // Edge case tests for rmbrr
 
-use rmbrr::{broker::Broker, tree, worker};
+use rmbrr_core::{broker::Broker, tree, worker};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
@@ -199,6 +199,39 @@ fn test_symlinks_unix() {
     assert!(!temp.exists());
 }
 
+#[test]
+#[cfg(windows)]
+fn test_junction_farm_windows() {
+    let temp = std::env::temp_dir().join("rmbrr_test_junction_farm");
+    let store = std::env::temp_dir().join("rmbrr_test_junction_farm_store");
+    let _ = fs::remove_dir_all(&temp);
+    let _ = fs::remove_dir_all(&store);
+
+    // A pnpm-style layout: packages are junctions into a store outside the tree, each
+    // deleted with a single delete-on-close open of the junction itself
+    fs::create_dir_all(store.join("pkg/lib")).unwrap();
+    File::create(store.join("pkg/lib/index.js")).unwrap();
+    fs::create_dir_all(temp.join("node_modules")).unwrap();
+    for i in 0..200 {
+        let status = std::process::Command::new("cmd")
+            .arg("/c")
+            .arg("mklink")
+            .arg("/J")
+            .arg(temp.join(format!("node_modules\\dep{}", i)))
+            .arg(store.join("pkg"))
+            .stdout(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    delete_directory(&temp).unwrap();
+
+    assert!(!temp.exists());
+    assert!(store.join("pkg/lib/index.js").exists());
+    fs::remove_dir_all(&store).ok();
+}
+
 #[test]
 fn test_readonly_files() {
     let temp = std::env::temp_dir().join("rmbrr_test_readonly");
@@ -235,6 +268,45 @@ fn test_readonly_files() {
     }
 }
 
+#[test]
+fn test_readonly_directories() {
+    let temp = std::env::temp_dir().join("rmbrr_test_readonly_dirs");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Like a .git objects tree: read-only directories holding read-only files
+    let objects = temp.join("objects").join("ab");
+    fs::create_dir_all(&objects).unwrap();
+    fs::write(objects.join("cdef"), b"blob").unwrap();
+    for path in [objects.join("cdef"), objects.clone(), temp.join("objects")] {
+        let mut perms = fs::metadata(&path).unwrap().permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&path, perms).unwrap();
+    }
+
+    #[cfg_attr(unix, allow(unused_variables))]
+    let result = delete_directory(&temp);
+
+    #[cfg(windows)]
+    {
+        assert!(result.is_ok());
+        assert!(!temp.exists());
+    }
+
+    #[cfg(unix)]
+    {
+        // On Unix, a read-only directory keeps its entries unless we are root
+        for path in [temp.join("objects"), objects] {
+            if let Ok(meta) = fs::metadata(&path) {
+                let mut perms = meta.permissions();
+                #[allow(clippy::permissions_set_readonly_false)]
+                perms.set_readonly(false);
+                fs::set_permissions(&path, perms).ok();
+            }
+        }
+        let _ = fs::remove_dir_all(&temp); // Cleanup
+    }
+}
+
 #[test]
 fn test_very_long_filenames() {
     let temp = std::env::temp_dir().join("rmbrr_test_long_names");
@@ -274,3 +346,55 @@ fn test_case_sensitivity() {
 
     assert!(!temp.exists());
 }
+
+#[test]
+fn test_case_colliding_directories() {
+    let temp = std::env::temp_dir().join("rmbrr_test_case_dirs");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Both exist on case-sensitive filesystems and in case-sensitive Windows directories;
+    // elsewhere the second name opens the first directory
+    fs::create_dir_all(temp.join("Foo")).unwrap();
+    fs::create_dir_all(temp.join("foo")).unwrap();
+    File::create(temp.join("Foo").join("a.txt")).unwrap();
+    File::create(temp.join("foo").join("b.txt")).unwrap();
+    fs::create_dir_all(temp.join("foo").join("Inner")).unwrap();
+    fs::create_dir_all(temp.join("foo").join("inner")).unwrap();
+
+    delete_directory(&temp).unwrap();
+
+    assert!(!temp.exists());
+}
+
+#[cfg(windows)]
+#[test]
+fn test_delete_by_id_after_rename() {
+    let temp = std::env::temp_dir().join("rmbrr_test_by_id");
+    let _ = fs::remove_dir_all(&temp);
+    fs::create_dir_all(temp.join("a/old")).unwrap();
+    fs::write(temp.join("a/old/file"), b"data").unwrap();
+
+    let mut tree = tree::discover_tree(&temp).unwrap();
+    tree.record_dir_ids();
+    let ids = std::mem::take(&mut tree.dir_ids);
+    assert_eq!(ids.len(), 3);
+    // Renamed between the scan and the deletion
+    fs::rename(temp.join("a/old"), temp.join("a/new")).unwrap();
+
+    let (broker, tx, rx) = Broker::new(tree);
+    let config = worker::WorkerConfig {
+        dir_ids: Some(Arc::new(
+            rmbrr_core::winapi::DirIds::open(&temp, ids).unwrap(),
+        )),
+        ..Default::default()
+    };
+    let error_tracker = Arc::new(worker::ErrorTracker::new());
+    let handles = worker::spawn_workers(2, rx, Arc::new(broker), config, error_tracker.clone());
+    drop(tx);
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(error_tracker.failure_count(), 0);
+    assert!(!temp.exists());
+}
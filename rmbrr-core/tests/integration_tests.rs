@@ -0,0 +1,436 @@
+// Integration tests for rmbrr
+
+use rmbrr_core::filter::{self, Filter};
+use rmbrr_core::{broker::Broker, tree, worker};
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Test helper: create a directory tree with specified structure
+fn create_test_tree(base: &Path, depth: usize, dirs_per_level: usize, files_per_dir: usize) {
+    fs::create_dir_all(base).unwrap();
+
+    // Create files in current directory
+    for i in 0..files_per_dir {
+        File::create(base.join(format!("file_{}.txt", i))).unwrap();
+    }
+
+    // Recurse to create subdirectories
+    if depth > 0 {
+        for i in 0..dirs_per_level {
+            let subdir = base.join(format!("dir_{}", i));
+            create_test_tree(&subdir, depth - 1, dirs_per_level, files_per_dir);
+        }
+    }
+}
+
+/// Count total directories in a path
+fn count_dirs(path: &Path) -> usize {
+    if !path.is_dir() {
+        return 0;
+    }
+
+    let mut count = 1; // Count self
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                count += count_dirs(&entry.path());
+            }
+        }
+    }
+    count
+}
+
+/// Count total files in a path
+fn count_files(path: &Path) -> usize {
+    if !path.is_dir() {
+        return 0;
+    }
+
+    let mut count = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                count += 1;
+            } else if path.is_dir() {
+                count += count_files(&path);
+            }
+        }
+    }
+    count
+}
+
+/// Run the deletion pipeline on a directory
+fn delete_with_pipeline(path: &Path) {
+    let tree = tree::discover_tree(path).unwrap();
+    let (broker, tx, rx) = Broker::new(tree);
+    let broker = Arc::new(broker);
+
+    let worker_count = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+
+    let error_tracker = Arc::new(worker::ErrorTracker::new());
+    let config = worker::WorkerConfig::default();
+
+    let handles = worker::spawn_workers(worker_count, rx, broker, config, error_tracker);
+    drop(tx);
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn test_large_tree_1000_files_100_dirs() {
+    let temp = std::env::temp_dir().join("win_rmdir_large_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Create tree: depth=2, 10 dirs per level, 10 files per dir
+    // Structure: root + 10 children + 100 grandchildren = 111 dirs
+    // Files: 111 dirs * 10 files = 1110 files
+    create_test_tree(&temp, 2, 10, 10);
+
+    let dir_count = count_dirs(&temp);
+    let file_count = count_files(&temp);
+
+    println!("Created {} dirs with {} files", dir_count, file_count);
+    assert!(dir_count >= 100, "Should have at least 100 dirs");
+    assert!(file_count >= 1000, "Should have at least 1000 files");
+
+    let start = Instant::now();
+    delete_with_pipeline(&temp);
+    let elapsed = start.elapsed();
+
+    println!(
+        "Deleted {} dirs and {} files in {:?}",
+        dir_count, file_count, elapsed
+    );
+    assert!(!temp.exists(), "Directory should be deleted");
+}
+
+#[test]
+fn test_deep_nesting_50_levels() {
+    let temp = std::env::temp_dir().join("win_rmdir_deep_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Create 50 levels deep: each level has 1 subdir and 1 file
+    create_test_tree(&temp, 50, 1, 1);
+
+    let dir_count = count_dirs(&temp);
+    println!("Created {} nested directories", dir_count);
+    assert_eq!(dir_count, 51, "Should have 51 dirs (root + 50 levels)");
+
+    let start = Instant::now();
+    delete_with_pipeline(&temp);
+    let elapsed = start.elapsed();
+
+    println!("Deleted deep tree ({} levels) in {:?}", dir_count, elapsed);
+    assert!(!temp.exists(), "Directory should be deleted");
+}
+
+#[test]
+fn test_wide_tree_1000_siblings() {
+    let temp = std::env::temp_dir().join("win_rmdir_wide_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Create 1000 sibling directories at root level
+    create_test_tree(&temp, 1, 1000, 1);
+
+    let dir_count = count_dirs(&temp);
+    println!("Created {} sibling directories", dir_count);
+    assert_eq!(
+        dir_count, 1001,
+        "Should have 1001 dirs (root + 1000 siblings)"
+    );
+
+    let start = Instant::now();
+    delete_with_pipeline(&temp);
+    let elapsed = start.elapsed();
+
+    println!(
+        "Deleted wide tree ({} siblings) in {:?}",
+        dir_count, elapsed
+    );
+    assert!(!temp.exists(), "Directory should be deleted");
+}
+
+#[test]
+fn test_empty_directories() {
+    let temp = std::env::temp_dir().join("win_rmdir_empty_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Create empty directory structure
+    fs::create_dir_all(temp.join("a/b/c")).unwrap();
+    fs::create_dir_all(temp.join("d/e")).unwrap();
+    fs::create_dir(temp.join("f")).unwrap();
+
+    assert!(temp.exists());
+
+    let start = Instant::now();
+    delete_with_pipeline(&temp);
+    let elapsed = start.elapsed();
+
+    println!("Deleted empty tree in {:?}", elapsed);
+    assert!(!temp.exists(), "Directory should be deleted");
+}
+
+#[test]
+fn test_directory_with_only_files() {
+    let temp = std::env::temp_dir().join("win_rmdir_files_only_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Create directory with only files, no subdirectories
+    fs::create_dir(&temp).unwrap();
+    for i in 0..100 {
+        File::create(temp.join(format!("file_{}.txt", i))).unwrap();
+    }
+
+    let file_count = count_files(&temp);
+    assert_eq!(file_count, 100, "Should have 100 files");
+
+    let start = Instant::now();
+    delete_with_pipeline(&temp);
+    let elapsed = start.elapsed();
+
+    println!(
+        "Deleted directory with {} files in {:?}",
+        file_count, elapsed
+    );
+    assert!(!temp.exists(), "Directory should be deleted");
+}
+
+#[test]
+fn test_mixed_structure() {
+    let temp = std::env::temp_dir().join("win_rmdir_mixed_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    // Create mixed structure with varying depth and file counts
+    fs::create_dir_all(&temp).unwrap();
+
+    // Deep branch
+    create_test_tree(&temp.join("deep"), 10, 1, 5);
+
+    // Wide branch
+    create_test_tree(&temp.join("wide"), 1, 50, 2);
+
+    // Empty branch
+    fs::create_dir_all(temp.join("empty/a/b/c")).unwrap();
+
+    // Files-only branch
+    fs::create_dir(temp.join("files")).unwrap();
+    for i in 0..20 {
+        File::create(temp.join("files").join(format!("f{}.txt", i))).unwrap();
+    }
+
+    assert!(temp.exists());
+
+    let start = Instant::now();
+    delete_with_pipeline(&temp);
+    let elapsed = start.elapsed();
+
+    println!("Deleted mixed structure in {:?}", elapsed);
+    assert!(!temp.exists(), "Directory should be deleted");
+}
+
+#[test]
+fn test_only_filter_preserves_structure() {
+    let temp = std::env::temp_dir().join("win_rmdir_only_filter_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    fs::create_dir_all(temp.join("logs")).unwrap();
+    fs::create_dir_all(temp.join("src/nested")).unwrap();
+    File::create(temp.join("logs/a.log")).unwrap();
+    File::create(temp.join("src/main.rs")).unwrap();
+    File::create(temp.join("src/b.tmp")).unwrap();
+    File::create(temp.join("src/nested/c.log")).unwrap();
+
+    let mut filter = Filter::new(&temp);
+    filter.only = filter::parse_patterns(&["*.log,*.tmp"]);
+    let filter = Arc::new(filter);
+
+    let tree = tree::discover_tree_filtered(&temp, Some(&filter)).unwrap();
+    let (broker, tx, rx) = Broker::new(tree);
+    let broker = Arc::new(broker);
+
+    let config = worker::WorkerConfig {
+        filter: Some(filter),
+        ..Default::default()
+    };
+    let error_tracker = Arc::new(worker::ErrorTracker::new());
+    let handles = worker::spawn_workers(4, rx, broker.clone(), config, error_tracker.clone());
+    drop(tx);
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(error_tracker.failure_count(), 0);
+    assert_eq!(broker.kept_count(), 2, "root and src should be kept");
+    assert!(temp.join("src/main.rs").exists());
+    assert!(!temp.join("src/b.tmp").exists());
+    assert!(!temp.join("logs").exists());
+    assert!(!temp.join("src/nested").exists());
+
+    fs::remove_dir_all(&temp).ok();
+}
+
+#[test]
+fn test_keep_preserves_subpaths_and_ancestors() {
+    let temp = std::env::temp_dir().join("win_rmdir_keep_test");
+    let _ = fs::remove_dir_all(&temp);
+
+    fs::create_dir_all(temp.join("config/nested")).unwrap();
+    fs::create_dir_all(temp.join("cache/blobs")).unwrap();
+    fs::create_dir_all(temp.join("build")).unwrap();
+    File::create(temp.join("config/nested/app.toml")).unwrap();
+    File::create(temp.join("cache/manifest.json")).unwrap();
+    File::create(temp.join("cache/blobs/1.bin")).unwrap();
+    File::create(temp.join("build/out.o")).unwrap();
+    File::create(temp.join("readme.txt")).unwrap();
+
+    let mut filter = Filter::new(&temp);
+    filter.add_keep(Path::new("config"));
+    filter.add_keep(Path::new("cache/manifest.json"));
+    let filter = Arc::new(filter);
+
+    let tree = tree::discover_tree_filtered(&temp, Some(&filter)).unwrap();
+    let (broker, tx, rx) = Broker::new(tree);
+    let broker = Arc::new(broker);
+
+    let config = worker::WorkerConfig {
+        filter: Some(filter),
+        ..Default::default()
+    };
+    let error_tracker = Arc::new(worker::ErrorTracker::new());
+    let handles = worker::spawn_workers(4, rx, broker, config, error_tracker.clone());
+    drop(tx);
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(error_tracker.failure_count(), 0);
+    assert!(temp.join("config/nested/app.toml").exists());
+    assert!(temp.join("cache/manifest.json").exists());
+    assert!(!temp.join("cache/blobs").exists());
+    assert!(!temp.join("build").exists());
+    assert!(!temp.join("readme.txt").exists());
+
+    fs::remove_dir_all(&temp).ok();
+}
+
+#[test]
+fn test_merged_roots_share_one_broker() {
+    let temp = std::env::temp_dir().join("rmbrr_test_merged_roots");
+    let _ = fs::remove_dir_all(&temp);
+    let roots: Vec<_> = (0..3).map(|i| temp.join(format!("target{}", i))).collect();
+    for root in &roots {
+        create_test_tree(root, 2, 3, 4);
+    }
+
+    let mut tree = tree::discover_tree(&roots[0]).unwrap();
+    for root in &roots[1..] {
+        tree.merge(tree::discover_tree(root).unwrap());
+    }
+    let dirs_total = tree.dirs.len();
+    let (broker, tx, rx) = Broker::new(tree);
+    let broker = Arc::new(broker);
+    let error_tracker = Arc::new(worker::ErrorTracker::new());
+    let config = worker::WorkerConfig::default();
+    let handles = worker::spawn_workers(4, rx, broker.clone(), config, error_tracker.clone());
+    drop(tx);
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert!(broker.is_finished());
+    assert_eq!(broker.completed_count(), dirs_total);
+    assert_eq!(error_tracker.failure_count(), 0);
+    for root in &roots {
+        assert!(!root.exists());
+    }
+    fs::remove_dir_all(&temp).ok();
+}
+
+#[test]
+fn test_library_remove_dir_all() {
+    let temp = std::env::temp_dir().join("win_rmdir_library_api_test");
+    let _ = fs::remove_dir_all(&temp);
+    create_test_tree(&temp, 2, 3, 2);
+
+    let mut updates = 0;
+    let report =
+        rmbrr_core::remove_dir_all_with_progress(&temp, &rmbrr_core::RmOptions::default(), |_| {
+            updates += 1
+        })
+        .unwrap();
+
+    assert!(!temp.exists(), "Directory should be deleted");
+    assert_eq!(report.dirs_deleted, 13);
+    assert_eq!(report.files_deleted, 26);
+    assert!(updates >= 1);
+}
+
+#[test]
+fn test_library_run_scoped() {
+    let temp = std::env::temp_dir().join("win_rmdir_library_scoped_test");
+    let _ = fs::remove_dir_all(&temp);
+    create_test_tree(&temp, 2, 3, 2);
+
+    // Borrowed and not Send: fine for scoped hooks
+    let updates = std::cell::Cell::new(0);
+    let mut events = Vec::new();
+    let hooks = rmbrr_core::ScopedHooks {
+        on_progress: Some(&mut |_| updates.set(updates.get() + 1)),
+        on_event: Some(&mut |event| events.push(event.clone())),
+        ..Default::default()
+    };
+    let report = rmbrr_core::run_scoped(&temp, &rmbrr_core::RmOptions::default(), hooks).unwrap();
+
+    assert!(!temp.exists(), "Directory should be deleted");
+    assert_eq!(report.dirs_deleted, 13);
+    assert!(updates.get() >= 1);
+    assert!(matches!(
+        events.first(),
+        Some(rmbrr_core::Event::Started { .. })
+    ));
+    assert!(matches!(
+        events.last(),
+        Some(rmbrr_core::Event::Finished { .. })
+    ));
+    let files_done = events
+        .iter()
+        .filter(|e| matches!(e, rmbrr_core::Event::FileDone { .. }))
+        .count();
+    assert_eq!(files_done, 26);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_library_symlinked_root() {
+    let temp = std::env::temp_dir().join("win_rmdir_library_link_root_test");
+    let _ = fs::remove_dir_all(&temp);
+    create_test_tree(&temp.join("real"), 1, 2, 1);
+    let link = temp.join("link");
+    std::os::unix::fs::symlink(temp.join("real"), &link).unwrap();
+
+    // By default only the link goes
+    rmbrr_core::remove_dir_all(&link, &rmbrr_core::RmOptions::default()).unwrap();
+    assert!(!link.exists());
+    assert!(temp.join("real").exists());
+
+    std::os::unix::fs::symlink(temp.join("real"), &link).unwrap();
+    let options = rmbrr_core::RmOptions {
+        dereference_root: true,
+        ..Default::default()
+    };
+    let report = rmbrr_core::remove_dir_all(&link, &options).unwrap();
+    assert!(link.symlink_metadata().is_err());
+    assert!(!temp.join("real").exists());
+    assert_eq!(report.dirs_deleted, 3);
+
+    fs::remove_dir_all(&temp).ok();
+}
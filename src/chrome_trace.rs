@@ -0,0 +1,67 @@
+// Chrome trace export (--trace-out): open the file in chrome://tracing or ui.perfetto.dev
+
+use rmbrr_core::trace::{Tracer, MAIN_THREAD};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const PID: u32 = 1;
+
+/// Write every recorded span as a complete ("X") event, plus thread names
+pub fn write(path: &Path, tracer: &Tracer) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut file, &to_json(tracer))?;
+    file.flush()
+}
+
+fn to_json(tracer: &Tracer) -> Value {
+    let spans = tracer.spans();
+    let threads: BTreeSet<usize> = spans.iter().map(|s| s.thread).collect();
+
+    let names = threads.into_iter().map(|tid| {
+        let name = if tid == MAIN_THREAD {
+            "main".to_string()
+        } else {
+            format!("worker-{}", tid - 1)
+        };
+        json!({"name": "thread_name", "ph": "M", "pid": PID, "tid": tid, "args": {"name": name}})
+    });
+    let events = spans.iter().map(|span| {
+        json!({
+            "name": span.name,
+            "cat": span.category,
+            "ph": "X",
+            "pid": PID,
+            "tid": span.thread,
+            "ts": span.start.as_micros() as u64,
+            "dur": span.duration.as_micros() as u64,
+        })
+    });
+
+    json!({
+        "traceEvents": names.chain(events).collect::<Vec<_>>(),
+        "displayTimeUnit": "ms",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_trace_json() {
+        let tracer = Tracer::new();
+        tracer.record("scan", "scan /x", MAIN_THREAD, Instant::now());
+        tracer.record("delete", "/x/a", 1, Instant::now());
+
+        let trace = to_json(&tracer);
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[1]["args"]["name"], "worker-0");
+        assert_eq!(events[3]["ph"], "X");
+        assert_eq!(events[3]["cat"], "delete");
+    }
+}
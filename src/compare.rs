@@ -0,0 +1,308 @@
+// `rmbrr compare`: time rmbrr against `std::fs::remove_dir_all` and the platform's own
+// recursive delete on identical copies of a tree
+//
+// Each run builds a fresh copy per method (untimed), deletes it (timed) and checks that it
+// is gone. Methods take turns within a run, so drift in disk or cache state spreads evenly.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Content of each generated file
+const FILE_CONTENT: &[u8] = b"rmbrr compare\n";
+
+/// The tree every method deletes a copy of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Copies of an existing directory (files and directories; links are left out)
+    Template(PathBuf),
+    /// A generated tree `depth` levels deep, each directory with `width` subdirectories
+    /// (above the last level) and `files` files
+    Shape { depth: u32, width: u32, files: u32 },
+}
+
+impl Source {
+    /// Parse an existing directory, or a shape such as `4x8x16` (depth x width x files)
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let path = Path::new(value);
+        if path.is_dir() {
+            return Ok(Source::Template(path.to_path_buf()));
+        }
+        let parts: Vec<_> = value.split('x').map(|p| p.parse::<u32>()).collect();
+        match parts.as_slice() {
+            [Ok(depth), Ok(width), Ok(files)] if *depth <= 16 && *width > 0 => Ok(Source::Shape {
+                depth: *depth,
+                width: *width,
+                files: *files,
+            }),
+            _ => Err(format!(
+                "'{}' is neither a directory nor a shape like 4x8x16 (depth x width x files)",
+                value
+            )),
+        }
+    }
+
+    /// Build a copy at `dest`, which must not exist yet
+    fn build(&self, dest: &Path) -> io::Result<()> {
+        match self {
+            Source::Template(template) => copy_tree(template, dest),
+            Source::Shape {
+                depth,
+                width,
+                files,
+            } => generate(dest, *depth, *width, *files),
+        }
+    }
+
+    /// Where copies go unless told otherwise: beside the template, so they land on its
+    /// volume, or the temp directory for a generated tree
+    pub fn default_dir(&self) -> PathBuf {
+        match self {
+            Source::Template(template) => template
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+            Source::Shape { .. } => std::env::temp_dir(),
+        }
+    }
+}
+
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn generate(dir: &Path, depth: u32, width: u32, files: u32) -> io::Result<()> {
+    fs::create_dir(dir)?;
+    for i in 0..files {
+        fs::write(dir.join(format!("file{}.txt", i)), FILE_CONTENT)?;
+    }
+    if depth > 0 {
+        for i in 0..width {
+            generate(&dir.join(format!("dir{}", i)), depth - 1, width, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// A way of deleting a tree
+pub struct Method {
+    pub name: &'static str,
+    delete: fn(&Path) -> io::Result<()>,
+}
+
+/// rmbrr first: the others are compared against it
+pub fn methods() -> Vec<Method> {
+    vec![
+        Method {
+            name: "rmbrr",
+            delete: |path| {
+                rmbrr_core::remove_dir_all(path, &rmbrr_core::RmOptions::default())
+                    .map(|_| ())
+                    .map_err(io::Error::other)
+            },
+        },
+        Method {
+            name: "std::fs::remove_dir_all",
+            delete: |path| fs::remove_dir_all(path),
+        },
+        Method {
+            name: NATIVE_NAME,
+            delete: native_delete,
+        },
+    ]
+}
+
+#[cfg(windows)]
+const NATIVE_NAME: &str = "cmd /c rd /s /q";
+
+#[cfg(not(windows))]
+const NATIVE_NAME: &str = "rm -rf";
+
+#[cfg(windows)]
+fn native_delete(path: &Path) -> io::Result<()> {
+    let status = std::process::Command::new("cmd")
+        .args(["/c", "rd", "/s", "/q"])
+        .arg(path)
+        .status()?;
+    // rd exits 0 even when it leaves entries behind; `run` checks the tree is gone
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("rd exited with {}", status)))
+    }
+}
+
+#[cfg(not(windows))]
+fn native_delete(path: &Path) -> io::Result<()> {
+    let status = std::process::Command::new("rm")
+        .arg("-rf")
+        .arg(path)
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("rm exited with {}", status)))
+    }
+}
+
+/// Times of one method, one per successful run
+#[derive(Debug, Default)]
+pub struct Timing {
+    pub name: &'static str,
+    pub times: Vec<Duration>,
+    pub failed: u32,
+    /// Why the last failed run failed
+    pub failure: Option<String>,
+}
+
+impl Timing {
+    pub fn median(&self) -> Option<Duration> {
+        let mut times = self.times.clone();
+        times.sort();
+        times.get(times.len() / 2).copied()
+    }
+}
+
+/// Delete `runs` copies of `source` per method, built in `dir`
+///
+/// Only building a copy stops the comparison; a method that fails or leaves entries behind
+/// has that run recorded as its failure instead.
+pub fn run(source: &Source, runs: u32, dir: &Path, methods: &[Method]) -> io::Result<Vec<Timing>> {
+    let mut timings: Vec<Timing> = methods
+        .iter()
+        .map(|m| Timing {
+            name: m.name,
+            ..Default::default()
+        })
+        .collect();
+    for run in 0..runs {
+        for (method, timing) in methods.iter().zip(&mut timings) {
+            let copy = dir.join(format!(".rmbrr-compare-{}-{}", std::process::id(), run));
+            let _ = fs::remove_dir_all(&copy);
+            if let Err(e) = source.build(&copy) {
+                let _ = fs::remove_dir_all(&copy);
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("cannot build {}: {}", copy.display(), e),
+                ));
+            }
+            let started = Instant::now();
+            let result = (method.delete)(&copy);
+            let elapsed = started.elapsed();
+            match result {
+                Ok(()) if copy.symlink_metadata().is_err() => timing.times.push(elapsed),
+                Ok(()) => {
+                    timing.failed += 1;
+                    timing.failure = Some("left entries behind".to_string());
+                }
+                Err(e) => {
+                    timing.failed += 1;
+                    timing.failure = Some(e.to_string());
+                }
+            }
+            let _ = fs::remove_dir_all(&copy);
+        }
+    }
+    Ok(timings)
+}
+
+/// Print the timings as a table, each method's median relative to the first's
+pub fn print(timings: &[Timing]) {
+    let baseline = timings.first().and_then(Timing::median);
+    println!(
+        "{:<24} {:>10} {:>10} {:>10} {:>9}",
+        "METHOD", "MEDIAN", "MIN", "MAX", "VS RMBRR"
+    );
+    for timing in timings {
+        let Some(median) = timing.median() else {
+            let reason = timing.failure.as_deref().unwrap_or("no runs");
+            println!("{:<24} failed: {}", timing.name, reason);
+            continue;
+        };
+        let ratio = match baseline {
+            Some(base) if !base.is_zero() => {
+                format!("{:.2}x", median.as_secs_f64() / base.as_secs_f64())
+            }
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:<24} {:>10} {:>10} {:>10} {:>9}",
+            timing.name,
+            format!("{:.2?}", median),
+            format!("{:.2?}", timing.times.iter().min().unwrap()),
+            format!("{:.2?}", timing.times.iter().max().unwrap()),
+            ratio
+        );
+        if let Some(ref failure) = timing.failure {
+            let runs = timing.times.len() as u32 + timing.failed;
+            println!(
+                "{:<24} ({} of {} runs failed: {})",
+                "", timing.failed, runs, failure
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            Source::parse("3x2x5"),
+            Ok(Source::Shape {
+                depth: 3,
+                width: 2,
+                files: 5
+            })
+        );
+        let temp = std::env::temp_dir();
+        assert_eq!(
+            Source::parse(&temp.to_string_lossy()),
+            Ok(Source::Template(temp.clone()))
+        );
+        assert!(Source::parse("3x0x5").is_err());
+        assert!(Source::parse("no/such/dir").is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let temp = std::env::temp_dir().join("win_rmdir_compare_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir(&temp).unwrap();
+        let source = Source::Shape {
+            depth: 2,
+            width: 2,
+            files: 3,
+        };
+
+        let timings = run(&source, 2, &temp, &methods()).unwrap();
+        assert_eq!(timings.len(), 3);
+        for timing in &timings {
+            assert_eq!(
+                timing.times.len(),
+                2,
+                "{}: {:?}",
+                timing.name,
+                timing.failure
+            );
+        }
+        // Every copy is cleaned up
+        assert_eq!(fs::read_dir(&temp).unwrap().count(), 0);
+        fs::remove_dir_all(&temp).ok();
+    }
+}
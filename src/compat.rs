@@ -0,0 +1,90 @@
+// Command-line compatibility with other deletion tools
+
+use std::ffi::OsString;
+
+/// Translate cmd-style `rd`/`rmdir` switches into rmbrr arguments
+///
+/// `/S` and `/Q` are accepted as no-ops (deletion is always recursive and quiet) and `/?`
+/// shows help. Switches are case-insensitive and may be combined (`/S/Q`). Anything that
+/// isn't entirely made of known switches is passed through untouched.
+pub fn translate_cmd_switches<I>(args: I) -> Vec<OsString>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut translated = Vec::new();
+    let mut args = args.into_iter();
+
+    // Program name
+    if let Some(arg0) = args.next() {
+        translated.push(arg0);
+    }
+
+    for arg in args {
+        match parse_cmd_switches(&arg) {
+            Some(switches) => {
+                for switch in switches {
+                    if switch == '?' {
+                        translated.push(OsString::from("--help"));
+                    }
+                }
+            }
+            None => translated.push(arg),
+        }
+    }
+
+    translated
+}
+
+/// Parse an argument like `/S`, `/q` or `/S/Q` into its switch letters
+fn parse_cmd_switches(arg: &OsString) -> Option<Vec<char>> {
+    let arg = arg.to_str()?;
+    let rest = arg.strip_prefix('/')?;
+
+    let mut switches = Vec::new();
+    for part in rest.split('/') {
+        let mut chars = part.chars();
+        let switch = chars.next()?.to_ascii_uppercase();
+        if chars.next().is_some() || !matches!(switch, 'S' | 'Q' | '?') {
+            return None;
+        }
+        switches.push(switch);
+    }
+    Some(switches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate(args: &[&str]) -> Vec<String> {
+        translate_cmd_switches(args.iter().map(OsString::from))
+            .into_iter()
+            .map(|a| a.into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_rd_switches_are_dropped() {
+        assert_eq!(
+            translate(&["rmbrr", "/S", "/Q", "build"]),
+            vec!["rmbrr", "build"]
+        );
+        assert_eq!(
+            translate(&["rmbrr", "/s/q", "build"]),
+            vec!["rmbrr", "build"]
+        );
+    }
+
+    #[test]
+    fn test_help_switch() {
+        assert_eq!(translate(&["rmbrr", "/?"]), vec!["rmbrr", "--help"]);
+    }
+
+    #[test]
+    fn test_paths_pass_through() {
+        assert_eq!(
+            translate(&["rmbrr", "/Sources", "/tmp/q", "-v"]),
+            vec!["rmbrr", "/Sources", "/tmp/q", "-v"]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+// Wiping a data drive's contents (`rmbrr D:\ --keep-root --i-know-what-im-doing`): the
+// preview of what is on the drive and the typed confirmation that follow the scan
+
+use crate::i18n::{tf, Msg};
+use crate::space::format_bytes;
+use rmbrr_core::output::{paint, Color};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Top-level entries listed before the rest are summarized as "... and N more"
+const PREVIEW_ENTRIES: usize = 20;
+
+/// Names of the entries directly in `root`, directories first and marked with a trailing
+/// separator
+pub fn top_level_entries(root: &Path) -> io::Result<Vec<String>> {
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            dirs.push(format!("{}{}", name, std::path::MAIN_SEPARATOR));
+        } else {
+            files.push(name);
+        }
+    }
+    dirs.sort_by_key(|name| name.to_lowercase());
+    files.sort_by_key(|name| name.to_lowercase());
+    dirs.append(&mut files);
+    Ok(dirs)
+}
+
+/// Show what the wipe would delete: the scanned totals and the drive's top-level entries
+pub fn preview(root: &Path, dirs: usize, files: usize, bytes: u64) {
+    eprintln!(
+        "\n{}",
+        paint(tf(Msg::DriveErase, &[&root.display()]), Color::Yellow)
+    );
+    eprintln!(
+        "{}",
+        tf(
+            Msg::DriveTotals,
+            &[&dirs, &files, &format_bytes(bytes as i64)]
+        )
+    );
+    match top_level_entries(root) {
+        Ok(entries) => {
+            for name in entries.iter().take(PREVIEW_ENTRIES) {
+                eprintln!("     {}", name);
+            }
+            if entries.len() > PREVIEW_ENTRIES {
+                eprintln!(
+                    "{}",
+                    tf(Msg::MoreEntries, &[&(entries.len() - PREVIEW_ENTRIES)])
+                );
+            }
+        }
+        Err(e) => eprintln!("{}", tf(Msg::DriveCannotList, &[&e])),
+    }
+    eprintln!();
+}
+
+/// Ask the user to type the drive (`D:`, or `Volume{GUID}` for a volume without a letter) to
+/// go ahead; anything else, or EOF, means no
+pub fn confirm(expected: &str) -> bool {
+    eprint!("{}", tf(Msg::DriveTypeConfirm, &[&expected]));
+    io::stderr().flush().ok();
+
+    let mut response = String::new();
+    io::stdin().lock().read_line(&mut response).ok();
+    response.trim().eq_ignore_ascii_case(expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_level_entries() {
+        let temp = std::env::temp_dir().join("win_rmdir_drive_preview_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("Projects/inner")).unwrap();
+        fs::create_dir_all(temp.join("backups")).unwrap();
+        fs::write(temp.join("notes.txt"), "x").unwrap();
+        fs::write(temp.join("Archive.zip"), "x").unwrap();
+
+        let sep = std::path::MAIN_SEPARATOR;
+        assert_eq!(
+            top_level_entries(&temp).unwrap(),
+            vec![
+                format!("backups{}", sep),
+                format!("Projects{}", sep),
+                "Archive.zip".to_string(),
+                "notes.txt".to_string(),
+            ]
+        );
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
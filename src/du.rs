@@ -0,0 +1,167 @@
+// `rmbrr du`: measure a tree with the deletion scanner, without deleting anything
+
+use crate::largest;
+use crate::space::format_bytes;
+use rmbrr_core::error::RmError;
+use rmbrr_core::output;
+use rmbrr_core::{tree, winapi};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Totals for one top-level entry of the measured directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DuRow {
+    /// Subdirectory name, or None for the files directly in the root
+    pub name: Option<String>,
+    pub dirs: usize,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// What `measure` found
+#[derive(Debug, Default)]
+pub struct Measured {
+    /// Largest first; the root's own files are one row with no name
+    pub rows: Vec<DuRow>,
+    /// The biggest files of each subdirectory and of the root's own, unsorted
+    pub largest: Vec<(PathBuf, u64)>,
+}
+
+/// Measure `root`, scanning its top-level subdirectories in parallel
+pub fn measure(root: &Path, threads: usize) -> Result<Measured, RmError> {
+    if !root.is_dir() {
+        return Err(RmError::InvalidPath {
+            path: root.to_path_buf(),
+            reason: "not a directory".to_string(),
+        });
+    }
+
+    let mut subdirs: Vec<PathBuf> = Vec::new();
+    let mut loose = DuRow::default();
+    let mut loose_files = Vec::new();
+    winapi::enumerate_entries(root, true, |path, is_dir, size, _, _| {
+        if is_dir {
+            subdirs.push(path.to_path_buf());
+        } else {
+            loose.files += 1;
+            loose.bytes += size;
+            loose_files.push((path.to_path_buf(), size));
+        }
+        Ok(())
+    })
+    .map_err(|source| RmError::ScanError {
+        path: root.to_path_buf(),
+        source,
+    })?;
+
+    let next = AtomicUsize::new(0);
+    let rows = Mutex::new(Vec::with_capacity(subdirs.len() + 1));
+    let largest = Mutex::new(largest::top(loose_files, tree::LARGEST_FILES));
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, subdirs.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(dir) = subdirs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    match tree::discover_tree_sized(dir, None) {
+                        Ok(tree) => {
+                            rows.lock().unwrap().push(DuRow {
+                                name: dir.file_name().map(|n| n.to_string_lossy().into_owned()),
+                                dirs: tree.dirs.len(),
+                                files: tree.file_count,
+                                bytes: tree.bytes,
+                            });
+                            largest.lock().unwrap().extend(tree.largest);
+                        }
+                        Err(e) => output::warn(format!("Cannot read {}: {}", dir.display(), e)),
+                    }
+                }
+            });
+        }
+    });
+
+    output::flush_log();
+    let mut rows = rows.into_inner().unwrap();
+    if loose.files > 0 {
+        rows.push(loose);
+    }
+    rows.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    Ok(Measured {
+        rows,
+        largest: largest.into_inner().unwrap(),
+    })
+}
+
+/// Print the rows as a table with a total line
+pub fn print(root: &Path, rows: &[DuRow]) {
+    println!("{:>10} {:>9} {:>10}  PATH", "SIZE", "DIRS", "FILES");
+    for row in rows {
+        let name = match row.name {
+            Some(ref name) => name.clone(),
+            None => "(files)".to_string(),
+        };
+        println!(
+            "{:>10} {:>9} {:>10}  {}",
+            format_bytes(row.bytes as i64),
+            row.dirs,
+            row.files,
+            name
+        );
+    }
+    println!(
+        "{:>10} {:>9} {:>10}  {} (total)",
+        format_bytes(rows.iter().map(|r| r.bytes).sum::<u64>() as i64),
+        // The root itself is a directory too
+        rows.iter().map(|r| r.dirs).sum::<usize>() + 1,
+        rows.iter().map(|r| r.files).sum::<usize>(),
+        root.display()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_measure() {
+        let temp = std::env::temp_dir().join("win_rmdir_du_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("big/inner")).unwrap();
+        fs::create_dir_all(temp.join("small")).unwrap();
+        fs::write(temp.join("big/inner/a"), [0u8; 300]).unwrap();
+        fs::write(temp.join("big/b"), [0u8; 200]).unwrap();
+        fs::write(temp.join("small/c"), [0u8; 10]).unwrap();
+        fs::write(temp.join("loose"), [0u8; 50]).unwrap();
+
+        let measured = measure(&temp, 4).unwrap();
+        assert_eq!(
+            measured.rows,
+            vec![
+                DuRow {
+                    name: Some("big".into()),
+                    dirs: 2,
+                    files: 2,
+                    bytes: 500
+                },
+                DuRow {
+                    name: None,
+                    dirs: 0,
+                    files: 1,
+                    bytes: 50
+                },
+                DuRow {
+                    name: Some("small".into()),
+                    dirs: 1,
+                    files: 1,
+                    bytes: 10
+                },
+            ]
+        );
+        let mut largest = measured.largest;
+        largest.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        assert_eq!(largest[0], (temp.join("big/inner/a"), 300));
+        assert_eq!(largest.len(), 4);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
@@ -0,0 +1,95 @@
+// Free-space target mode (`--until-free 200G DIR`): the entries directly in DIR are deleted
+// least recently used first until the volume has the requested free space
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// An entry directly in the target that may be evicted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Last modification; for a directory, the newest of it and its direct entries
+    pub used: SystemTime,
+}
+
+/// Entries directly in `root`, least recently used first, leaving out those used within
+/// `min_age`
+///
+/// Links count as files: evicting one removes the link, never what it points to.
+pub fn candidates(root: &Path, min_age: Option<Duration>) -> io::Result<Vec<Candidate>> {
+    let now = SystemTime::now();
+    let mut candidates = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let is_dir = metadata.is_dir();
+        let mut used = metadata.modified()?;
+        if is_dir {
+            // Rewriting a file in place leaves its directory's own time alone
+            for child in fs::read_dir(entry.path())?.flatten() {
+                if let Ok(modified) = child.metadata().and_then(|m| m.modified()) {
+                    used = used.max(modified);
+                }
+            }
+        }
+        let recent = min_age.is_some_and(|age| now.duration_since(used).unwrap_or_default() < age);
+        if !recent {
+            candidates.push(Candidate {
+                path: entry.path(),
+                is_dir,
+                used,
+            });
+        }
+    }
+    candidates.sort_by(|a, b| a.used.cmp(&b.used).then_with(|| a.path.cmp(&b.path)));
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_modified(path: &Path, ago: Duration) {
+        let time = SystemTime::now() - ago;
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_candidates_oldest_first() {
+        let temp = std::env::temp_dir().join("win_rmdir_evict_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("old_dir")).unwrap();
+        fs::create_dir_all(temp.join("busy_dir")).unwrap();
+        fs::write(temp.join("old_dir/a"), "x").unwrap();
+        fs::write(temp.join("busy_dir/b"), "x").unwrap();
+        fs::write(temp.join("older.bin"), "x").unwrap();
+        let day = Duration::from_secs(24 * 60 * 60);
+        set_modified(&temp.join("old_dir/a"), 2 * day);
+        set_modified(&temp.join("older.bin"), 3 * day);
+
+        let names = |candidates: Vec<Candidate>| -> Vec<String> {
+            candidates
+                .iter()
+                .map(|c| c.path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        let all = names(candidates(&temp, None).unwrap());
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0], "older.bin");
+        // old_dir's own time is recent, since its file was just created
+        assert_eq!(
+            names(candidates(&temp, Some(day)).unwrap()),
+            vec!["older.bin"]
+        );
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
@@ -0,0 +1,70 @@
+// The `--expect-max-*` guards: how big a target may turn out to be before rmbrr refuses to
+// delete it
+
+use crate::i18n::{tf, Msg};
+use crate::space;
+
+/// Upper bounds on what a target's scan may find; `None` means unchecked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub dirs: Option<usize>,
+    pub files: Option<usize>,
+    pub bytes: Option<u64>,
+}
+
+impl Limits {
+    /// Why a target holding `dirs` directories and `files` files of `bytes` in total is
+    /// too big, or `None` if it is within every limit
+    pub fn exceeded(&self, dirs: usize, files: usize, bytes: u64) -> Option<String> {
+        if let Some(max) = self.dirs.filter(|&max| dirs > max) {
+            Some(tf(Msg::ExpectMaxDirs, &[&dirs, &max]))
+        } else if let Some(max) = self.files.filter(|&max| files > max) {
+            Some(tf(Msg::ExpectMaxFiles, &[&files, &max]))
+        } else {
+            self.bytes.filter(|&max| bytes > max).map(|max| {
+                tf(
+                    Msg::ExpectMaxSize,
+                    &[
+                        &space::format_bytes(bytes as i64),
+                        &space::format_bytes(max as i64),
+                    ],
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_dirs() {
+        let limits = Limits {
+            dirs: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(limits.exceeded(10, 1_000, u64::MAX), None);
+        assert!(limits.exceeded(11, 0, 0).is_some());
+    }
+
+    #[test]
+    fn test_max_files() {
+        let limits = Limits {
+            files: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(limits.exceeded(1_000, 100, u64::MAX), None);
+        assert!(limits.exceeded(1, 101, 0).is_some());
+    }
+
+    #[test]
+    fn test_max_size() {
+        let limits = Limits {
+            bytes: Some(1 << 20),
+            ..Default::default()
+        };
+        assert_eq!(limits.exceeded(1_000, 1_000, 1 << 20), None);
+        assert!(limits.exceeded(1, 1, (1 << 20) + 1).is_some());
+    }
+}
@@ -0,0 +1,1251 @@
+// Message catalogs for prompts, warnings and summaries, picked with `--lang` or from the
+// user's locale. Templates use `{}` placeholders filled in order by `tf`; verbose progress
+// lines and errors from the library stay in English, except the warning label and the panicked
+// worker warnings, which main hands to the library.
+
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    En,
+    De,
+    Ja,
+}
+
+/// Select the catalog for the rest of the run
+pub fn set(lang: Lang) {
+    LANG.set(lang).ok();
+}
+
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::En)
+}
+
+/// Language of the user's locale: `LC_ALL`, `LC_MESSAGES`, `LANG`, then on Windows the user's
+/// default locale; English if none names a known language
+pub fn detect() -> Lang {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .or_else(rmbrr_core::winapi::user_locale)
+        .and_then(|locale| parse_locale(&locale))
+        .unwrap_or(Lang::En)
+}
+
+/// Language of a locale name such as `de_DE.UTF-8`, `ja-JP` or `en`
+pub fn parse_locale(locale: &str) -> Option<Lang> {
+    let language = locale
+        .split(['_', '-', '.', '@'])
+        .next()?
+        .to_ascii_lowercase();
+    match language.as_str() {
+        "en" => Some(Lang::En),
+        "de" => Some(Lang::De),
+        "ja" => Some(Lang::Ja),
+        _ => None,
+    }
+}
+
+/// True if `answer` means yes in the selected language or English
+pub fn is_yes(answer: &str) -> bool {
+    is_yes_in(answer, lang())
+}
+
+fn is_yes_in(answer: &str, lang: Lang) -> bool {
+    let yes = |lang| match lang {
+        Lang::En => &["y", "yes"][..],
+        Lang::De => &["j", "ja"][..],
+        Lang::Ja => &["はい"][..],
+    };
+    let answer = answer.trim().to_lowercase();
+    [Lang::En, lang]
+        .iter()
+        .any(|&lang| yes(lang).contains(&answer.as_str()))
+}
+
+/// An answer to [`Msg::FailurePrompt`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureChoice {
+    Skip,
+    Retry,
+    SkipAll,
+    Abort,
+}
+
+/// Read an answer to [`Msg::FailurePrompt`]: its key letter, or its word in the selected
+/// language or English
+pub fn failure_choice(answer: &str) -> Option<FailureChoice> {
+    failure_choice_in(answer, lang())
+}
+
+fn failure_choice_in(answer: &str, lang: Lang) -> Option<FailureChoice> {
+    use FailureChoice::*;
+    const CHOICES: [FailureChoice; 4] = [Skip, Retry, SkipAll, Abort];
+    let words = |lang| match lang {
+        Lang::En => ["skip", "retry", "all", "abort"],
+        Lang::De => ["überspringen", "wiederholen", "alle", "abbrechen"],
+        Lang::Ja => ["スキップ", "再試行", "すべて", "中止"],
+    };
+    let answer = answer.trim().to_lowercase();
+    [["s", "r", "a", "b"], words(Lang::En), words(lang)]
+        .iter()
+        .find_map(|keys| keys.iter().position(|key| *key == answer))
+        .map(|index| CHOICES[index])
+}
+
+/// Message text in the selected language
+pub fn t(msg: Msg) -> &'static str {
+    msg.text(lang())
+}
+
+/// Message text with its `{}` placeholders replaced by `args`, in order
+pub fn tf(msg: Msg, args: &[&dyn Display]) -> String {
+    fill(t(msg), args)
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(at) = rest.find("{}") {
+        out.push_str(&rest[..at]);
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        rest = &rest[at + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Msg {
+    Error,
+    Warning,
+    Aborted,
+    // Prompts
+    AboutToDelete,
+    Dirs,
+    Files,
+    TotalItems,
+    AreYouSure,
+    ExpectMaxDirs,
+    ExpectMaxFiles,
+    ExpectMaxSize,
+    GitWarning,
+    GitRisk,
+    ProcessesWarning,
+    NetworkTarget,
+    AllowNetworkHint,
+    ProcessCwd,
+    ProcessModule,
+    DeleteAnyway,
+    FailedToDelete,
+    FailurePrompt,
+    DriveErase,
+    DriveTotals,
+    MoreEntries,
+    DriveCannotList,
+    DriveTypeConfirm,
+    // Safety
+    DangerousDetected,
+    ProceedWithForce,
+    ForceExample,
+    CannotDeleteForSafety,
+    SystemDirsNotAllowed,
+    CannotDeleteSystemDir,
+    SystemDirsEvenWithForce,
+    DeletingDangerousWithForce,
+    CannotDeleteDriveRoot,
+    DriveRootHint,
+    // Warnings
+    #[cfg(feature = "tui")]
+    NoConsoleForTui,
+    CannotLowerPriority,
+    NativeDeleteUnavailable,
+    PresetMatchedNothing,
+    TraceNotWritten,
+    ManifestNotWritten,
+    WarningsWritten,
+    SummaryNotWritten,
+    #[cfg(feature = "webhook")]
+    WebhookFailed,
+    HistoryNotWritten,
+    RootNotHeld,
+    RootNotPinned,
+    ByIdUnavailable,
+    ControlledFolderHint,
+    ControlledFolderPossibleHint,
+    AlreadyGone,
+    ErrorsIgnored,
+    SnapshotNotWritten,
+    PlanNotWritten,
+    DiffUnreadable,
+    DiffNoEntry,
+    DiffHeader,
+    DiffNoChanges,
+    DiffAppeared,
+    DiffDisappeared,
+    DiffGrew,
+    DiffShrank,
+    DiffMore,
+    DiffTotal,
+    LargestFiles,
+    LargestNone,
+    ManifestHashed,
+    QuarantineUnreadable,
+    WouldQuarantine,
+    Quarantined,
+    WouldEvict,
+    UntilFreeShort,
+    UntilFreeReached,
+    ScheduleInstalled,
+    ScheduleRemoved,
+    ScheduleEmpty,
+    CannotTakeOwnership,
+    KeepPathNotFound,
+    SkippingCovered,
+    RemovedLink,
+    NetworkProfileUsed,
+    WorkerPanicked,
+    NotRetryingPanicked,
+    #[cfg(feature = "tui")]
+    TuiFailed,
+    // Dry run
+    DryRunMode,
+    DryRunResults,
+    WouldDelete,
+    WouldRemoveLink,
+    TotalItemsShort,
+    FileData,
+    HardlinkedData,
+    AllocatedData,
+    EstimatedTime,
+    ToProceed,
+    // Summaries
+    Summary,
+    ResultDeleted,
+    ResultWouldDelete,
+    ResultPaths,
+    ResultFailedItems,
+    ResultFailedPaths,
+    ResultIgnored,
+    ResultLinks,
+    FailedToProcess,
+    TargetItemsFailed,
+    PathsProcessed,
+    DirsDeleted,
+    FilesDeleted,
+    FailedItems,
+    FailedPaths,
+    IgnoredItems,
+    Timing,
+    SumScanTime,
+    SumDeleteTime,
+    SumTotalTime,
+    SumDeleteRate,
+    SpaceFreed,
+    Stopped,
+    StoppedDetail,
+    Interrupted,
+    InterruptedDetail,
+    ItemsFailed,
+    DeletionComplete,
+    Statistics,
+    StatDirs,
+    StatFiles,
+    StatTotal,
+    CaseSensitiveDirs,
+    EncryptedFiles,
+    LongestPath,
+    DeepestDir,
+    PathsTooLong,
+    SuspiciousDepth,
+    ScanTime,
+    DeleteTime,
+    TotalTime,
+    Performance,
+    Throughput,
+    ScanRate,
+    DeleteRate,
+    Threads,
+    Settled,
+    StillTuning,
+    CompletedWithErrors,
+    ErrorSummary,
+    ItemsFailedOf,
+    DirsRemovedFailed,
+    ByCause,
+    DeniedByOwner,
+    UnknownOwner,
+    OwnersSampled,
+    FirstFailures,
+    Dir,
+    File,
+    MoreFailures,
+    RunVerbose,
+    // Link breakdown
+    LinksHeader,
+    Symlinks,
+    Junctions,
+    MountPoints,
+    AppExecLinks,
+    LinksRemoved,
+    LinksSkipped,
+    LinksFailed,
+}
+
+impl Msg {
+    #[cfg(test)]
+    const ALL: &'static [Msg] = &[
+        Msg::Error,
+        Msg::Warning,
+        Msg::Aborted,
+        Msg::AboutToDelete,
+        Msg::Dirs,
+        Msg::Files,
+        Msg::TotalItems,
+        Msg::AreYouSure,
+        Msg::ExpectMaxDirs,
+        Msg::ExpectMaxFiles,
+        Msg::ExpectMaxSize,
+        Msg::GitWarning,
+        Msg::GitRisk,
+        Msg::ProcessesWarning,
+        Msg::NetworkTarget,
+        Msg::AllowNetworkHint,
+        Msg::ProcessCwd,
+        Msg::ProcessModule,
+        Msg::DeleteAnyway,
+        Msg::FailedToDelete,
+        Msg::FailurePrompt,
+        Msg::DriveErase,
+        Msg::DriveTotals,
+        Msg::MoreEntries,
+        Msg::DriveCannotList,
+        Msg::DriveTypeConfirm,
+        Msg::DangerousDetected,
+        Msg::ProceedWithForce,
+        Msg::ForceExample,
+        Msg::CannotDeleteForSafety,
+        Msg::SystemDirsNotAllowed,
+        Msg::CannotDeleteSystemDir,
+        Msg::SystemDirsEvenWithForce,
+        Msg::DeletingDangerousWithForce,
+        Msg::CannotDeleteDriveRoot,
+        Msg::DriveRootHint,
+        #[cfg(feature = "tui")]
+        Msg::NoConsoleForTui,
+        Msg::CannotLowerPriority,
+        Msg::NativeDeleteUnavailable,
+        Msg::PresetMatchedNothing,
+        Msg::TraceNotWritten,
+        Msg::ManifestNotWritten,
+        Msg::WarningsWritten,
+        Msg::SummaryNotWritten,
+        #[cfg(feature = "webhook")]
+        Msg::WebhookFailed,
+        Msg::HistoryNotWritten,
+        Msg::RootNotHeld,
+        Msg::RootNotPinned,
+        Msg::ByIdUnavailable,
+        Msg::ControlledFolderHint,
+        Msg::ControlledFolderPossibleHint,
+        Msg::AlreadyGone,
+        Msg::ErrorsIgnored,
+        Msg::SnapshotNotWritten,
+        Msg::PlanNotWritten,
+        Msg::DiffUnreadable,
+        Msg::DiffNoEntry,
+        Msg::DiffHeader,
+        Msg::DiffNoChanges,
+        Msg::DiffAppeared,
+        Msg::DiffDisappeared,
+        Msg::DiffGrew,
+        Msg::DiffShrank,
+        Msg::DiffMore,
+        Msg::DiffTotal,
+        Msg::LargestFiles,
+        Msg::LargestNone,
+        Msg::ManifestHashed,
+        Msg::QuarantineUnreadable,
+        Msg::WouldQuarantine,
+        Msg::Quarantined,
+        Msg::WouldEvict,
+        Msg::UntilFreeShort,
+        Msg::UntilFreeReached,
+        Msg::ScheduleInstalled,
+        Msg::ScheduleRemoved,
+        Msg::ScheduleEmpty,
+        Msg::CannotTakeOwnership,
+        Msg::KeepPathNotFound,
+        Msg::SkippingCovered,
+        Msg::RemovedLink,
+        Msg::NetworkProfileUsed,
+        Msg::WorkerPanicked,
+        Msg::NotRetryingPanicked,
+        #[cfg(feature = "tui")]
+        Msg::TuiFailed,
+        Msg::DryRunMode,
+        Msg::DryRunResults,
+        Msg::WouldDelete,
+        Msg::WouldRemoveLink,
+        Msg::TotalItemsShort,
+        Msg::FileData,
+        Msg::HardlinkedData,
+        Msg::AllocatedData,
+        Msg::EstimatedTime,
+        Msg::ToProceed,
+        Msg::Summary,
+        Msg::ResultDeleted,
+        Msg::ResultWouldDelete,
+        Msg::ResultPaths,
+        Msg::ResultFailedItems,
+        Msg::ResultFailedPaths,
+        Msg::ResultIgnored,
+        Msg::ResultLinks,
+        Msg::FailedToProcess,
+        Msg::TargetItemsFailed,
+        Msg::PathsProcessed,
+        Msg::DirsDeleted,
+        Msg::FilesDeleted,
+        Msg::FailedItems,
+        Msg::FailedPaths,
+        Msg::IgnoredItems,
+        Msg::Timing,
+        Msg::SumScanTime,
+        Msg::SumDeleteTime,
+        Msg::SumTotalTime,
+        Msg::SumDeleteRate,
+        Msg::SpaceFreed,
+        Msg::Stopped,
+        Msg::StoppedDetail,
+        Msg::Interrupted,
+        Msg::InterruptedDetail,
+        Msg::ItemsFailed,
+        Msg::DeletionComplete,
+        Msg::Statistics,
+        Msg::StatDirs,
+        Msg::StatFiles,
+        Msg::StatTotal,
+        Msg::CaseSensitiveDirs,
+        Msg::EncryptedFiles,
+        Msg::LongestPath,
+        Msg::DeepestDir,
+        Msg::PathsTooLong,
+        Msg::SuspiciousDepth,
+        Msg::ScanTime,
+        Msg::DeleteTime,
+        Msg::TotalTime,
+        Msg::Performance,
+        Msg::Throughput,
+        Msg::ScanRate,
+        Msg::DeleteRate,
+        Msg::Threads,
+        Msg::Settled,
+        Msg::StillTuning,
+        Msg::CompletedWithErrors,
+        Msg::ErrorSummary,
+        Msg::ItemsFailedOf,
+        Msg::DirsRemovedFailed,
+        Msg::ByCause,
+        Msg::DeniedByOwner,
+        Msg::UnknownOwner,
+        Msg::OwnersSampled,
+        Msg::FirstFailures,
+        Msg::Dir,
+        Msg::File,
+        Msg::MoreFailures,
+        Msg::RunVerbose,
+        Msg::LinksHeader,
+        Msg::Symlinks,
+        Msg::Junctions,
+        Msg::MountPoints,
+        Msg::AppExecLinks,
+        Msg::LinksRemoved,
+        Msg::LinksSkipped,
+        Msg::LinksFailed,
+    ];
+
+    fn text(self, lang: Lang) -> &'static str {
+        let [en, de, ja] = self.catalog();
+        match lang {
+            Lang::En => en,
+            Lang::De => de,
+            Lang::Ja => ja,
+        }
+    }
+
+    /// English, German and Japanese text
+    fn catalog(self) -> [&'static str; 3] {
+        match self {
+            Msg::Error => ["Error:", "Fehler:", "エラー:"],
+            Msg::Warning => ["Warning:", "Warnung:", "警告:"],
+            Msg::Aborted => ["Aborted.", "Abgebrochen.", "中止しました。"],
+            Msg::AboutToDelete => ["\nAbout to delete:", "\nEs wird gelöscht:", "\n削除の対象:"],
+            Msg::ExpectMaxDirs => [
+                "the scan found {} directories, more than --expect-max-dirs {}",
+                "der Scan fand {} Verzeichnisse, mehr als --expect-max-dirs {}",
+                "スキャンで {} 個のディレクトリが見つかりました (--expect-max-dirs {} を超えています)",
+            ],
+            Msg::ExpectMaxFiles => [
+                "the scan found {} files, more than --expect-max-files {}",
+                "der Scan fand {} Dateien, mehr als --expect-max-files {}",
+                "スキャンで {} 個のファイルが見つかりました (--expect-max-files {} を超えています)",
+            ],
+            Msg::ExpectMaxSize => [
+                "the scan found {} of files, more than --expect-max-size {}",
+                "der Scan fand {} an Dateien, mehr als --expect-max-size {}",
+                "スキャンで {} のファイルが見つかりました (--expect-max-size {} を超えています)",
+            ],
+            Msg::Dirs => [
+                "  {} directories",
+                "  {} Verzeichnisse",
+                "  {} 個のディレクトリ",
+            ],
+            Msg::Files => ["  {} files", "  {} Dateien", "  {} 個のファイル"],
+            Msg::TotalItems => [
+                "  Total: {} items",
+                "  Gesamt: {} Einträge",
+                "  合計: {} 項目",
+            ],
+            Msg::AreYouSure => [
+                "\nAre you sure? [y/N] ",
+                "\nSind Sie sicher? [j/N] ",
+                "\nよろしいですか? [y/N] ",
+            ],
+            Msg::GitWarning => [
+                "⚠️  WARNING: Target contains git working copies with unsaved work!",
+                "⚠️  WARNUNG: Das Ziel enthält Git-Arbeitskopien mit ungesicherter Arbeit!",
+                "⚠️  警告: 対象に未保存の作業を含む Git 作業コピーがあります!",
+            ],
+            Msg::GitRisk => [
+                "   {}: {} uncommitted change(s), {} unpushed commit(s)",
+                "   {}: {} nicht committete Änderung(en), {} nicht gepushte Commit(s)",
+                "   {}: 未コミットの変更 {} 件、未プッシュのコミット {} 件",
+            ],
+            Msg::ProcessesWarning => [
+                "⚠️  WARNING: {} running process(es) are using the target!",
+                "⚠️  WARNUNG: {} laufende(r) Prozess(e) verwenden das Ziel!",
+                "⚠️  警告: {} 個の実行中のプロセスが対象を使用しています!",
+            ],
+            Msg::NetworkTarget => [
+                "⚠️  WARNING: {} is on a network share",
+                "⚠️  WARNUNG: {} liegt auf einer Netzwerkfreigabe",
+                "⚠️  警告: {} はネットワーク共有上にあります",
+            ],
+            Msg::AllowNetworkHint => [
+                "   Pass --allow-network to delete on shares without this question",
+                "   Mit --allow-network wird auf Freigaben ohne diese Frage gelöscht",
+                "   この確認を省略して共有上で削除するには --allow-network を指定してください",
+            ],
+            Msg::ProcessCwd => [
+                "   {} {}: working directory {}",
+                "   {} {}: Arbeitsverzeichnis {}",
+                "   {} {}: 作業ディレクトリ {}",
+            ],
+            Msg::ProcessModule => [
+                "   {} {}: loaded {}",
+                "   {} {}: geladen {}",
+                "   {} {}: 読み込み済み {}",
+            ],
+            Msg::DeleteAnyway => [
+                "Delete anyway? [y/N] ",
+                "Trotzdem löschen? [j/N] ",
+                "それでも削除しますか? [y/N] ",
+            ],
+            Msg::FailedToDelete => [
+                "Failed to delete:",
+                "Löschen fehlgeschlagen:",
+                "削除に失敗しました:",
+            ],
+            Msg::FailurePrompt => [
+                "[s]kip / [r]etry / [a]ll-skip / a[b]ort: ",
+                "[s] überspringen / [r] wiederholen / [a] alle überspringen / a[b]brechen: ",
+                "[s] スキップ / [r] 再試行 / [a] 以降すべてスキップ / [b] 中止: ",
+            ],
+            Msg::DriveErase => [
+                "⚠️  About to erase the contents of drive {}",
+                "⚠️  Der Inhalt von Laufwerk {} wird gelöscht",
+                "⚠️  ドライブ {} の内容をすべて消去します",
+            ],
+            Msg::DriveTotals => [
+                "   {} directories, {} files, {}",
+                "   {} Verzeichnisse, {} Dateien, {}",
+                "   ディレクトリ {} 個、ファイル {} 個、{}",
+            ],
+            Msg::MoreEntries => [
+                "     ... and {} more",
+                "     ... und {} weitere",
+                "     ... ほか {} 項目",
+            ],
+            Msg::DriveCannotList => [
+                "   (cannot list the drive: {})",
+                "   (Laufwerk kann nicht aufgelistet werden: {})",
+                "   (ドライブを一覧表示できません: {})",
+            ],
+            Msg::DriveTypeConfirm => [
+                "Type {} to erase everything on it: ",
+                "Geben Sie {} ein, um alles darauf zu löschen: ",
+                "すべてを消去するには {} と入力してください: ",
+            ],
+            Msg::DangerousDetected => [
+                "⚠️  WARNING: Dangerous operation detected!",
+                "⚠️  WARNUNG: Gefährlicher Vorgang erkannt!",
+                "⚠️  警告: 危険な操作が検出されました!",
+            ],
+            Msg::ProceedWithForce => [
+                "   To proceed anyway, use the --force flag",
+                "   Um trotzdem fortzufahren, verwenden Sie --force",
+                "   それでも続行するには --force を指定してください",
+            ],
+            Msg::ForceExample => [
+                "   Example: rmbrr --force {}",
+                "   Beispiel: rmbrr --force {}",
+                "   例: rmbrr --force {}",
+            ],
+            Msg::CannotDeleteForSafety => [
+                "   This path cannot be deleted for safety reasons.",
+                "   Dieser Pfad kann aus Sicherheitsgründen nicht gelöscht werden.",
+                "   安全上の理由により、このパスは削除できません。",
+            ],
+            Msg::SystemDirsNotAllowed => [
+                "   Deletion of system directories is not allowed.",
+                "   Das Löschen von Systemverzeichnissen ist nicht erlaubt.",
+                "   システムディレクトリの削除は許可されていません。",
+            ],
+            Msg::CannotDeleteSystemDir => [
+                "⛔ ERROR: Cannot delete system directory",
+                "⛔ FEHLER: Systemverzeichnis kann nicht gelöscht werden",
+                "⛔ エラー: システムディレクトリは削除できません",
+            ],
+            Msg::SystemDirsEvenWithForce => [
+                "   System directories cannot be deleted even with --force",
+                "   Systemverzeichnisse können auch mit --force nicht gelöscht werden",
+                "   システムディレクトリは --force を指定しても削除できません",
+            ],
+            Msg::DeletingDangerousWithForce => [
+                "⚠️  WARNING: Deleting dangerous path with --force",
+                "⚠️  WARNUNG: Gefährlicher Pfad wird mit --force gelöscht",
+                "⚠️  警告: --force により危険なパスを削除します",
+            ],
+            Msg::CannotDeleteDriveRoot => [
+                "⛔ ERROR: Cannot delete a drive root",
+                "⛔ FEHLER: Das Stammverzeichnis eines Laufwerks kann nicht gelöscht werden",
+                "⛔ エラー: ドライブのルートは削除できません",
+            ],
+            Msg::DriveRootHint => [
+                "   To erase a data drive's contents while keeping the drive:",
+                "   Um den Inhalt eines Datenlaufwerks zu löschen und das Laufwerk zu behalten:",
+                "   ドライブを残したままデータドライブの内容を消去するには:",
+            ],
+            #[cfg(feature = "tui")]
+            Msg::NoConsoleForTui => [
+                "--tui needs a console; falling back to plain output",
+                "--tui benötigt eine Konsole; es wird die einfache Ausgabe verwendet",
+                "--tui にはコンソールが必要です。通常の出力を使用します",
+            ],
+            Msg::CannotLowerPriority => [
+                "cannot lower priority, running normally: {}",
+                "Priorität kann nicht gesenkt werden, normale Ausführung: {}",
+                "優先度を下げられないため通常どおり実行します: {}",
+            ],
+            Msg::NativeDeleteUnavailable => [
+                "native deletion is not available here; deleting the usual way",
+                "Native Löschung ist hier nicht verfügbar; es wird wie üblich gelöscht",
+                "ネイティブ削除はここでは使用できないため、通常の方法で削除します",
+            ],
+            Msg::PresetMatchedNothing => [
+                "preset matched no existing directories",
+                "die Voreinstellung trifft auf keine vorhandenen Verzeichnisse zu",
+                "プリセットに一致するディレクトリがありません",
+            ],
+            Msg::TraceNotWritten => [
+                "could not write trace {}: {}",
+                "Trace {} konnte nicht geschrieben werden: {}",
+                "トレース {} を書き込めませんでした: {}",
+            ],
+            Msg::ManifestNotWritten => [
+                "could not write manifest {}: {}",
+                "Manifest {} konnte nicht geschrieben werden: {}",
+                "マニフェスト {} を書き込めませんでした: {}",
+            ],
+            Msg::WarningsWritten => [
+                "{} warnings written to {}",
+                "{} Warnungen nach {} geschrieben",
+                "{} 件の警告を {} に書き込みました",
+            ],
+            Msg::SummaryNotWritten => [
+                "could not write summary to {}: {}",
+                "Zusammenfassung konnte nicht in {} geschrieben werden: {}",
+                "サマリーを {} に書き込めませんでした: {}",
+            ],
+            #[cfg(feature = "webhook")]
+            Msg::WebhookFailed => [
+                "could not notify the webhook: {}",
+                "Webhook konnte nicht benachrichtigt werden: {}",
+                "Webhook に通知できませんでした: {}",
+            ],
+            Msg::HistoryNotWritten => [
+                "could not update throughput history {}: {}",
+                "Durchsatzverlauf {} konnte nicht aktualisiert werden: {}",
+                "スループット履歴 {} を更新できませんでした: {}",
+            ],
+            Msg::RootNotHeld => [
+                "could not hold {} against new entries: {}",
+                "{} konnte nicht gegen neue Einträge gesperrt werden: {}",
+                "{} への新規作成を禁止できませんでした: {}",
+            ],
+            Msg::RootNotPinned => [
+                "could not hold {} open, so it is not protected against being swapped mid-run: {}",
+                "{} konnte nicht offen gehalten werden und ist nicht gegen Austausch während des Laufs geschützt: {}",
+                "{} を開いたまま保持できないため、実行中の差し替えから保護されません: {}",
+            ],
+            Msg::ControlledFolderHint => [
+                "Windows Security's Controlled Folder Access blocked some deletions. To allow them, add {} under Virus & threat protection > Ransomware protection > Allow an app through Controlled folder access, then run again.",
+                "Der überwachte Ordnerzugriff von Windows-Sicherheit hat Löschvorgänge blockiert. Um sie zuzulassen, fügen Sie {} unter Viren- & Bedrohungsschutz > Ransomware-Schutz > App durch überwachten Ordnerzugriff zulassen hinzu und starten Sie erneut.",
+                "Windows セキュリティのコントロールされたフォルダー アクセスが削除をブロックしました。許可するには、ウイルスと脅威の防止 > ランサムウェア防止 > コントロールされたフォルダー アクセスでアプリを許可する で {} を追加し、再実行してください。",
+            ],
+            Msg::ControlledFolderPossibleHint => [
+                "Some deletions were denied inside folders Controlled Folder Access guards, so it may have blocked them (Defender's log, which may need an elevated run to read, did not confirm it). If it did, add {} under Virus & threat protection > Ransomware protection > Allow an app through Controlled folder access, then run again.",
+                "Einige Löschvorgänge wurden in Ordnern verweigert, die der überwachte Ordnerzugriff schützt; er hat sie möglicherweise blockiert (das Defender-Protokoll, das evtl. nur mit erhöhten Rechten lesbar ist, hat es nicht bestätigt). Falls ja, fügen Sie {} unter Viren- & Bedrohungsschutz > Ransomware-Schutz > App durch überwachten Ordnerzugriff zulassen hinzu und starten Sie erneut.",
+                "コントロールされたフォルダー アクセスで保護されたフォルダー内で一部の削除が拒否されました。ブロックされた可能性があります (Defender のログでは確認できませんでした。読み取りには管理者権限が必要な場合があります)。その場合は、ウイルスと脅威の防止 > ランサムウェア防止 > コントロールされたフォルダー アクセスでアプリを許可する で {} を追加し、再実行してください。",
+            ],
+            Msg::ByIdUnavailable => [
+                "cannot delete {} by file ID, deleting by path: {}",
+                "{} kann nicht per Datei-ID gelöscht werden, es wird per Pfad gelöscht: {}",
+                "{} をファイル ID で削除できないため、パスで削除します: {}",
+            ],
+            Msg::SnapshotNotWritten => [
+                "could not write snapshot {}: {}",
+                "Snapshot {} konnte nicht geschrieben werden: {}",
+                "スナップショット {} を書き込めませんでした: {}",
+            ],
+            Msg::PlanNotWritten => [
+                "could not write plan {}: {}",
+                "Plan {} konnte nicht geschrieben werden: {}",
+                "プラン {} を書き込めませんでした: {}",
+            ],
+            Msg::DiffUnreadable => [
+                "could not read snapshot {}: {}",
+                "Snapshot {} konnte nicht gelesen werden: {}",
+                "スナップショット {} を読み込めませんでした: {}",
+            ],
+            Msg::DiffNoEntry => [
+                "no snapshot of {} in {}; nothing to compare",
+                "kein Snapshot von {} in {}; nichts zu vergleichen",
+                "{} のスナップショットが {} にありません。比較できません",
+            ],
+            Msg::DiffHeader => [
+                "Changes in {} since the snapshot {} ago:",
+                "Änderungen in {} seit dem Snapshot vor {}:",
+                "{} の変更 (スナップショットから {} 経過):",
+            ],
+            Msg::DiffNoChanges => ["  no changes", "  keine Änderungen", "  変更なし"],
+            Msg::DiffAppeared => ["appeared", "neu", "出現"],
+            Msg::DiffDisappeared => ["disappeared", "verschwunden", "消失"],
+            Msg::DiffGrew => ["grew", "gewachsen", "増加"],
+            Msg::DiffShrank => ["shrank", "geschrumpft", "減少"],
+            Msg::DiffMore => [
+                "  ... and {} more changes",
+                "  ... und {} weitere Änderungen",
+                "  ... ほか {} 件の変更",
+            ],
+            Msg::DiffTotal => [
+                "Total: {} -> {} ({}), {} -> {} files",
+                "Gesamt: {} -> {} ({}), {} -> {} Dateien",
+                "合計: {} -> {} ({})、ファイル {} -> {}",
+            ],
+            Msg::LargestFiles => ["Largest files:", "Größte Dateien:", "最大のファイル:"],
+            Msg::LargestNone => [
+                "(no files to delete)",
+                "(keine Dateien zu löschen)",
+                "(削除するファイルはありません)",
+            ],
+            Msg::AlreadyGone => [
+                "{} entries were already gone (deleted by another process)",
+                "{} Einträge waren bereits weg (von einem anderen Prozess gelöscht)",
+                "{} 項目は既に削除されていました (別のプロセスによる削除)",
+            ],
+            Msg::ErrorsIgnored => [
+                "{} failures ignored (--ignore-errors); those entries were left in place",
+                "{} Fehler ignoriert (--ignore-errors); diese Einträge wurden belassen",
+                "{} 件の失敗を無視しました (--ignore-errors)。それらの項目は残っています",
+            ],
+            Msg::ManifestHashed => [
+                "Manifest: hashed {} in {} of worker time ({}/s)",
+                "Manifest: {} in {} Worker-Zeit gehasht ({}/s)",
+                "マニフェスト: {} をワーカー時間 {} でハッシュ ({}/秒)",
+            ],
+            Msg::QuarantineUnreadable => [
+                "cannot read quarantine {}: {}",
+                "Quarantäne {} kann nicht gelesen werden: {}",
+                "隔離先 {} を読み取れません: {}",
+            ],
+            Msg::WouldQuarantine => [
+                "Would move {} to quarantine in {}",
+                "Würde {} in die Quarantäne {} verschieben",
+                "{} を隔離先 {} に移動します",
+            ],
+            Msg::Quarantined => [
+                "Moved {} to quarantine: {}",
+                "{} in die Quarantäne verschoben: {}",
+                "{} を隔離しました: {}",
+            ],
+            Msg::WouldEvict => [
+                "Would delete {} ({})",
+                "Würde {} löschen ({})",
+                "{} を削除します ({})",
+            ],
+            Msg::UntilFreeShort => [
+                "only {} free after deleting every eligible entry (wanted {})",
+                "nach Löschen aller infrage kommenden Einträge nur {} frei (gewünscht {})",
+                "対象をすべて削除しても空き容量は {} です (目標 {})",
+            ],
+            Msg::UntilFreeReached => [
+                "{} free (wanted {})",
+                "{} frei (gewünscht {})",
+                "空き容量 {} (目標 {})",
+            ],
+            Msg::ScheduleInstalled => [
+                "Scheduled cleanup '{}' runs daily at {}",
+                "Geplante Bereinigung '{}' läuft täglich um {}",
+                "定期クリーンアップ '{}' は毎日 {} に実行されます",
+            ],
+            Msg::ScheduleRemoved => [
+                "Removed scheduled cleanup '{}'",
+                "Geplante Bereinigung '{}' entfernt",
+                "定期クリーンアップ '{}' を削除しました",
+            ],
+            Msg::ScheduleEmpty => [
+                "No scheduled cleanups",
+                "Keine geplanten Bereinigungen",
+                "定期クリーンアップはありません",
+            ],
+            Msg::CannotTakeOwnership => [
+                "cannot take ownership of {} entries",
+                "Besitz von {} Einträgen kann nicht übernommen werden",
+                "{} 項目の所有権を取得できません",
+            ],
+            Msg::KeepPathNotFound => [
+                "--keep path not found: {}",
+                "--keep-Pfad nicht gefunden: {}",
+                "--keep のパスが見つかりません: {}",
+            ],
+            Msg::SkippingCovered => [
+                "Skipping {}: already covered by {}",
+                "{} wird übersprungen: bereits in {} enthalten",
+                "{} をスキップします: {} に含まれています",
+            ],
+            Msg::RemovedLink => [
+                "Removed the link {}; its target was left alone (see --dereference-root)",
+                "Verknüpfung {} entfernt; ihr Ziel blieb unberührt (siehe --dereference-root)",
+                "リンク {} を削除しました。リンク先はそのままです (--dereference-root を参照)",
+            ],
+            Msg::NetworkProfileUsed => [
+                "Network target: using the network profile",
+                "Netzwerkziel: das Netzwerkprofil wird verwendet",
+                "ネットワーク上の対象: ネットワーク用の設定を使用します",
+            ],
+            Msg::WorkerPanicked => [
+                "Worker {} panicked on {}: {}",
+                "Worker {} ist bei {} abgestürzt: {}",
+                "ワーカー {} が {} でパニックしました: {}",
+            ],
+            Msg::NotRetryingPanicked => [
+                "Not retrying {}: its worker panicked, so it is skipped",
+                "{} wird nicht erneut versucht: sein Worker ist abgestürzt, daher wird es übersprungen",
+                "{} は再試行しません: ワーカーがパニックしたためスキップします",
+            ],
+            #[cfg(feature = "tui")]
+            Msg::TuiFailed => [
+                "terminal interface failed: {}",
+                "Terminaloberfläche fehlgeschlagen: {}",
+                "ターミナル画面の表示に失敗しました: {}",
+            ],
+            Msg::DryRunMode => [
+                "DRY RUN MODE - no files will be deleted",
+                "PROBELAUF - es werden keine Dateien gelöscht",
+                "ドライラン - ファイルは削除されません",
+            ],
+            Msg::DryRunResults => [
+                "DRY RUN RESULTS",
+                "ERGEBNIS DES PROBELAUFS",
+                "ドライランの結果",
+            ],
+            Msg::WouldDelete => ["\nWould delete:", "\nWürde löschen:", "\n削除される項目:"],
+            Msg::WouldRemoveLink => [
+                "Would remove the link {} (not what it points to)",
+                "Würde die Verknüpfung {} entfernen (nicht ihr Ziel)",
+                "リンク {} を削除します (リンク先は削除しません)",
+            ],
+            Msg::TotalItemsShort => [
+                "  {} total items",
+                "  {} Einträge insgesamt",
+                "  合計 {} 項目",
+            ],
+            Msg::FileData => [
+                "  {} of file data",
+                "  {} Dateidaten",
+                "  ファイルデータ {}",
+            ],
+            Msg::HardlinkedData => [
+                "  {} of it stays allocated (hardlinked from outside the tree)",
+                "  {} davon bleibt belegt (Hardlinks außerhalb des Baums)",
+                "  うち {} はツリー外のハードリンクから参照されるため解放されません",
+            ],
+            Msg::AllocatedData => [
+                "  {} on disk to be freed (after compression and sparse ranges)",
+                "  {} werden auf dem Datenträger frei (nach Komprimierung und Sparse-Bereichen)",
+                "  ディスク上で解放される容量 {} (圧縮・スパース領域を反映)",
+            ],
+            Msg::EstimatedTime => [
+                "  about {} to delete, going by {} earlier runs on this volume",
+                "  etwa {} zum Löschen, nach {} früheren Läufen auf diesem Volume",
+                "  削除にかかる時間は約 {} (このボリュームでの過去 {} 回の実行に基づく)",
+            ],
+            Msg::ToProceed => [
+                "\nTo proceed with deletion:",
+                "\nZum Löschen:",
+                "\n削除を実行するには:",
+            ],
+            Msg::Summary => ["SUMMARY", "ZUSAMMENFASSUNG", "概要"],
+            Msg::ResultDeleted => [
+                "rmbrr {}: deleted {} directories and {} files in {}",
+                "rmbrr {}: {} Verzeichnisse und {} Dateien in {} gelöscht",
+                "rmbrr {}: ディレクトリ {} 個とファイル {} 個を削除 ({})",
+            ],
+            Msg::ResultWouldDelete => [
+                "rmbrr {}: would delete {} directories and {} files (scanned in {})",
+                "rmbrr {}: würde {} Verzeichnisse und {} Dateien löschen (gescannt in {})",
+                "rmbrr {}: ディレクトリ {} 個とファイル {} 個を削除予定 (スキャン {})",
+            ],
+            Msg::ResultPaths => ["{} paths", "{} Pfade", "{} 個のパス"],
+            Msg::ResultFailedItems => [
+                ", {} items failed",
+                ", {} Einträge fehlgeschlagen",
+                "、{} 個の項目が失敗",
+            ],
+            Msg::ResultFailedPaths => [
+                ", {} paths failed",
+                ", {} Pfade fehlgeschlagen",
+                "、{} 個のパスが失敗",
+            ],
+            Msg::ResultIgnored => [
+                ", {} failures ignored",
+                ", {} Fehler ignoriert",
+                "、{} 件の失敗を無視",
+            ],
+            Msg::FailedToProcess => [
+                "Failed to process",
+                "Fehler beim Verarbeiten von",
+                "処理に失敗:",
+            ],
+            Msg::TargetItemsFailed => [
+                "{}: {} items failed",
+                "{}: {} Einträge fehlgeschlagen",
+                "{}: {} 項目が失敗しました",
+            ],
+            Msg::ResultLinks => [
+                ", {} links ({} removed, {} skipped, {} failed)",
+                ", {} Verknüpfungen ({} entfernt, {} übersprungen, {} fehlgeschlagen)",
+                "、{} 個のリンク ({} 件削除、{} 件スキップ、{} 件失敗)",
+            ],
+            Msg::PathsProcessed => [
+                "Paths processed: {}",
+                "Verarbeitete Pfade: {}",
+                "処理したパス: {}",
+            ],
+            Msg::DirsDeleted => [
+                "Directories deleted: {}",
+                "Gelöschte Verzeichnisse: {}",
+                "削除したディレクトリ: {}",
+            ],
+            Msg::FilesDeleted => [
+                "Files deleted: {}",
+                "Gelöschte Dateien: {}",
+                "削除したファイル: {}",
+            ],
+            Msg::FailedItems => [
+                "Failed items: {}",
+                "Fehlgeschlagene Einträge: {}",
+                "失敗した項目: {}",
+            ],
+            Msg::FailedPaths => [
+                "Failed paths: {}",
+                "Fehlgeschlagene Pfade: {}",
+                "失敗したパス: {}",
+            ],
+            Msg::IgnoredItems => [
+                "Failures ignored: {}",
+                "Ignorierte Fehler: {}",
+                "無視した失敗: {}",
+            ],
+            Msg::Timing => ["\nTiming:", "\nZeiten:", "\n所要時間:"],
+            Msg::SumScanTime => [
+                "  Total scan time:   {}",
+                "  Scanzeit gesamt:   {}",
+                "  スキャン時間合計:  {}",
+            ],
+            Msg::SumDeleteTime => [
+                "  Total delete time: {}",
+                "  Löschzeit gesamt:  {}",
+                "  削除時間合計:      {}",
+            ],
+            Msg::SumTotalTime => [
+                "  Total time:        {}",
+                "  Gesamtzeit:        {}",
+                "  合計時間:          {}",
+            ],
+            Msg::SumDeleteRate => [
+                "  Delete rate:       {} files/sec, {} dirs/sec",
+                "  Löschrate:         {} Dateien/s, {} Verzeichnisse/s",
+                "  削除速度:          {} ファイル/秒、{} ディレクトリ/秒",
+            ],
+            Msg::SpaceFreed => [
+                "  Space freed: {} (scanned size {})",
+                "  Freigegeben: {} (gescannte Größe {})",
+                "  解放した容量: {} (スキャンしたサイズ {})",
+            ],
+            Msg::Stopped => ["Stopped", "Angehalten", "停止"],
+            Msg::StoppedDetail => [
+                "{} after {} failure(s); {} of {} directories deleted",
+                "{} nach {} Fehler(n); {} von {} Verzeichnissen gelöscht",
+                "{}: {} 件の失敗により停止、{} / {} ディレクトリを削除済み",
+            ],
+            Msg::Interrupted => ["Interrupted", "Unterbrochen", "中断"],
+            Msg::InterruptedDetail => [
+                "{} after deleting {} of {} directories in {}",
+                "{} nach dem Löschen von {} von {} Verzeichnissen in {}",
+                "{}: {} / {} ディレクトリを削除した時点 ({})",
+            ],
+            Msg::ItemsFailed => [
+                "  {} items failed to delete",
+                "  {} Einträge konnten nicht gelöscht werden",
+                "  {} 項目を削除できませんでした",
+            ],
+            Msg::DeletionComplete => [
+                "Deletion complete!",
+                "Löschen abgeschlossen!",
+                "削除が完了しました!",
+            ],
+            Msg::Statistics => ["\nStatistics:", "\nStatistik:", "\n統計:"],
+            Msg::StatDirs => [
+                "  Directories: {}",
+                "  Verzeichnisse: {}",
+                "  ディレクトリ: {}",
+            ],
+            Msg::StatFiles => [
+                "  Files:       {}",
+                "  Dateien:       {}",
+                "  ファイル:     {}",
+            ],
+            Msg::StatTotal => [
+                "  Total items: {}",
+                "  Gesamt:        {}",
+                "  合計:         {}",
+            ],
+            Msg::CaseSensitiveDirs => [
+                "  Case-sensitive directories: {}",
+                "  Verzeichnisse mit Groß-/Kleinschreibung: {}",
+                "  大文字と小文字を区別するディレクトリ: {}",
+            ],
+            Msg::EncryptedFiles => [
+                "  EFS-encrypted files: {}",
+                "  EFS-verschlüsselte Dateien: {}",
+                "  EFS 暗号化ファイル: {}",
+            ],
+            Msg::LongestPath => [
+                "  Longest path: {} characters ({})",
+                "  Längster Pfad: {} Zeichen ({})",
+                "  最長のパス: {} 文字 ({})",
+            ],
+            Msg::DeepestDir => [
+                "  Deepest nesting: {} levels ({})",
+                "  Tiefste Verschachtelung: {} Ebenen ({})",
+                "  最深の階層: {} 階層 ({})",
+            ],
+            Msg::PathsTooLong => [
+                "{} paths are longer than {} characters; Windows cannot open them by path",
+                "{} Pfade sind länger als {} Zeichen; Windows kann sie nicht über den Pfad öffnen",
+                "{} 個のパスが {} 文字を超えています。Windows はパスで開けません",
+            ],
+            Msg::SuspiciousDepth => [
+                "{} is nested {} levels deep, which usually means runaway recursion",
+                "{} ist {} Ebenen tief verschachtelt, meist die Folge einer außer Kontrolle geratenen Rekursion",
+                "{} は {} 階層の深さにあります。通常は暴走した再帰が原因です",
+            ],
+            Msg::ScanTime => [
+                "  Scan time:   {}",
+                "  Scanzeit:    {}",
+                "  スキャン:    {}",
+            ],
+            Msg::DeleteTime => [
+                "  Delete time: {}",
+                "  Löschzeit:   {}",
+                "  削除:        {}",
+            ],
+            Msg::TotalTime => [
+                "  Total time:  {}",
+                "  Gesamtzeit:  {}",
+                "  合計:        {}",
+            ],
+            Msg::Performance => ["\nPerformance:", "\nLeistung:", "\n性能:"],
+            Msg::Throughput => [
+                "  Throughput:  {} items/sec",
+                "  Durchsatz:   {} Einträge/s",
+                "  スループット: {} 項目/秒",
+            ],
+            Msg::ScanRate => [
+                "  Scan rate:   {} entries/sec",
+                "  Scanrate:    {} Einträge/s",
+                "  スキャン速度: {} 項目/秒",
+            ],
+            Msg::DeleteRate => [
+                "  Delete rate: {} files/sec, {} dirs/sec",
+                "  Löschrate:   {} Dateien/s, {} Verzeichnisse/s",
+                "  削除速度:    {} ファイル/秒、{} ディレクトリ/秒",
+            ],
+            Msg::Threads => [
+                "  Threads:     {} (auto, {})",
+                "  Threads:     {} (auto, {})",
+                "  スレッド:    {} (auto、{})",
+            ],
+            Msg::Settled => ["settled", "eingependelt", "確定"],
+            Msg::StillTuning => ["still tuning", "noch in Anpassung", "調整中"],
+            Msg::CompletedWithErrors => [
+                "Deletion completed with errors!",
+                "Löschen mit Fehlern abgeschlossen!",
+                "削除はエラーありで完了しました!",
+            ],
+            Msg::ErrorSummary => ["Error Summary:", "Fehlerübersicht:", "エラーの概要:"],
+            Msg::ItemsFailedOf => [
+                "  {} of {} items failed to delete",
+                "  {} von {} Einträgen konnten nicht gelöscht werden",
+                "  {} / {} 項目を削除できませんでした",
+            ],
+            Msg::DirsRemovedFailed => [
+                "  {} of {} directories removed, {} failed",
+                "  {} von {} Verzeichnissen entfernt, {} fehlgeschlagen",
+                "  {} / {} ディレクトリを削除、{} 件失敗",
+            ],
+            Msg::ByCause => ["\nBy cause:", "\nNach Ursache:", "\n原因別:"],
+            Msg::DeniedByOwner => [
+                "\nAccess denied, by owner:",
+                "\nZugriff verweigert, nach Besitzer:",
+                "\nアクセス拒否 (所有者別):",
+            ],
+            Msg::UnknownOwner => ["(owner unknown)", "(Besitzer unbekannt)", "(所有者不明)"],
+            Msg::OwnersSampled => [
+                "  (owners looked up for {} of {} denied items)",
+                "  (Besitzer für {} von {} verweigerten Einträgen ermittelt)",
+                "  ({} 項目の所有者を確認 / 拒否された項目 {} 件)",
+            ],
+            Msg::FirstFailures => [
+                "\nFirst {} failures:",
+                "\nErste {} Fehler:",
+                "\n最初の {} 件の失敗:",
+            ],
+            Msg::Dir => ["dir", "Verz", "ディレクトリ"],
+            Msg::File => ["file", "Datei", "ファイル"],
+            Msg::MoreFailures => [
+                "\n  ... and {} more failures",
+                "\n  ... und {} weitere Fehler",
+                "\n  ... ほか {} 件の失敗",
+            ],
+            Msg::RunVerbose => [
+                "\nRun with --verbose to see all errors as they occur",
+                "\nMit --verbose werden alle Fehler angezeigt, sobald sie auftreten",
+                "\nすべてのエラーを発生時に表示するには --verbose を指定してください",
+            ],
+            Msg::LinksHeader => [
+                "\nLinks (removed as links, targets never followed):",
+                "\nVerknüpfungen (als Verknüpfung entfernt, Ziele nie verfolgt):",
+                "\nリンク (リンクとして削除、リンク先はたどりません):",
+            ],
+            Msg::Symlinks => ["symlinks", "Symbolische Links", "シンボリックリンク"],
+            Msg::Junctions => ["junctions", "Junctions", "ジャンクション"],
+            Msg::MountPoints => ["mount points", "Einhängepunkte", "マウントポイント"],
+            Msg::AppExecLinks => [
+                "app execution aliases",
+                "App-Ausführungsaliase",
+                "アプリ実行エイリアス",
+            ],
+            Msg::LinksRemoved => ["{} removed", "{} entfernt", "{} 件削除"],
+            Msg::LinksSkipped => ["{} skipped", "{} übersprungen", "{} 件スキップ"],
+            Msg::LinksFailed => ["{} failed", "{} fehlgeschlagen", "{} 件失敗"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(parse_locale("de_DE.UTF-8"), Some(Lang::De));
+        assert_eq!(parse_locale("ja-JP"), Some(Lang::Ja));
+        assert_eq!(parse_locale("en"), Some(Lang::En));
+        assert_eq!(parse_locale("C"), None);
+        assert_eq!(parse_locale("fr_FR"), None);
+    }
+
+    #[test]
+    fn test_catalogs_share_placeholders() {
+        for msg in Msg::ALL {
+            let [en, de, ja] = msg.catalog();
+            let count = en.matches("{}").count();
+            assert_eq!(de.matches("{}").count(), count, "{:?} (de)", msg);
+            assert_eq!(ja.matches("{}").count(), count, "{:?} (ja)", msg);
+        }
+    }
+
+    #[test]
+    fn test_is_yes() {
+        assert!(is_yes_in(" Yes\n", Lang::En));
+        assert!(is_yes_in("j", Lang::De));
+        assert!(is_yes_in("y", Lang::De));
+        assert!(is_yes_in("はい", Lang::Ja));
+        // "ja" is only a yes to whoever was asked in German
+        assert!(!is_yes_in("ja", Lang::En));
+        assert!(!is_yes_in("ja", Lang::Ja));
+        assert!(!is_yes_in("n", Lang::En));
+    }
+
+    #[test]
+    fn test_failure_choice() {
+        assert_eq!(
+            failure_choice_in("a", Lang::De),
+            Some(FailureChoice::SkipAll)
+        );
+        assert_eq!(
+            failure_choice_in(" Alle\n", Lang::De),
+            Some(FailureChoice::SkipAll)
+        );
+        assert_eq!(
+            failure_choice_in("abort", Lang::De),
+            Some(FailureChoice::Abort)
+        );
+        assert_eq!(
+            failure_choice_in("中止", Lang::Ja),
+            Some(FailureChoice::Abort)
+        );
+        assert_eq!(failure_choice_in("alle", Lang::Ja), None);
+        assert_eq!(failure_choice_in("maybe", Lang::En), None);
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill("{} of {} items", &[&3, &"10"]),
+            "3 of 10 items".to_string()
+        );
+        assert_eq!(fill("no placeholders", &[&1]), "no placeholders");
+    }
+}
@@ -0,0 +1,67 @@
+// Graceful shutdown on console control events (Ctrl+Break, console close, logoff) and, on
+// Unix, SIGTERM and SIGHUP: the run is cancelled, the partial summary and trace are written,
+// and the process exits before the caller's stop timeout
+
+use rmbrr_core::broker::Broker;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Windows kills the process about 5 seconds after a close event, and service managers allow
+/// a similar grace period after SIGTERM; leave room to exit cleanly
+const CLEANUP_TIMEOUT: Duration = Duration::from_millis(4500);
+/// Exit code when cleanup outlasts the timeout (that of a cancelled run)
+const TIMED_OUT_EXIT_CODE: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static ACTIVE_BROKER: Mutex<Option<Arc<Broker>>> = Mutex::new(None);
+static FINISHED: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+
+/// Install the console control or signal handler; call before spawning any threads
+pub fn install() {
+    rmbrr_core::winapi::install_console_ctrl_handler(on_interrupt);
+}
+
+/// Set the broker whose dispatching stops on interruption
+pub fn set_active(broker: Option<Arc<Broker>>) {
+    let mut active = ACTIVE_BROKER.lock().unwrap();
+    if let Some(ref broker) = broker {
+        if interrupted() {
+            broker.cancel();
+        }
+    }
+    *active = broker;
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Signal that the summary has been printed and output flushed, releasing the handler
+pub fn finished() {
+    use std::io::Write;
+    std::io::stdout().flush().ok();
+    std::io::stderr().flush().ok();
+
+    let (lock, cvar) = &FINISHED;
+    *lock.lock().unwrap() = true;
+    cvar.notify_all();
+}
+
+fn on_interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    if let Some(ref broker) = *ACTIVE_BROKER.lock().unwrap() {
+        broker.cancel();
+    }
+
+    // Keep the process alive until main has printed the partial summary
+    let (lock, cvar) = &FINISHED;
+    let done = lock.lock().unwrap();
+    if let Ok((done, _)) = cvar.wait_timeout_while(done, CLEANUP_TIMEOUT, |done| !*done) {
+        // Workers stuck in slow deletions: stop now rather than be killed mid-write
+        if !*done {
+            rmbrr_core::winapi::release_root_holds();
+            std::process::exit(TIMED_OUT_EXIT_CODE);
+        }
+    }
+}
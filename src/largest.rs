@@ -0,0 +1,48 @@
+// `--largest N`: the biggest individual files a dry run or `rmbrr du` found, since one
+// forgotten disk image is often most of a tree
+
+use crate::i18n::{t, Msg};
+use crate::space::format_bytes;
+use std::path::PathBuf;
+
+/// The `n` biggest of `files`, largest first
+pub fn top(files: impl IntoIterator<Item = (PathBuf, u64)>, n: usize) -> Vec<(PathBuf, u64)> {
+    let mut files: Vec<_> = files.into_iter().collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    files.truncate(n);
+    files
+}
+
+pub fn print(files: &[(PathBuf, u64)]) {
+    println!("\n{}", t(Msg::LargestFiles));
+    if files.is_empty() {
+        println!("  {}", t(Msg::LargestNone));
+    }
+    for (path, size) in files {
+        println!("{:>10}  {}", format_bytes(*size as i64), path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top() {
+        let files = vec![
+            (PathBuf::from("b.log"), 10),
+            (PathBuf::from("disk.vhdx"), 400),
+            (PathBuf::from("a.log"), 10),
+            (PathBuf::from("tiny"), 1),
+        ];
+        assert_eq!(
+            top(files, 3),
+            vec![
+                (PathBuf::from("disk.vhdx"), 400),
+                (PathBuf::from("a.log"), 10),
+                (PathBuf::from("b.log"), 10),
+            ]
+        );
+        assert!(top(Vec::new(), 5).is_empty());
+    }
+}
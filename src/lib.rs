@@ -1,6 +0,0 @@
-pub mod broker;
-pub mod error;
-pub mod safety;
-pub mod tree;
-pub mod winapi;
-pub mod worker;
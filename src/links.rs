@@ -0,0 +1,182 @@
+// Link audit for the summary: how many symlinks, junctions, mount points and app execution
+// aliases the scan met, and what happened to each (targets are never followed)
+
+use crate::i18n::{t, tf, Msg};
+use rmbrr_core::error::FailedItem;
+use rmbrr_core::tree::FoundLink;
+use rmbrr_core::winapi::LinkKind;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// What happened to the links of one kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkCounts {
+    /// The link itself was deleted
+    pub removed: usize,
+    /// Kept by a filter
+    pub skipped: usize,
+    /// Deleting the link failed
+    pub failed: usize,
+}
+
+impl LinkCounts {
+    pub fn found(&self) -> usize {
+        self.removed + self.skipped + self.failed
+    }
+
+    fn add(&mut self, other: &LinkCounts) {
+        self.removed += other.removed;
+        self.skipped += other.skipped;
+        self.failed += other.failed;
+    }
+}
+
+/// Add the counts of `other` into `into`, kind by kind
+pub fn merge(into: &mut BTreeMap<LinkKind, LinkCounts>, other: &BTreeMap<LinkKind, LinkCounts>) {
+    for (kind, counts) in other {
+        into.entry(*kind).or_default().add(counts);
+    }
+}
+
+/// The counts of all kinds together
+pub fn total(summary: &BTreeMap<LinkKind, LinkCounts>) -> LinkCounts {
+    let mut all = LinkCounts::default();
+    for counts in summary.values() {
+        all.add(counts);
+    }
+    all
+}
+
+pub fn summarize(links: &[FoundLink], failures: &[FailedItem]) -> BTreeMap<LinkKind, LinkCounts> {
+    let failed: HashSet<&Path> = failures.iter().map(|f| f.path.as_path()).collect();
+    let mut summary: BTreeMap<LinkKind, LinkCounts> = BTreeMap::new();
+    for link in links {
+        let counts = summary.entry(link.kind).or_default();
+        if !link.selected {
+            counts.skipped += 1;
+        } else if failed.contains(link.path.as_path()) {
+            counts.failed += 1;
+        } else {
+            counts.removed += 1;
+        }
+    }
+    summary
+}
+
+/// The links part of the result line; empty if the scan met no links
+pub fn result_text(summary: &BTreeMap<LinkKind, LinkCounts>) -> String {
+    let all = total(summary);
+    if all.found() == 0 {
+        return String::new();
+    }
+    tf(
+        Msg::ResultLinks,
+        &[&all.found(), &all.removed, &all.skipped, &all.failed],
+    )
+}
+
+pub fn print(summary: &BTreeMap<LinkKind, LinkCounts>) {
+    if summary.is_empty() {
+        return;
+    }
+    println!("{}", t(Msg::LinksHeader));
+    for (kind, counts) in summary {
+        let mut parts = vec![tf(Msg::LinksRemoved, &[&counts.removed])];
+        if counts.skipped > 0 {
+            parts.push(tf(Msg::LinksSkipped, &[&counts.skipped]));
+        }
+        if counts.failed > 0 {
+            parts.push(tf(Msg::LinksFailed, &[&counts.failed]));
+        }
+        println!(
+            "  {:<22} {}",
+            format!("{}:", label(*kind)),
+            parts.join(", ")
+        );
+    }
+}
+
+/// Plural name of a link kind in the selected language
+fn label(kind: LinkKind) -> &'static str {
+    t(match kind {
+        LinkKind::Symlink => Msg::Symlinks,
+        LinkKind::Junction => Msg::Junctions,
+        LinkKind::MountPoint => Msg::MountPoints,
+        LinkKind::AppExecLink => Msg::AppExecLinks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmbrr_core::pipeline::Report;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_summarize() {
+        let link = |path: &str, kind, selected| FoundLink {
+            path: PathBuf::from(path),
+            kind,
+            selected,
+        };
+        let links = vec![
+            link("a", LinkKind::Symlink, true),
+            link("b", LinkKind::Symlink, false),
+            link("c", LinkKind::Junction, true),
+            link("d", LinkKind::Junction, true),
+        ];
+        let failures = vec![FailedItem {
+            path: PathBuf::from("d"),
+            error: "Access is denied".into(),
+            is_dir: false,
+            os_error: Some(5),
+        }];
+
+        let summary = summarize(&links, &failures);
+        assert_eq!(
+            summary[&LinkKind::Symlink],
+            LinkCounts {
+                removed: 1,
+                skipped: 1,
+                failed: 0
+            }
+        );
+        assert_eq!(
+            summary[&LinkKind::Junction],
+            LinkCounts {
+                removed: 1,
+                skipped: 0,
+                failed: 1
+            }
+        );
+
+        let all = total(&summary);
+        assert_eq!((all.found(), all.removed, all.skipped), (4, 2, 1));
+        let mut merged = summary.clone();
+        merge(&mut merged, &summary);
+        assert_eq!(merged[&LinkKind::Junction].failed, 2);
+    }
+
+    #[test]
+    fn test_result_text_counts_failed_link() {
+        assert_eq!(result_text(&BTreeMap::new()), "");
+
+        // What a target that partly failed reports: its links and failures travel in the Report
+        let report = Report {
+            failures: vec![FailedItem {
+                path: PathBuf::from("link"),
+                error: "Access is denied".into(),
+                is_dir: true,
+                os_error: Some(5),
+            }],
+            links: vec![FoundLink {
+                path: PathBuf::from("link"),
+                kind: LinkKind::Junction,
+                selected: true,
+            }],
+            ..Default::default()
+        };
+        let text = result_text(&summarize(&report.links, &report.failures));
+        assert!(text.contains("1 failed"), "{}", text);
+    }
+}
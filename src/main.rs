@@ -1,9 +1,62 @@
-use clap::Parser;
-use rmbrr::{broker::Broker, error::Error, safety, tree, worker};
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use i18n::{t, tf, FailureChoice, Msg};
+use reporter::{ProgressMode, Reporter};
+use rmbrr_core::error::{
+    Decision, ErrorHandler, ErrorStrategy, FailedItem, IgnoredErrors, RmError,
+};
+use rmbrr_core::events::{self, Event};
+use rmbrr_core::filter::{self, Filter};
+use rmbrr_core::manifest::{HashAlgorithm, Manifest};
+use rmbrr_core::output::{self, paint, Color, StreamKind};
+use rmbrr_core::retry::{self, RetryPolicy};
+use rmbrr_core::trace::{self, Tracer};
+use rmbrr_core::tuner::ThreadTuner;
+use rmbrr_core::winapi::{LinkKind, ProcessUse};
+use rmbrr_core::{broker::Broker, progress::Progress, safety, targets, tree, worker, Report};
+use sink::{EventSink, SinkTarget};
+use space::SpaceCheck;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
+
+mod chrome_trace;
+mod compare;
+mod compat;
+mod drive;
+mod du;
+mod evict;
+mod expect;
+mod i18n;
+mod interrupt;
+mod largest;
+mod links;
+mod owners;
+mod plan;
+mod presets;
+mod profile;
+mod quarantine;
+mod reporter;
+mod schedule;
+mod sink;
+mod snapshot;
+mod space;
+mod throughput;
+mod tree_view;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "webhook")]
+mod webhook;
+
+/// Heartbeat interval used when verbose output goes to a pipe
+const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(10);
+
+/// Workers on network shares, where each thread mostly waits on a synchronous round trip
+const NETWORK_THREADS: usize = 64;
+/// Retry defaults for network shares, where transient failures last longer
+const NETWORK_RETRIES: u32 = 5;
+const NETWORK_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 /// Windows efficient rmdir with cross-platform compatibility
 #[derive(Parser, Debug)]
@@ -21,57 +74,1006 @@ for immediate namespace removal. Benchmarks show 2-6x faster than alternatives l
   rmbrr -v ./dist                   Verbose mode (show all errors)\n  \
   rmbrr --stats ./target            Show detailed statistics\n  \
   rmbrr --confirm ./data            Ask for confirmation before deleting\n  \
-  rmbrr ./dir1 ./dir2 ./dir3        Delete multiple directories\n\n\
+  rmbrr ./dir1 ./dir2 ./dir3        Delete multiple directories\n  \
+  rmbrr --from-file targets.txt     Delete the directories listed in a file\n  \
+  rmbrr -rf ./build/                rm -rf style (missing targets are ignored)\n  \
+  rmbrr --preset temp -v            Clean old temp and cache files\n  \
+  rmbrr du ./node_modules           Measure sizes per subdirectory without deleting\n  \
+  rmbrr compare 4x8x16              Time rmbrr against other deletion methods\n\n\
 For more information, visit: https://github.com/mtopolski/rmbrr")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Target directory(s) to delete
-    #[arg(required = true)]
+    #[arg(required_unless_present_any = ["preset", "quarantine", "from_file"])]
     paths: Vec<PathBuf>,
 
-    /// Number of worker threads (default: logical CPU count)
-    #[arg(short = 't', long)]
-    threads: Option<usize>,
+    /// Also delete the directories listed in FILE, one per line (blank lines and lines
+    /// starting with # are skipped)
+    #[arg(long, value_name = "FILE")]
+    from_file: Option<PathBuf>,
+
+    /// Expand environment variables (`%TEMP%`, `$HOME`, `${NAME}`) in the targets typed on
+    /// the command line too, for callers that pass them unexpanded (scheduled tasks, service
+    /// configs); --from-file and profile targets are always expanded
+    #[arg(long)]
+    expand_env: bool,
+
+    /// Clean a built-in set of locations; `temp` empties temp and cache directories
+    /// of old files (the directories themselves are kept)
+    #[arg(long, value_enum)]
+    preset: Option<presets::Preset>,
+
+    /// Only delete files not modified for this long (e.g. 12h, 7d, 2w); directories
+    /// are removed only if they end up empty
+    #[arg(long, value_name = "AGE", value_parser = filter::parse_age)]
+    older_than: Option<Duration>,
+
+    /// Only delete files larger than this size (e.g. 100M, 2G); directories are removed
+    /// only if they end up empty
+    #[arg(long, value_name = "SIZE", value_parser = filter::parse_size)]
+    larger_than: Option<u64>,
+
+    /// Delete the entries directly in the target, least recently used first, until the
+    /// volume has this much free space (e.g. 200G); --older-than spares recent entries
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = filter::parse_size,
+        conflicts_with_all = ["only", "except", "keep", "larger_than", "keep_root", "quarantine", "preset"]
+    )]
+    until_free: Option<u64>,
+
+    /// Number of worker threads, or `auto` to start low and add workers while that still
+    /// speeds up the deletion
+    #[arg(short = 't', long, value_name = "N|auto", default_value = "auto", value_parser = parse_threads)]
+    threads: ThreadCount,
+
+    /// If a target is itself a symlink or junction, delete the directory it points to (after
+    /// checking that real path) and then the link; by default only the link is removed
+    #[arg(long)]
+    dereference_root: bool,
+
+    /// Start on the leaf directories in random order, spreading workers across the volume
+    /// instead of sibling directories (less lock contention on some filesystems)
+    #[arg(long)]
+    shuffle: bool,
+
+    /// Delete with a single worker in sorted order (directories, and the files in each), so a
+    /// hang or failure happens the same way on every run; slow, meant for bug reports
+    #[arg(long, conflicts_with_all = ["threads", "shuffle", "dir_threads"])]
+    serial: bool,
+
+    /// Scan only the directories, leaving the files to the workers that list them anyway:
+    /// less memory and a faster scan on trees of many files
+    #[arg(long, conflicts_with_all = ["tree", "snapshot", "diff"])]
+    dirs_only: bool,
+
+    /// Remove directories on a separate pool of N threads, leaving --threads to delete files
+    #[arg(long, value_name = "N")]
+    dir_threads: Option<usize>,
+
+    /// Run at background priority so other work on the machine comes first; on Windows
+    /// deletion I/O is also marked low priority
+    #[arg(long)]
+    background: bool,
+
+    /// Delete through ntdll (NtOpenFile, NtSetInformationFile) instead of the Win32 calls,
+    /// skipping a few layers per entry; falls back to Win32 where unavailable (Windows,
+    /// experimental)
+    #[arg(long)]
+    native_delete: bool,
+
+    /// Delete with the classic disposition instead of POSIX semantics, for backup, sync or
+    /// security agents that misbehave with it; entries still open elsewhere then block
+    /// their parent's removal until closed (Windows)
+    #[arg(long, conflicts_with = "native_delete")]
+    no_posix: bool,
+
+    /// Run the scan and every worker on the processors of this NUMA node (pick the node
+    /// nearest the storage controller on multi-socket servers)
+    #[arg(long, value_name = "NODE")]
+    numa_node: Option<u16>,
+
+    /// Cap rmbrr's CPU use at this percentage of all cores (Windows Job Object)
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u32).range(1..=100))]
+    limit_cpu: Option<u32>,
+
+    /// Cap rmbrr's disk bandwidth, e.g. 50M for 50 MiB/s (Windows Job Object)
+    #[arg(long, value_name = "RATE", value_parser = filter::parse_size)]
+    limit_io: Option<u64>,
+
+    /// Take ownership of the whole tree and grant Administrators full control before deleting,
+    /// for trees whose ACLs lock out even administrators (Windows, run as administrator)
+    #[arg(long)]
+    take_ownership_recursive: bool,
 
     /// Dry run - scan and plan but don't delete anything
     #[arg(short = 'n', long)]
     dry_run: bool,
 
+    /// With --dry-run, print the directories as a tree with the size and count of the files
+    /// each would lose
+    #[arg(long, requires = "dry_run")]
+    tree: bool,
+
+    /// Levels shown by --tree below the target
+    #[arg(long, value_name = "N", default_value_t = 3, requires = "tree")]
+    tree_depth: usize,
+
+    /// With --dry-run, list the N largest files that would be deleted (at most 100)
+    #[arg(long, value_name = "N", requires = "dry_run", value_parser = parse_largest)]
+    largest: Option<usize>,
+
+    /// With --dry-run, save the size of every directory of each target to FILE, for a
+    /// later --diff
+    #[arg(long, value_name = "FILE", requires = "dry_run")]
+    snapshot: Option<PathBuf>,
+
+    /// With --dry-run, compare each target with its entry in a --snapshot FILE: which
+    /// directories appeared, disappeared, grew or shrank since
+    #[arg(long, value_name = "FILE", requires = "dry_run")]
+    diff: Option<PathBuf>,
+
+    /// With --dry-run, save the scanned directories of the target to FILE for a later
+    /// --plan (compact binary, or JSON for a .json name)
+    #[arg(long, value_name = "FILE", requires = "dry_run", conflicts_with_all = PLAN_CONFLICTS)]
+    save_plan: Option<PathBuf>,
+
+    /// Delete the directories saved by --save-plan in FILE instead of scanning the target
+    /// again
+    #[arg(long, value_name = "FILE", conflicts_with_all = PLAN_CONFLICTS)]
+    plan: Option<PathBuf>,
+
+    /// Apply a version 1 --plan, which has no file IDs, once each of its directories is found
+    /// to still be a directory; newer plans are always checked by ID
+    #[arg(long, requires = "plan")]
+    verify_plan: bool,
+
     /// Show progress and completion messages
     #[arg(short = 'v', long)]
     verbose: bool,
 
-    /// Ignore errors and continue deletion (default behavior)
-    #[arg(long, default_value_t = true)]
-    ignore_errors: bool,
+    /// Print a complete progress line every SECS seconds (default 10) instead of a
+    /// redrawn progress bar; suited to CI logs
+    #[arg(long, value_name = "SECS", num_args = 0..=1, default_missing_value = "10")]
+    heartbeat: Option<u64>,
+
+    /// Print progress as JSON lines on stdout (started, dir_started, dir_done, file_done, error,
+    /// finished); other output is suppressed
+    #[arg(long)]
+    json: bool,
+
+    /// Mirror the --json event stream to a named pipe (a FIFO path on Unix, a pipe name
+    /// on Windows) or a TCP address (host:port) for external dashboards
+    #[arg(long, value_name = "TARGET")]
+    progress_pipe: Option<String>,
+
+    /// Write scan, per-directory and queue-wait timings to FILE in Chrome trace format
+    /// (chrome://tracing, ui.perfetto.dev)
+    #[arg(long, value_name = "FILE")]
+    trace_out: Option<PathBuf>,
+
+    /// Before deleting each file, record its path, size and timestamps as a JSON line in
+    /// FILE; files that cannot be recorded are not deleted
+    #[arg(long, value_name = "FILE")]
+    manifest: Option<PathBuf>,
+
+    /// Also record each file's content hash in the manifest
+    #[arg(long, value_enum, requires = "manifest")]
+    hash: Option<HashArg>,
+
+    /// What to do when an entry cannot be deleted: stop at the first error, continue and
+    /// report failures at the end, or ask each time
+    #[arg(long, value_enum, default_value_t = ErrorsArg::Continue)]
+    errors: ErrorsArg,
+
+    /// Retry deletions that fail transiently (sharing violations, busy files) up to N times
+    /// (default: 0, or 5 on network shares)
+    #[arg(long, value_name = "N")]
+    retries: Option<u32>,
+
+    /// Wait before the first retry (e.g. 200ms, 1s); doubles after each attempt
+    /// (default: 100ms, or 500ms on network shares)
+    #[arg(long, value_name = "DELAY", value_parser = retry::parse_delay)]
+    retry_delay: Option<Duration>,
+
+    /// When a directory gains entries after the scan, delete them and retry its removal up
+    /// to N times before it counts as failed
+    #[arg(long, value_name = "N", default_value_t = worker::DEFAULT_RESCANS)]
+    rescans: u32,
+
+    /// Deny everyone the right to create entries in the target while it is deleted, so
+    /// busy directories cannot refill as they empty; kept entries get their permissions
+    /// back afterwards (Windows only)
+    #[arg(long)]
+    hold_root: bool,
+
+    /// Remove directories by the file ID read before deleting starts, so one renamed in the
+    /// meantime is still found, and deep paths are not parsed again (Windows only)
+    #[arg(long)]
+    by_id: bool,
+
+    /// Tune for network shares (many workers in flight, patient retries): when the target
+    /// is detected as one, always, or never
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = NetworkProfile::Auto)]
+    network_profile: NetworkProfile,
+
+    /// Randomize retry waits by ±50% so workers don't retry in lockstep
+    #[arg(long)]
+    retry_jitter: bool,
+
+    /// Count failures of these error classes (e.g. --ignore-errors=ACCESS_DENIED) or OS
+    /// error codes without retrying them or failing the run, leaving their entries in place.
+    /// Alone, as older versions accepted it, it changes nothing (same as --errors continue)
+    #[arg(
+        long,
+        value_name = "CODES",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        value_parser = parse_ignore_errors
+    )]
+    ignore_errors: Option<IgnoredErrors>,
+
+    /// Delete the directory's contents but keep the directory itself
+    #[arg(long)]
+    keep_root: bool,
+
+    /// Required, with --keep-root, to erase the contents of a data drive's root (D:\);
+    /// a preview and a typed confirmation follow
+    #[arg(long)]
+    i_know_what_im_doing: bool,
 
     /// Ask for confirmation before deleting
     #[arg(short = 'c', long)]
     confirm: bool,
 
+    /// Refuse to delete a target whose scan finds more than N directories: a safety belt
+    /// for scripts against a variable that expanded to the wrong path
+    #[arg(long, value_name = "N", conflicts_with = "quarantine")]
+    expect_max_dirs: Option<usize>,
+
+    /// Refuse to delete a target whose scan finds more than N files
+    #[arg(long, value_name = "N", conflicts_with_all = ["dirs_only", "quarantine"])]
+    expect_max_files: Option<usize>,
+
+    /// Refuse to delete a target whose files add up to more than SIZE (e.g. 500M, 2G)
+    #[arg(long, value_name = "SIZE", value_parser = filter::parse_size, conflicts_with_all = ["dirs_only", "quarantine"])]
+    expect_max_size: Option<u64>,
+
     /// Show detailed statistics at the end
     #[arg(long)]
     stats: bool,
 
+    /// Print nothing but a one-line result at the end (for cron jobs); failures are counted
+    /// in it, while warnings and the error of a lone target still go to stderr
+    #[arg(long, conflicts_with_all = ["verbose", "stats", "json"])]
+    summary_only: bool,
+
+    /// Append the one-line result to FILE, whatever else is printed
+    #[arg(long, value_name = "FILE")]
+    summary_file: Option<PathBuf>,
+
+    /// When the run ends, POST its outcome as JSON (counts, failures or the error) to URL
+    #[cfg(feature = "webhook")]
+    #[arg(long, value_name = "URL")]
+    webhook: Option<String>,
+
+    /// Write warnings (unreadable directories, retried deletions, ...) to FILE instead of
+    /// the console
+    #[arg(long, value_name = "FILE")]
+    warnings_file: Option<PathBuf>,
+
     /// Force deletion of dangerous paths (use with extreme caution)
     #[arg(long)]
     force: bool,
+
+    /// Warn and ask before deleting git working copies with uncommitted or unpushed work
+    /// (--force skips the prompt)
+    #[arg(long)]
+    git_check: bool,
+
+    /// Delete on network shares (UNC paths, mapped drives, NFS/SMB mounts) without asking
+    #[arg(long)]
+    allow_network: bool,
+
+    /// Warn and ask before deleting a tree that running processes have as their working
+    /// directory or load modules from (--force skips the prompt)
+    #[arg(long)]
+    check_processes: bool,
+
+    /// Ignore nonexistent targets, like `rm -f` (does not override safety checks)
+    #[arg(short = 'f', long)]
+    ignore_missing: bool,
+
+    /// Accepted for `rm -r` compatibility; deletion is always recursive
+    #[arg(short = 'r', short_alias = 'R', long, hide = true)]
+    recursive: bool,
+
+    /// Only delete files matching these patterns (comma-separated, e.g. "*.tmp,*.log");
+    /// directories are removed only if they end up empty
+    #[arg(long, value_name = "PATTERNS")]
+    only: Vec<String>,
+
+    /// Delete everything except entries matching these patterns (comma-separated, e.g.
+    /// "*.dat,important/**"); a matching directory is kept with all its contents
+    #[arg(long, value_name = "PATTERNS")]
+    except: Vec<String>,
+
+    /// Keep this path (relative to the target) and everything under it; repeatable.
+    /// Ancestors of kept paths are preserved automatically
+    #[arg(long, value_name = "PATH")]
+    keep: Vec<PathBuf>,
+
+    /// Move targets into DIR instead of deleting them (a rename, so DIR must be on the same
+    /// volume); every run first deletes the entries there whose --expire age has passed
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["older_than", "larger_than", "only", "except", "keep", "keep_root", "preset"])]
+    quarantine: Option<PathBuf>,
+
+    /// How long quarantined targets are kept before a later run deletes them (default: 7d)
+    #[arg(long, value_name = "AGE", value_parser = filter::parse_age, requires = "quarantine")]
+    expire: Option<Duration>,
+
+    /// Language of prompts, warnings and summaries (default: from the locale)
+    #[arg(long, value_enum)]
+    lang: Option<i18n::Lang>,
+
+    /// When to use colored output (NO_COLOR is honored in auto mode)
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Run with the options and targets of a `[profile.NAME]` section of the config file;
+    /// options given on the command line override the profile's, targets add to them
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Config file holding the profiles (default: $RMBRR_CONFIG, else
+    /// %APPDATA%\rmbrr\config.toml on Windows and ~/.config/rmbrr/config.toml elsewhere)
+    #[arg(long, value_name = "FILE", requires = "profile")]
+    config: Option<PathBuf>,
+
+    /// Full-screen interface with per-worker activity, throughput and errors
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    tui: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Measure a directory without deleting: directory count, file count and size
+    /// per top-level subdirectory
+    Du {
+        /// Directory to measure
+        path: PathBuf,
+
+        /// Number of scanner threads (default: logical CPU count)
+        #[arg(short = 't', long)]
+        threads: Option<usize>,
+
+        /// Also list the N largest files (at most 100)
+        #[arg(long, value_name = "N", value_parser = parse_largest)]
+        largest: Option<usize>,
+    },
+    /// Time rmbrr against std::fs::remove_dir_all and the platform's own recursive delete
+    /// on identical copies of a tree, and print a comparison table
+    Compare {
+        /// Directory to copy for each run, or the shape of a tree to generate as
+        /// DEPTHxWIDTHxFILES (e.g. 4x8x16)
+        #[arg(value_name = "TREE", value_parser = compare::Source::parse)]
+        tree: compare::Source,
+
+        /// Copies deleted per method; the table shows the median
+        #[arg(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+        runs: u32,
+
+        /// Where to build the copies (default: beside the template, or the temp directory
+        /// for a generated tree)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// Register, list or remove daily cleanups run by Task Scheduler (Windows) or cron
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ScheduleAction {
+    /// Run rmbrr on a directory every day; an existing cleanup of the same name is replaced
+    Install {
+        /// Time of day to run at
+        #[arg(long, value_name = "HH:MM", value_parser = schedule::parse_time)]
+        daily: (u8, u8),
+
+        /// Directory to clean
+        #[arg(long)]
+        path: PathBuf,
+
+        /// Only delete files not modified for this long (e.g. 3d)
+        #[arg(long, value_name = "AGE", value_parser = |v: &str| filter::parse_age(v).map(|_| v.to_string()))]
+        older_than: Option<String>,
+
+        /// Name of the cleanup (default: derived from the path)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove a registered cleanup
+    Remove {
+        /// Name shown by `rmbrr schedule list`
+        name: String,
+    },
+    /// List registered cleanups
+    List,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ErrorsArg {
+    FailFast,
+    Continue,
+    Prompt,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum HashArg {
+    Sha256,
+}
+
+impl From<HashArg> for HashAlgorithm {
+    fn from(hash: HashArg) -> Self {
+        match hash {
+            HashArg::Sha256 => HashAlgorithm::Sha256,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum NetworkProfile {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ThreadCount {
+    Auto,
+    Fixed(usize),
+}
+
+/// Options that change what a scan selects, which a plan records once and for all
+const PLAN_CONFLICTS: [&str; 9] = [
+    "only",
+    "except",
+    "keep",
+    "older_than",
+    "larger_than",
+    "until_free",
+    "keep_root",
+    "quarantine",
+    "preset",
+];
+
+fn parse_threads(value: &str) -> Result<ThreadCount, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(ThreadCount::Auto);
+    }
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(ThreadCount::Fixed(n)),
+        _ => Err(format!(
+            "expected a thread count or 'auto', got '{}'",
+            value
+        )),
+    }
+}
+
+/// A count for --largest; scans keep no more than `tree::LARGEST_FILES`
+fn parse_largest(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if (1..=tree::LARGEST_FILES).contains(&n) => Ok(n),
+        _ => Err(format!(
+            "expected a count from 1 to {}, got '{}'",
+            tree::LARGEST_FILES,
+            value
+        )),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Args {
+    /// Retry settings, with defaults depending on whether the target is a network share
+    fn retry_policy(&self, network: bool) -> RetryPolicy {
+        let (retries, delay) = if network {
+            (NETWORK_RETRIES, NETWORK_RETRY_DELAY)
+        } else {
+            let local = RetryPolicy::default();
+            (local.retries, local.delay)
+        };
+        RetryPolicy {
+            retries: self.retries.unwrap_or(retries),
+            delay: self.retry_delay.unwrap_or(delay),
+            jitter: self.retry_jitter,
+        }
+    }
+
+    fn progress_mode(&self) -> Option<ProgressMode> {
+        if self.json {
+            Some(ProgressMode::Json)
+        } else if self.tui_enabled() {
+            None
+        } else if let Some(secs) = self.heartbeat {
+            Some(ProgressMode::Heartbeat(Duration::from_secs(secs.max(1))))
+        } else if !self.verbose {
+            None
+        } else {
+            // A redrawn bar only makes sense on a console; pipes (CI logs) get heartbeat
+            // lines and redirected files get no progress at all
+            match output::stdout_kind() {
+                StreamKind::Console => Some(ProgressMode::Bar),
+                StreamKind::Pipe => Some(ProgressMode::Heartbeat(DEFAULT_HEARTBEAT)),
+                StreamKind::File => None,
+            }
+        }
+    }
+
+    fn error_strategy(&self) -> ErrorStrategy {
+        match self.errors {
+            ErrorsArg::FailFast => ErrorStrategy::FailFast,
+            ErrorsArg::Continue => ErrorStrategy::Continue,
+            ErrorsArg::Prompt => ErrorStrategy::Prompt(prompt_on_error()),
+        }
+    }
+
+    fn tui_enabled(&self) -> bool {
+        #[cfg(feature = "tui")]
+        {
+            self.tui
+        }
+        #[cfg(not(feature = "tui"))]
+        {
+            false
+        }
+    }
+}
+
+/// Parses the command line, letting a repeated single-valued option replace the earlier one
+/// (a profile's value, say) while list options such as `--only` keep accumulating
+fn parse_args(argv: Vec<std::ffi::OsString>) -> Args {
+    let command = Args::command().mut_args(|arg| {
+        if arg.is_positional() || matches!(arg.get_action(), ArgAction::Append) {
+            arg
+        } else {
+            let id = arg.get_id().clone();
+            arg.overrides_with(id)
+        }
+    });
+    let matches = command.get_matches_from(argv);
+    Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
 }
 
 fn main() {
-    let args = Args::parse();
+    // A profile's options go ahead of the user's, so the command line has the last word
+    let argv = match profile::expand(std::env::args_os().collect()) {
+        Ok(argv) => argv,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }
+    };
+    // On Windows, accept `rd /S /Q`-style switches so rmbrr can replace rd in batch files
+    let mut args = if cfg!(windows) {
+        parse_args(compat::translate_cmd_switches(argv))
+    } else {
+        parse_args(argv)
+    };
+    let listed = match args.from_file {
+        Some(ref file) => match std::fs::read_to_string(file) {
+            Ok(text) => targets::parse_target_list(&text),
+            Err(e) => {
+                eprintln!("error: cannot read {}: {}", file.display(), e);
+                process::exit(2);
+            }
+        },
+        None => Vec::new(),
+    };
+    args.paths = match targets::collect_targets(&args.paths, &listed, args.expand_env) {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }
+    };
+
+    i18n::set(args.lang.unwrap_or_else(i18n::detect));
+    output::set_warning_label(t(Msg::Warning));
+    worker::set_panic_texts(worker::PanicTexts {
+        panicked: |id, dir, msg| tf(Msg::WorkerPanicked, &[&id, &dir.display(), &msg]),
+        not_retrying: |dir| tf(Msg::NotRetryingPanicked, &[&dir.display()]),
+    });
+    // Opened before anything can warn, so the file gets every warning of the run
+    if let Some(ref path) = args.warnings_file {
+        match std::fs::File::create(path) {
+            Ok(file) => output::divert_warnings(file),
+            Err(e) => {
+                eprintln!(
+                    "{} cannot create warnings file {}: {}",
+                    paint(t(Msg::Error), Color::Red),
+                    path.display(),
+                    e
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    if args.serial {
+        args.threads = ThreadCount::Fixed(1);
+    }
+
+    // Keep stdout machine-readable
+    if args.json {
+        args.verbose = false;
+        args.stats = false;
+        #[cfg(feature = "tui")]
+        {
+            args.tui = false;
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    if args.tui && output::stdout_kind() != StreamKind::Console {
+        output::warn(t(Msg::NoConsoleForTui));
+        args.tui = false;
+    }
+
+    output::set_color_enabled(match args.color {
+        ColorChoice::Auto => output::auto_color(),
+        ColorChoice::Always => {
+            rmbrr_core::winapi::enable_virtual_terminal();
+            true
+        }
+        ColorChoice::Never => false,
+    });
+
+    // Running unthrottled on a production host is worse than not running
+    if args.limit_cpu.is_some() || args.limit_io.is_some() {
+        if let Err(e) = rmbrr_core::winapi::limit_job(args.limit_cpu, args.limit_io) {
+            eprintln!(
+                "{} cannot apply resource limits: {}",
+                paint(t(Msg::Error), Color::Red),
+                e
+            );
+            process::exit(1);
+        }
+    }
+
+    // Pinning the main thread first also places the scanned tree and broker state on the node
+    if let Some(node) = args.numa_node {
+        if let Err(e) = rmbrr_core::winapi::pin_to_numa_node(node) {
+            eprintln!(
+                "{} cannot place workers on NUMA node {}: {}",
+                paint(t(Msg::Error), Color::Red),
+                node,
+                e
+            );
+            process::exit(1);
+        }
+    }
+
+    if args.take_ownership_recursive && !args.dry_run {
+        if let Err(e) = rmbrr_core::winapi::enable_take_ownership() {
+            eprintln!(
+                "{} cannot take ownership: {}",
+                paint(t(Msg::Error), Color::Red),
+                e
+            );
+            process::exit(1);
+        }
+    }
+
+    if args.background {
+        if let Err(e) = rmbrr_core::winapi::enter_background_mode() {
+            output::warn(tf(Msg::CannotLowerPriority, &[&e]));
+        }
+    }
 
-    if let Err(e) = run(args) {
-        eprintln!("Error: {}", e);
+    if args.native_delete && !rmbrr_core::winapi::enable_native_delete() {
+        output::warn(t(Msg::NativeDeleteUnavailable));
+    }
+    if args.no_posix {
+        rmbrr_core::winapi::disable_posix_delete();
+    }
+
+    interrupt::install();
+
+    let summary_only = args.summary_only;
+    let warnings_file = args.warnings_file.clone();
+    let report_warnings = args.verbose || args.stats;
+    #[cfg(feature = "webhook")]
+    let webhook = args
+        .webhook
+        .clone()
+        .map(|url| (url, args.paths.clone(), args.dry_run));
+    #[cfg(feature = "webhook")]
+    let start = Instant::now();
+    let result = run(args);
+    match result {
+        // The result line already counts the failures
+        Err(RmError::PartialFailure { .. }) if summary_only => {}
+        Err(ref e) => eprintln!("{} {}", paint(t(Msg::Error), Color::Red), e),
+        Ok(_) => {}
+    }
+    #[cfg(feature = "webhook")]
+    if let Some((url, targets, dry_run)) = webhook {
+        let summary = match result {
+            Ok(ref stats) => webhook::Summary {
+                dirs_deleted: stats.dirs_deleted,
+                files_deleted: stats.files_deleted,
+                ..Default::default()
+            },
+            Err(ref e) => webhook::Summary::failed(e),
+        };
+        let body = webhook::payload(&targets, dry_run, &summary, start.elapsed());
+        if let Err(e) = webhook::post(&url, &body) {
+            output::warn(tf(Msg::WebhookFailed, &[&e]));
+        }
+    }
+    if let Some(path) = warnings_file {
+        match output::flush_warnings() {
+            Ok(count) if count > 0 && report_warnings => {
+                println!("{}", tf(Msg::WarningsWritten, &[&count, &path.display()]))
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!(
+                "{} cannot write warnings file {}: {}",
+                paint(t(Msg::Error), Color::Red),
+                path.display(),
+                e
+            ),
+        }
+    }
+    interrupt::finished();
+    if let Err(e) = result {
         process::exit(e.exit_code());
     }
 }
 
-fn run(args: Args) -> Result<(), Error> {
+/// Per-target filter settings that don't come straight from the command line
+#[derive(Debug, Clone, Default)]
+struct TargetOptions {
+    min_age: Option<Duration>,
+    keep_root: bool,
+    /// An expired quarantine entry: deleted for real rather than quarantined again
+    purge: bool,
+    /// An entry --until-free picked inside a target that already passed the confirmations
+    evicting: bool,
+}
+
+fn run(mut args: Args) -> Result<DeletionStats, RmError> {
+    if let Some(Command::Du {
+        ref path,
+        threads,
+        largest,
+    }) = args.command
+    {
+        let measured = du::measure(path, threads.unwrap_or_else(default_threads))?;
+        du::print(path, &measured.rows);
+        if let Some(n) = largest {
+            largest::print(&largest::top(measured.largest, n));
+        }
+        return Ok(DeletionStats::default());
+    }
+    if let Some(Command::Compare {
+        ref tree,
+        runs,
+        ref dir,
+    }) = args.command
+    {
+        let dir = dir.clone().unwrap_or_else(|| tree.default_dir());
+        match compare::run(tree, runs, &dir, &compare::methods()) {
+            Ok(timings) => compare::print(&timings),
+            Err(e) => {
+                eprintln!("{} {}", paint(t(Msg::Error), Color::Red), e);
+                process::exit(1);
+            }
+        }
+        return Ok(DeletionStats::default());
+    }
+    if let Some(Command::Schedule { ref action }) = args.command {
+        if let Err(e) = schedule_command(action) {
+            eprintln!("{} {}", paint(t(Msg::Error), Color::Red), e);
+            process::exit(1);
+        }
+        return Ok(DeletionStats::default());
+    }
+
+    let mut target_options: HashMap<PathBuf, TargetOptions> = HashMap::new();
+    if let Some(preset) = args.preset {
+        let targets = presets::targets(preset);
+        if targets.is_empty() {
+            output::warn(t(Msg::PresetMatchedNothing));
+        }
+        for target in targets {
+            if args.verbose {
+                println!("Preset target: {}", target.path.display());
+            }
+            target_options.insert(
+                target.path.clone(),
+                TargetOptions {
+                    min_age: Some(target.min_age),
+                    keep_root: true,
+                    ..Default::default()
+                },
+            );
+            args.paths.push(target.path);
+        }
+    }
+
+    if let Some(ref dir) = args.quarantine {
+        let expired = match quarantine::expired(dir, SystemTime::now()) {
+            Ok(expired) => expired,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                output::warn(tf(Msg::QuarantineUnreadable, &[&dir.display(), &e]));
+                Vec::new()
+            }
+        };
+        for entry in expired {
+            if args.verbose {
+                println!("Expired quarantine entry: {}", entry.display());
+            }
+            target_options.insert(
+                entry.clone(),
+                TargetOptions {
+                    purge: true,
+                    ..Default::default()
+                },
+            );
+            args.paths.push(entry);
+        }
+    }
+
+    // Nested or duplicate targets would race two brokers over the same subtree
+    let (paths, overlaps) = targets::dedupe_targets(&args.paths);
+    if args.verbose {
+        for overlap in &overlaps {
+            println!(
+                "{}",
+                tf(
+                    Msg::SkippingCovered,
+                    &[&overlap.dropped.display(), &overlap.covered_by.display()]
+                )
+            );
+        }
+    }
+    args.paths = paths;
+    if let Some(file) = args.plan.as_ref().or(args.save_plan.as_ref()) {
+        if args.paths.len() != 1 {
+            return Err(RmError::InvalidPath {
+                path: file.clone(),
+                reason: "a plan covers exactly one target".to_string(),
+            });
+        }
+    }
+
+    let sink = match args.progress_pipe {
+        Some(ref target) => Some(Arc::new(
+            EventSink::open(&SinkTarget::parse(target)).map_err(|e| RmError::InvalidPath {
+                path: PathBuf::from(target),
+                reason: format!("cannot open progress pipe: {}", e),
+            })?,
+        )),
+        None => None,
+    };
+    // A dry run deletes nothing, so there is nothing to record
+    let manifest = match args.manifest {
+        Some(ref path) if !args.dry_run => Some(Arc::new(
+            Manifest::create(path, args.hash.map(HashAlgorithm::from)).map_err(|e| {
+                RmError::InvalidPath {
+                    path: path.clone(),
+                    reason: format!("cannot create manifest: {}", e),
+                }
+            })?,
+        )),
+        _ => None,
+    };
+    let outputs = RunOutputs {
+        sink,
+        tracer: args.trace_out.as_ref().map(|_| Arc::new(Tracer::new())),
+        manifest,
+    };
+    let write_trace = || {
+        if let (Some(path), Some(tracer)) = (&args.trace_out, &outputs.tracer) {
+            if let Err(e) = chrome_trace::write(path, tracer) {
+                output::warn(tf(Msg::TraceNotWritten, &[&path.display(), &e]));
+            }
+        }
+        if let (Some(path), Some(manifest)) = (&args.manifest, &outputs.manifest) {
+            if let Err(e) = manifest.flush() {
+                output::warn(tf(Msg::ManifestNotWritten, &[&path.display(), &e]));
+            }
+            let (hashed, hash_time) = manifest.hash_stats();
+            if args.hash.is_some() && (args.verbose || args.stats) && !args.json {
+                println!(
+                    "{}",
+                    tf(
+                        Msg::ManifestHashed,
+                        &[
+                            &space::format_bytes(hashed as i64),
+                            &duration(hash_time),
+                            &space::format_bytes(per_sec(hashed as usize, hash_time) as i64)
+                        ]
+                    )
+                );
+            }
+        }
+    };
+
     let mut total_stats = DeletionStats::default();
     let mut all_failures = Vec::new();
     let mut failed_paths = Vec::new();
+    // Adds one run's outcome to the totals; false once interrupted
+    let mut record =
+        |label: &dyn std::fmt::Display, path: &Path, result: Result<DeletionStats, RmError>| {
+            match result {
+                Ok(stats) => {
+                    total_stats.merge(&stats);
+                }
+                Err(RmError::Cancelled) => return false,
+                Err(RmError::PartialFailure { stats }) => {
+                    if !args.summary_only {
+                        eprintln!(
+                            "{} {}",
+                            paint(t(Msg::FailedToProcess), Color::Red),
+                            tf(Msg::TargetItemsFailed, &[&label, &stats.failures.len()])
+                        );
+                    }
+                    total_stats.merge(&DeletionStats::from_report(&stats));
+                    all_failures.extend(stats.failures);
+                }
+                Err(e) => {
+                    // A lone target's error is reported by main
+                    if args.paths.len() > 1 && !args.summary_only {
+                        eprintln!(
+                            "{} {}: {}",
+                            paint(t(Msg::FailedToProcess), Color::Red),
+                            label,
+                            e
+                        );
+                    }
+                    failed_paths.push((path.to_path_buf(), e));
+                }
+            }
+            true
+        };
 
+    // Plain directories share one scan and worker pool instead of running one by one, each
+    // with an idle tail while its last directories finish
+    let batch = can_batch(&args, &outputs);
+    let mut batched = Vec::new();
     for (i, path) in args.paths.iter().enumerate() {
+        let mut options = target_options.get(path).cloned().unwrap_or_default();
+        options.min_age = args.older_than.or(options.min_age);
+        options.keep_root |= args.keep_root;
+
+        // Batched targets are only checked here; their progress is printed once they start
+        if batch && is_plain_dir(path, &options) {
+            match check_root(path, &args, &options) {
+                Ok(root) => batched.push((i, root)),
+                Err(e) => {
+                    record(&path.display(), path, Err(e));
+                }
+            }
+            continue;
+        }
         if args.paths.len() > 1 && args.verbose {
             println!(
                 "\n[{}/{}] Processing: {}",
@@ -80,34 +1082,171 @@ fn run(args: Args) -> Result<(), Error> {
                 path.display()
             );
         }
-
-        match process_single_path(path, &args) {
-            Ok(stats) => {
-                total_stats.merge(&stats);
+        // Remaining targets are skipped once interrupted
+        if !record(
+            &path.display(),
+            path,
+            process_single_path(path, &args, &options, &outputs),
+        ) {
+            write_trace();
+            return Err(RmError::Cancelled);
+        }
+    }
+    if !batched.is_empty() {
+        let label = tf(Msg::ResultPaths, &[&batched.len()]);
+        if args.verbose {
+            println!("\nDeleting {} together", label);
+            for (i, root) in &batched {
+                println!(
+                    "[{}/{}] Processing: {}",
+                    i + 1,
+                    args.paths.len(),
+                    root.path.display()
+                );
             }
-            Err(e) => {
-                eprintln!("Failed to process {}: {}", path.display(), e);
-                failed_paths.push(path.to_path_buf());
-                if let Error::PartialFailure { errors, .. } = e {
-                    all_failures.extend(errors);
-                }
+        }
+        let (_, roots): (Vec<_>, Vec<_>) = batched.into_iter().unzip();
+        let paths: Vec<PathBuf> = roots.iter().map(|root| root.path.clone()).collect();
+        let result = delete_roots(roots, &args, &TargetOptions::default(), &outputs);
+        let stopped = match result {
+            Err(ref e) if !matches!(e, RmError::Cancelled | RmError::PartialFailure { .. }) => {
+                Some(e.to_string())
+            }
+            _ => None,
+        };
+        if !record(&label, &paths[0], result) {
+            write_trace();
+            return Err(RmError::Cancelled);
+        }
+        // The combined run stopped outright: every other root still there failed with it
+        if let Some(reason) = stopped {
+            for path in paths[1..].iter().filter(|p| p.symlink_metadata().is_ok()) {
+                let source = std::io::Error::other(format!("the combined run stopped: {}", reason));
+                failed_paths.push((
+                    path.clone(),
+                    RmError::RemoveDirError {
+                        path: path.clone(),
+                        source,
+                    },
+                ));
             }
         }
     }
 
+    write_trace();
+
     if args.paths.len() > 1 && args.verbose {
         print_summary(&total_stats, &all_failures, &failed_paths, &args);
     }
+    if args.summary_only || args.summary_file.is_some() {
+        let line = result_line(&total_stats, all_failures.len(), failed_paths.len(), &args);
+        if args.summary_only {
+            println!("{}", line);
+        }
+        if let Some(ref file) = args.summary_file {
+            use std::io::Write;
+            let appended = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file)
+                .and_then(|mut f| writeln!(f, "{}", line));
+            if let Err(e) = appended {
+                output::warn(tf(Msg::SummaryNotWritten, &[&file.display(), &e]));
+            }
+        }
+    }
 
-    if !failed_paths.is_empty() || !all_failures.is_empty() {
-        Err(Error::PartialFailure {
-            total: total_stats.total_items(),
-            failed: all_failures.len() + failed_paths.len(),
-            errors: all_failures,
-        })
-    } else {
-        Ok(())
+    if failed_paths.is_empty() && all_failures.is_empty() {
+        return Ok(total_stats);
+    }
+    // A single target that failed outright keeps its specific error (and exit code)
+    if args.paths.len() == 1 && all_failures.is_empty() {
+        if let Some((_, e)) = failed_paths.pop() {
+            return Err(e);
+        }
     }
+
+    all_failures.extend(failed_paths.iter().map(|(path, e)| FailedItem {
+        path: path.clone(),
+        error: e.to_string(),
+        is_dir: true,
+        os_error: None,
+    }));
+    Err(RmError::PartialFailure {
+        stats: Report {
+            dirs_deleted: total_stats.dirs_deleted,
+            files_deleted: total_stats.files_deleted,
+            scan_time: total_stats.total_scan_time,
+            delete_time: total_stats.total_delete_time,
+            failures: all_failures,
+            already_gone: total_stats.already_gone,
+            ignored: total_stats.ignored,
+            links: Vec::new(),
+        },
+    })
+}
+
+/// Refuse a target holding more than --expect-max-dirs, --expect-max-files or
+/// --expect-max-size allow
+fn check_expectations(
+    path: &Path,
+    dirs: usize,
+    files: usize,
+    bytes: u64,
+    args: &Args,
+) -> Result<(), RmError> {
+    let limits = expect::Limits {
+        dirs: args.expect_max_dirs,
+        files: args.expect_max_files,
+        bytes: args.expect_max_size,
+    };
+    let Some(reason) = limits.exceeded(dirs, files, bytes) else {
+        return Ok(());
+    };
+    Err(RmError::SafetyRefused {
+        path: path.to_path_buf(),
+        reason,
+        can_override: false,
+    })
+}
+
+/// Whether the run's targets may be deleted together: nothing about it is specific to
+/// one target or needs its own output
+fn can_batch(args: &Args, outputs: &RunOutputs) -> bool {
+    args.paths.len() > 1
+        && !args.dry_run
+        && !args.json
+        && !args.tui_enabled()
+        && outputs.sink.is_none()
+        && args.quarantine.is_none()
+        && args.until_free.is_none()
+        && args.plan.is_none()
+        && !args.by_id
+        && args.only.is_empty()
+        && args.except.is_empty()
+        && args.keep.is_empty()
+        && args.larger_than.is_none()
+}
+
+/// A target deleted as it is: an existing local directory (not a link) without per-target
+/// filters
+fn is_plain_dir(path: &Path, options: &TargetOptions) -> bool {
+    options.min_age.is_none()
+        && !options.keep_root
+        && !options.purge
+        && !targets::is_link(path)
+        && path.is_dir()
+        && !rmbrr_core::winapi::is_network_path(path)
+}
+
+/// Run-wide destinations shared by every target
+struct RunOutputs {
+    /// --progress-pipe
+    sink: Option<Arc<EventSink>>,
+    /// --trace-out
+    tracer: Option<Arc<Tracer>>,
+    /// --manifest
+    manifest: Option<Arc<Manifest>>,
 }
 
 #[derive(Default)]
@@ -116,6 +1255,12 @@ struct DeletionStats {
     files_deleted: usize,
     total_scan_time: std::time::Duration,
     total_delete_time: std::time::Duration,
+    /// Entries another process deleted first (counted as deleted too)
+    already_gone: usize,
+    /// Symlinks, junctions and other links met, by what happened to them
+    links: BTreeMap<LinkKind, links::LinkCounts>,
+    /// Failures of an --ignore-errors class; their entries were left in place
+    ignored: usize,
 }
 
 impl DeletionStats {
@@ -124,306 +1269,1526 @@ impl DeletionStats {
         self.files_deleted += other.files_deleted;
         self.total_scan_time += other.total_scan_time;
         self.total_delete_time += other.total_delete_time;
+        self.already_gone += other.already_gone;
+        self.ignored += other.ignored;
+        links::merge(&mut self.links, &other.links);
     }
 
-    fn total_items(&self) -> usize {
-        self.dirs_deleted + self.files_deleted
-    }
+    fn from_report(report: &Report) -> Self {
+        Self {
+            dirs_deleted: report.dirs_deleted,
+            files_deleted: report.files_deleted,
+            total_scan_time: report.scan_time,
+            total_delete_time: report.delete_time,
+            already_gone: report.already_gone,
+            ignored: report.ignored,
+            links: links::summarize(&report.links, &report.failures),
+        }
+    }
+}
+
+fn print_summary(
+    stats: &DeletionStats,
+    failures: &[rmbrr_core::error::FailedItem],
+    failed_paths: &[(PathBuf, RmError)],
+    args: &Args,
+) {
+    println!("\n{}", "=".repeat(60));
+    println!("{}", paint(t(Msg::Summary), Color::Green));
+    println!("{}", "=".repeat(60));
+    println!("{}", tf(Msg::PathsProcessed, &[&args.paths.len()]));
+    println!(
+        "{}",
+        tf(Msg::DirsDeleted, &[&paint(stats.dirs_deleted, Color::Cyan)])
+    );
+    println!(
+        "{}",
+        tf(
+            Msg::FilesDeleted,
+            &[&paint(stats.files_deleted, Color::Cyan)]
+        )
+    );
+    if !failures.is_empty() {
+        println!(
+            "{}",
+            tf(Msg::FailedItems, &[&paint(failures.len(), Color::Red)])
+        );
+    }
+    if !failed_paths.is_empty() {
+        println!(
+            "{}",
+            tf(Msg::FailedPaths, &[&paint(failed_paths.len(), Color::Red)])
+        );
+    }
+    if stats.ignored > 0 {
+        println!("{}", tf(Msg::IgnoredItems, &[&stats.ignored]));
+    }
+    links::print(&stats.links);
+    if args.stats {
+        println!("{}", t(Msg::Timing));
+        println!(
+            "{}",
+            tf(Msg::SumScanTime, &[&duration(stats.total_scan_time)])
+        );
+        println!(
+            "{}",
+            tf(Msg::SumDeleteTime, &[&duration(stats.total_delete_time)])
+        );
+        println!(
+            "{}",
+            tf(
+                Msg::SumTotalTime,
+                &[&duration(stats.total_scan_time + stats.total_delete_time)]
+            )
+        );
+        println!(
+            "{}",
+            tf(
+                Msg::SumDeleteRate,
+                &[
+                    &rate(per_sec(stats.files_deleted, stats.total_delete_time)),
+                    &rate(per_sec(stats.dirs_deleted, stats.total_delete_time))
+                ]
+            )
+        );
+    }
+}
+
+/// The one-line result of --summary-only and --summary-file
+fn result_line(
+    stats: &DeletionStats,
+    failed_items: usize,
+    failed_paths: usize,
+    args: &Args,
+) -> String {
+    let target = match args.paths.as_slice() {
+        [path] => path.display().to_string(),
+        paths => tf(Msg::ResultPaths, &[&paths.len()]),
+    };
+    let msg = if args.dry_run {
+        Msg::ResultWouldDelete
+    } else {
+        Msg::ResultDeleted
+    };
+    let mut line = tf(
+        msg,
+        &[
+            &target,
+            &stats.dirs_deleted,
+            &stats.files_deleted,
+            &duration(stats.total_scan_time + stats.total_delete_time),
+        ],
+    );
+    if failed_items > 0 {
+        line.push_str(&tf(Msg::ResultFailedItems, &[&failed_items]));
+    }
+    if failed_paths > 0 {
+        line.push_str(&tf(Msg::ResultFailedPaths, &[&failed_paths]));
+    }
+    if stats.ignored > 0 {
+        line.push_str(&tf(Msg::ResultIgnored, &[&stats.ignored]));
+    }
+    line.push_str(&links::result_text(&stats.links));
+    line
+}
+
+/// Print the scan, delete and total times of one target
+fn print_times(scan_time: Duration, delete_time: Duration, total_time: Duration) {
+    println!("{}", tf(Msg::ScanTime, &[&duration(scan_time)]));
+    println!("{}", tf(Msg::DeleteTime, &[&duration(delete_time)]));
+    println!("{}", tf(Msg::TotalTime, &[&duration(total_time)]));
+}
+
+/// Print the volume's reclaimed space next to the scanned size, flagging mismatches
+fn print_space(space: Option<SpaceCheck>) {
+    let Some(space) = space else {
+        return;
+    };
+    println!(
+        "{}",
+        tf(
+            Msg::SpaceFreed,
+            &[
+                &space::format_bytes(space.reclaimed),
+                &space::format_bytes(space.computed as i64)
+            ]
+        )
+    );
+    if space.shared > 0 {
+        println!(
+            "{}",
+            tf(
+                Msg::HardlinkedData,
+                &[&space::format_bytes(space.shared as i64)]
+            )
+        );
+    }
+    if space.allocated != space.computed - space.shared {
+        println!(
+            "{}",
+            tf(
+                Msg::AllocatedData,
+                &[&space::format_bytes(space.allocated as i64)]
+            )
+        );
+    }
+    if let Some(reason) = space.discrepancy() {
+        output::warn(reason);
+    }
+}
+
+fn schedule_command(action: &ScheduleAction) -> std::io::Result<()> {
+    match action {
+        ScheduleAction::Install {
+            daily,
+            path,
+            older_than,
+            name,
+        } => {
+            let path = std::path::absolute(path)?;
+            let exe = std::env::current_exe()?;
+            let mut run_args = Vec::new();
+            if let Some(age) = older_than {
+                run_args.extend(["--older-than".to_string(), age.clone()]);
+            }
+            run_args.push(path.to_string_lossy().into_owned());
+            let schedule = schedule::Schedule {
+                name: name
+                    .clone()
+                    .unwrap_or_else(|| schedule::default_name(&path)),
+                at: *daily,
+                args: run_args,
+            };
+            schedule::install(&exe.to_string_lossy(), &schedule)?;
+            println!(
+                "{}",
+                tf(
+                    Msg::ScheduleInstalled,
+                    &[&schedule.name, &format!("{:02}:{:02}", daily.0, daily.1)]
+                )
+            );
+        }
+        ScheduleAction::Remove { name } => {
+            schedule::remove(name)?;
+            println!("{}", tf(Msg::ScheduleRemoved, &[name]));
+        }
+        ScheduleAction::List => {
+            let entries = schedule::list()?;
+            if entries.is_empty() {
+                println!("{}", t(Msg::ScheduleEmpty));
+            }
+            for entry in entries {
+                println!("{}", entry);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Delete the entries directly in `path`, least recently used first, until its volume has
+/// `goal` bytes free (--until-free)
+fn free_up(
+    path: &Path,
+    goal: u64,
+    args: &Args,
+    options: &TargetOptions,
+    outputs: &RunOutputs,
+) -> Result<DeletionStats, RmError> {
+    let scan_error = |source| RmError::ScanError {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut free = rmbrr_core::winapi::free_space(path).map_err(scan_error)?;
+    let candidates = evict::candidates(path, options.min_age).map_err(scan_error)?;
+    let entry_options = TargetOptions {
+        evicting: true,
+        ..Default::default()
+    };
+    // Loose files go the way workers delete theirs: into the manifest, and past ignored errors
+    let file_config = worker::WorkerConfig {
+        errors: args.error_strategy(),
+        retry: args.retry_policy(rmbrr_core::winapi::is_network_path(path)),
+        manifest: outputs.manifest.clone(),
+        ignore_errors: args.ignore_errors.clone().unwrap_or_default(),
+        ..Default::default()
+    };
+    let error_tracker = worker::ErrorTracker::new();
+
+    let mut stats = DeletionStats::default();
+    for candidate in candidates {
+        if free >= goal {
+            break;
+        }
+        if args.dry_run {
+            // Nothing is freed, so estimate from the sizes
+            let size = if candidate.is_dir {
+                tree::discover_tree_sized(&candidate.path, None)
+                    .map(|tree| tree.allocated_bytes)
+                    .unwrap_or(0)
+            } else {
+                candidate.path.symlink_metadata().map_or(0, |m| m.len())
+            };
+            println!(
+                "{}",
+                tf(
+                    Msg::WouldEvict,
+                    &[&candidate.path.display(), &space::format_bytes(size as i64)]
+                )
+            );
+            free += size;
+            continue;
+        }
+        if candidate.is_dir {
+            stats.merge(&process_single_path(
+                &candidate.path,
+                args,
+                &entry_options,
+                outputs,
+            )?);
+        } else {
+            let (failed, ignored) = (error_tracker.failure_count(), error_tracker.ignored_count());
+            worker::delete_files(
+                std::slice::from_ref(&candidate.path),
+                &file_config,
+                &error_tracker,
+            );
+            if let Some(item) = error_tracker.aborted_by() {
+                return Err(item.to_error());
+            }
+            if error_tracker.failure_count() > failed {
+                return Err(RmError::PartialFailure {
+                    stats: Report {
+                        dirs_deleted: stats.dirs_deleted,
+                        files_deleted: stats.files_deleted,
+                        scan_time: stats.total_scan_time,
+                        delete_time: stats.total_delete_time,
+                        failures: error_tracker.get_failures(),
+                        already_gone: stats.already_gone + error_tracker.gone_count(),
+                        ignored: stats.ignored + error_tracker.ignored_count(),
+                        links: Vec::new(),
+                    },
+                });
+            }
+            if error_tracker.ignored_count() == ignored {
+                stats.files_deleted += 1;
+            }
+        }
+        free = rmbrr_core::winapi::free_space(path).map_err(scan_error)?;
+    }
+    stats.already_gone += error_tracker.gone_count();
+    stats.ignored += error_tracker.ignored_count();
+
+    let amounts: [&dyn std::fmt::Display; 2] = [
+        &space::format_bytes(free as i64),
+        &space::format_bytes(goal as i64),
+    ];
+    if free < goal {
+        output::warn(tf(Msg::UntilFreeShort, &amounts));
+    } else if args.verbose {
+        println!("{}", tf(Msg::UntilFreeReached, &amounts));
+    }
+    Ok(stats)
+}
+
+/// The tree saved in `file`, which must be a plan for `path` whose directories are still
+/// the ones on disk
+fn load_plan(
+    file: &Path,
+    path: &Path,
+    accept_unidentified: bool,
+) -> std::io::Result<tree::DirectoryTree> {
+    let plan = plan::load(file)?;
+    let same = |a: &Path, b: &Path| match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    };
+    if !same(&plan.root, path) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("the plan {} is for {}", file.display(), plan.root.display()),
+        ));
+    }
+    plan::verify(&plan, accept_unidentified)?;
+    Ok(plan.tree)
+}
+
+/// Count a root link removed by [`remove_link`] in the run's link audit
+fn count_removed_link(stats: &mut DeletionStats, kind: Option<LinkKind>) {
+    if let Some(kind) = kind {
+        stats.links.entry(kind).or_default().removed += 1;
+    }
+}
+
+fn remove_link(path: &Path) -> Result<(), RmError> {
+    targets::remove_link(path).map_err(|source| RmError::RemoveDirError {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn default_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+fn per_sec(count: usize, time: Duration) -> f64 {
+    count as f64 / time.as_secs_f64().max(0.001)
+}
+
+/// A duration as the summaries show it
+fn duration(time: Duration) -> String {
+    format!("{:.2?}", time)
+}
+
+/// A per-second rate as the summaries show it
+fn rate(per_sec: f64) -> String {
+    format!("{:.0}", per_sec)
 }
 
-fn print_summary(
-    stats: &DeletionStats,
-    failures: &[rmbrr::error::FailedItem],
-    failed_paths: &[PathBuf],
-    args: &Args,
-) {
-    println!("\n{}", "=".repeat(60));
-    println!("SUMMARY");
-    println!("{}", "=".repeat(60));
-    println!("Paths processed: {}", args.paths.len());
-    println!("Directories deleted: {}", stats.dirs_deleted);
-    println!("Files deleted: {}", stats.files_deleted);
-    if !failures.is_empty() {
-        println!("Failed items: {}", failures.len());
-    }
-    if !failed_paths.is_empty() {
-        println!("Failed paths: {}", failed_paths.len());
+/// `--ignore-errors` alone ignores nothing, so `rmbrr --ignore-errors DIR` keeps working
+fn parse_ignore_errors(value: &str) -> Result<IgnoredErrors, String> {
+    if value.is_empty() {
+        Ok(IgnoredErrors::default())
+    } else {
+        IgnoredErrors::parse(value)
     }
-    if args.stats {
-        println!("\nTiming:");
-        println!("  Total scan time:   {:.2?}", stats.total_scan_time);
-        println!("  Total delete time: {:.2?}", stats.total_delete_time);
-        println!(
-            "  Total time:        {:.2?}",
-            stats.total_scan_time + stats.total_delete_time
+}
+
+/// Ask on the console what to do about each failure, robocopy-style: skip it, retry it,
+/// skip this and every later failure without asking, or abort
+fn prompt_on_error() -> ErrorHandler {
+    use std::io::{self, BufRead, Write};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    let skip_all = AtomicBool::new(false);
+    // Workers fail concurrently; one question at a time
+    let asking = Mutex::new(());
+    Arc::new(move |item: &FailedItem| {
+        let _asking = asking.lock().unwrap();
+        if skip_all.load(Ordering::Relaxed) {
+            return Decision::Skip;
+        }
+        eprintln!(
+            "\n{} {}: {}",
+            paint(t(Msg::FailedToDelete), Color::Red),
+            item.path.display(),
+            item.error
         );
-    }
+        loop {
+            eprint!("{}", t(Msg::FailurePrompt));
+            io::stderr().flush().ok();
+
+            let mut response = String::new();
+            // Nobody left to answer: stop rather than guess
+            if io::stdin().lock().read_line(&mut response).unwrap_or(0) == 0 {
+                return Decision::Abort;
+            }
+            match i18n::failure_choice(&response) {
+                Some(FailureChoice::Skip) => return Decision::Skip,
+                Some(FailureChoice::Retry) => return Decision::Retry,
+                Some(FailureChoice::SkipAll) => {
+                    skip_all.store(true, Ordering::Relaxed);
+                    return Decision::Skip;
+                }
+                Some(FailureChoice::Abort) => return Decision::Abort,
+                None => {}
+            }
+        }
+    })
 }
 
-fn process_single_path(path: &Path, args: &Args) -> Result<DeletionStats, Error> {
-    if !path.exists() {
-        return Err(Error::InvalidPath {
-            path: path.to_path_buf(),
-            reason: "path does not exist".to_string(),
-        });
+/// Most common failure messages first, with how often each occurred
+fn failure_causes(failures: &[FailedItem]) -> Vec<(&str, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for failure in failures {
+        *counts.entry(failure.error.as_str()).or_default() += 1;
     }
+    let mut causes: Vec<_> = counts.into_iter().collect();
+    causes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    causes
+}
 
-    if !path.is_dir() {
-        return Err(Error::InvalidPath {
-            path: path.to_path_buf(),
-            reason: "not a directory".to_string(),
-        });
-    }
+/// Read a yes/no answer from stdin; anything but a yes in the selected language or English
+/// (including EOF) means no
+fn ask_yes_no() -> bool {
+    use std::io::{self, BufRead};
+    let mut response = String::new();
+    io::stdin().lock().read_line(&mut response).ok();
 
+    i18n::is_yes(&response)
+}
+
+/// A target that passed the safety checks
+struct CheckedRoot {
+    path: PathBuf,
+    /// Held until the root is removed through it (none on dry runs)
+    pin: Option<rmbrr_core::winapi::RootPin>,
+    /// The volume to confirm before emptying a drive root
+    drive_wipe: Option<String>,
+}
+
+/// Pin `path` and run the safety checks on it, printing why it was refused
+fn check_root(path: &Path, args: &Args, options: &TargetOptions) -> Result<CheckedRoot, RmError> {
+    // Held from before the safety checks until the root is removed through it, so the
+    // directory checked is the one deleted even if the path is swapped meanwhile
+    let pin = if args.dry_run {
+        None
+    } else {
+        match rmbrr_core::winapi::RootPin::open(path) {
+            Ok(pin) => Some(pin),
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidInput => {
+                return Err(RmError::InvalidPath {
+                    path: path.to_path_buf(),
+                    reason: e.to_string(),
+                });
+            }
+            Err(e) => {
+                output::warn(tf(Msg::RootNotPinned, &[&path.display(), &e]));
+                None
+            }
+        }
+    };
+
+    let mut drive_wipe = None;
     match safety::check_path_safety(path) {
         safety::SafetyCheck::Safe => {}
+        // --force is not enough here: each gate has to be passed explicitly
+        safety::SafetyCheck::DriveRoot { reason } => {
+            if !options.keep_root || !args.i_know_what_im_doing {
+                eprintln!("\n{}", paint(t(Msg::CannotDeleteDriveRoot), Color::Red));
+                eprintln!("   {}", reason);
+                eprintln!("{}", t(Msg::DriveRootHint));
+                eprintln!(
+                    "   rmbrr --keep-root --i-know-what-im-doing {}",
+                    path.display()
+                );
+                eprintln!();
+
+                return Err(RmError::SafetyRefused {
+                    path: path.to_path_buf(),
+                    reason,
+                    can_override: false,
+                });
+            }
+            drive_wipe = safety::drive_letter(path)
+                .map(|letter| format!("{}:", letter))
+                .or_else(|| safety::volume_guid(path));
+        }
         safety::SafetyCheck::Dangerous {
             reason,
             can_override,
         } => {
             if !args.force {
-                eprintln!("\n⚠️  WARNING: Dangerous operation detected!");
+                eprintln!("\n{}", paint(t(Msg::DangerousDetected), Color::Yellow));
                 eprintln!("   {}", reason);
                 eprintln!();
 
                 if can_override {
-                    eprintln!("   To proceed anyway, use the --force flag");
-                    eprintln!("   Example: rmbrr --force {}", path.display());
+                    eprintln!("{}", t(Msg::ProceedWithForce));
+                    eprintln!("{}", tf(Msg::ForceExample, &[&path.display()]));
                 } else {
-                    eprintln!("   This path cannot be deleted for safety reasons.");
-                    eprintln!("   Deletion of system directories is not allowed.");
+                    eprintln!("{}", t(Msg::CannotDeleteForSafety));
+                    eprintln!("{}", t(Msg::SystemDirsNotAllowed));
                 }
                 eprintln!();
 
-                return Err(Error::InvalidPath {
+                return Err(RmError::SafetyRefused {
                     path: path.to_path_buf(),
-                    reason: "dangerous path - requires --force (if allowed)".to_string(),
+                    reason,
+                    can_override,
                 });
             } else if !can_override {
-                eprintln!("\n⛔ ERROR: Cannot delete system directory");
+                eprintln!("\n{}", paint(t(Msg::CannotDeleteSystemDir), Color::Red));
                 eprintln!("   {}", reason);
-                eprintln!("   System directories cannot be deleted even with --force");
+                eprintln!("{}", t(Msg::SystemDirsEvenWithForce));
                 eprintln!();
 
-                return Err(Error::InvalidPath {
+                return Err(RmError::SafetyRefused {
                     path: path.to_path_buf(),
-                    reason: "system directory cannot be deleted".to_string(),
+                    reason,
+                    can_override,
                 });
             } else if args.verbose {
-                eprintln!("\n⚠️  WARNING: Deleting dangerous path with --force");
+                eprintln!(
+                    "\n{}",
+                    paint(t(Msg::DeletingDangerousWithForce), Color::Yellow)
+                );
                 eprintln!("   {}", reason);
                 eprintln!();
             }
         }
     }
+    Ok(CheckedRoot {
+        path: path.to_path_buf(),
+        pin,
+        drive_wipe,
+    })
+}
+
+fn process_single_path(
+    path: &Path,
+    args: &Args,
+    options: &TargetOptions,
+    outputs: &RunOutputs,
+) -> Result<DeletionStats, RmError> {
+    if args.ignore_missing && path.symlink_metadata().is_err() {
+        return Ok(DeletionStats::default());
+    }
+
+    // A contents-only target (presets) names the directory to empty, wherever it lives
+    if targets::is_link(path) {
+        if args.dereference_root || options.keep_root {
+            let real = path.canonicalize().map_err(|e| RmError::InvalidPath {
+                path: path.to_path_buf(),
+                reason: format!("cannot resolve link: {}", e),
+            })?;
+            if args.verbose {
+                println!("{} is a link to {}", path.display(), real.display());
+            }
+            // The real path goes through every safety check on its own
+            let mut stats = process_single_path(&real, args, options, outputs)?;
+            if !args.dry_run && !options.keep_root {
+                let kind = rmbrr_core::winapi::link_kind(path);
+                remove_link(path)?;
+                count_removed_link(&mut stats, kind);
+            }
+            return Ok(stats);
+        }
+        // The link itself is the one entry removed
+        check_expectations(path, 1, 0, 0, args)?;
+        let mut stats = DeletionStats {
+            dirs_deleted: 1,
+            ..Default::default()
+        };
+        if args.dry_run {
+            println!("{}", tf(Msg::WouldRemoveLink, &[&path.display()]));
+        } else {
+            let kind = rmbrr_core::winapi::link_kind(path);
+            remove_link(path)?;
+            count_removed_link(&mut stats, kind);
+            if args.verbose {
+                println!("{}", tf(Msg::RemovedLink, &[&path.display()]));
+            }
+        }
+        return Ok(stats);
+    }
+
+    if !path.exists() {
+        // Pointed out rather than picked: a near miss may be just as wrong to delete
+        let similar: Vec<_> = targets::similar_dirs(path)
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        let reason = if similar.is_empty() {
+            "path does not exist".to_string()
+        } else {
+            format!(
+                "path does not exist; did you mean {}?",
+                similar.join(" or ")
+            )
+        };
+        return Err(RmError::InvalidPath {
+            path: path.to_path_buf(),
+            reason,
+        });
+    }
+
+    if !path.is_dir() {
+        return Err(RmError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: "not a directory".to_string(),
+        });
+    }
+
+    let root = check_root(path, args, options)?;
 
     if args.dry_run && args.verbose {
-        println!("DRY RUN MODE - no files will be deleted");
+        println!("{}", t(Msg::DryRunMode));
     }
 
-    let worker_count = args.threads.unwrap_or_else(|| {
-        std::thread::available_parallelism()
-            .map(|n| n.get())
-            .unwrap_or(4)
-    });
+    if let Some(dir) = args.quarantine.as_ref().filter(|_| !options.purge) {
+        if args.dry_run {
+            println!(
+                "{}",
+                tf(Msg::WouldQuarantine, &[&path.display(), &dir.display()])
+            );
+            return Ok(DeletionStats::default());
+        }
+        // The pin would keep the root from moving
+        drop(root);
+        let expire = args.expire.unwrap_or(quarantine::DEFAULT_EXPIRE);
+        let moved =
+            quarantine::quarantine(path, dir, expire).map_err(|e| RmError::InvalidPath {
+                path: path.to_path_buf(),
+                reason: format!(
+                    "cannot move into quarantine {} (it must be on the same volume): {}",
+                    dir.display(),
+                    e
+                ),
+            })?;
+        if args.verbose {
+            println!(
+                "{}",
+                tf(Msg::Quarantined, &[&path.display(), &moved.display()])
+            );
+        }
+        return Ok(DeletionStats::default());
+    }
 
-    if args.verbose {
-        println!("Scanning directory tree: {}", path.display());
+    let on_share = rmbrr_core::winapi::is_network_path(path);
+    // Deleting the wrong share folder reaches far beyond one machine; --force doesn't cover it
+    if on_share && !args.allow_network && !args.dry_run && !options.evicting {
+        eprintln!(
+            "\n{}",
+            paint(tf(Msg::NetworkTarget, &[&path.display()]), Color::Yellow)
+        );
+        eprintln!("{}", t(Msg::AllowNetworkHint));
+        eprintln!();
+        println!("{}", t(Msg::DeleteAnyway));
+        if !ask_yes_no() {
+            println!("{}", t(Msg::Aborted));
+            return Ok(DeletionStats::default());
+        }
+    }
+
+    if let Some(goal) = args.until_free.filter(|_| !options.evicting) {
+        return free_up(path, goal, args, options, outputs);
+    }
+
+    delete_roots(vec![root], args, options, outputs)
+}
+
+/// Scan the roots into one tree and delete it with a single broker and worker pool
+///
+/// The roots must not overlap. Labels and the full-screen interface use the first root.
+fn delete_roots(
+    roots: Vec<CheckedRoot>,
+    args: &Args,
+    options: &TargetOptions,
+    outputs: &RunOutputs,
+) -> Result<DeletionStats, RmError> {
+    let first = roots[0].path.clone();
+    let path = first.as_path();
+    let drive_wipe = roots[0].drive_wipe.clone();
+    let on_share = roots
+        .iter()
+        .any(|root| rmbrr_core::winapi::is_network_path(&root.path));
+    let network = match args.network_profile {
+        NetworkProfile::Auto => on_share,
+        NetworkProfile::Always => true,
+        NetworkProfile::Never => false,
+    };
+    if network && args.verbose {
+        println!("{}", t(Msg::NetworkProfileUsed));
+    }
+
+    // Throughput tuning reads local-disk behaviour; over the network, round trips dominate
+    let tuner = (args.threads == ThreadCount::Auto && !network)
+        .then(|| Arc::new(ThreadTuner::auto(default_threads())));
+    let worker_count = match args.threads {
+        ThreadCount::Fixed(n) => n,
+        ThreadCount::Auto if network => NETWORK_THREADS,
+        ThreadCount::Auto => tuner.as_ref().map_or(1, |t| t.max()),
+    };
+
+    // Unreadable directories would otherwise be missing from the scan altogether
+    let take_ownership = args.take_ownership_recursive && !args.dry_run;
+    if take_ownership {
+        for root in &roots {
+            if args.verbose {
+                println!("Taking ownership: {}", root.path.display());
+            }
+            let (taken, failed) = rmbrr_core::winapi::take_ownership_recursive(&root.path);
+            if args.verbose {
+                println!("Took ownership of {} entries", taken);
+            }
+            if failed > 0 {
+                output::warn(tf(Msg::CannotTakeOwnership, &[&failed]));
+            }
+        }
     }
+
     let start = Instant::now();
 
-    let tree = tree::discover_tree(path).map_err(|e| Error::io_with_path(path.to_path_buf(), e))?;
+    let mut filter = Filter::new(path);
+    filter.only = filter::parse_patterns(&args.only);
+    filter.except = filter::parse_patterns(&args.except);
+    for keep in &args.keep {
+        let full = path.join(keep);
+        if full.symlink_metadata().is_err() {
+            output::warn(tf(Msg::KeepPathNotFound, &[&full.display()]));
+        }
+        filter.add_keep(keep);
+    }
+    filter.min_age = options.min_age;
+    filter.min_size = args.larger_than;
+    filter.keep_root = options.keep_root;
+    let filter = filter.is_selective().then(|| Arc::new(filter));
+
+    // Sizes feed the free-space check and drive-wipe preview; on Unix they cost a stat per file
+    let measure = args.verbose
+        || args.stats
+        || args.largest.is_some()
+        || drive_wipe.is_some()
+        || args.expect_max_size.is_some();
+    let error_tracker = Arc::new(worker::ErrorTracker::new());
+    let batched = roots.len() > 1;
+    let mut tree: Option<tree::DirectoryTree> = None;
+    // Each root's pin, with the root as the tree spells it
+    let mut pins = Vec::new();
+    let mut scanned_roots = Vec::new();
+    for root in roots {
+        if args.verbose {
+            println!("Scanning directory tree: {}", root.path.display());
+        }
+        let scan_start = Instant::now();
+        let scanned = match args.plan {
+            Some(ref file) => load_plan(file, &root.path, args.verify_plan),
+            None if args.by_id => {
+                tree::discover_tree_by_id(&root.path, filter.as_deref(), measure, !args.dirs_only)
+            }
+            None if args.dirs_only => {
+                tree::discover_tree_dirs_only(&root.path, filter.as_deref(), measure)
+            }
+            None if measure => tree::discover_tree_sized(&root.path, filter.as_deref()),
+            None => tree::discover_tree_filtered(&root.path, filter.as_deref()),
+        };
+        if let Some(ref tracer) = outputs.tracer {
+            let name = format!("scan {}", root.path.display());
+            tracer.record("scan", name, trace::MAIN_THREAD, scan_start);
+        }
+        let scanned = scanned
+            .map_err(|source| RmError::ScanError {
+                path: root.path.clone(),
+                source,
+            })
+            .and_then(|scanned| {
+                // --until-free picks its entries by the space it needs, whatever their size
+                if !options.evicting {
+                    let dirs = scanned.dirs.len() - scanned.kept_dirs.len();
+                    check_expectations(&root.path, dirs, scanned.file_count, scanned.bytes, args)?;
+                }
+                Ok(scanned)
+            });
+        let scanned = match scanned {
+            Ok(scanned) => scanned,
+            // Alongside other roots, one that can't be scanned (or exceeds --expect-max-*)
+            // fails on its own
+            Err(error) if batched => {
+                let os_error = match error {
+                    RmError::ScanError { ref source, .. } => source.raw_os_error(),
+                    _ => None,
+                };
+                error_tracker.record_failure(FailedItem {
+                    path: root.path,
+                    error: error.to_string(),
+                    is_dir: true,
+                    os_error,
+                });
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        if let Some(pin) = root.pin {
+            let spelled = scanned.dirs.first().cloned().unwrap_or(root.path.clone());
+            pins.push((pin, spelled));
+        }
+        scanned_roots.push(root.path);
+        match tree {
+            Some(ref mut tree) => tree.merge(scanned),
+            None => tree = Some(scanned),
+        }
+    }
+    // Unreadable directories are reported before anything about the scan's results
+    output::flush_log();
 
     let scan_time = start.elapsed();
-    let dir_count = tree.dirs.len();
+    let Some(mut tree) = tree else {
+        return Err(RmError::PartialFailure {
+            stats: Report {
+                scan_time,
+                failures: error_tracker.get_failures(),
+                ..Default::default()
+            },
+        });
+    };
+    if interrupt::interrupted() {
+        return Err(RmError::Cancelled);
+    }
+    let kept_count = tree.kept_dirs.len();
+    let dir_count = tree.dirs.len() - kept_count;
     let file_count = tree.file_count;
+    let scanned_count = tree.dirs.len() + file_count;
+    // A handle per directory, so only looked up for --stats (and before they are gone)
+    let case_sensitive_dirs = if args.stats {
+        tree.dirs
+            .iter()
+            .filter(|dir| rmbrr_core::winapi::is_case_sensitive_dir(dir))
+            .count()
+    } else {
+        0
+    };
+    let bytes = tree.bytes;
+    let shared_bytes = tree.shared_bytes;
+    let allocated_bytes = tree.allocated_bytes;
+    let encrypted_files = tree.encrypted_files;
+    let longest_path = tree.longest_path.take();
+    let deepest_dir = tree.deepest_dir.take();
+
+    // Both explain failures to come, so they are pointed out before deleting
+    if tree.too_long_paths > 0 {
+        output::warn(tf(
+            Msg::PathsTooLong,
+            &[&tree.too_long_paths, &tree::MAX_PATH_CHARS],
+        ));
+    }
+    if let Some((ref dir, depth)) = deepest_dir {
+        if depth > tree::SUSPICIOUS_DEPTH {
+            output::warn(tf(Msg::SuspiciousDepth, &[&dir.display(), &depth]));
+        }
+    }
 
     if args.verbose {
         println!(
             "Found {} directories ({} initial leaves), {} files in {:.2?}",
-            dir_count,
+            tree.dirs.len(),
             tree.leaves.len(),
             file_count,
             scan_time
         );
+        if filter.is_some() {
+            println!(
+                "{} directories will be kept because they hold kept or unmatched entries",
+                kept_count
+            );
+        }
+    }
+
+    let aborted = DeletionStats {
+        total_scan_time: scan_time,
+        ..Default::default()
+    };
+
+    if let Some(ref expected) = drive_wipe {
+        drive::preview(path, dir_count, file_count, bytes);
+        if !args.dry_run && !drive::confirm(expected) {
+            println!("{}", t(Msg::Aborted));
+            return Ok(aborted);
+        }
+    }
+
+    if args.git_check {
+        let risks = safety::find_git_risks(&tree.dirs);
+        if !risks.is_empty() {
+            eprintln!("\n{}", paint(t(Msg::GitWarning), Color::Yellow));
+            for risk in &risks {
+                eprintln!(
+                    "{}",
+                    tf(
+                        Msg::GitRisk,
+                        &[&risk.repo.display(), &risk.uncommitted, &risk.unpushed]
+                    )
+                );
+            }
+            eprintln!();
+
+            if !args.dry_run && !args.force {
+                println!("{}", t(Msg::DeleteAnyway));
+                if !ask_yes_no() {
+                    println!("{}", t(Msg::Aborted));
+                    return Ok(aborted);
+                }
+            }
+        }
+    }
+
+    if args.check_processes {
+        let processes: Vec<_> = scanned_roots
+            .iter()
+            .flat_map(|root| {
+                let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+                rmbrr_core::winapi::processes_using(&root)
+            })
+            .collect();
+        if !processes.is_empty() {
+            eprintln!(
+                "\n{}",
+                paint(
+                    tf(Msg::ProcessesWarning, &[&processes.len()]),
+                    Color::Yellow
+                )
+            );
+            for process in &processes {
+                for usage in &process.uses {
+                    let (msg, used) = match usage {
+                        ProcessUse::WorkingDir(dir) => (Msg::ProcessCwd, dir),
+                        ProcessUse::Module(module) => (Msg::ProcessModule, module),
+                    };
+                    eprintln!(
+                        "{}",
+                        tf(msg, &[&process.pid, &process.name, &used.display()])
+                    );
+                }
+            }
+            eprintln!();
+
+            if !args.dry_run && !args.force {
+                println!("{}", t(Msg::DeleteAnyway));
+                if !ask_yes_no() {
+                    println!("{}", t(Msg::Aborted));
+                    return Ok(aborted);
+                }
+            }
+        }
     }
 
     if args.confirm && !args.dry_run {
-        println!("\nAbout to delete:");
-        println!("  {} directories", dir_count);
-        println!("  {} files", file_count);
-        println!("  Total: {} items", dir_count + file_count);
-        println!("\nAre you sure? [y/N] ");
-
-        use std::io::{self, BufRead};
-        let stdin = io::stdin();
-        let mut response = String::new();
-        stdin.lock().read_line(&mut response).ok();
-
-        let response = response.trim().to_lowercase();
-        if response != "y" && response != "yes" {
-            println!("Aborted.");
-            return Ok(DeletionStats {
-                dirs_deleted: 0,
-                files_deleted: 0,
-                total_scan_time: scan_time,
-                total_delete_time: std::time::Duration::ZERO,
-            });
+        println!("{}", t(Msg::AboutToDelete));
+        println!("{}", tf(Msg::Dirs, &[&dir_count]));
+        println!("{}", tf(Msg::Files, &[&file_count]));
+        println!("{}", tf(Msg::TotalItems, &[&(dir_count + file_count)]));
+        println!("{}", t(Msg::AreYouSure));
+
+        if !ask_yes_no() {
+            println!("{}", t(Msg::Aborted));
+            return Ok(aborted);
         }
     }
 
+    // Rates of earlier runs on this volume estimate the duration before any deletion; roots
+    // spread over several volumes have no one volume to learn from
+    let volume = rmbrr_core::winapi::volume_id(path).ok().filter(|volume| {
+        scanned_roots
+            .iter()
+            .all(|root| rmbrr_core::winapi::volume_id(root).ok().as_ref() == Some(volume))
+    });
+    let history_file = throughput::history_path();
+    let history = history_file
+        .as_deref()
+        .zip(volume.as_deref())
+        .and_then(|(file, volume)| throughput::load(file, volume));
+    let estimate = history.and_then(|(rates, runs)| {
+        let time = rates.estimate(file_count, dir_count)?;
+        Some(tf(
+            Msg::EstimatedTime,
+            &[&reporter::format_eta(time), &runs],
+        ))
+    });
+
     if args.dry_run {
+        if let Some(n) = args.largest {
+            largest::print(&tree.largest[..n.min(tree.largest.len())]);
+        }
+        if args.tree {
+            println!();
+            for line in tree_view::render(path, &tree, args.tree_depth) {
+                println!("{}", line);
+            }
+        }
+        if args.diff.is_some() || args.snapshot.is_some() {
+            let now = snapshot::Snapshot::take(path, &tree);
+            // Compared before saving, so one file can serve as both
+            if let Some(ref file) = args.diff {
+                match snapshot::load(file, path) {
+                    Ok(Some(before)) => snapshot::print_diff(path, &before, &now),
+                    Ok(None) => {
+                        output::warn(tf(Msg::DiffNoEntry, &[&path.display(), &file.display()]))
+                    }
+                    Err(e) => output::warn(tf(Msg::DiffUnreadable, &[&file.display(), &e])),
+                }
+            }
+            if let Some(ref file) = args.snapshot {
+                if let Err(e) = snapshot::save(file, path, &now) {
+                    output::warn(tf(Msg::SnapshotNotWritten, &[&file.display(), &e]));
+                }
+            }
+        }
+        if let Some(ref file) = args.save_plan {
+            if let Err(e) = plan::save(file, path, &tree, plan::Format::for_path(file)) {
+                output::warn(tf(Msg::PlanNotWritten, &[&file.display(), &e]));
+            }
+        }
         if args.verbose {
             println!("\n{}", "=".repeat(60));
-            println!("DRY RUN RESULTS");
+            println!("{}", t(Msg::DryRunResults));
             println!("{}", "=".repeat(60));
-            println!("\nWould delete:");
-            println!("  {} directories", dir_count);
-            println!("  {} files", file_count);
-            println!("  {} total items", dir_count + file_count);
+            println!("{}", t(Msg::WouldDelete));
+            println!("{}", tf(Msg::Dirs, &[&dir_count]));
+            println!("{}", tf(Msg::Files, &[&file_count]));
+            println!("{}", tf(Msg::TotalItemsShort, &[&(dir_count + file_count)]));
+            println!(
+                "{}",
+                tf(Msg::FileData, &[&space::format_bytes(bytes as i64)])
+            );
+            if shared_bytes > 0 {
+                println!(
+                    "{}",
+                    tf(
+                        Msg::HardlinkedData,
+                        &[&space::format_bytes(shared_bytes as i64)]
+                    )
+                );
+            }
+            if allocated_bytes != bytes - shared_bytes {
+                println!(
+                    "{}",
+                    tf(
+                        Msg::AllocatedData,
+                        &[&space::format_bytes(allocated_bytes as i64)]
+                    )
+                );
+            }
+            if let Some(ref estimate) = estimate {
+                println!("{}", estimate);
+            }
 
-            println!("\nTo proceed with deletion:");
-            println!("  rmbrr {}", path.display());
+            if !options.keep_root {
+                println!("{}", t(Msg::ToProceed));
+                println!("  rmbrr {}", path.display());
+            }
         }
         return Ok(DeletionStats {
             dirs_deleted: dir_count,
             files_deleted: file_count,
             total_scan_time: scan_time,
-            total_delete_time: std::time::Duration::ZERO,
+            ..Default::default()
         });
     }
 
+    let free_space = || rmbrr_core::winapi::free_space(space::probe_dir(path)).ok();
+    // Share free space moves with other clients, snapshots and quotas; it proves nothing there
+    let free_before = (measure && !network && (!batched || volume.is_some()))
+        .then(free_space)
+        .flatten();
+
+    if args.shuffle {
+        tree.shuffle_leaves();
+    }
+    if args.serial {
+        tree.sort_leaves();
+    }
+    let found_links = std::mem::take(&mut tree.links);
+    // Released (and the roots' permissions restored) when this function returns
+    let _holds: Vec<_> = scanned_roots
+        .iter()
+        .filter(|_| args.hold_root)
+        .filter_map(|root| {
+            rmbrr_core::winapi::hold_root(root)
+                .map_err(|e| output::warn(tf(Msg::RootNotHeld, &[&root.display(), &e])))
+                .ok()
+        })
+        .collect();
+    // Checked once more before anything is deleted, under the root's spelling in the tree:
+    // a plan may spell it differently, and the broker hands it out as the plan does
+    let root_pins = pins
+        .into_iter()
+        .map(|(pin, root)| {
+            pin.with_path(&root)
+                .map(Arc::new)
+                .map_err(|e| RmError::InvalidPath {
+                    path: root,
+                    reason: e.to_string(),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let dir_ids = args
+        .by_id
+        .then(|| {
+            // A loaded plan was not scanned for them
+            if tree.dir_ids.is_empty() {
+                tree.record_dir_ids();
+            }
+            let ids = std::mem::take(&mut tree.dir_ids);
+            rmbrr_core::winapi::DirIds::open(path, ids)
+                .map(Arc::new)
+                .map_err(|e| output::warn(tf(Msg::ByIdUnavailable, &[&path.display(), &e])))
+                .ok()
+        })
+        .flatten();
     let (broker, tx, rx) = Broker::new(tree);
     let broker = Arc::new(broker);
+    interrupt::set_active(Some(broker.clone()));
 
-    let error_tracker = Arc::new(worker::ErrorTracker::new());
+    let progress_mode = args.progress_mode();
+    let progress = args
+        .tui_enabled()
+        .then(|| Arc::new(Progress::new(worker_count)));
+    let (event_tx, event_rx) = events::channel();
+    // Explorer's context menu and plain console windows get progress on the taskbar button
+    let taskbar =
+        cfg!(windows) && !args.tui_enabled() && output::stdout_kind() == StreamKind::Console;
+    let event_tx =
+        (progress_mode.is_some() || outputs.sink.is_some() || taskbar).then_some(event_tx);
     let worker_config = worker::WorkerConfig {
-        verbose: args.verbose,
-        ignore_errors: args.ignore_errors,
+        serial: args.serial,
+        // Warnings would corrupt the full-screen interface; it shows errors itself
+        verbose: args.verbose && !args.tui_enabled(),
+        errors: args.error_strategy(),
+        retry: args.retry_policy(network),
+        progress: progress.clone(),
+        filter,
+        events: event_tx.clone(),
+        trace: outputs.tracer.clone(),
+        tuner: tuner.clone(),
+        numa_node: args.numa_node,
+        take_ownership,
+        manifest: outputs.manifest.clone(),
+        rescans: args.rescans,
+        spawner: None,
+        dir_ids,
+        root_pins,
+        ignore_errors: args.ignore_errors.clone().unwrap_or_default(),
+        planned: args.plan.is_some(),
     };
 
     if args.verbose {
-        println!("Spawning {} worker threads...", worker_count);
-    }
-    let handles = worker::spawn_workers(
-        worker_count,
-        rx,
-        broker.clone(),
-        worker_config,
-        error_tracker.clone(),
+        if let Some(ref estimate) = estimate {
+            println!("{}", estimate.trim_start());
+        }
+        if let Some(ref tuner) = tuner {
+            println!(
+                "Tuning worker count: starting with {}, up to {}",
+                tuner.limit(),
+                tuner.max()
+            );
+        }
+        match args.dir_threads {
+            Some(dir_threads) => println!(
+                "Spawning {} file and {} directory worker threads...",
+                worker_count, dir_threads
+            ),
+            None => println!("Spawning {} worker threads...", worker_count),
+        }
+    }
+    let reporter = event_tx.is_some().then(|| {
+        Reporter::new(progress_mode, event_rx, outputs.sink.clone())
+            .with_history(history.map(|(rates, _)| rates))
+            .with_taskbar(taskbar)
+            .spawn()
+    });
+    events::emit(
+        &event_tx,
+        Event::Started {
+            root: path.to_path_buf(),
+            dirs_total: broker.total_dirs(),
+            files_total: file_count,
+        },
     );
+    let handles = match args.dir_threads {
+        Some(dir_threads) => worker::spawn_split_workers(
+            worker_count,
+            dir_threads,
+            rx,
+            broker.clone(),
+            worker_config,
+            error_tracker.clone(),
+        ),
+        None => worker::spawn_workers(
+            worker_count,
+            rx,
+            broker.clone(),
+            worker_config,
+            error_tracker.clone(),
+        ),
+    };
+    let tuning = tuner.clone().map(|t| t.spawn(broker.clone()));
 
     drop(tx);
 
-    if args.verbose {
+    if args.verbose && !args.tui_enabled() {
         println!("Deleting directories...");
     }
     let delete_start = Instant::now();
 
-    let progress_handle = if args.verbose {
-        let total = broker.total_dirs();
-        let broker_clone = broker.clone();
-        Some(std::thread::spawn(move || loop {
-            std::thread::sleep(std::time::Duration::from_millis(250));
-            let completed = broker_clone.completed_count();
-            if completed >= total {
-                break;
-            }
-            let pct = (completed as f64 / total as f64 * 100.0) as u32;
-            print!("\rDeleting... {}% ({}/{} dirs)", pct, completed, total);
-            use std::io::Write;
-            std::io::stdout().flush().ok();
-        }))
-    } else {
-        None
-    };
+    #[cfg(feature = "tui")]
+    if let Some(progress) = progress.as_ref().filter(|_| args.tui_enabled()) {
+        let state = tui::TuiState {
+            root: path.to_path_buf(),
+            broker: broker.clone(),
+            progress: progress.clone(),
+            error_tracker: error_tracker.clone(),
+        };
+        if let Err(e) = tui::run(&state, &handles) {
+            output::warn(tf(Msg::TuiFailed, &[&e]));
+        }
+    }
 
     for handle in handles {
-        handle.join().expect("Worker thread panicked");
+        // Panics on a directory are caught inside the worker and recorded as failures
+        handle.join().ok();
+    }
+    if let Some(tuning) = tuning {
+        tuning.join().ok();
     }
+    output::flush_log();
+    let delete_time = delete_start.elapsed();
 
-    if let Some(handle) = progress_handle {
-        handle.join().ok();
-        let total = broker.total_dirs();
-        println!("\rDeleting... 100% ({}/{} dirs) - Complete!", total, total);
+    if let Some(reporter) = reporter {
+        let failures = error_tracker.get_failures();
+        events::emit(
+            &event_tx,
+            Event::Finished {
+                root: path.to_path_buf(),
+                dirs_deleted: broker.completed_count() - broker.kept_count(),
+                files_deleted: file_count.saturating_sub(error_tracker.files_not_deleted()),
+                failed: failures.len(),
+                elapsed: delete_time,
+                cancelled: broker.is_cancelled(),
+            },
+        );
+        drop(event_tx);
+        reporter.join().ok();
+    }
+
+    interrupt::set_active(None);
+
+    // Interrupted and aborted runs stopped early; their rates would mislead
+    let complete = !broker.is_cancelled() && error_tracker.aborted_by().is_none();
+    if complete && delete_time >= throughput::MIN_RECORDED_RUN {
+        if let (Some(file), Some(volume)) = (&history_file, &volume) {
+            let run = throughput::Throughput::measured(file_count, dir_count, delete_time);
+            if let Err(e) = throughput::record(file, volume, run) {
+                if args.verbose {
+                    output::warn(tf(Msg::HistoryNotWritten, &[&file.display(), &e]));
+                }
+            }
+        }
     }
 
-    let delete_time = delete_start.elapsed();
     let total_time = start.elapsed();
+    let space = free_before.zip(free_space()).map(|(before, after)| {
+        SpaceCheck::new(bytes, shared_bytes, allocated_bytes, before, after)
+    });
+
+    if let Some(item) = error_tracker.aborted_by() {
+        if !args.json {
+            println!(
+                "\n{}",
+                tf(
+                    Msg::StoppedDetail,
+                    &[
+                        &paint(t(Msg::Stopped), Color::Red),
+                        &error_tracker.failure_count(),
+                        &(broker.completed_count() - broker.kept_count()),
+                        &broker.total_dirs()
+                    ]
+                )
+            );
+        }
+        return Err(item.to_error());
+    }
+    if broker.is_cancelled() && args.json {
+        return Err(RmError::Cancelled);
+    }
+    if broker.is_cancelled() {
+        println!(
+            "\n{}",
+            tf(
+                Msg::InterruptedDetail,
+                &[
+                    &paint(t(Msg::Interrupted), Color::Yellow),
+                    &broker.completed_count(),
+                    &broker.total_dirs(),
+                    &duration(delete_time)
+                ]
+            )
+        );
+        let failures = error_tracker.get_failures();
+        if !failures.is_empty() {
+            println!(
+                "{}",
+                tf(Msg::ItemsFailed, &[&paint(failures.len(), Color::Red)])
+            );
+        }
+        return Err(RmError::Cancelled);
+    }
 
-    let failures = error_tracker.get_failures();
+    let mut failures = error_tracker.get_failures();
+    // Only Defender's log tells Controlled Folder Access apart from an ACL denial
+    rmbrr_core::error::confirm_controlled_folder_access(&mut failures, total_time);
     let failure_count = failures.len();
+    let dir_count = broker.completed_count() - broker.kept_count();
+    let link_summary = links::summarize(&found_links, &failures);
 
+    let already_gone = error_tracker.gone_count();
+    let ignored = error_tracker.ignored_count();
+    let files_deleted = file_count.saturating_sub(error_tracker.files_not_deleted());
     let stats = DeletionStats {
         dirs_deleted: dir_count,
-        files_deleted: file_count,
+        files_deleted,
         total_scan_time: scan_time,
         total_delete_time: delete_time,
+        already_gone,
+        links: link_summary.clone(),
+        ignored,
     };
+    if already_gone > 0 && (args.verbose || args.stats) {
+        println!("{}", tf(Msg::AlreadyGone, &[&already_gone]));
+    }
+    if ignored > 0 && !args.json && !args.summary_only {
+        output::warn(tf(Msg::ErrorsIgnored, &[&ignored]));
+    }
 
     if failure_count == 0 {
         if args.verbose {
-            println!("\nDeletion complete!");
+            println!("\n{}", paint(t(Msg::DeletionComplete), Color::Green));
         }
         if args.stats {
-            println!("\nStatistics:");
-            println!("  Directories: {}", dir_count);
-            println!("  Files:       {}", file_count);
-            println!("  Total items: {}", dir_count + file_count);
-            println!("\nTiming:");
-            println!("  Scan time:   {:.2?}", scan_time);
-            println!("  Delete time: {:.2?}", delete_time);
-            println!("  Total time:  {:.2?}", total_time);
-            println!("\nPerformance:");
+            println!("{}", t(Msg::Statistics));
+            println!("{}", tf(Msg::StatDirs, &[&paint(dir_count, Color::Cyan)]));
+            println!(
+                "{}",
+                tf(Msg::StatFiles, &[&paint(files_deleted, Color::Cyan)])
+            );
+            println!(
+                "{}",
+                tf(
+                    Msg::StatTotal,
+                    &[&paint(dir_count + files_deleted, Color::Cyan)]
+                )
+            );
+            if case_sensitive_dirs > 0 {
+                println!("{}", tf(Msg::CaseSensitiveDirs, &[&case_sensitive_dirs]));
+            }
+            if encrypted_files > 0 {
+                println!("{}", tf(Msg::EncryptedFiles, &[&encrypted_files]));
+            }
+            if let Some((ref path, len)) = longest_path {
+                println!("{}", tf(Msg::LongestPath, &[&len, &path.display()]));
+            }
+            if let Some((ref dir, depth)) = deepest_dir {
+                println!("{}", tf(Msg::DeepestDir, &[&depth, &dir.display()]));
+            }
+            println!("{}", t(Msg::Timing));
+            print_times(scan_time, delete_time, total_time);
+            println!("{}", t(Msg::Performance));
             let items_per_sec = (dir_count + file_count) as f64 / total_time.as_secs_f64();
-            println!("  Throughput:  {:.0} items/sec", items_per_sec);
+            println!("{}", tf(Msg::Throughput, &[&rate(items_per_sec)]));
+            println!(
+                "{}",
+                tf(Msg::ScanRate, &[&rate(per_sec(scanned_count, scan_time))])
+            );
+            println!(
+                "{}",
+                tf(
+                    Msg::DeleteRate,
+                    &[
+                        &rate(per_sec(file_count, delete_time)),
+                        &rate(per_sec(dir_count, delete_time))
+                    ]
+                )
+            );
+            if let Some(ref tuner) = tuner {
+                let state = if tuner.is_settled() {
+                    t(Msg::Settled)
+                } else {
+                    t(Msg::StillTuning)
+                };
+                println!("{}", tf(Msg::Threads, &[&tuner.limit(), &state]));
+            }
+            print_space(space);
+            links::print(&link_summary);
         } else if args.verbose {
-            println!("  Scan time:   {:.2?}", scan_time);
-            println!("  Delete time: {:.2?}", delete_time);
-            println!("  Total time:  {:.2?}", total_time);
+            print_times(scan_time, delete_time, total_time);
+            print_space(space);
+            links::print(&link_summary);
         }
         Ok(stats)
     } else {
         if args.verbose {
-            println!("\nDeletion completed with errors!");
+            println!("\n{}", paint(t(Msg::CompletedWithErrors), Color::Red));
         }
         if args.verbose {
-            println!("  Scan time:   {:.2?}", scan_time);
-            println!("  Delete time: {:.2?}", delete_time);
-            println!("  Total time:  {:.2?}", total_time);
+            print_times(scan_time, delete_time, total_time);
+            print_space(space);
+            links::print(&link_summary);
         }
 
-        let total_completed = broker.completed_count();
-        let total_items = total_completed + failure_count;
-
-        println!("\nError Summary:");
-        println!(
-            "  {} of {} items failed to delete",
-            failure_count, total_items
-        );
+        // --json already reported each failure as an event; --summary-only counts them
+        if !args.json && !args.summary_only {
+            let total_completed = broker.completed_count();
+            let total_items = total_completed + failure_count;
 
-        let display_count = std::cmp::min(10, failure_count);
-        println!("\nFirst {} failures:", display_count);
-        for (i, failure) in failures.iter().take(display_count).enumerate() {
-            let item_type = if failure.is_dir { "dir" } else { "file" };
+            println!("\n{}", paint(t(Msg::ErrorSummary), Color::Red));
             println!(
-                "  {}. [{}] {}: {}",
-                i + 1,
-                item_type,
-                failure.path.display(),
-                failure.error
+                "{}",
+                tf(
+                    Msg::ItemsFailedOf,
+                    &[&paint(failure_count, Color::Red), &total_items]
+                )
+            );
+            println!(
+                "{}",
+                tf(
+                    Msg::DirsRemovedFailed,
+                    &[
+                        &dir_count,
+                        &broker.total_dirs(),
+                        &paint(broker.failed_count(), Color::Red)
+                    ]
+                )
             );
-        }
 
-        if failure_count > 10 {
-            println!("\n  ... and {} more failures", failure_count - 10);
-            println!("\nRun with --verbose to see all errors as they occur");
+            println!("{}", t(Msg::ByCause));
+            for (cause, count) in failure_causes(&failures).into_iter().take(5) {
+                println!("  {:>6}  {}", count, cause);
+            }
+            let owners = owners::summarize(&failures, |path| rmbrr_core::winapi::owner(path).ok());
+            if let Some(owners) = owners {
+                owners::print(&owners);
+            }
+            // Ownership and permissions are not the problem there, so say what is
+            let hint = if failures.iter().any(FailedItem::is_controlled_folder_access) {
+                Some(Msg::ControlledFolderHint)
+            } else if failures
+                .iter()
+                .any(FailedItem::is_possibly_controlled_folder_access)
+            {
+                Some(Msg::ControlledFolderPossibleHint)
+            } else {
+                None
+            };
+            if let Some(hint) = hint {
+                let exe = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("rmbrr.exe"));
+                println!("\n{}", paint(tf(hint, &[&exe.display()]), Color::Yellow));
+            }
+
+            let display_count = std::cmp::min(10, failure_count);
+            println!("{}", tf(Msg::FirstFailures, &[&display_count]));
+            for (i, failure) in failures.iter().take(display_count).enumerate() {
+                let item_type = if failure.is_dir {
+                    t(Msg::Dir)
+                } else {
+                    t(Msg::File)
+                };
+                println!(
+                    "  {}. [{}] {}: {}",
+                    i + 1,
+                    item_type,
+                    failure.path.display(),
+                    paint(&failure.error, Color::Red)
+                );
+            }
+
+            if failure_count > 10 {
+                println!("{}", tf(Msg::MoreFailures, &[&(failure_count - 10)]));
+                println!("{}", t(Msg::RunVerbose));
+            }
         }
 
-        Err(Error::PartialFailure {
-            total: total_items,
-            failed: failure_count,
-            errors: failures,
+        Err(RmError::PartialFailure {
+            stats: Report {
+                dirs_deleted: dir_count,
+                files_deleted,
+                scan_time,
+                delete_time,
+                failures,
+                already_gone,
+                ignored,
+                links: found_links,
+            },
         })
     }
 }
@@ -0,0 +1,111 @@
+// Permission-denied failures grouped by the owner of the failing path, so the error summary
+// says whose permissions stand in the way ("4312  NT SERVICE\TrustedInstaller")
+
+use crate::i18n::{t, tf, Msg};
+use rmbrr_core::error::FailedItem;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// Owners are looked up for at most this many denied paths; each lookup is a syscall or two
+const SAMPLE: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerSummary {
+    /// (owner, denied items in the sample), most first; `None` when the lookup failed
+    pub owners: Vec<(Option<String>, usize)>,
+    /// Denied items whose owner was looked up
+    pub sampled: usize,
+    /// All denied items
+    pub denied: usize,
+}
+
+fn is_access_denied(failure: &FailedItem) -> bool {
+    failure.os_error.is_some_and(|code| {
+        io::Error::from_raw_os_error(code).kind() == io::ErrorKind::PermissionDenied
+    })
+}
+
+/// Group the access-denied failures by owner, using `lookup` on a sample of their paths;
+/// `None` if there were none
+pub fn summarize<F>(failures: &[FailedItem], lookup: F) -> Option<OwnerSummary>
+where
+    F: Fn(&Path) -> Option<String>,
+{
+    let denied: Vec<&FailedItem> = failures.iter().filter(|f| is_access_denied(f)).collect();
+    if denied.is_empty() {
+        return None;
+    }
+    // Spread the sample over the whole run rather than its first directories
+    let step = denied.len().div_ceil(SAMPLE);
+    let mut counts: HashMap<Option<String>, usize> = HashMap::new();
+    let mut sampled = 0;
+    for failure in denied.iter().step_by(step) {
+        *counts.entry(lookup(&failure.path)).or_default() += 1;
+        sampled += 1;
+    }
+    let mut owners: Vec<_> = counts.into_iter().collect();
+    owners.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Some(OwnerSummary {
+        owners,
+        sampled,
+        denied: denied.len(),
+    })
+}
+
+pub fn print(summary: &OwnerSummary) {
+    println!("{}", t(Msg::DeniedByOwner));
+    for (owner, count) in summary.owners.iter().take(5) {
+        let owner = owner.as_deref().unwrap_or(t(Msg::UnknownOwner));
+        println!("  {:>6}  {}", count, owner);
+    }
+    if summary.sampled < summary.denied {
+        println!(
+            "{}",
+            tf(Msg::OwnersSampled, &[&summary.sampled, &summary.denied])
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn failure(path: &str, os_error: Option<i32>) -> FailedItem {
+        FailedItem {
+            path: PathBuf::from(path),
+            error: String::new(),
+            is_dir: false,
+            os_error,
+        }
+    }
+
+    #[test]
+    fn test_summarize_by_owner() {
+        // ERROR_ACCESS_DENIED, EACCES
+        let code = if cfg!(windows) { 5 } else { 13 };
+        let failures = vec![
+            failure("winsxs/a.dll", Some(code)),
+            failure("winsxs/b.dll", Some(code)),
+            failure("users/c.txt", Some(code)),
+            failure("gone.txt", Some(code)),
+            failure("busy.log", None),
+        ];
+        let lookup = |path: &Path| match path.to_str().unwrap() {
+            p if p.starts_with("winsxs") => Some(r"NT SERVICE\TrustedInstaller".to_string()),
+            p if p.starts_with("users") => Some(r"CORP\alice".to_string()),
+            _ => None,
+        };
+
+        let summary = summarize(&failures, lookup).unwrap();
+        assert_eq!(summary.denied, 4);
+        assert_eq!(summary.sampled, 4);
+        assert_eq!(
+            summary.owners[0],
+            (Some(r"NT SERVICE\TrustedInstaller".to_string()), 2)
+        );
+        assert_eq!(summary.owners.len(), 3);
+        assert!(summarize(&failures[4..], lookup).is_none());
+    }
+}
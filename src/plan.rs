@@ -0,0 +1,718 @@
+// Saved deletion plans: the directories of a scanned tree, so a later run can delete them
+// without scanning again
+//
+// Plans come in two formats. The binary one is compact enough for trees with millions of
+// directories: a header, then a table of length-prefixed directory names, each with how
+// many entries back its parent is (the root comes first, with its full path). JSON holds the same table for other tools:
+// {"version":2,"root":"...","files":..,"bytes":..,"allocated_bytes":..,"volume":..,
+//  "encoding":..,"dirs":[["name",parent,file index],...],"kept":[index,...]}
+// A name that is not valid Unicode is stored as an array of its raw bytes instead of a
+// string: the OS's own bytes on Unix, UTF-16LE on Windows, as "encoding" says.
+//
+// Since version 2 each directory also has its file ID, so a plan is checked against the disk
+// before it is applied (`verify`).
+
+use rmbrr_core::tree::DirectoryTree;
+use rmbrr_core::winapi;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"RMBRPLAN";
+const VERSION: u32 = 2;
+/// Oldest version `load` reads; version 1 plans have no file IDs
+const MIN_VERSION: u32 = 1;
+/// Parent index of the root
+const NO_PARENT: u32 = u32::MAX;
+/// How names are stored: raw bytes on Unix, UTF-16LE on Windows
+const NAME_ENCODING: u8 = if cfg!(windows) { 2 } else { 1 };
+
+/// File format of a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Binary,
+    Json,
+}
+
+impl Format {
+    /// JSON for `.json` files, binary otherwise
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Binary,
+        }
+    }
+}
+
+/// A saved scan of one target
+#[derive(Debug)]
+pub struct Plan {
+    pub root: PathBuf,
+    pub tree: DirectoryTree,
+    /// File ID of each of `tree.dirs`, which are in table order
+    recorded: Vec<Option<(u64, u64)>>,
+    /// Format version the plan was saved with
+    version: u32,
+}
+
+/// One directory of the table
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    /// The full path for the root, the file name for everything else
+    name: OsString,
+    parent: u32,
+    kept: bool,
+    /// Volume and file index when saved, if they could be read
+    id: Option<(u64, u64)>,
+}
+
+/// Totals recorded with the table
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Totals {
+    files: u64,
+    bytes: u64,
+    allocated_bytes: u64,
+    /// Format version of a plan read back
+    version: u32,
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// The directories of `tree` under `root`, parents before their children
+fn table(root: &Path, tree: &DirectoryTree) -> io::Result<Vec<Entry>> {
+    let mut entries = vec![Entry {
+        name: root.as_os_str().to_os_string(),
+        parent: NO_PARENT,
+        kept: tree.kept_dirs.contains(root),
+        id: winapi::file_id(root).ok(),
+    }];
+    let mut queue = vec![(root.to_path_buf(), 0u32)];
+    while let Some((dir, index)) = queue.pop() {
+        for child in tree.children.get(&dir).into_iter().flatten() {
+            let name = child
+                .file_name()
+                .ok_or_else(|| invalid(format!("{} has no name", child.display())))?;
+            let child_index = u32::try_from(entries.len())
+                .ok()
+                .filter(|&i| i != NO_PARENT)
+                .ok_or_else(|| invalid("too many directories for a plan"))?;
+            entries.push(Entry {
+                name: name.to_os_string(),
+                parent: index,
+                kept: tree.kept_dirs.contains(child),
+                id: winapi::file_id(child).ok(),
+            });
+            queue.push((child.clone(), child_index));
+        }
+    }
+    if entries.len() != tree.dirs.len() {
+        return Err(invalid(format!(
+            "{} of {} directories are not under {}",
+            tree.dirs.len().saturating_sub(entries.len()),
+            tree.dirs.len(),
+            root.display()
+        )));
+    }
+    Ok(entries)
+}
+
+/// Rebuild the tree from its table
+fn tree_from(entries: Vec<Entry>, totals: Totals) -> io::Result<Plan> {
+    let mut tree = DirectoryTree::new();
+    let mut has_children = HashSet::new();
+    let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut recorded = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.into_iter().enumerate() {
+        recorded.push(entry.id);
+        let path = if index == 0 {
+            if entry.parent != NO_PARENT {
+                return Err(invalid("the first directory is not the root"));
+            }
+            PathBuf::from(entry.name)
+        } else {
+            // Parents come first, so a later index is corrupt
+            let parent = tree
+                .dirs
+                .get(entry.parent as usize)
+                .ok_or_else(|| invalid(format!("bad parent index {}", entry.parent)))?;
+            // A name like ".." or "a/b" would put the directory outside its parent
+            let mut components = Path::new(&entry.name).components();
+            if !matches!(
+                (components.next(), components.next()),
+                (Some(Component::Normal(_)), None)
+            ) {
+                return Err(invalid(format!("bad directory name {:?}", entry.name)));
+            }
+            let path = parent.join(&entry.name);
+            has_children.insert(entry.parent as usize);
+            children
+                .entry(parent.clone())
+                .or_default()
+                .push(path.clone());
+            path
+        };
+        if entry.kept {
+            tree.kept_dirs.insert(path.clone());
+        }
+        tree.dirs.push(path);
+    }
+    if tree.dirs.is_empty() {
+        return Err(invalid("the plan has no directories"));
+    }
+    tree.leaves = (0..tree.dirs.len())
+        .filter(|i| !has_children.contains(i))
+        .map(|i| tree.dirs[i].clone())
+        .collect();
+    tree.children = children;
+    tree.file_count = totals.files as usize;
+    tree.bytes = totals.bytes;
+    tree.allocated_bytes = totals.allocated_bytes;
+    Ok(Plan {
+        root: tree.dirs[0].clone(),
+        tree,
+        recorded,
+        version: totals.version,
+    })
+}
+
+/// Check that every saved directory is still the one on disk: still a directory, not a link,
+/// with the file ID it was saved with
+///
+/// A tree moved, or deleted and created again, since the plan was saved fails, so a stale
+/// plan is never applied to whatever has its paths now. Version 1 plans have no IDs to check
+/// and are refused unless `accept_unidentified`.
+pub fn verify(plan: &Plan, accept_unidentified: bool) -> io::Result<()> {
+    if plan.version < 2 && !accept_unidentified {
+        return Err(invalid(format!(
+            "the plan for {} has no file IDs to check it against the disk (version {}); \
+             save it again, or pass --verify-plan to apply it anyway",
+            plan.root.display(),
+            plan.version
+        )));
+    }
+    let check = |index: usize| -> io::Result<()> {
+        let dir = &plan.tree.dirs[index];
+        // A link in a directory's place would have its target's files deleted
+        match dir.symlink_metadata() {
+            Ok(metadata) if !metadata.is_dir() => {
+                return Err(invalid(format!(
+                    "{} is no longer a directory since the plan was saved",
+                    dir.display()
+                )));
+            }
+            _ => {}
+        }
+        let Some(id) = plan.recorded[index] else {
+            return Ok(());
+        };
+        match winapi::file_id(dir) {
+            Ok(now) if now == id => Ok(()),
+            Ok(_) => Err(invalid(format!(
+                "{} was replaced since the plan was saved",
+                dir.display()
+            ))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Err(invalid(format!(
+                "{} was moved or deleted since the plan was saved",
+                dir.display()
+            ))),
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("cannot check {}: {}", dir.display(), e),
+            )),
+        }
+    };
+
+    (0..plan.recorded.len()).try_for_each(check)
+}
+
+/// Save the scan of `root` to `file`
+pub fn save(file: &Path, root: &Path, tree: &DirectoryTree, format: Format) -> io::Result<()> {
+    let entries = table(root, tree)?;
+    let totals = Totals {
+        files: tree.file_count as u64,
+        bytes: tree.bytes,
+        allocated_bytes: tree.allocated_bytes,
+        version: VERSION,
+    };
+    let mut out = BufWriter::new(File::create(file)?);
+    match format {
+        Format::Binary => write_binary(&mut out, &entries, totals)?,
+        Format::Json => write_json(&mut out, &entries, totals)?,
+    }
+    out.flush()
+}
+
+/// Load a plan saved in either format
+pub fn load(file: &Path) -> io::Result<Plan> {
+    let mut input = BufReader::new(File::open(file)?);
+    let mut magic = [0u8; 8];
+    let binary = match input.read_exact(&mut magic) {
+        Ok(()) => &magic == MAGIC,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => false,
+        Err(e) => return Err(e),
+    };
+    let (entries, totals) = if binary {
+        read_binary(&mut input)?
+    } else {
+        let mut text = magic.to_vec();
+        input.read_to_end(&mut text)?;
+        read_json(&text)?
+    };
+    tree_from(entries, totals)
+}
+
+fn write_binary(out: &mut impl Write, entries: &[Entry], totals: Totals) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&[NAME_ENCODING])?;
+    for value in [totals.files, totals.bytes, totals.allocated_bytes] {
+        out.write_all(&value.to_le_bytes())?;
+    }
+    // File IDs are stored as indexes on the root's volume; mounts are never descended into
+    let volume = entries.first().and_then(|e| e.id).map(|(volume, _)| volume);
+    out.write_all(&[volume.is_some() as u8])?;
+    out.write_all(&volume.unwrap_or(0).to_le_bytes())?;
+    out.write_all(&(entries.len() as u64).to_le_bytes())?;
+    for (index, entry) in entries.iter().enumerate() {
+        let name = encode_name(&entry.name);
+        // Parents are usually close, so their distance takes a byte or two
+        let distance = match entry.parent {
+            NO_PARENT => 0,
+            parent => index as u64 - parent as u64,
+        };
+        let index = entry
+            .id
+            .filter(|&(v, _)| Some(v) == volume)
+            .map(|(_, index)| index);
+        write_varint(out, distance)?;
+        write_varint(
+            out,
+            (name.len() as u64) << 2 | (index.is_some() as u64) << 1 | entry.kept as u64,
+        )?;
+        out.write_all(&name)?;
+        if let Some(index) = index {
+            write_varint(out, index)?;
+        }
+    }
+    Ok(())
+}
+
+/// LEB128: seven bits per byte, low bits first
+fn write_varint(out: &mut impl Write, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+        out.write_all(&[value as u8 | 0x80])?;
+        value >>= 7;
+    }
+    out.write_all(&[value as u8])
+}
+
+fn read_varint(input: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let [byte] = read_array(input)?;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("bad number"))
+}
+
+fn read_binary(input: &mut impl Read) -> io::Result<(Vec<Entry>, Totals)> {
+    let version = u32::from_le_bytes(read_array(input)?);
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        return Err(invalid(format!("unsupported plan version {}", version)));
+    }
+    let [encoding] = read_array(input)?;
+    if encoding != NAME_ENCODING {
+        return Err(invalid("the plan was saved on another operating system"));
+    }
+    let mut read_u64 = || read_array(input).map(u64::from_le_bytes);
+    let totals = Totals {
+        files: read_u64()?,
+        bytes: read_u64()?,
+        allocated_bytes: read_u64()?,
+        version,
+    };
+    let volume = if version >= 2 {
+        let [has_volume] = read_array(input)?;
+        let volume = u64::from_le_bytes(read_array(input)?);
+        (has_volume != 0).then_some(volume)
+    } else {
+        None
+    };
+    let count = read_array(input).map(u64::from_le_bytes)?;
+    // The count comes from the file; let the entries themselves grow the table
+    let mut entries = Vec::with_capacity(count.min(1 << 20) as usize);
+    for index in 0..count {
+        let parent = match read_varint(input)? {
+            0 => NO_PARENT,
+            distance => index
+                .checked_sub(distance)
+                .and_then(|p| u32::try_from(p).ok())
+                .ok_or_else(|| invalid("bad parent index"))?,
+        };
+        // Version 1 has no file ID bit
+        let len_and_flags = read_varint(input)?;
+        let (len, has_id) = if version >= 2 {
+            (len_and_flags >> 2, len_and_flags & 2 != 0)
+        } else {
+            (len_and_flags >> 1, false)
+        };
+        let mut name = Vec::new();
+        input.take(len).read_to_end(&mut name)?;
+        if name.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let index = if has_id {
+            Some(read_varint(input)?)
+        } else {
+            None
+        };
+        entries.push(Entry {
+            name: decode_name(name)?,
+            parent,
+            kept: len_and_flags & 1 != 0,
+            id: volume.zip(index),
+        });
+    }
+    Ok((entries, totals))
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn write_json(out: &mut impl Write, entries: &[Entry], totals: Totals) -> io::Result<()> {
+    let volume = entries.first().and_then(|e| e.id).map(|(volume, _)| volume);
+    let dirs: Vec<Value> = entries
+        .iter()
+        .map(|e| {
+            let parent = (e.parent != NO_PARENT).then_some(e.parent);
+            let index =
+                e.id.filter(|&(v, _)| Some(v) == volume)
+                    .map(|(_, index)| index);
+            json!([name_value(&e.name), parent, index])
+        })
+        .collect();
+    let kept: Vec<usize> = (0..entries.len()).filter(|&i| entries[i].kept).collect();
+    let plan = json!({
+        "version": VERSION,
+        "root": name_value(&entries[0].name),
+        "files": totals.files,
+        "bytes": totals.bytes,
+        "allocated_bytes": totals.allocated_bytes,
+        "volume": volume,
+        "encoding": NAME_ENCODING,
+        "dirs": dirs,
+        "kept": kept,
+    });
+    serde_json::to_writer(&mut *out, &plan).map_err(io::Error::other)?;
+    out.write_all(b"\n")
+}
+
+fn read_json(text: &[u8]) -> io::Result<(Vec<Entry>, Totals)> {
+    let plan: Value =
+        serde_json::from_slice(text).map_err(|_| invalid("not an rmbrr plan file"))?;
+    let version = match plan.get("version").and_then(Value::as_u64) {
+        Some(v) if (MIN_VERSION as u64..=VERSION as u64).contains(&v) => v as u32,
+        Some(v) => return Err(invalid(format!("unsupported plan version {}", v))),
+        None => return Err(invalid("not an rmbrr plan file")),
+    };
+    let number = |key: &str| plan.get(key).and_then(Value::as_u64).unwrap_or(0);
+    let totals = Totals {
+        files: number("files"),
+        bytes: number("bytes"),
+        allocated_bytes: number("allocated_bytes"),
+        version,
+    };
+    let volume = plan.get("volume").and_then(Value::as_u64);
+    let encoding = plan.get("encoding").and_then(Value::as_u64);
+    let malformed = || invalid("malformed plan entry");
+    let mut entries = plan
+        .get("dirs")
+        .and_then(Value::as_array)
+        .ok_or_else(malformed)?
+        .iter()
+        .map(|dir| {
+            let name = match dir.get(0) {
+                Some(Value::String(name)) => OsString::from(name),
+                Some(Value::Array(raw)) => {
+                    if encoding != Some(NAME_ENCODING as u64) {
+                        return Err(invalid("the plan was saved on another operating system"));
+                    }
+                    let bytes = raw
+                        .iter()
+                        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                        .collect::<Option<Vec<u8>>>()
+                        .ok_or_else(malformed)?;
+                    decode_name(bytes)?
+                }
+                _ => return Err(malformed()),
+            };
+            let parent = match dir.get(1) {
+                Some(Value::Null) | None => NO_PARENT,
+                Some(parent) => parent
+                    .as_u64()
+                    .and_then(|p| u32::try_from(p).ok())
+                    .ok_or_else(malformed)?,
+            };
+            let index = dir.get(2).and_then(Value::as_u64);
+            Ok(Entry {
+                name,
+                parent,
+                kept: false,
+                id: volume.zip(index),
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    for index in plan
+        .get("kept")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let entry = index
+            .as_u64()
+            .and_then(|i| entries.get_mut(i as usize))
+            .ok_or_else(malformed)?;
+        entry.kept = true;
+    }
+    Ok((entries, totals))
+}
+
+/// A name for the JSON format: a string if it is valid Unicode, else its raw bytes
+fn name_value(name: &OsStr) -> Value {
+    match name.to_str() {
+        Some(name) => Value::from(name),
+        None => Value::from(encode_name(name)),
+    }
+}
+
+#[cfg(unix)]
+fn encode_name(name: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    name.as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+fn decode_name(bytes: Vec<u8>) -> io::Result<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(OsString::from_vec(bytes))
+}
+
+#[cfg(windows)]
+fn encode_name(name: &OsStr) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    name.encode_wide().flat_map(u16::to_le_bytes).collect()
+}
+
+#[cfg(windows)]
+fn decode_name(bytes: Vec<u8>) -> io::Result<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    if !bytes.len().is_multiple_of(2) {
+        return Err(invalid("bad directory name"));
+    }
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Ok(OsString::from_wide(&wide))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmbrr_core::tree;
+    use std::fs;
+
+    fn check_round_trip(format: Format) {
+        let temp = std::env::temp_dir().join(format!("win_rmdir_plan_{:?}_test", format));
+        let _ = fs::remove_dir_all(&temp);
+        let root = temp.join("root");
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join("c")).unwrap();
+        fs::write(root.join("a/b/file"), [0u8; 10]).unwrap();
+        fs::write(root.join("c/file"), [0u8; 5]).unwrap();
+        let scanned = tree::discover_tree_sized(&root, None).unwrap();
+
+        let file = temp.join("plan");
+        save(&file, &root, &scanned, format).unwrap();
+        let plan = load(&file).unwrap();
+        assert_eq!(plan.root, root);
+        assert_eq!(plan.tree.file_count, 2);
+        assert_eq!(plan.tree.bytes, 15);
+        let sorted = |dirs: &[PathBuf]| {
+            let mut dirs = dirs.to_vec();
+            dirs.sort();
+            dirs
+        };
+        assert_eq!(sorted(&plan.tree.dirs), sorted(&scanned.dirs));
+        assert_eq!(sorted(&plan.tree.leaves), sorted(&scanned.leaves));
+        assert_eq!(plan.tree.children[&root.join("a")], vec![root.join("a/b")]);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[test]
+    fn test_round_trip_binary() {
+        check_round_trip(Format::Binary);
+    }
+
+    #[test]
+    fn test_round_trip_json() {
+        check_round_trip(Format::Json);
+    }
+
+    #[test]
+    fn test_rejects_corrupt_plans() {
+        let entries = vec![
+            Entry {
+                name: OsString::from("/r"),
+                parent: NO_PARENT,
+                kept: false,
+                id: Some((7, 1)),
+            },
+            Entry {
+                name: OsString::from("a".repeat(300)),
+                parent: 0,
+                kept: true,
+                id: Some((7, u64::MAX)),
+            },
+        ];
+        let mut bytes = Vec::new();
+        write_binary(&mut bytes, &entries, Totals::default()).unwrap();
+        let (read, _) = read_binary(&mut &bytes[MAGIC.len()..]).unwrap();
+        assert_eq!(read, entries);
+        // Cut off in the middle of a name
+        assert!(read_binary(&mut &bytes[MAGIC.len()..bytes.len() - 1]).is_err());
+        // A parent after its child
+        let (forward, totals) =
+            read_json(br#"{"version":1,"dirs":[["/r",null],["a",5]]}"#).unwrap();
+        assert!(tree_from(forward, totals).is_err());
+        assert!(read_json(br#"{"version":9,"dirs":[]}"#).is_err());
+        let (escaping, totals) =
+            read_json(br#"{"version":1,"dirs":[["/r",null],["..",0]]}"#).unwrap();
+        assert!(tree_from(escaping, totals).is_err());
+        assert!(read_json(b"[]").is_err());
+    }
+
+    #[test]
+    fn test_reads_version_1() {
+        // Names without the file ID bit, and no volume
+        let mut bytes = 1u32.to_le_bytes().to_vec();
+        bytes.push(NAME_ENCODING);
+        bytes.extend([0u8; 24]);
+        bytes.extend(1u64.to_le_bytes());
+        bytes.extend([0, 2 << 1]);
+        bytes.extend(b"/r");
+        let (entries, _) = read_binary(&mut &bytes[..]).unwrap();
+        assert_eq!(entries[0].name, "/r");
+        assert_eq!(entries[0].id, None);
+    }
+
+    #[test]
+    fn test_verify() {
+        let temp = std::env::temp_dir().join("win_rmdir_plan_verify_test");
+        let _ = fs::remove_dir_all(&temp);
+        let root = temp.join("root");
+        for dir in ["a/b", "c/d"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let file = temp.join("plan.json");
+        save(
+            &file,
+            &root,
+            &tree::discover_tree(&root).unwrap(),
+            Format::Json,
+        )
+        .unwrap();
+        verify(&load(&file).unwrap(), false).unwrap();
+
+        // Version 1 plans have no IDs to check
+        let old = temp.join("old.json");
+        let text = json!({"version": 1, "dirs": [[root.to_str().unwrap(), null]]});
+        fs::write(&old, text.to_string()).unwrap();
+        assert!(verify(&load(&old).unwrap(), false)
+            .unwrap_err()
+            .to_string()
+            .contains("no file IDs"));
+        verify(&load(&old).unwrap(), true).unwrap();
+
+        // Replaced while the old one lives on elsewhere, so its ID cannot be reused
+        fs::rename(root.join("c"), temp.join("old_c")).unwrap();
+        fs::create_dir_all(root.join("c/d")).unwrap();
+        let plan = load(&file).unwrap();
+        assert!(verify(&plan, false)
+            .unwrap_err()
+            .to_string()
+            .contains("was replaced"));
+        fs::rename(&root, temp.join("old_root")).unwrap();
+        assert!(verify(&plan, false)
+            .unwrap_err()
+            .to_string()
+            .contains("moved or deleted"));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verify_refuses_links() {
+        let temp = std::env::temp_dir().join("win_rmdir_plan_link_test");
+        let _ = fs::remove_dir_all(&temp);
+        let root = temp.join("root");
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(temp.join("elsewhere")).unwrap();
+        let file = temp.join("plan");
+        save(
+            &file,
+            &root,
+            &tree::discover_tree(&root).unwrap(),
+            Format::Binary,
+        )
+        .unwrap();
+
+        // Swapped for a link to a directory outside the plan
+        fs::remove_dir(root.join("a/b")).unwrap();
+        std::os::unix::fs::symlink(temp.join("elsewhere"), root.join("a/b")).unwrap();
+        assert!(verify(&load(&file).unwrap(), false)
+            .unwrap_err()
+            .to_string()
+            .contains("no longer a directory"));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_json_keeps_raw_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let entries = vec![
+            Entry {
+                name: OsString::from("/r"),
+                parent: NO_PARENT,
+                kept: false,
+                id: None,
+            },
+            Entry {
+                name: OsStr::from_bytes(b"caf\xe9").to_os_string(),
+                parent: 0,
+                kept: false,
+                id: None,
+            },
+        ];
+        let mut text = Vec::new();
+        write_json(&mut text, &entries, Totals::default()).unwrap();
+        let (read, _) = read_json(&text).unwrap();
+        assert_eq!(read, entries);
+    }
+}
@@ -0,0 +1,105 @@
+// Built-in target sets (--preset)
+
+use clap::ValueEnum;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Files in temp directories untouched for this long are considered abandoned
+const TEMP_MIN_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Caches are rebuilt on demand, but recently used entries are still worth keeping
+const CACHE_MIN_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+    /// User and system temp directories plus standard cache locations
+    Temp,
+}
+
+/// A directory whose contents a preset cleans; the directory itself is always kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresetTarget {
+    pub path: PathBuf,
+    /// Only files older than this are deleted
+    pub min_age: Duration,
+}
+
+/// Resolve a preset to the existing directories it cleans
+pub fn targets(preset: Preset) -> Vec<PresetTarget> {
+    let candidates = match preset {
+        Preset::Temp => temp_locations(),
+    };
+
+    let mut targets: Vec<PresetTarget> = Vec::new();
+    for (path, min_age) in candidates {
+        if path.is_dir() && !targets.iter().any(|t| t.path == path) {
+            targets.push(PresetTarget { path, min_age });
+        }
+    }
+    targets
+}
+
+fn env_path(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+fn under(base: Option<PathBuf>, relative: &str) -> Option<PathBuf> {
+    base.map(|b| b.join(Path::new(relative)))
+}
+
+#[cfg(windows)]
+fn temp_locations() -> Vec<(PathBuf, Duration)> {
+    let local = env_path("LOCALAPPDATA");
+    let temp = [
+        Some(std::env::temp_dir()),
+        env_path("TEMP"),
+        env_path("TMP"),
+        under(local.clone(), "Temp"),
+        under(env_path("SystemRoot"), "Temp"),
+    ];
+    let caches = [
+        under(local.clone(), "CrashDumps"),
+        under(local.clone(), "D3DSCache"),
+        under(local, "Microsoft\\Windows\\INetCache"),
+    ];
+    collect(temp, caches)
+}
+
+#[cfg(not(windows))]
+fn temp_locations() -> Vec<(PathBuf, Duration)> {
+    let home = env_path("HOME");
+    let temp = [
+        Some(std::env::temp_dir()),
+        Some(PathBuf::from("/tmp")),
+        Some(PathBuf::from("/var/tmp")),
+    ];
+    let caches = if cfg!(target_os = "macos") {
+        [under(home, "Library/Caches")]
+    } else {
+        [env_path("XDG_CACHE_HOME").or_else(|| under(home, ".cache"))]
+    };
+    collect(temp, caches)
+}
+
+fn collect<const T: usize, const C: usize>(
+    temp: [Option<PathBuf>; T],
+    caches: [Option<PathBuf>; C],
+) -> Vec<(PathBuf, Duration)> {
+    let temp = temp.into_iter().flatten().map(|p| (p, TEMP_MIN_AGE));
+    let caches = caches.into_iter().flatten().map(|p| (p, CACHE_MIN_AGE));
+    temp.chain(caches).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_preset_targets() {
+        let targets = targets(Preset::Temp);
+        assert!(targets.iter().any(|t| t.path == std::env::temp_dir()));
+        assert!(targets.iter().all(|t| t.path.is_dir()));
+        assert!(targets.iter().all(|t| t.min_age >= TEMP_MIN_AGE));
+    }
+}
@@ -0,0 +1,237 @@
+// Named deletion recipes (`rmbrr --profile ci-clean`) from `[profile.<name>]` sections of
+// the config file
+//
+// A profile's keys are long options (`older-than = "3d"`, `threads = 8`, `force = true`,
+// `only = ["*.tmp", "*.log"]`) plus `paths` for the targets. They are expanded into command
+// line arguments ahead of the user's own: single-valued options given on the command line
+// replace the profile's, while lists (`--only`, targets) add to them. Environment variables
+// (`%TEMP%`, `$HOME`) in option values and `paths` are expanded here, as no shell has seen
+// them.
+
+use rmbrr_core::targets::expand_env_vars;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Environment variable naming the config file, ahead of the default location
+const CONFIG_ENV: &str = "RMBRR_CONFIG";
+
+/// Default config file: `%APPDATA%\rmbrr\config.toml` on Windows,
+/// `$XDG_CONFIG_HOME/rmbrr/config.toml` (or `~/.config/rmbrr/config.toml`) elsewhere
+pub fn default_config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let base = if cfg!(windows) {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else {
+        match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        }
+    };
+    Some(base.join("rmbrr").join("config.toml"))
+}
+
+/// Value of `--name VALUE` or `--name=VALUE` on the command line, if given
+fn option_value(args: &[OsString], name: &str) -> Option<OsString> {
+    let prefix = format!("{}=", name);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let text = arg.to_string_lossy();
+        if text == "--" {
+            break;
+        }
+        if text == name {
+            return iter.next().cloned();
+        }
+        if let Some(value) = text.strip_prefix(&prefix) {
+            return Some(value.into());
+        }
+    }
+    None
+}
+
+/// Insert the arguments of the profile `--profile` names (if any) after the program name
+pub fn expand(args: Vec<OsString>) -> Result<Vec<OsString>, String> {
+    let Some(name) = option_value(&args, "--profile") else {
+        return Ok(args);
+    };
+    let name = name.to_string_lossy();
+    let config = option_value(&args, "--config")
+        .map(PathBuf::from)
+        .or_else(default_config_path)
+        .ok_or("no config file: pass --config or set RMBRR_CONFIG")?;
+    let text = fs::read_to_string(&config)
+        .map_err(|e| format!("cannot read config file {}: {}", config.display(), e))?;
+    let profile_args = profile_args(&text, &name).map_err(|e| in_file(&config, e))?;
+
+    let mut expanded = Vec::with_capacity(args.len() + profile_args.len());
+    let mut args = args.into_iter();
+    expanded.extend(args.next());
+    expanded.extend(profile_args);
+    expanded.extend(args);
+    Ok(expanded)
+}
+
+fn in_file(config: &Path, error: String) -> String {
+    format!("{}: {}", config.display(), error)
+}
+
+/// Command line arguments for profile `name` of a config file's contents
+pub fn profile_args(config: &str, name: &str) -> Result<Vec<OsString>, String> {
+    let table: Table = config
+        .parse()
+        .map_err(|e: toml::de::Error| e.message().to_string())?;
+    let profiles = table.get("profile").and_then(Value::as_table);
+    let Some(profile) = profiles.and_then(|p| p.get(name)).and_then(Value::as_table) else {
+        let mut known: Vec<&str> = profiles
+            .map(|p| p.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        known.sort();
+        return Err(format!(
+            "no profile '{}' (defined: {})",
+            name,
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        ));
+    };
+
+    let mut args = Vec::new();
+    let mut paths = Vec::new();
+    for (key, value) in profile {
+        let key = key.replace('_', "-");
+        if key == "paths" {
+            for path in values(&key, value)? {
+                let path = expand_env_vars(&path)
+                    .map_err(|name| format!("'paths': environment variable {} is not set", name))?;
+                paths.push(path.into());
+            }
+            continue;
+        }
+        if matches!(key.as_str(), "profile" | "config") {
+            return Err(format!("'{}' cannot be set in a profile", key));
+        }
+        let flag = format!("--{}", key);
+        match value {
+            Value::Boolean(true) => args.push(flag.into()),
+            Value::Boolean(false) => {}
+            _ => {
+                for value in values(&key, value)? {
+                    let value = expand_env_vars(&value).map_err(|name| {
+                        format!("'{}': environment variable {} is not set", key, name)
+                    })?;
+                    // Joined, as options taking an optional value (--ignore-errors) require
+                    args.push(format!("{}={}", flag, value).into());
+                }
+            }
+        }
+    }
+    // Targets go after the options, which keeps them apart from any subcommand
+    args.extend(paths);
+    Ok(args)
+}
+
+/// A scalar or an array of scalars, as argument strings
+fn values(key: &str, value: &Value) -> Result<Vec<String>, String> {
+    match value {
+        Value::String(s) => Ok(vec![s.clone()]),
+        Value::Integer(n) => Ok(vec![n.to_string()]),
+        Value::Float(f) => Ok(vec![f.to_string()]),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Table(_) => Err(format!("'{}' has a nested value", key)),
+                item => values(key, item).map(|mut v| v.remove(0)),
+            })
+            .collect(),
+        _ => Err(format!(
+            "'{}' must be a string, number, boolean or list",
+            key
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[profile.ci-clean]
+paths = ['D:\ci\workspaces', 'D:\ci\cache']
+older_than = "3d"
+threads = 8
+only = ["*.obj", "*.pdb"]
+force = true
+dry-run = false
+"#;
+
+    fn strings(args: Vec<OsString>) -> Vec<String> {
+        args.into_iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_profile_args() {
+        assert_eq!(
+            strings(profile_args(CONFIG, "ci-clean").unwrap()),
+            vec![
+                "--force",
+                "--older-than=3d",
+                "--only=*.obj",
+                "--only=*.pdb",
+                "--threads=8",
+                r"D:\ci\workspaces",
+                r"D:\ci\cache",
+            ]
+        );
+        let missing = profile_args(CONFIG, "nightly").unwrap_err();
+        assert!(missing.contains("ci-clean"));
+        assert!(profile_args("[profile.x]\nprofile = \"y\"", "x").is_err());
+
+        std::env::set_var("RMBRR_PROFILE_TEST", "q");
+        let vars = "[profile.x]\nquarantine = '%RMBRR_PROFILE_TEST%/bin'\npaths = ['$RMBRR_PROFILE_TEST/out']";
+        assert_eq!(
+            strings(profile_args(vars, "x").unwrap()),
+            vec!["--quarantine=q/bin", "q/out"]
+        );
+        let unset = "[profile.x]\nquarantine = '$RMBRR_UNSET_TEST'";
+        assert!(profile_args(unset, "x")
+            .unwrap_err()
+            .contains("RMBRR_UNSET_TEST"));
+    }
+
+    #[test]
+    fn test_expand() {
+        let temp = std::env::temp_dir().join("win_rmdir_profile_test.toml");
+        fs::write(&temp, CONFIG).unwrap();
+        let args: Vec<OsString> = ["rmbrr", "--profile", "ci-clean", "-v"]
+            .into_iter()
+            .map(OsString::from)
+            .chain([OsString::from(format!("--config={}", temp.display()))])
+            .collect();
+
+        let expanded = strings(expand(args).unwrap());
+        assert_eq!(expanded[0], "rmbrr");
+        assert_eq!(expanded[1], "--force");
+        // The user's own arguments come last, so they override the profile's
+        assert_eq!(
+            &expanded[expanded.len() - 4..expanded.len() - 1],
+            ["--profile", "ci-clean", "-v"]
+        );
+        assert_eq!(expanded.len(), 1 + 7 + 4);
+
+        let unknown = vec![OsString::from("rmbrr"), "--profile=nightly".into()];
+        let unknown = [unknown, vec![format!("--config={}", temp.display()).into()]].concat();
+        assert!(expand(unknown)
+            .unwrap_err()
+            .contains("no profile 'nightly'"));
+
+        fs::remove_file(&temp).ok();
+    }
+}
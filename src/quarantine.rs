@@ -0,0 +1,115 @@
+// Quarantine mode (`--quarantine DIR --expire 7d`): targets are renamed into DIR instead of
+// being deleted, and each later run deletes the entries whose expiry has passed
+//
+// Every target gets its own entry, `DIR/<expiry>-<name>/`, holding the target under its own
+// name and an `origin` file with the path it came from, so it can be moved back by hand.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long quarantined targets are kept when --expire is not given
+pub const DEFAULT_EXPIRE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// File in each entry recording where the target was quarantined from
+const ORIGIN_FILE: &str = "origin";
+
+/// Move `target` into a new entry under `dir` that expires after `expire`; returns the
+/// target's new path
+///
+/// This is a rename, so `dir` has to be on the same volume as the target.
+pub fn quarantine(target: &Path, dir: &Path, expire: Duration) -> io::Result<PathBuf> {
+    let name = target
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "target has no name"))?;
+    let expires = (SystemTime::now() + expire)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    fs::create_dir_all(dir)?;
+
+    let base = format!("{}-{}", expires, name.to_string_lossy());
+    let mut entry = dir.join(&base);
+    let mut n = 1;
+    // create_dir fails on existing entries, so concurrent runs never share one
+    loop {
+        match fs::create_dir(&entry) {
+            Ok(()) => break,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                n += 1;
+                entry = dir.join(format!("{}-{}", base, n));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let origin = std::path::absolute(target)?;
+    let moved = entry.join(name);
+    let result = fs::write(entry.join(ORIGIN_FILE), origin.to_string_lossy().as_bytes())
+        .and_then(|()| fs::rename(target, &moved));
+    if let Err(e) = result {
+        fs::remove_dir_all(&entry).ok();
+        return Err(e);
+    }
+    Ok(moved)
+}
+
+/// Expiry of a quarantine entry (Unix seconds), from its name
+pub fn expiry(name: &str) -> Option<u64> {
+    name.split_once('-')?.0.parse().ok()
+}
+
+/// Entries under `dir` whose expiry is at or before `now`, oldest first; entries rmbrr did
+/// not create are left alone
+pub fn expired(dir: &Path, now: SystemTime) -> io::Result<Vec<PathBuf>> {
+    let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(expires) = expiry(&name.to_string_lossy()) else {
+            continue;
+        };
+        let path = entry.path();
+        if expires <= now && entry.file_type()?.is_dir() && path.join(ORIGIN_FILE).is_file() {
+            entries.push((expires, path));
+        }
+    }
+    entries.sort();
+    Ok(entries.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantine_and_expire() {
+        let temp = std::env::temp_dir().join("win_rmdir_quarantine_test");
+        let _ = fs::remove_dir_all(&temp);
+        let quarantine_dir = temp.join("quarantine");
+        fs::create_dir_all(temp.join("build/out")).unwrap();
+        fs::write(temp.join("build/out/app.o"), "x").unwrap();
+        fs::create_dir_all(temp.join("other/build")).unwrap();
+
+        let moved = quarantine(&temp.join("build"), &quarantine_dir, Duration::ZERO).unwrap();
+        assert!(!temp.join("build").exists());
+        assert!(moved.join("out/app.o").is_file());
+        // A second target with the same name gets its own entry
+        let second =
+            quarantine(&temp.join("other/build"), &quarantine_dir, DEFAULT_EXPIRE).unwrap();
+        assert_ne!(moved.parent(), second.parent());
+        fs::create_dir_all(quarantine_dir.join("unrelated")).unwrap();
+
+        let now = SystemTime::now() + Duration::from_secs(1);
+        assert_eq!(
+            expired(&quarantine_dir, now).unwrap(),
+            vec![moved.parent().unwrap().to_path_buf()]
+        );
+        let origin = fs::read_to_string(moved.parent().unwrap().join(ORIGIN_FILE)).unwrap();
+        assert!(origin.ends_with("build"));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
@@ -0,0 +1,613 @@
+// Progress reporting while workers run: in-place progress bar, periodic heartbeat lines,
+// or one JSON object per event, plus the console's taskbar button on Windows and the terminal
+// tab (OSC 9;4) next to the bar. Driven by the workers' event channel.
+
+use crate::sink::EventSink;
+use crate::throughput::Throughput;
+use crossbeam_channel::RecvTimeoutError;
+use rmbrr_core::events::{Event, EventReceiver};
+use rmbrr_core::output;
+use rmbrr_core::winapi::TaskbarProgress;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const BAR_INTERVAL: Duration = Duration::from_millis(250);
+/// A directory still being processed after this long is named in the progress line
+const SLOW_DIR: Duration = Duration::from_secs(2);
+/// Rates are averaged over roughly this much recent history
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+/// Longest path shown in the progress bar before eliding its start
+const BAR_PATH_WIDTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// Single line redrawn in place with `\r`
+    Bar,
+    /// A complete line every interval, for CI logs and redirected output
+    Heartbeat(Duration),
+    /// Every event as a JSON line (--json)
+    Json,
+}
+
+pub struct Reporter {
+    /// None when only mirroring to a --progress-pipe
+    mode: Option<ProgressMode>,
+    events: EventReceiver,
+    sink: Option<Arc<EventSink>>,
+    start: Instant,
+    dirs_total: usize,
+    dirs_done: usize,
+    /// Directories that could not be removed; they count toward completion too
+    dirs_failed: usize,
+    files_total: usize,
+    files_done: usize,
+    cancelled: bool,
+    /// Rates of earlier runs on the volume, for an estimate before this run has its own
+    history: Option<Throughput>,
+    /// Directory each worker is processing, and since when
+    active: HashMap<usize, (PathBuf, Instant)>,
+    rates: Rates,
+    /// Length of the last bar drawn, so a shorter one can blank the rest
+    bar_len: usize,
+    /// Also show progress on the console window's taskbar button
+    taskbar: bool,
+    /// Mirror the bar on the terminal tab with OSC 9;4 sequences
+    terminal_progress: bool,
+}
+
+impl Reporter {
+    pub fn new(
+        mode: Option<ProgressMode>,
+        events: EventReceiver,
+        sink: Option<Arc<EventSink>>,
+    ) -> Self {
+        Self {
+            mode,
+            events,
+            sink,
+            start: Instant::now(),
+            dirs_total: 0,
+            dirs_done: 0,
+            dirs_failed: 0,
+            files_total: 0,
+            files_done: 0,
+            cancelled: false,
+            history: None,
+            active: HashMap::new(),
+            rates: Rates::default(),
+            bar_len: 0,
+            taskbar: false,
+            terminal_progress: false,
+        }
+    }
+
+    /// Estimate the remaining time from `history` until this run's own rates take over
+    pub fn with_history(mut self, history: Option<Throughput>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Show progress on the console window's taskbar button too, where there is one
+    pub fn with_taskbar(mut self, taskbar: bool) -> Self {
+        self.taskbar = taskbar;
+        self
+    }
+
+    /// Report until the `Finished` event arrives (or every sender is gone); runs on its own thread
+    pub fn spawn(self) -> JoinHandle<()> {
+        if self.mode == Some(ProgressMode::Bar) {
+            output::set_progress_active(true);
+        }
+        std::thread::spawn(move || self.run())
+    }
+
+    fn run(mut self) {
+        self.terminal_progress =
+            self.mode == Some(ProgressMode::Bar) && output::terminal_progress_supported();
+        // Opened here: the COM object belongs to this thread
+        let taskbar = self.taskbar.then(TaskbarProgress::open).flatten();
+        let interval = match self.mode {
+            Some(ProgressMode::Bar) => BAR_INTERVAL,
+            Some(ProgressMode::Heartbeat(interval)) => interval,
+            _ if taskbar.is_some() => BAR_INTERVAL,
+            // Nothing is drawn between events
+            Some(ProgressMode::Json) | None => Duration::MAX,
+        };
+        let mut next_tick = Instant::now().checked_add(interval);
+
+        loop {
+            let received = match next_tick {
+                Some(deadline) => self.events.recv_deadline(deadline),
+                None => self
+                    .events
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(event) => {
+                    if self.mode == Some(ProgressMode::Json) || self.sink.is_some() {
+                        let json = to_json(&event);
+                        if let Some(ref sink) = self.sink {
+                            sink.send(&json);
+                        }
+                        if self.mode == Some(ProgressMode::Json) {
+                            println!("{}", json);
+                        }
+                    }
+                    if self.apply(&event) {
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if next_tick.is_some_and(|t| Instant::now() >= t) {
+                self.tick();
+                if let Some(ref taskbar) = taskbar {
+                    taskbar.set(
+                        (self.dirs_done + self.dirs_failed) as u64,
+                        self.dirs_total as u64,
+                    );
+                }
+                next_tick = Instant::now().checked_add(interval);
+            }
+        }
+        self.finish();
+        // A red button is left behind until the window's next program takes it over
+        match taskbar {
+            Some(taskbar) if self.dirs_failed > 0 && !self.cancelled => taskbar.set_error(),
+            Some(taskbar) => taskbar.clear(),
+            None => {}
+        }
+    }
+
+    /// Update counters from an event; true once the run is over
+    fn apply(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Started {
+                dirs_total,
+                files_total,
+                ..
+            } => {
+                self.dirs_total = *dirs_total;
+                self.files_total = *files_total;
+            }
+            Event::DirStarted { worker, path } => {
+                self.active.insert(*worker, (path.clone(), Instant::now()));
+            }
+            Event::DirDone { path, .. } => {
+                self.dirs_done += 1;
+                self.active.retain(|_, (p, _)| p != path);
+            }
+            Event::FileDone { .. } => self.files_done += 1,
+            Event::Error(item) if item.is_dir => {
+                self.dirs_failed += 1;
+                self.active.retain(|_, (p, _)| *p != item.path);
+            }
+            Event::Error(_) => {}
+            Event::Finished { cancelled, .. } => {
+                self.cancelled = *cancelled;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn tick(&mut self) {
+        self.rates
+            .record(Instant::now(), self.dirs_done, self.files_done);
+        match self.mode {
+            Some(ProgressMode::Bar) => {
+                output::print_pending_warnings();
+                self.print_bar()
+            }
+            Some(ProgressMode::Heartbeat(_)) => println!("{}", self.heartbeat_line()),
+            Some(ProgressMode::Json) | None => {}
+        }
+    }
+
+    /// Print the final progress line
+    fn finish(&mut self) {
+        self.rates
+            .record(Instant::now(), self.dirs_done, self.files_done);
+        match self.mode {
+            Some(ProgressMode::Bar) if self.cancelled => {
+                println!("{}", self.osc(OSC_CLEAR, 0));
+                output::set_progress_active(false);
+            }
+            Some(ProgressMode::Bar) if self.dirs_failed > 0 => {
+                // The tab stays red until the terminal's next program takes it over
+                println!(
+                    "{}\rDeleting... 100% ({}) - Finished with failures",
+                    self.osc(OSC_ERROR, 100),
+                    self.dirs_counts()
+                );
+                output::set_progress_active(false);
+            }
+            Some(ProgressMode::Bar) => {
+                let total = self.dirs_total;
+                println!(
+                    "{}\rDeleting... 100% ({}/{} dirs) - Complete!",
+                    self.osc(OSC_CLEAR, 0),
+                    total,
+                    total
+                );
+                output::set_progress_active(false);
+            }
+            Some(ProgressMode::Heartbeat(_)) if self.cancelled => {
+                println!("{} - interrupted", self.heartbeat_line())
+            }
+            Some(ProgressMode::Heartbeat(_)) => {
+                println!("{} - complete", self.heartbeat_line())
+            }
+            Some(ProgressMode::Json) | None => {}
+        }
+    }
+
+    fn print_bar(&mut self) {
+        let finished = self.dirs_done + self.dirs_failed;
+        let pct = (finished as f64 / self.dirs_total.max(1) as f64 * 100.0) as u32;
+        let (dirs_rate, files_rate) = self.rates.per_sec();
+        let mut line = format!(
+            "Deleting... {}% ({}) {} files/s, {} dirs/s",
+            pct,
+            self.dirs_counts(),
+            abbreviate(files_rate as usize),
+            abbreviate(dirs_rate as usize)
+        );
+        if let Some(eta) = self.eta() {
+            line.push_str(&format!(", ETA {}", format_eta(eta)));
+        }
+        if let Some((path, elapsed)) = self.slowest() {
+            line.push_str(&format!(
+                " - {} ({}s)",
+                elide(path, BAR_PATH_WIDTH),
+                elapsed.as_secs()
+            ));
+        }
+        let len = line.chars().count();
+        print!(
+            "{}\r{}{}",
+            self.osc(OSC_NORMAL, pct),
+            line,
+            " ".repeat(self.bar_len.saturating_sub(len))
+        );
+        self.bar_len = len;
+        std::io::stdout().flush().ok();
+    }
+
+    /// The OSC 9;4 sequence setting the tab's progress, if the terminal shows it
+    fn osc(&self, state: u8, percent: u32) -> String {
+        if self.terminal_progress {
+            osc_progress(state, percent)
+        } else {
+            String::new()
+        }
+    }
+
+    /// `done/total dirs`, plus the failed count once there is one
+    fn dirs_counts(&self) -> String {
+        let mut counts = format!("{}/{} dirs", self.dirs_done, self.dirs_total);
+        if self.dirs_failed > 0 {
+            counts.push_str(&format!(", {} failed", self.dirs_failed));
+        }
+        counts
+    }
+
+    /// Time left: from the volume's history at first, shifting to this run's own rates in
+    /// proportion to the work done
+    fn eta(&self) -> Option<Duration> {
+        let files_left = self.files_total.saturating_sub(self.files_done);
+        let dirs_left = self
+            .dirs_total
+            .saturating_sub(self.dirs_done + self.dirs_failed);
+        if files_left + dirs_left == 0 {
+            return None;
+        }
+        let (dirs_per_sec, files_per_sec) = self.rates.per_sec();
+        let live = Throughput {
+            files_per_sec,
+            dirs_per_sec,
+        }
+        .estimate(files_left, dirs_left);
+        let expected = self.history.and_then(|h| h.estimate(files_left, dirs_left));
+        match (expected, live) {
+            (Some(expected), Some(live)) => {
+                let total = (self.files_total + self.dirs_total).max(1) as f64;
+                let done = (self.files_done + self.dirs_done + self.dirs_failed) as f64 / total;
+                Some(expected.mul_f64(1.0 - done) + live.mul_f64(done))
+            }
+            (expected, live) => expected.or(live),
+        }
+    }
+
+    /// The directory that has been in progress longest, once it counts as slow
+    fn slowest(&self) -> Option<(&Path, Duration)> {
+        self.active
+            .values()
+            .map(|(path, since)| (path.as_path(), since.elapsed()))
+            .filter(|(_, elapsed)| *elapsed >= SLOW_DIR)
+            .max_by_key(|(_, elapsed)| *elapsed)
+    }
+
+    fn heartbeat_line(&self) -> String {
+        let elapsed = self.start.elapsed();
+        let (dirs_rate, files_rate) = self.rates.per_sec();
+        let mut line = format!(
+            "[{:>4}s] deleted {}/{} dirs, {} files, {} files/s, {} dirs/s",
+            elapsed.as_secs(),
+            abbreviate(self.dirs_done),
+            abbreviate(self.dirs_total),
+            abbreviate(self.files_done),
+            abbreviate(files_rate as usize),
+            abbreviate(dirs_rate as usize)
+        );
+        if self.dirs_failed > 0 {
+            line.push_str(&format!(", {} dirs failed", abbreviate(self.dirs_failed)));
+        }
+        if let Some(eta) = self.eta() {
+            line.push_str(&format!(", ETA {}", format_eta(eta)));
+        }
+        if let Some((path, elapsed)) = self.slowest() {
+            line.push_str(&format!(
+                ", busy on {} for {}s",
+                path.display(),
+                elapsed.as_secs()
+            ));
+        }
+        line
+    }
+}
+
+/// Rolling dirs/s and files/s from periodic samples of the running totals
+#[derive(Default)]
+struct Rates {
+    samples: VecDeque<(Instant, usize, usize)>,
+}
+
+impl Rates {
+    fn record(&mut self, at: Instant, dirs: usize, files: usize) {
+        self.samples.push_back((at, dirs, files));
+        // Keep two samples at least, so slow heartbeats still get a rate
+        while self.samples.len() > 2 && at.duration_since(self.samples[0].0) > RATE_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    /// (dirs/s, files/s) across the window
+    fn per_sec(&self) -> (f64, f64) {
+        let (Some(first), Some(last)) = (self.samples.front(), self.samples.back()) else {
+            return (0.0, 0.0);
+        };
+        let secs = last.0.duration_since(first.0).as_secs_f64();
+        if secs <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (
+            (last.1 - first.1) as f64 / secs,
+            (last.2 - first.2) as f64 / secs,
+        )
+    }
+}
+
+/// OSC 9;4 states: no progress, a normal bar, an error bar
+const OSC_CLEAR: u8 = 0;
+const OSC_NORMAL: u8 = 1;
+const OSC_ERROR: u8 = 2;
+
+/// The ConEmu/Windows Terminal escape sequence showing `percent` in `state` on the tab
+fn osc_progress(state: u8, percent: u32) -> String {
+    format!("\x1b]9;4;{};{}\x07", state, percent.min(100))
+}
+
+/// Serialize an event for --json and --progress-pipe output
+pub fn to_json(event: &Event) -> Value {
+    match event {
+        Event::Started {
+            root,
+            dirs_total,
+            files_total,
+        } => json!({
+            "event": "started",
+            "root": root.display().to_string(),
+            "dirs_total": dirs_total,
+            "files_total": files_total,
+        }),
+        Event::DirStarted { worker, path } => json!({
+            "event": "dir_started",
+            "worker": worker,
+            "path": path.display().to_string(),
+        }),
+        Event::DirDone { path, kept } => json!({
+            "event": "dir_done",
+            "path": path.display().to_string(),
+            "kept": kept,
+        }),
+        Event::FileDone { path } => json!({
+            "event": "file_done",
+            "path": path.display().to_string(),
+        }),
+        Event::Error(item) => json!({
+            "event": "error",
+            "path": item.path.display().to_string(),
+            "is_dir": item.is_dir,
+            "error": item.error,
+            "os_error": item.os_error,
+        }),
+        Event::Finished {
+            root,
+            dirs_deleted,
+            files_deleted,
+            failed,
+            elapsed,
+            cancelled,
+        } => json!({
+            "event": "finished",
+            "root": root.display().to_string(),
+            "dirs_deleted": dirs_deleted,
+            "files_deleted": files_deleted,
+            "failed": failed,
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "cancelled": cancelled,
+        }),
+    }
+}
+
+/// Shorten a path to at most `max` characters by replacing its start with "..."
+fn elide(path: &Path, max: usize) -> String {
+    let s = path.display().to_string();
+    let len = s.chars().count();
+    if len <= max {
+        return s;
+    }
+    let tail: String = s.chars().skip(len - max.saturating_sub(3)).collect();
+    format!("...{}", tail)
+}
+
+/// Format a remaining time to the nearest second (45s, 3m05s, 2h10m)
+pub fn format_eta(time: Duration) -> String {
+    let secs = time.as_secs_f64().round() as u64;
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Format a count compactly (950, 12.3k, 4.1M)
+pub fn abbreviate(n: usize) -> String {
+    match n {
+        0..=999 => n.to_string(),
+        1_000..=999_999 => format!("{:.1}k", n as f64 / 1e3),
+        _ => format!("{:.1}M", n as f64 / 1e6),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_abbreviate() {
+        assert_eq!(abbreviate(950), "950");
+        assert_eq!(abbreviate(12_345), "12.3k");
+        assert_eq!(abbreviate(4_100_000), "4.1M");
+    }
+
+    #[test]
+    fn test_rolling_rates() {
+        let start = Instant::now();
+        let mut rates = Rates::default();
+        assert_eq!(rates.per_sec(), (0.0, 0.0));
+
+        rates.record(start, 0, 0);
+        rates.record(start + Duration::from_secs(1), 10, 1000);
+        assert_eq!(rates.per_sec(), (10.0, 1000.0));
+
+        // Samples older than the window stop counting
+        rates.record(start + Duration::from_secs(10), 10, 1000);
+        rates.record(start + Duration::from_secs(12), 30, 1200);
+        assert_eq!(rates.per_sec(), (10.0, 100.0));
+    }
+
+    #[test]
+    fn test_elide() {
+        assert_eq!(elide(Path::new("/a/b"), 10), "/a/b");
+        assert_eq!(
+            elide(Path::new("/share/foo/giant_dir"), 13),
+            ".../giant_dir"
+        );
+    }
+
+    #[test]
+    fn test_failed_dirs_count_as_finished() {
+        let (_tx, rx) = rmbrr_core::events::channel();
+        let mut reporter = Reporter::new(None, rx, None);
+        reporter.apply(&Event::Started {
+            root: PathBuf::from("root"),
+            dirs_total: 2,
+            files_total: 0,
+        });
+        reporter.apply(&Event::DirDone {
+            path: PathBuf::from("root/a"),
+            kept: false,
+        });
+        assert_eq!(reporter.dirs_counts(), "1/2 dirs");
+        reporter.apply(&Event::Error(rmbrr_core::error::FailedItem {
+            path: PathBuf::from("root"),
+            error: "Access is denied.".to_string(),
+            is_dir: true,
+            os_error: Some(5),
+        }));
+        assert_eq!(reporter.dirs_counts(), "1/2 dirs, 1 failed");
+        assert_eq!(
+            reporter.dirs_done + reporter.dirs_failed,
+            reporter.dirs_total
+        );
+    }
+
+    #[test]
+    fn test_eta_from_history_then_live_rates() {
+        let (_tx, rx) = rmbrr_core::events::channel();
+        let history = Throughput {
+            files_per_sec: 100.0,
+            dirs_per_sec: 20.0,
+        };
+        let mut reporter = Reporter::new(None, rx, None).with_history(Some(history));
+        reporter.apply(&Event::Started {
+            root: PathBuf::from("root"),
+            dirs_total: 100,
+            files_total: 900,
+        });
+        // Before any progress, history alone: 900 files at 100/s
+        assert_eq!(reporter.eta(), Some(Duration::from_secs(9)));
+
+        // Half done at twice the historical rates: 450 files left take 2.25s live, 4.5s
+        // by history; the estimate sits halfway
+        let start = Instant::now();
+        reporter.rates.record(start, 0, 0);
+        reporter.dirs_done = 50;
+        reporter.files_done = 450;
+        reporter
+            .rates
+            .record(start + Duration::from_millis(2250), 50, 450);
+        let eta = reporter.eta().unwrap().as_secs_f64();
+        assert!((eta - 3.375).abs() < 1e-9, "{}", eta);
+
+        reporter.history = None;
+        let eta = reporter.eta().unwrap().as_secs_f64();
+        assert!((eta - 2.25).abs() < 1e-9, "{}", eta);
+    }
+
+    #[test]
+    fn test_osc_progress() {
+        assert_eq!(osc_progress(OSC_NORMAL, 42), "\x1b]9;4;1;42\x07");
+        assert_eq!(osc_progress(OSC_ERROR, 250), "\x1b]9;4;2;100\x07");
+        assert_eq!(osc_progress(OSC_CLEAR, 0), "\x1b]9;4;0;0\x07");
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(Duration::from_millis(44_600)), "45s");
+        assert_eq!(format_eta(Duration::from_secs(185)), "3m05s");
+        assert_eq!(format_eta(Duration::from_secs(7800)), "2h10m");
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = Event::DirDone {
+            path: PathBuf::from("a"),
+            kept: true,
+        };
+        assert_eq!(
+            to_json(&event),
+            json!({"event": "dir_done", "path": "a", "kept": true})
+        );
+    }
+}
@@ -0,0 +1,329 @@
+// `rmbrr schedule`: register a daily cleanup with Task Scheduler (Windows) or cron (Unix),
+// and list or remove the ones rmbrr registered
+//
+// Windows tasks live in the `\rmbrr\` Task Scheduler folder; cron entries carry a
+// `# rmbrr:<name>` marker so they can be told apart from the user's own.
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Task Scheduler folder holding rmbrr's tasks
+#[cfg(windows)]
+const TASK_FOLDER: &str = r"\rmbrr\";
+
+/// Marker ending each cron line rmbrr manages
+#[cfg(not(windows))]
+const CRON_MARKER: &str = "# rmbrr:";
+
+/// A daily cleanup to register
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub name: String,
+    /// Time of day as (hour, minute)
+    pub at: (u8, u8),
+    /// Arguments rmbrr is run with, target last
+    pub args: Vec<String>,
+}
+
+/// Parse a time of day such as `03:00` or `23:45`
+pub fn parse_time(value: &str) -> Result<(u8, u8), String> {
+    let parsed = value.split_once(':').and_then(|(hour, minute)| {
+        let hour: u8 = hour.parse().ok()?;
+        let minute: u8 = minute.parse().ok()?;
+        (hour < 24 && minute < 60).then_some((hour, minute))
+    });
+    parsed.ok_or_else(|| format!("invalid time '{}' (expected HH:MM, e.g. 03:00)", value))
+}
+
+/// Default task name for a target: its path with separators and drive colons as dashes
+pub fn default_name(path: &Path) -> String {
+    let name: String = path
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() {
+        "root".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Quote an argument for a POSIX shell (cron runs its lines with sh)
+#[cfg_attr(windows, allow(dead_code))]
+fn sh_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Refuse text a crontab line cannot carry: control characters would end or split the
+/// line, and a `%` in the name would be read as a newline by cron
+#[cfg_attr(windows, allow(dead_code))]
+fn check_cron_text(what: &str, text: &str, allow_percent: bool) -> io::Result<()> {
+    if text.chars().any(char::is_control) || (!allow_percent && text.contains('%')) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} '{}' cannot be written to a crontab",
+                what,
+                text.escape_debug()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// The crontab line running `exe` for `schedule`
+#[cfg_attr(windows, allow(dead_code))]
+pub fn cron_line(exe: &str, schedule: &Schedule) -> io::Result<String> {
+    check_cron_text("name", &schedule.name, false)?;
+    let mut words = Vec::new();
+    for arg in std::iter::once(exe).chain(schedule.args.iter().map(String::as_str)) {
+        check_cron_text("argument", arg, true)?;
+        // cron turns an unescaped % into a newline, even inside shell quotes
+        words.push(sh_quote(arg).replace('%', r"\%"));
+    }
+    Ok(format!(
+        "{} {} * * * {} # rmbrr:{}",
+        schedule.at.1,
+        schedule.at.0,
+        words.join(" "),
+        schedule.name
+    ))
+}
+
+/// Quote an argument for a Windows command line (the task's action)
+#[cfg_attr(not(windows), allow(dead_code))]
+fn win_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    // A trailing backslash would escape the closing quote (D:\ -> "D:\\")
+    let trailing = arg.len() - arg.trim_end_matches('\\').len();
+    format!("\"{}{}\"", arg.replace('"', "\\\""), "\\".repeat(trailing))
+}
+
+/// Name the helper program when it cannot be started at all
+fn cannot_run(program: &str) -> impl FnOnce(io::Error) -> io::Error + '_ {
+    move |e| io::Error::new(e.kind(), format!("cannot run {}: {}", program, e))
+}
+
+/// Run a helper program, turning a non-zero exit into an error carrying its output
+#[cfg(windows)]
+fn run(command: &mut Command) -> io::Result<String> {
+    let output = command.output().map_err(cannot_run("schtasks"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(stderr.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(windows)]
+pub fn install(exe: &str, schedule: &Schedule) -> io::Result<()> {
+    let mut action = vec![win_quote(exe)];
+    action.extend(schedule.args.iter().map(|arg| win_quote(arg)));
+    run(Command::new("schtasks").args([
+        "/Create",
+        "/F",
+        "/SC",
+        "DAILY",
+        "/ST",
+        &format!("{:02}:{:02}", schedule.at.0, schedule.at.1),
+        "/TN",
+        &format!("{}{}", TASK_FOLDER, schedule.name),
+        "/TR",
+        &action.join(" "),
+    ]))
+    .map(drop)
+}
+
+#[cfg(windows)]
+pub fn remove(name: &str) -> io::Result<()> {
+    let task = format!("{}{}", TASK_FOLDER, name);
+    run(Command::new("schtasks").args(["/Delete", "/F", "/TN", &task])).map(drop)
+}
+
+/// Registered cleanups, one line each
+#[cfg(windows)]
+pub fn list() -> io::Result<Vec<String>> {
+    // With no tasks in the folder schtasks reports an error rather than nothing
+    let Ok(output) =
+        run(Command::new("schtasks").args(["/Query", "/FO", "CSV", "/NH", "/TN", TASK_FOLDER]))
+    else {
+        return Ok(Vec::new());
+    };
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split("\",\"").collect();
+            let name = fields
+                .first()?
+                .trim_matches('"')
+                .strip_prefix(TASK_FOLDER)?;
+            let next = fields.get(1).map_or("", |f| f.trim_matches('"'));
+            Some(format!("{}  (next run: {})", name, next))
+        })
+        .collect())
+}
+
+/// The user's crontab, empty if they have none yet
+#[cfg(not(windows))]
+fn read_crontab() -> io::Result<String> {
+    let output = Command::new("crontab")
+        .arg("-l")
+        .output()
+        .map_err(cannot_run("crontab"))?;
+    // crontab -l fails when there is no crontab
+    Ok(if output.status.success() {
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    } else {
+        String::new()
+    })
+}
+
+#[cfg(not(windows))]
+fn write_crontab(contents: &str) -> io::Result<()> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(cannot_run("crontab"))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(contents.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("crontab exited with {}", status)));
+    }
+    Ok(())
+}
+
+/// Remove the lines for `name` from a crontab; returns the new contents and whether any
+/// line matched
+#[cfg(not(windows))]
+pub fn without_entry(crontab: &str, name: &str) -> (String, bool) {
+    let marker = format!("{}{}", CRON_MARKER, name);
+    let mut found = false;
+    let mut kept = String::new();
+    for line in crontab.lines() {
+        if line.trim_end().ends_with(&marker) {
+            found = true;
+        } else {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+    (kept, found)
+}
+
+/// Add (or replace) the cron entry for `schedule`
+#[cfg(not(windows))]
+pub fn install(exe: &str, schedule: &Schedule) -> io::Result<()> {
+    let (mut crontab, _) = without_entry(&read_crontab()?, &schedule.name);
+    crontab.push_str(&cron_line(exe, schedule)?);
+    crontab.push('\n');
+    write_crontab(&crontab)
+}
+
+#[cfg(not(windows))]
+pub fn remove(name: &str) -> io::Result<()> {
+    let (crontab, found) = without_entry(&read_crontab()?, name);
+    if !found {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no scheduled cleanup named '{}'", name),
+        ));
+    }
+    write_crontab(&crontab)
+}
+
+/// Registered cleanups, one line each
+#[cfg(not(windows))]
+pub fn list() -> io::Result<Vec<String>> {
+    Ok(read_crontab()?
+        .lines()
+        .filter_map(|line| {
+            let (entry, name) = line.rsplit_once(CRON_MARKER)?;
+            Some(format!("{}  {}", name.trim(), entry.trim()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("03:00"), Ok((3, 0)));
+        assert_eq!(parse_time("23:59"), Ok((23, 59)));
+        assert!(parse_time("24:00").is_err());
+        assert!(parse_time("3").is_err());
+        assert!(parse_time("03:60").is_err());
+    }
+
+    #[test]
+    fn test_cron_line_and_quoting() {
+        let schedule = Schedule {
+            name: "ci".to_string(),
+            at: (3, 5),
+            args: vec![
+                "--older-than".to_string(),
+                "3d".to_string(),
+                "/srv/ci/it's here".to_string(),
+            ],
+        };
+        assert_eq!(
+            cron_line("/usr/bin/rmbrr", &schedule).unwrap(),
+            r"5 3 * * * /usr/bin/rmbrr --older-than 3d '/srv/ci/it'\''s here' # rmbrr:ci"
+        );
+        let mut odd = schedule.clone();
+        odd.args = vec!["/srv/100%".to_string()];
+        assert_eq!(
+            cron_line("/usr/bin/rmbrr", &odd).unwrap(),
+            r"5 3 * * * /usr/bin/rmbrr '/srv/100\%' # rmbrr:ci"
+        );
+        odd.args = vec!["/srv/a\nb".to_string()];
+        assert!(cron_line("/usr/bin/rmbrr", &odd).is_err());
+        odd.args.clear();
+        odd.name = "50%".to_string();
+        assert!(cron_line("/usr/bin/rmbrr", &odd).is_err());
+        assert_eq!(win_quote(r"D:\my dir\"), r#""D:\my dir\\""#);
+        assert_eq!(win_quote(r"D:\ci\workspaces"), r"D:\ci\workspaces");
+        assert_eq!(
+            default_name(Path::new(r"D:\ci\workspaces")),
+            "D--ci-workspaces"
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_without_entry() {
+        let crontab = "0 1 * * * backup\n0 3 * * * rmbrr /srv # rmbrr:srv\n";
+        let (kept, found) = without_entry(crontab, "srv");
+        assert!(found);
+        assert_eq!(kept, "0 1 * * * backup\n");
+        assert!(!without_entry(&kept, "srv").1);
+    }
+}
@@ -0,0 +1,98 @@
+// Mirror of the JSON event stream for external dashboards (--progress-pipe)
+
+use rmbrr_core::output;
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Mutex;
+
+/// Where --progress-pipe sends events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SinkTarget {
+    /// `tcp://host:port` or a bare socket address
+    Tcp(String),
+    /// A FIFO on Unix or a named pipe on Windows (a bare name means `\\.\pipe\<name>`)
+    Pipe(String),
+}
+
+impl SinkTarget {
+    pub fn parse(target: &str) -> Self {
+        if let Some(addr) = target.strip_prefix("tcp://") {
+            return SinkTarget::Tcp(addr.to_string());
+        }
+        if target.parse::<SocketAddr>().is_ok() {
+            return SinkTarget::Tcp(target.to_string());
+        }
+        if cfg!(windows) && !target.contains(['\\', '/']) {
+            return SinkTarget::Pipe(format!(r"\\.\pipe\{}", target));
+        }
+        SinkTarget::Pipe(target.to_string())
+    }
+}
+
+/// A writer that receives one JSON event per line; the first write error disables it
+pub struct EventSink {
+    writer: Mutex<Option<Box<dyn Write + Send>>>,
+}
+
+impl EventSink {
+    /// Connect to the target; opening a FIFO waits until something reads from it
+    pub fn open(target: &SinkTarget) -> io::Result<Self> {
+        let writer: Box<dyn Write + Send> = match target {
+            SinkTarget::Tcp(addr) => Box::new(TcpStream::connect(addr)?),
+            SinkTarget::Pipe(path) => Box::new(OpenOptions::new().write(true).open(path)?),
+        };
+        Ok(Self {
+            writer: Mutex::new(Some(writer)),
+        })
+    }
+
+    pub fn send(&self, event: &Value) {
+        let mut writer = self.writer.lock().unwrap();
+        let Some(w) = writer.as_mut() else {
+            return;
+        };
+        // A dashboard going away must not stop the deletion
+        if let Err(e) = writeln!(w, "{}", event).and_then(|_| w.flush()) {
+            output::warn(format!("progress pipe closed, no longer mirroring: {}", e));
+            *writer = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            SinkTarget::parse("tcp://localhost:9000"),
+            SinkTarget::Tcp("localhost:9000".into())
+        );
+        assert_eq!(
+            SinkTarget::parse("127.0.0.1:9000"),
+            SinkTarget::Tcp("127.0.0.1:9000".into())
+        );
+        assert_eq!(
+            SinkTarget::parse("/tmp/rmbrr.fifo"),
+            SinkTarget::Pipe("/tmp/rmbrr.fifo".into())
+        );
+    }
+
+    #[test]
+    fn test_tcp_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = SinkTarget::parse(&listener.local_addr().unwrap().to_string());
+        let sink = EventSink::open(&target).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        sink.send(&serde_json::json!({"event": "started"}));
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"event\":\"started\"}\n");
+    }
+}
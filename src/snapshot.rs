@@ -0,0 +1,339 @@
+// Dry-run snapshots (`-n --snapshot FILE`) and the diff of a later dry run against one
+// (`-n --diff FILE`): which directories appeared, disappeared, grew or shrank in between
+//
+// A snapshot file holds one entry per target, keyed by its absolute path, so several
+// targets can share a file. Each entry maps directories (relative to the target) to the
+// files they would lose, themselves included.
+
+use crate::i18n::{t, tf, Msg};
+use crate::space::format_bytes;
+use crate::tree_view::{self, Totals};
+use rmbrr_core::tree::DirectoryTree;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Changes listed before the rest are only counted
+const MAX_CHANGES: usize = 50;
+
+/// Per-directory totals of one target at one point in time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Unix seconds
+    pub taken: u64,
+    /// Directory relative to the target ("" for the target itself) -> totals below it
+    pub dirs: BTreeMap<String, Totals>,
+}
+
+impl Snapshot {
+    pub fn take(root: &Path, tree: &DirectoryTree) -> Self {
+        let dirs = tree_view::totals(tree)
+            .into_iter()
+            .filter_map(|(dir, totals)| Some((relative(root, &dir)?, totals)))
+            .collect();
+        Self {
+            taken: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            dirs,
+        }
+    }
+
+    fn total(&self) -> Totals {
+        self.dirs.get("").copied().unwrap_or_default()
+    }
+
+    fn to_json(&self) -> Value {
+        let dirs: Map<String, Value> = self
+            .dirs
+            .iter()
+            .map(|(dir, totals)| (dir.clone(), json!([totals.files, totals.bytes])))
+            .collect();
+        json!({"taken": self.taken, "dirs": dirs})
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let mut dirs = BTreeMap::new();
+        for (dir, totals) in value.get("dirs")?.as_object()? {
+            let files = totals.get(0)?.as_u64()? as usize;
+            let bytes = totals.get(1)?.as_u64()?;
+            dirs.insert(dir.clone(), Totals { files, bytes });
+        }
+        Some(Self {
+            taken: value.get("taken")?.as_u64()?,
+            dirs,
+        })
+    }
+}
+
+fn relative(root: &Path, dir: &Path) -> Option<String> {
+    Some(dir.strip_prefix(root).ok()?.to_string_lossy().into_owned())
+}
+
+/// The key a target is stored under
+fn target_key(root: &Path) -> io::Result<String> {
+    Ok(std::path::absolute(root)?.to_string_lossy().into_owned())
+}
+
+fn read_file(file: &Path) -> io::Result<Map<String, Value>> {
+    let text = fs::read_to_string(file)?;
+    let value: Value =
+        serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    match value.get("targets").and_then(Value::as_object) {
+        Some(targets) => Ok(targets.clone()),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an rmbrr snapshot file",
+        )),
+    }
+}
+
+/// Store `snapshot` as the entry for `root` in `file`, keeping the other targets' entries
+pub fn save(file: &Path, root: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    let mut targets = match read_file(file) {
+        Ok(targets) => targets,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Map::new(),
+        Err(e) => return Err(e),
+    };
+    targets.insert(target_key(root)?, snapshot.to_json());
+    let text = serde_json::to_string(&json!({"version": 1, "targets": targets}))
+        .map_err(io::Error::other)?;
+    fs::write(file, text + "\n")
+}
+
+/// The entry for `root` in `file`, if it has one
+pub fn load(file: &Path, root: &Path) -> io::Result<Option<Snapshot>> {
+    let targets = read_file(file)?;
+    let Some(entry) = targets.get(&target_key(root)?) else {
+        return Ok(None);
+    };
+    Snapshot::from_json(entry)
+        .map(Some)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed snapshot entry"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Appeared,
+    Disappeared,
+    /// The files directly in the directory changed
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub dir: String,
+    pub kind: ChangeKind,
+    /// Files and bytes gained (negative when lost)
+    pub files: i64,
+    pub bytes: i64,
+}
+
+/// Totals of the files directly in each directory: its totals less its subdirectories'
+fn own_totals(dirs: &BTreeMap<String, Totals>) -> HashMap<&str, (i64, i64)> {
+    let mut own: HashMap<&str, (i64, i64)> = dirs
+        .iter()
+        .map(|(dir, t)| (dir.as_str(), (t.files as i64, t.bytes as i64)))
+        .collect();
+    for (dir, totals) in dirs {
+        if dir.is_empty() {
+            continue;
+        }
+        let parent = parent_key(dir);
+        if let Some(entry) = own.get_mut(parent.as_str()) {
+            entry.0 -= totals.files as i64;
+            entry.1 -= totals.bytes as i64;
+        }
+    }
+    own
+}
+
+fn parent_key(dir: &str) -> String {
+    Path::new(dir)
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// What changed between two snapshots of a target
+///
+/// New and vanished subtrees are listed by their top directory only; other directories are
+/// listed when the files directly in them changed.
+pub fn diff(before: &Snapshot, after: &Snapshot) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (dir, totals) in &after.dirs {
+        if !before.dirs.contains_key(dir) && before.dirs.contains_key(&parent_key(dir)) {
+            changes.push(Change {
+                dir: dir.clone(),
+                kind: ChangeKind::Appeared,
+                files: totals.files as i64,
+                bytes: totals.bytes as i64,
+            });
+        }
+    }
+    for (dir, totals) in &before.dirs {
+        if !after.dirs.contains_key(dir) && after.dirs.contains_key(&parent_key(dir)) {
+            changes.push(Change {
+                dir: dir.clone(),
+                kind: ChangeKind::Disappeared,
+                files: -(totals.files as i64),
+                bytes: -(totals.bytes as i64),
+            });
+        }
+    }
+    let (own_before, own_after) = (own_totals(&before.dirs), own_totals(&after.dirs));
+    for (dir, now) in &own_after {
+        if let Some(then) = own_before.get(dir).filter(|then| *then != now) {
+            changes.push(Change {
+                dir: dir.to_string(),
+                kind: ChangeKind::Changed,
+                files: now.0 - then.0,
+                bytes: now.1 - then.1,
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.dir.cmp(&b.dir));
+    changes
+}
+
+/// How long ago a snapshot was taken, in its largest whole unit
+fn ago(taken: u64) -> String {
+    let now = SystemTime::now();
+    let secs = now
+        .duration_since(UNIX_EPOCH + Duration::from_secs(taken))
+        .unwrap_or_default()
+        .as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h", secs / 3600)
+    } else {
+        format!("{}d", secs / 86400)
+    }
+}
+
+fn signed_bytes(bytes: i64) -> String {
+    if bytes >= 0 {
+        format!("+{}", format_bytes(bytes))
+    } else {
+        format_bytes(bytes)
+    }
+}
+
+/// Print the changes from `before` to `after` for the target `root`
+pub fn print_diff(root: &Path, before: &Snapshot, after: &Snapshot) {
+    let changes = diff(before, after);
+    println!(
+        "\n{}",
+        tf(Msg::DiffHeader, &[&root.display(), &ago(before.taken)])
+    );
+    if changes.is_empty() {
+        println!("{}", t(Msg::DiffNoChanges));
+    }
+    for change in changes.iter().take(MAX_CHANGES) {
+        let (mark, label) = match change.kind {
+            ChangeKind::Appeared => ('+', t(Msg::DiffAppeared)),
+            ChangeKind::Disappeared => ('-', t(Msg::DiffDisappeared)),
+            ChangeKind::Changed if change.bytes >= 0 => ('~', t(Msg::DiffGrew)),
+            ChangeKind::Changed => ('~', t(Msg::DiffShrank)),
+        };
+        let dir = if change.dir.is_empty() {
+            "."
+        } else {
+            &change.dir
+        };
+        println!(
+            "  {} {:>12} {:>+8} files  {}{} ({})",
+            mark,
+            signed_bytes(change.bytes),
+            change.files,
+            dir,
+            std::path::MAIN_SEPARATOR,
+            label
+        );
+    }
+    if changes.len() > MAX_CHANGES {
+        println!("{}", tf(Msg::DiffMore, &[&(changes.len() - MAX_CHANGES)]));
+    }
+    let (then, now) = (before.total(), after.total());
+    println!(
+        "{}",
+        tf(
+            Msg::DiffTotal,
+            &[
+                &format_bytes(then.bytes as i64),
+                &format_bytes(now.bytes as i64),
+                &signed_bytes(now.bytes as i64 - then.bytes as i64),
+                &then.files,
+                &now.files
+            ]
+        )
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(dirs: &[(&str, usize, u64)]) -> Snapshot {
+        Snapshot {
+            taken: 0,
+            dirs: dirs
+                .iter()
+                .map(|&(dir, files, bytes)| (dir.to_string(), Totals { files, bytes }))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let sep = std::path::MAIN_SEPARATOR;
+        let nested = format!("new{}inner", sep);
+        let before = snapshot(&[("", 3, 300), ("logs", 2, 200), ("old", 1, 100)]);
+        let after = snapshot(&[
+            ("", 6, 900),
+            ("logs", 3, 500),
+            ("new", 3, 400),
+            (&nested, 1, 100),
+        ]);
+
+        let changes = diff(&before, &after);
+        let summary: Vec<(&str, ChangeKind, i64, i64)> = changes
+            .iter()
+            .map(|c| (c.dir.as_str(), c.kind, c.files, c.bytes))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("logs", ChangeKind::Changed, 1, 300),
+                ("new", ChangeKind::Appeared, 3, 400),
+                ("old", ChangeKind::Disappeared, -1, -100),
+            ]
+        );
+        assert!(diff(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp = std::env::temp_dir().join("win_rmdir_snapshot_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+        let file = temp.join("prev.json");
+        let first = snapshot(&[("", 1, 10)]);
+        let second = snapshot(&[("", 2, 20), ("sub", 1, 10)]);
+
+        save(&file, Path::new("/a"), &first).unwrap();
+        save(&file, Path::new("/b"), &second).unwrap();
+        assert_eq!(load(&file, Path::new("/a")).unwrap(), Some(first));
+        assert_eq!(load(&file, Path::new("/b")).unwrap(), Some(second));
+        assert_eq!(load(&file, Path::new("/c")).unwrap(), None);
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
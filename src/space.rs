@@ -0,0 +1,121 @@
+// Free-space verification: compares what the volume actually reclaimed with what the scan
+// expected a deletion to free
+
+use std::path::Path;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Volume free space around a deletion, next to the size the scan computed
+#[derive(Debug, Clone, Copy)]
+pub struct SpaceCheck {
+    /// Total size of the files selected for deletion
+    pub computed: u64,
+    /// Part of `computed` still referenced by hardlinks outside the tree, so never freed
+    pub shared: u64,
+    /// Disk space the deletion should give back, after compression, sparse ranges and
+    /// hardlinks kept elsewhere
+    pub allocated: u64,
+    /// Change in free space; negative if the volume filled up meanwhile
+    pub reclaimed: i64,
+}
+
+impl SpaceCheck {
+    pub fn new(
+        computed: u64,
+        shared: u64,
+        allocated: u64,
+        free_before: u64,
+        free_after: u64,
+    ) -> Self {
+        Self {
+            computed,
+            shared,
+            allocated,
+            reclaimed: free_after as i64 - free_before as i64,
+        }
+    }
+
+    /// Explain a noticeable mismatch; allocation rounding alone stays within the slack
+    pub fn discrepancy(&self) -> Option<&'static str> {
+        let computed = self.allocated as i64;
+        let slack = (computed / 10).max(MIB as i64);
+        if self.reclaimed + slack < computed {
+            Some("less space was reclaimed than deleted; hardlinked or still-open files, snapshots or other writers may be holding it")
+        } else if self.reclaimed > computed * 2 + 64 * MIB as i64 {
+            Some("more space was reclaimed than deleted; another process freed space on the volume meanwhile")
+        } else {
+            None
+        }
+    }
+}
+
+/// Directory whose volume a deletion of `path` frees space on, still present afterwards
+pub fn probe_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => path,
+    }
+}
+
+/// Format a byte count with binary units (512 B, 1.5 KiB, 3.2 GiB)
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes.unsigned_abs() as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let sign = if bytes < 0 { "-" } else { "" };
+    if unit == 0 {
+        format!("{}{} B", sign, value)
+    } else {
+        format!("{}{:.1} {}", sign, value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discrepancy() {
+        let gib = 1024 * MIB;
+        assert_eq!(
+            SpaceCheck::new(gib, 0, gib, 0, gib + 4 * MIB).discrepancy(),
+            None
+        );
+        assert!(SpaceCheck::new(gib, 0, gib, 0, gib / 2)
+            .discrepancy()
+            .is_some());
+        assert!(SpaceCheck::new(MIB, 0, MIB, 0, 10 * gib)
+            .discrepancy()
+            .is_some());
+        // The volume filling up meanwhile shows as negative reclaimed space
+        assert!(SpaceCheck::new(gib, 0, gib, gib, 0).discrepancy().is_some());
+        // Hardlinked data still referenced elsewhere is not expected back
+        assert_eq!(
+            SpaceCheck::new(gib, gib / 2, gib / 2, 0, gib / 2).discrepancy(),
+            None
+        );
+        // Compressed files give back their allocation, not their logical size
+        assert_eq!(
+            SpaceCheck::new(gib, 0, gib / 4, 0, gib / 4).discrepancy(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(-3 * 1024 * 1024), "-3.0 MiB");
+    }
+
+    #[test]
+    fn test_probe_dir() {
+        assert_eq!(probe_dir(Path::new("build")), Path::new("."));
+        assert_eq!(probe_dir(Path::new("/a/b")), Path::new("/a"));
+    }
+}
@@ -0,0 +1,183 @@
+// Deletion rates of past runs per volume, kept in the user's cache directory, so a run can
+// estimate its duration right after the scan instead of waiting for its own rates to settle
+//
+// The file maps volume ids to rates averaged over earlier runs, newer runs weighing more:
+// {"version":1,"volumes":{"\\?\Volume{...}\":{"files_per_sec":..,"dirs_per_sec":..,"runs":3}}}
+
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Share of a new run in the stored average
+const NEW_RUN_WEIGHT: f64 = 0.3;
+
+/// Runs shorter than this say more about startup costs than throughput and are not recorded
+pub const MIN_RECORDED_RUN: Duration = Duration::from_secs(1);
+
+/// Deletion rates of one run, or the average of several
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    pub files_per_sec: f64,
+    pub dirs_per_sec: f64,
+}
+
+impl Throughput {
+    /// Rates of a run that deleted `files` and `dirs` in `elapsed`
+    pub fn measured(files: usize, dirs: usize, elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64().max(0.001);
+        Self {
+            files_per_sec: files as f64 / secs,
+            dirs_per_sec: dirs as f64 / secs,
+        }
+    }
+
+    /// Time to delete `files` and `dirs` at these rates; whichever takes longer bounds the
+    /// run, since files and directories are deleted side by side. `None` if a count has no
+    /// rate to go by.
+    pub fn estimate(&self, files: usize, dirs: usize) -> Option<Duration> {
+        let secs = |count: usize, rate: f64| match count {
+            0 => Some(0.0),
+            _ if rate > 0.0 => Some(count as f64 / rate),
+            _ => None,
+        };
+        let secs = secs(files, self.files_per_sec)?.max(secs(dirs, self.dirs_per_sec)?);
+        Some(Duration::from_secs_f64(secs))
+    }
+
+    fn to_json(self, runs: u64) -> Value {
+        json!({
+            "files_per_sec": self.files_per_sec,
+            "dirs_per_sec": self.dirs_per_sec,
+            "runs": runs,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<(Self, u64)> {
+        let throughput = Self {
+            files_per_sec: value.get("files_per_sec")?.as_f64()?,
+            dirs_per_sec: value.get("dirs_per_sec")?.as_f64()?,
+        };
+        Some((throughput, value.get("runs")?.as_u64()?))
+    }
+}
+
+/// `%LOCALAPPDATA%\rmbrr\throughput.json` on Windows, `$XDG_CACHE_HOME/rmbrr/throughput.json`
+/// (or `~/.cache/rmbrr/throughput.json`) elsewhere
+pub fn history_path() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(std::env::var_os("LOCALAPPDATA")?)
+    } else {
+        match std::env::var_os("XDG_CACHE_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+        }
+    };
+    Some(base.join("rmbrr").join("throughput.json"))
+}
+
+fn read_volumes(file: &Path) -> io::Result<Map<String, Value>> {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Map::new()),
+        Err(e) => return Err(e),
+    };
+    let value: Value =
+        serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(value
+        .get("volumes")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// The average rates on `volume` and the number of runs behind them, if any were recorded
+pub fn load(file: &Path, volume: &str) -> Option<(Throughput, u64)> {
+    Throughput::from_json(read_volumes(file).ok()?.get(volume)?)
+}
+
+/// Fold a run's rates into the average for `volume`
+pub fn record(file: &Path, volume: &str, run: Throughput) -> io::Result<()> {
+    // A corrupt history only costs the estimate; start over rather than fail every run
+    let mut volumes = match read_volumes(file) {
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Map::new(),
+        volumes => volumes?,
+    };
+    let (average, runs) = match volumes.get(volume).and_then(Throughput::from_json) {
+        Some((old, runs)) => (
+            Throughput {
+                files_per_sec: old.files_per_sec
+                    + (run.files_per_sec - old.files_per_sec) * NEW_RUN_WEIGHT,
+                dirs_per_sec: old.dirs_per_sec
+                    + (run.dirs_per_sec - old.dirs_per_sec) * NEW_RUN_WEIGHT,
+            },
+            runs + 1,
+        ),
+        None => (run, 1),
+    };
+    volumes.insert(volume.to_string(), average.to_json(runs));
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = serde_json::to_string(&json!({"version": 1, "volumes": volumes}))
+        .map_err(io::Error::other)?;
+    // Write beside the file and rename over it, so a crash mid-write never leaves half a history
+    let temp = file.with_extension("json.tmp");
+    fs::write(&temp, text + "\n")?;
+    fs::rename(&temp, file).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let rates = Throughput {
+            files_per_sec: 1000.0,
+            dirs_per_sec: 10.0,
+        };
+        assert_eq!(rates.estimate(5000, 20), Some(Duration::from_secs(5)));
+        assert_eq!(rates.estimate(1000, 100), Some(Duration::from_secs(10)));
+        assert_eq!(rates.estimate(0, 0), Some(Duration::ZERO));
+        let no_files = Throughput {
+            files_per_sec: 0.0,
+            dirs_per_sec: 10.0,
+        };
+        assert_eq!(no_files.estimate(1, 10), None);
+        assert_eq!(no_files.estimate(0, 10), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let temp = std::env::temp_dir().join("win_rmdir_throughput_test");
+        let _ = fs::remove_dir_all(&temp);
+        let file = temp.join("cache").join("throughput.json");
+        assert_eq!(load(&file, "vol"), None);
+
+        let run = |files_per_sec| Throughput {
+            files_per_sec,
+            dirs_per_sec: 10.0,
+        };
+        record(&file, "vol", run(1000.0)).unwrap();
+        record(&file, "vol", run(2000.0)).unwrap();
+        record(&file, "other", run(5.0)).unwrap();
+        let (average, runs) = load(&file, "vol").unwrap();
+        assert_eq!(runs, 2);
+        assert_eq!(average.files_per_sec, 1300.0);
+        assert_eq!(average.dirs_per_sec, 10.0);
+        assert_eq!(load(&file, "other").unwrap().1, 1);
+        assert!(!file.with_extension("json.tmp").exists());
+
+        fs::write(&file, "{\"version\": 1, \"volu").unwrap();
+        assert_eq!(load(&file, "vol"), None);
+        record(&file, "vol", run(700.0)).unwrap();
+        let (average, runs) = load(&file, "vol").unwrap();
+        assert_eq!((average.files_per_sec, runs), (700.0, 1));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
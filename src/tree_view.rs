@@ -0,0 +1,160 @@
+// `--dry-run --tree`: the scanned directories as an indented tree, each with the total size
+// and count of the files it would lose, down to --tree-depth levels
+
+use crate::space::format_bytes;
+use rmbrr_core::tree::DirectoryTree;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Subdirectories shown per directory, largest first; the rest are summed in one line
+const MAX_CHILDREN: usize = 20;
+
+/// Files to delete below a directory, itself included
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Total every directory of `tree`; hardlinked files count once per link
+///
+/// Sizes come from a stat per file, since the scan only keeps a tree-wide total.
+pub fn totals(tree: &DirectoryTree) -> HashMap<PathBuf, Totals> {
+    let mut totals: HashMap<PathBuf, Totals> = HashMap::with_capacity(tree.dirs.len());
+    for file in &tree.files {
+        let Some(parent) = file.parent() else {
+            continue;
+        };
+        let size = file.symlink_metadata().map_or(0, |m| m.len());
+        let entry = totals.entry(parent.to_path_buf()).or_default();
+        entry.files += 1;
+        entry.bytes += size;
+    }
+    // `dirs` is sorted, so every child comes after its parent
+    for dir in tree.dirs.iter().rev() {
+        let own = totals.get(dir).copied().unwrap_or_default();
+        if let Some(parent) = dir.parent().filter(|p| tree.children.contains_key(*p)) {
+            let entry = totals.entry(parent.to_path_buf()).or_default();
+            entry.files += own.files;
+            entry.bytes += own.bytes;
+        }
+    }
+    totals
+}
+
+/// The lines of the tree below `root`, `depth` levels deep
+pub fn render(root: &Path, tree: &DirectoryTree, depth: usize) -> Vec<String> {
+    let totals = totals(tree);
+    let mut lines = vec![format!("{:>10} {:>10}  PATH", "SIZE", "FILES")];
+    render_dir(
+        root,
+        &root.display().to_string(),
+        0,
+        depth,
+        tree,
+        &totals,
+        &mut lines,
+    );
+    lines
+}
+
+fn render_dir(
+    dir: &Path,
+    name: &str,
+    level: usize,
+    depth: usize,
+    tree: &DirectoryTree,
+    totals: &HashMap<PathBuf, Totals>,
+    lines: &mut Vec<String>,
+) {
+    let total = totals.get(dir).copied().unwrap_or_default();
+    let kept = if tree.kept_dirs.contains(dir) {
+        " (kept)"
+    } else {
+        ""
+    };
+    lines.push(format!(
+        "{:>10} {:>10}  {}{}{}{}",
+        format_bytes(total.bytes as i64),
+        total.files,
+        "  ".repeat(level),
+        name,
+        std::path::MAIN_SEPARATOR,
+        kept
+    ));
+    if level == depth {
+        return;
+    }
+    let Some(children) = tree.children.get(dir) else {
+        return;
+    };
+
+    let mut children: Vec<(&PathBuf, Totals)> = children
+        .iter()
+        .map(|child| (child, totals.get(child).copied().unwrap_or_default()))
+        .collect();
+    children.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+    for (child, _) in children.iter().take(MAX_CHILDREN) {
+        let name = child.file_name().unwrap_or_default().to_string_lossy();
+        render_dir(child, &name, level + 1, depth, tree, totals, lines);
+    }
+    if children.len() > MAX_CHILDREN {
+        let rest = &children[MAX_CHILDREN..];
+        lines.push(format!(
+            "{:>10} {:>10}  {}... {} more directories",
+            format_bytes(rest.iter().map(|(_, t)| t.bytes).sum::<u64>() as i64),
+            rest.iter().map(|(_, t)| t.files).sum::<usize>(),
+            "  ".repeat(level + 1),
+            rest.len()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmbrr_core::tree::discover_tree;
+    use std::fs;
+
+    #[test]
+    fn test_render_tree() {
+        let temp = std::env::temp_dir().join("win_rmdir_tree_view_test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(temp.join("big/inner/deep")).unwrap();
+        fs::create_dir_all(temp.join("small")).unwrap();
+        fs::write(temp.join("big/inner/deep/a"), [0u8; 300]).unwrap();
+        fs::write(temp.join("big/b"), [0u8; 200]).unwrap();
+        fs::write(temp.join("small/c"), [0u8; 10]).unwrap();
+        fs::write(temp.join("loose"), [0u8; 50]).unwrap();
+
+        let tree = discover_tree(&temp).unwrap();
+        let totals = totals(&tree);
+        assert_eq!(
+            totals[&temp],
+            Totals {
+                files: 4,
+                bytes: 560
+            }
+        );
+        assert_eq!(totals[&temp.join("big")].bytes, 500);
+
+        let sep = std::path::MAIN_SEPARATOR;
+        let lines = render(&temp, &tree, 2);
+        let names: Vec<&str> = lines[1..]
+            .iter()
+            .map(|line| line[23..].trim_start())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                format!("{}{}", temp.display(), sep),
+                format!("big{}", sep),
+                format!("inner{}", sep),
+                format!("small{}", sep),
+            ]
+        );
+        assert!(lines[3].contains("300 B"));
+
+        fs::remove_dir_all(&temp).ok();
+    }
+}
@@ -0,0 +1,221 @@
+// Full-screen terminal interface for long-running deletions (--tui)
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use rmbrr_core::broker::Broker;
+use rmbrr_core::progress::Progress;
+use rmbrr_core::worker::{ErrorTracker, WorkerHandle};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const TICK: Duration = Duration::from_millis(250);
+const HISTORY: usize = 240;
+const ERROR_ROWS: usize = 6;
+
+/// Everything the interface reads while workers run
+pub struct TuiState {
+    pub root: PathBuf,
+    pub broker: Arc<Broker>,
+    pub progress: Arc<Progress>,
+    pub error_tracker: Arc<ErrorTracker>,
+}
+
+/// Draw the interface until all workers finish or the user hides it
+///
+/// Pressing `q` hides the interface and lets deletion continue; Ctrl+C aborts the process.
+pub fn run(state: &TuiState, handles: &[WorkerHandle]) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, state, handles);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    state: &TuiState,
+    handles: &[WorkerHandle],
+) -> io::Result<()> {
+    let mut history: Vec<u64> = Vec::with_capacity(HISTORY);
+    let mut last_items = 0;
+    let mut last_tick = Instant::now();
+
+    loop {
+        let finished = handles.iter().all(|h| h.is_finished());
+
+        if last_tick.elapsed() >= TICK || history.is_empty() {
+            let items = state.broker.completed_count() + state.progress.files_deleted();
+            let rate = (items - last_items) as f64 / last_tick.elapsed().as_secs_f64();
+            if history.len() == HISTORY {
+                history.remove(0);
+            }
+            history.push(rate as u64);
+            last_items = items;
+            last_tick = Instant::now();
+        }
+
+        terminal.draw(|frame| draw(frame, state, &history))?;
+
+        if finished {
+            return Ok(());
+        }
+
+        if event::poll(TICK)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        ratatui::restore();
+                        eprintln!("Aborted.");
+                        rmbrr_core::winapi::release_root_holds();
+                        std::process::exit(130);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, state: &TuiState, history: &[u64]) {
+    let [header, middle, graph, errors, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(6),
+        Constraint::Length(6),
+        Constraint::Length(ERROR_ROWS as u16 + 2),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let total = state.broker.total_dirs();
+    let completed = state.broker.completed_count();
+    let failed = state.broker.failed_count();
+    // Failed directories are finished too, so the gauge still fills up
+    let ratio = if total == 0 {
+        1.0
+    } else {
+        ((completed + failed) as f64 / total as f64).min(1.0)
+    };
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" rmbrr: {} ", state.root.display())),
+        )
+        .gauge_style(Style::default().fg(Color::Green))
+        .ratio(ratio)
+        .label(format!(
+            "{}/{} dirs, {} failed, {} files deleted",
+            completed,
+            total,
+            failed,
+            state.progress.files_deleted()
+        ));
+    frame.render_widget(gauge, header);
+
+    let [workers_area, subtrees_area] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(middle);
+
+    let active = state.progress.active();
+    let worker_items: Vec<ListItem> = active
+        .iter()
+        .enumerate()
+        .map(|(i, dir)| match dir {
+            Some(dir) => ListItem::new(format!(
+                "worker-{:<3} {}",
+                i,
+                relative(&state.root, dir).display()
+            )),
+            None => ListItem::new(format!("worker-{:<3} idle", i))
+                .style(Style::default().fg(Color::DarkGray)),
+        })
+        .collect();
+    frame.render_widget(
+        List::new(worker_items).block(Block::default().borders(Borders::ALL).title(" Workers ")),
+        workers_area,
+    );
+
+    let subtree_items: Vec<ListItem> = active_subtrees(&state.root, &active)
+        .into_iter()
+        .map(|(name, workers)| ListItem::new(format!("{:>3}  {}", workers, name)))
+        .collect();
+    frame.render_widget(
+        List::new(subtree_items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" In-progress subtrees (workers) "),
+        ),
+        subtrees_area,
+    );
+
+    let current_rate = history.last().copied().unwrap_or(0);
+    let visible = history.len().min(graph.width.saturating_sub(2) as usize);
+    frame.render_widget(
+        Sparkline::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Throughput: {} items/sec ", current_rate)),
+            )
+            .style(Style::default().fg(Color::Cyan))
+            .data(&history[history.len() - visible..]),
+        graph,
+    );
+
+    let failure_count = state.error_tracker.failure_count();
+    let error_items: Vec<ListItem> = state
+        .error_tracker
+        .recent(ERROR_ROWS)
+        .into_iter()
+        .map(|failure| {
+            let item_type = if failure.is_dir { "dir" } else { "file" };
+            ListItem::new(format!(
+                "[{}] {}: {}",
+                item_type,
+                relative(&state.root, &failure.path).display(),
+                failure.error
+            ))
+            .style(Style::default().fg(Color::Red))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(error_items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Errors ({}) ", failure_count)),
+        ),
+        errors,
+    );
+
+    frame.render_widget(
+        Paragraph::new(" q: hide (deletion continues)   Ctrl+C: abort")
+            .style(Style::default().fg(Color::DarkGray)),
+        footer,
+    );
+}
+
+/// Count workers per top-level subdirectory of the root
+fn active_subtrees(root: &Path, active: &[Option<PathBuf>]) -> Vec<(String, usize)> {
+    let mut subtrees: BTreeMap<String, usize> = BTreeMap::new();
+    for dir in active.iter().flatten() {
+        let name = relative(root, dir)
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .unwrap_or_else(|| ".".to_string());
+        *subtrees.entry(name).or_default() += 1;
+    }
+    subtrees.into_iter().collect()
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
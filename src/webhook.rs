@@ -0,0 +1,251 @@
+// `--webhook URL`: POST the outcome of the run as JSON when it ends, so fleet cleanups report
+// into chat or monitoring without a wrapper script
+//
+// Besides the counts the body has a `text` line, which is what Slack and Teams incoming
+// webhooks display.
+
+use rmbrr_core::error::{FailedItem, RmError};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Failed items listed in the body; the count covers the rest
+const MAX_FAILURES: usize = 50;
+
+/// How long the endpoint gets before the run ends without it
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a run did, as far as the webhook reports it
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub dirs_deleted: usize,
+    pub files_deleted: usize,
+    pub failures: Vec<FailedItem>,
+    pub cancelled: bool,
+    /// Why the run stopped, if it failed as a whole
+    pub error: Option<String>,
+}
+
+impl Summary {
+    /// A run that ended with `error`
+    pub fn failed(error: &RmError) -> Self {
+        match error {
+            RmError::PartialFailure { stats } => Self {
+                dirs_deleted: stats.dirs_deleted,
+                files_deleted: stats.files_deleted,
+                failures: stats.failures.clone(),
+                ..Default::default()
+            },
+            RmError::Cancelled => Self {
+                cancelled: true,
+                ..Default::default()
+            },
+            error => Self {
+                error: Some(error.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        if self.error.is_some() {
+            "error"
+        } else if self.cancelled {
+            "cancelled"
+        } else if !self.failures.is_empty() {
+            "failed"
+        } else {
+            "ok"
+        }
+    }
+}
+
+/// Name of this machine, to tell the runs of a fleet apart
+fn host_name() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown host".to_string())
+}
+
+/// The JSON body for a run over `targets` that took `elapsed`
+pub fn payload(targets: &[PathBuf], dry_run: bool, summary: &Summary, elapsed: Duration) -> Value {
+    let host = host_name();
+    let mut text = format!(
+        "rmbrr on {}: {} {} directories and {} files",
+        host,
+        if dry_run { "would delete" } else { "deleted" },
+        summary.dirs_deleted,
+        summary.files_deleted
+    );
+    match targets {
+        [target] => text.push_str(&format!(" from {}", target.display())),
+        targets => text.push_str(&format!(" from {} targets", targets.len())),
+    }
+    if !summary.failures.is_empty() {
+        text.push_str(&format!(", {} items failed", summary.failures.len()));
+    }
+    if summary.cancelled {
+        text.push_str(", cancelled");
+    }
+    if let Some(ref error) = summary.error {
+        text.push_str(&format!(", error: {}", error));
+    }
+
+    let failures: Vec<Value> = summary
+        .failures
+        .iter()
+        .take(MAX_FAILURES)
+        .map(|item| {
+            json!({
+                "path": item.path.display().to_string(),
+                "is_dir": item.is_dir,
+                "error": item.error,
+                "os_error": item.os_error,
+            })
+        })
+        .collect();
+    json!({
+        "status": summary.status(),
+        "text": text,
+        "host": host,
+        "targets": targets.iter().map(|t| t.display().to_string()).collect::<Vec<_>>(),
+        "dry_run": dry_run,
+        "dirs_deleted": summary.dirs_deleted,
+        "files_deleted": summary.files_deleted,
+        "failed": summary.failures.len(),
+        "failures": failures,
+        "cancelled": summary.cancelled,
+        "error": summary.error,
+        "elapsed_ms": elapsed.as_millis() as u64,
+    })
+}
+
+/// POST `body` to `url`; a non-2xx answer is an error
+///
+/// HTTPS goes through the system's TLS library and certificate store (SChannel on Windows),
+/// so endpoints behind a corporate CA work as they do in the browser.
+pub fn post(url: &str, body: &Value) -> Result<(), ureq::Error> {
+    use ureq::tls::{RootCerts, TlsConfig, TlsProvider};
+
+    let tls = TlsConfig::builder()
+        .provider(TlsProvider::NativeTls)
+        .root_certs(RootCerts::PlatformVerifier)
+        .build();
+    let config = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .tls_config(tls)
+        .build();
+    ureq::Agent::new_with_config(config)
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(body.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmbrr_core::Report;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn failed_item(path: &str) -> FailedItem {
+        FailedItem {
+            path: PathBuf::from(path),
+            error: "Access is denied".to_string(),
+            is_dir: false,
+            os_error: Some(5),
+        }
+    }
+
+    #[test]
+    fn test_payload() {
+        let targets = [PathBuf::from("build")];
+        let ok = Summary {
+            dirs_deleted: 3,
+            files_deleted: 10,
+            ..Default::default()
+        };
+        let body = payload(&targets, false, &ok, Duration::from_millis(1500));
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["elapsed_ms"], 1500);
+        assert_eq!(body["targets"], json!(["build"]));
+        let text = body["text"].as_str().unwrap();
+        assert!(text.ends_with("deleted 3 directories and 10 files from build"));
+
+        let partial = Summary::failed(&RmError::PartialFailure {
+            stats: Report {
+                dirs_deleted: 1,
+                failures: (0..MAX_FAILURES + 5)
+                    .map(|i| failed_item(&format!("f{}", i)))
+                    .collect(),
+                ..Default::default()
+            },
+        });
+        let body = payload(&targets, false, &partial, Duration::ZERO);
+        assert_eq!(body["status"], "failed");
+        assert_eq!(body["failed"], MAX_FAILURES + 5);
+        assert_eq!(body["failures"].as_array().unwrap().len(), MAX_FAILURES);
+        assert_eq!(body["failures"][0]["os_error"], 5);
+
+        let cancelled = Summary::failed(&RmError::Cancelled);
+        assert_eq!(
+            payload(&targets, true, &cancelled, Duration::ZERO)["status"],
+            "cancelled"
+        );
+        let error = Summary::failed(&RmError::InvalidPath {
+            path: PathBuf::from("build"),
+            reason: "path does not exist".to_string(),
+        });
+        let body = payload(&targets, false, &error, Duration::ZERO);
+        assert_eq!(body["status"], "error");
+        assert!(body["error"].as_str().unwrap().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_post() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0u8; length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            (request_line, body)
+        });
+
+        post(&url, &json!({"status": "ok"})).unwrap();
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /hook "));
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            json!({"status": "ok"})
+        );
+    }
+}